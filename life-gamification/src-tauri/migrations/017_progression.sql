@@ -0,0 +1,47 @@
+-- Migration 017: Level-up pipeline
+-- Level and experience_to_next_level are now computed in Rust from a configurable
+-- curve (commands/progression.rs) on every XP grant, so the fixed 100-XP-per-level
+-- trigger from 001 would overwrite them.
+
+DROP TRIGGER IF EXISTS update_user_level;
+
+CREATE TABLE IF NOT EXISTS progression_settings (
+    id INTEGER PRIMARY KEY CHECK (id = 1),
+    base_xp_per_level INTEGER NOT NULL DEFAULT 100,  -- XP needed to go from level 1 to 2
+    level_growth REAL NOT NULL DEFAULT 1.0,          -- each level costs this many times the previous one
+    skill_points_per_level INTEGER NOT NULL DEFAULT 1,
+    max_health_per_level INTEGER NOT NULL DEFAULT 10,
+    updated_at DATETIME DEFAULT CURRENT_TIMESTAMP
+);
+
+INSERT OR IGNORE INTO progression_settings (id) VALUES (1);
+
+-- Highest level whose skill points / max_health have been granted, so losing XP
+-- and earning it back never pays out the same level twice.
+ALTER TABLE users ADD COLUMN highest_level_rewarded INTEGER;
+UPDATE users SET highest_level_rewarded = level;
+
+-- The skill tree reads and spends points from here; make sure it exists in the
+-- main database (same shape as 20240906000001_create_poe_skill_tree.sql).
+CREATE TABLE IF NOT EXISTS user_skill_stats (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    user_id INTEGER NOT NULL UNIQUE,
+    total_nodes_allocated INTEGER DEFAULT 0,
+    available_skill_points INTEGER DEFAULT 3,
+    strength_bonus INTEGER DEFAULT 0,
+    intelligence_bonus INTEGER DEFAULT 0,
+    luck_bonus INTEGER DEFAULT 0,
+    aura_bonus INTEGER DEFAULT 0,
+    will_bonus INTEGER DEFAULT 0,
+    health_bonus INTEGER DEFAULT 0,
+    mana_bonus INTEGER DEFAULT 0,
+    task_xp_multiplier REAL DEFAULT 1.0,
+    task_completion_bonus REAL DEFAULT 1.0,
+    streak_protection_count INTEGER DEFAULT 0,
+    last_calculated DATETIME DEFAULT CURRENT_TIMESTAMP,
+    FOREIGN KEY (user_id) REFERENCES users(id) ON DELETE CASCADE
+);
+
+-- Same starting allowance get_user_skill_stats hands out on first read
+INSERT OR IGNORE INTO user_skill_stats (user_id, available_skill_points)
+SELECT id, 10 FROM users;
@@ -0,0 +1,42 @@
+-- Migration 018: Append-only reward ledger
+-- Every XP and gold change is recorded here with its source, base amount, the
+-- multipliers applied (INT, LUCK, streak, buffs, ...) and the final amount, so
+-- users.experience_points / users.gold can be explained and reconciled.
+
+CREATE TABLE IF NOT EXISTS reward_ledger (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    user_id INTEGER NOT NULL DEFAULT 1,
+    currency TEXT NOT NULL CHECK (currency IN ('xp', 'gold')),
+    source_type TEXT NOT NULL,      -- e.g. 'task_completion', 'achievement', 'purchase', 'manual'
+    source_id TEXT,                 -- task / achievement / item id, as text
+    base_amount INTEGER NOT NULL,
+    multipliers TEXT NOT NULL DEFAULT '[]',  -- JSON [{"name": "intelligence", "value": 1.2}, ...]
+    final_amount INTEGER NOT NULL,
+    note TEXT,
+    created_at DATETIME DEFAULT CURRENT_TIMESTAMP,
+    FOREIGN KEY (user_id) REFERENCES users(id) ON DELETE CASCADE
+);
+
+CREATE INDEX IF NOT EXISTS idx_reward_ledger_source ON reward_ledger(source_type, source_id);
+CREATE INDEX IF NOT EXISTS idx_reward_ledger_created ON reward_ledger(created_at);
+
+CREATE TRIGGER IF NOT EXISTS reward_ledger_no_update
+BEFORE UPDATE ON reward_ledger
+BEGIN
+    SELECT RAISE(ABORT, 'reward_ledger is append-only');
+END;
+
+CREATE TRIGGER IF NOT EXISTS reward_ledger_no_delete
+BEFORE DELETE ON reward_ledger
+BEGIN
+    SELECT RAISE(ABORT, 'reward_ledger is append-only');
+END;
+
+-- Opening balances so existing totals reconcile against the ledger
+INSERT INTO reward_ledger (user_id, currency, source_type, base_amount, final_amount, note)
+SELECT id, 'xp', 'opening_balance', experience_points, experience_points, 'Balance before the ledger existed'
+FROM users;
+
+INSERT INTO reward_ledger (user_id, currency, source_type, base_amount, final_amount, note)
+SELECT id, 'gold', 'opening_balance', gold, gold, 'Balance before the ledger existed'
+FROM users;
@@ -0,0 +1,50 @@
+-- Migration 019: Reopening completed tasks
+-- reopen_task reverses a completion exactly: ledger entries are offset by
+-- reversal rows (the ledger stays append-only) and the pre-completion state
+-- captured in task_completions is restored.
+
+ALTER TABLE reward_ledger ADD COLUMN reverses_entry_id INTEGER REFERENCES reward_ledger(id);
+CREATE INDEX IF NOT EXISTS idx_reward_ledger_reverses ON reward_ledger(reverses_entry_id);
+
+CREATE TABLE IF NOT EXISTS task_completions (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    task_id INTEGER NOT NULL,
+    completed_at DATETIME DEFAULT CURRENT_TIMESTAMP,
+    reopened_at DATETIME,
+
+    -- Recurring series (parent task) streak state before this completion
+    parent_task_id INTEGER,
+    prev_current_streak INTEGER,
+    prev_longest_streak INTEGER,
+    prev_last_completed_date TEXT,
+    prev_streak_multiplier REAL,
+
+    -- Goal progress before the increment that completed the goal
+    prev_progress INTEGER,
+
+    FOREIGN KEY (task_id) REFERENCES tasks(id) ON DELETE CASCADE
+);
+
+CREATE INDEX IF NOT EXISTS idx_task_completions_task ON task_completions(task_id);
+CREATE INDEX IF NOT EXISTS idx_task_completions_parent ON task_completions(parent_task_id);
+
+-- Keep project stats right when a completed task goes back to active, and
+-- reopen a project that was auto-completed by that task.
+CREATE TRIGGER IF NOT EXISTS update_project_stats_on_task_reopen
+AFTER UPDATE OF status ON tasks
+WHEN NEW.project_id IS NOT NULL AND OLD.status = 'completed' AND NEW.status != 'completed'
+BEGIN
+    UPDATE projects
+    SET
+        completed_tasks = (
+            SELECT COUNT(*) FROM tasks
+            WHERE project_id = NEW.project_id AND status = 'completed'
+        ),
+        total_xp_earned = (
+            SELECT COALESCE(SUM(base_experience_reward), 0) FROM tasks
+            WHERE project_id = NEW.project_id AND status = 'completed'
+        ),
+        status = CASE WHEN status = 'completed' THEN 'active' ELSE status END,
+        completed_at = CASE WHEN status = 'completed' THEN NULL ELSE completed_at END
+    WHERE id = NEW.project_id;
+END;
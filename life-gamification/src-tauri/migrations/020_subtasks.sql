@@ -0,0 +1,24 @@
+-- Migration 020: Subtasks and "blocked by" dependencies
+-- A task can have child tasks (parent_task_id). When it does, its original
+-- rewards become a budget shared between the parent and its children, so adding
+-- subtasks never increases what the whole tree pays out.
+
+ALTER TABLE tasks ADD COLUMN parent_task_id INTEGER REFERENCES tasks(id) ON DELETE SET NULL;
+ALTER TABLE tasks ADD COLUMN complete_with_subtasks INTEGER NOT NULL DEFAULT 0;
+ALTER TABLE tasks ADD COLUMN reward_budget_xp INTEGER;    -- set while the task has subtasks
+ALTER TABLE tasks ADD COLUMN reward_budget_gold INTEGER;
+
+CREATE INDEX IF NOT EXISTS idx_tasks_parent_task ON tasks(parent_task_id);
+
+-- task_id can't be worked on until blocked_by_task_id is completed
+CREATE TABLE IF NOT EXISTS task_dependencies (
+    task_id INTEGER NOT NULL,
+    blocked_by_task_id INTEGER NOT NULL,
+    created_at DATETIME DEFAULT CURRENT_TIMESTAMP,
+    PRIMARY KEY (task_id, blocked_by_task_id),
+    CHECK (task_id != blocked_by_task_id),
+    FOREIGN KEY (task_id) REFERENCES tasks(id) ON DELETE CASCADE,
+    FOREIGN KEY (blocked_by_task_id) REFERENCES tasks(id) ON DELETE CASCADE
+);
+
+CREATE INDEX IF NOT EXISTS idx_task_dependencies_blocker ON task_dependencies(blocked_by_task_id);
@@ -0,0 +1,19 @@
+-- Migration 021: Reward economy settings
+-- The reward numbers that used to be hardcoded (base XP/gold per difficulty, the
+-- INT/LUCK bonuses, streak tiers) live here so they can be tuned in one place.
+-- Lists are stored as JSON; commands/economy.rs owns the shape and the defaults.
+
+CREATE TABLE IF NOT EXISTS economy_settings (
+    id INTEGER PRIMARY KEY CHECK (id = 1),
+    base_rewards TEXT,                               -- JSON [{"difficulty": 1, "xp": 10, "gold": 1}, ...]
+    xp_per_intelligence REAL NOT NULL DEFAULT 0.02,  -- +2% XP per INT point
+    gold_per_luck REAL NOT NULL DEFAULT 0.015,       -- +1.5% gold per LUCK point
+    max_stat_bonus REAL,                             -- NULL = uncapped INT/LUCK bonus
+    streak_tiers TEXT,                               -- JSON [{"min_streak": 7, "multiplier": 2.0}, ...]
+    verification_bonus REAL NOT NULL DEFAULT 0.5,    -- extra share of base XP for verified tasks
+    max_xp_per_completion INTEGER,                   -- NULL = uncapped
+    max_gold_per_completion INTEGER,
+    updated_at DATETIME DEFAULT CURRENT_TIMESTAMP
+);
+
+INSERT OR IGNORE INTO economy_settings (id) VALUES (1);
@@ -0,0 +1,38 @@
+-- Migration 022: Overdue damage and defeat
+-- A daily sweep deals HP damage for overdue tasks and missed recurring
+-- occurrences. Every hit (and every defeat at 0 HP) is recorded in
+-- damage_events so the user can see why they lost health.
+
+CREATE TABLE IF NOT EXISTS damage_settings (
+    id INTEGER PRIMARY KEY CHECK (id = 1),
+    enabled INTEGER NOT NULL DEFAULT 1,
+    damage_per_difficulty REAL NOT NULL DEFAULT 1.0,   -- HP per difficulty point, per missed day
+    priority_weight REAL NOT NULL DEFAULT 0.25,        -- each priority step above/below 3 adds/removes 25%
+    defeat_gold_loss_percent REAL NOT NULL DEFAULT 10.0,
+    defeat_resets_streaks INTEGER NOT NULL DEFAULT 1,
+    revive_health_percent REAL NOT NULL DEFAULT 50.0,  -- HP after a defeat, as % of max_health
+    last_sweep_date TEXT,                              -- YYYY-MM-DD of the last completed sweep
+    updated_at DATETIME DEFAULT CURRENT_TIMESTAMP
+);
+
+INSERT OR IGNORE INTO damage_settings (id) VALUES (1);
+
+CREATE TABLE IF NOT EXISTS damage_events (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    user_id INTEGER NOT NULL DEFAULT 1,
+    task_id INTEGER,
+    kind TEXT NOT NULL CHECK (kind IN ('overdue', 'missed_occurrence', 'defeat')),
+    occurrence_date TEXT,             -- day the damage is for (YYYY-MM-DD)
+    amount INTEGER NOT NULL,
+    health_before INTEGER NOT NULL,
+    health_after INTEGER NOT NULL,
+    gold_lost INTEGER NOT NULL DEFAULT 0,
+    reason TEXT NOT NULL,
+    created_at DATETIME DEFAULT CURRENT_TIMESTAMP,
+    FOREIGN KEY (user_id) REFERENCES users(id) ON DELETE CASCADE,
+    FOREIGN KEY (task_id) REFERENCES tasks(id) ON DELETE SET NULL,
+    -- A task is hit at most once per day
+    UNIQUE (task_id, kind, occurrence_date)
+);
+
+CREATE INDEX IF NOT EXISTS idx_damage_events_created ON damage_events(created_at);
@@ -0,0 +1,65 @@
+-- Migration 023: Full-text task search
+-- tasks_fts indexes each task (rowid = tasks.id) by title, description,
+-- category, project name and where it was imported from. Triggers on tasks and
+-- projects keep it in sync; task_search_source is the single definition of what
+-- gets indexed.
+
+CREATE VIRTUAL TABLE IF NOT EXISTS tasks_fts USING fts5(
+    title,
+    description,
+    category,
+    project,
+    source,
+    tokenize = 'unicode61 remove_diacritics 2',
+    prefix = '2 3'
+);
+
+CREATE VIEW IF NOT EXISTS task_search_source AS
+SELECT
+    t.id,
+    t.title,
+    COALESCE(t.description, '') AS description,
+    COALESCE(t.category, '') AS category,
+    COALESCE(p.name, '') AS project,
+    TRIM(
+        CASE WHEN t.github_repo IS NOT NULL THEN 'github ' || t.github_repo ELSE '' END || ' ' ||
+        CASE WHEN t.reminder_list IS NOT NULL THEN 'reminders ' || t.reminder_list ELSE '' END || ' ' ||
+        CASE WHEN t.source_event_uid IS NOT NULL THEN 'calendar' ELSE '' END
+    ) AS source
+FROM tasks t
+LEFT JOIN projects p ON p.id = t.project_id;
+
+CREATE TRIGGER IF NOT EXISTS tasks_fts_insert
+AFTER INSERT ON tasks
+BEGIN
+    INSERT INTO tasks_fts (rowid, title, description, category, project, source)
+    SELECT id, title, description, category, project, source FROM task_search_source WHERE id = NEW.id;
+END;
+
+CREATE TRIGGER IF NOT EXISTS tasks_fts_update
+AFTER UPDATE OF title, description, category, project_id, github_repo, reminder_list, source_event_uid ON tasks
+BEGIN
+    DELETE FROM tasks_fts WHERE rowid = OLD.id;
+    INSERT INTO tasks_fts (rowid, title, description, category, project, source)
+    SELECT id, title, description, category, project, source FROM task_search_source WHERE id = NEW.id;
+END;
+
+CREATE TRIGGER IF NOT EXISTS tasks_fts_delete
+AFTER DELETE ON tasks
+BEGIN
+    DELETE FROM tasks_fts WHERE rowid = OLD.id;
+END;
+
+CREATE TRIGGER IF NOT EXISTS tasks_fts_project_rename
+AFTER UPDATE OF name ON projects
+BEGIN
+    DELETE FROM tasks_fts WHERE rowid IN (SELECT id FROM tasks WHERE project_id = NEW.id);
+    INSERT INTO tasks_fts (rowid, title, description, category, project, source)
+    SELECT s.id, s.title, s.description, s.category, s.project, s.source
+    FROM task_search_source s JOIN tasks t ON t.id = s.id
+    WHERE t.project_id = NEW.id;
+END;
+
+-- Index everything that already exists
+INSERT INTO tasks_fts (rowid, title, description, category, project, source)
+SELECT id, title, description, category, project, source FROM task_search_source;
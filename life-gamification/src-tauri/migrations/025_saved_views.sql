@@ -0,0 +1,17 @@
+-- Migration 025: Saved views
+-- Named task filters written in the view query language (commands/views.rs),
+-- e.g. `due<=+3d AND priority>=4 AND NOT category:someday`. The query text is
+-- stored as written and compiled each time the view runs, so relative dates
+-- stay relative.
+
+CREATE TABLE IF NOT EXISTS saved_views (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    user_id INTEGER NOT NULL DEFAULT 1,
+    name TEXT NOT NULL COLLATE NOCASE,
+    query TEXT NOT NULL,
+    position INTEGER NOT NULL DEFAULT 0,
+    created_at DATETIME DEFAULT CURRENT_TIMESTAMP,
+    updated_at DATETIME DEFAULT CURRENT_TIMESTAMP,
+    FOREIGN KEY (user_id) REFERENCES users(id) ON DELETE CASCADE,
+    UNIQUE (user_id, name)
+);
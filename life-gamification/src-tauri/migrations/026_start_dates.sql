@@ -0,0 +1,8 @@
+-- Migration 026: Start dates
+-- A task with a start date in the future is deferred ("hidden until"): it stays
+-- out of the active list until then and comes back on its own. Stored as a UTC
+-- 'YYYY-MM-DD HH:MM:SS' timestamp so it compares with datetime('now').
+
+ALTER TABLE tasks ADD COLUMN start_date DATETIME;
+
+CREATE INDEX IF NOT EXISTS idx_tasks_start_date ON tasks(start_date) WHERE start_date IS NOT NULL;
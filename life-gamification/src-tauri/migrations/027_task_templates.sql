@@ -0,0 +1,33 @@
+-- Migration 027: Task templates
+-- Reusable task blueprints with an ordered checklist. Instantiating a template
+-- creates the task plus one subtask per checklist item; {{name}} placeholders in
+-- the title, description and checklist are filled in at that point.
+
+CREATE TABLE IF NOT EXISTS task_templates (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    user_id INTEGER NOT NULL DEFAULT 1,
+    name TEXT NOT NULL COLLATE NOCASE,
+    title TEXT NOT NULL,
+    description TEXT,
+    category TEXT NOT NULL DEFAULT 'general',
+    difficulty INTEGER NOT NULL DEFAULT 5,
+    priority INTEGER NOT NULL DEFAULT 3,
+    estimated_time_minutes INTEGER,
+    project_id INTEGER,
+    tags TEXT NOT NULL DEFAULT '[]', -- JSON array of tag names
+    created_at DATETIME DEFAULT CURRENT_TIMESTAMP,
+    updated_at DATETIME DEFAULT CURRENT_TIMESTAMP,
+    FOREIGN KEY (user_id) REFERENCES users(id) ON DELETE CASCADE,
+    FOREIGN KEY (project_id) REFERENCES projects(id) ON DELETE SET NULL,
+    UNIQUE (user_id, name)
+);
+
+CREATE TABLE IF NOT EXISTS task_template_items (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    template_id INTEGER NOT NULL,
+    position INTEGER NOT NULL,
+    text TEXT NOT NULL,
+    FOREIGN KEY (template_id) REFERENCES task_templates(id) ON DELETE CASCADE
+);
+
+CREATE INDEX IF NOT EXISTS idx_task_template_items_template ON task_template_items(template_id, position);
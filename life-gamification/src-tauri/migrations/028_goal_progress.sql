@@ -0,0 +1,37 @@
+-- Migration 028: Goal progress history and milestones
+-- Every change to a goal task's progress is logged, so progress can be charted
+-- over time and corrected. Crossing a milestone (25/50/75% by default) pays a
+-- share of the task's reward early; the final completion pays the rest.
+
+ALTER TABLE economy_settings ADD COLUMN goal_milestones TEXT;                          -- JSON [25, 50, 75]; NULL = default
+ALTER TABLE economy_settings ADD COLUMN goal_milestone_share REAL NOT NULL DEFAULT 0.1; -- share of base reward per milestone; 0 = off
+
+CREATE TABLE IF NOT EXISTS goal_progress_log (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    task_id INTEGER NOT NULL,
+    delta INTEGER NOT NULL,
+    progress_after INTEGER NOT NULL,
+    kind TEXT NOT NULL DEFAULT 'increment' CHECK (kind IN ('increment', 'correction')),
+    note TEXT,
+    created_at DATETIME DEFAULT CURRENT_TIMESTAMP,
+    FOREIGN KEY (task_id) REFERENCES tasks(id) ON DELETE CASCADE
+);
+
+CREATE INDEX IF NOT EXISTS idx_goal_progress_log_task ON goal_progress_log(task_id, created_at);
+
+CREATE TABLE IF NOT EXISTS goal_milestones (
+    task_id INTEGER NOT NULL,
+    percent INTEGER NOT NULL,
+    base_xp INTEGER NOT NULL DEFAULT 0,   -- base reward paid early, deducted from the final reward
+    base_gold INTEGER NOT NULL DEFAULT 0,
+    reached_at DATETIME DEFAULT CURRENT_TIMESTAMP,
+    PRIMARY KEY (task_id, percent),
+    FOREIGN KEY (task_id) REFERENCES tasks(id) ON DELETE CASCADE
+);
+
+-- Progress made before history was kept becomes a single starting entry
+INSERT INTO goal_progress_log (task_id, delta, progress_after, kind, note, created_at)
+SELECT task_id, current_progress, current_progress, 'increment', 'Progress before history was kept',
+       COALESCE(last_updated, CURRENT_TIMESTAMP)
+FROM task_progress
+WHERE current_progress > 0;
@@ -0,0 +1,25 @@
+-- Migration 029: Trash and undo
+-- Deleting a project or a finance account moves it to the trash (deleted_at)
+-- instead of removing it; trashed rows are purged for good after
+-- trash_settings.retention_days. Destructive commands also hand out a
+-- short-lived undo token describing how to reverse them.
+
+ALTER TABLE projects ADD COLUMN deleted_at DATETIME;
+ALTER TABLE accounts ADD COLUMN deleted_at DATETIME;
+
+CREATE TABLE IF NOT EXISTS trash_settings (
+    id INTEGER PRIMARY KEY CHECK (id = 1),
+    retention_days INTEGER NOT NULL DEFAULT 30 CHECK (retention_days >= 1),
+    updated_at DATETIME DEFAULT CURRENT_TIMESTAMP
+);
+
+INSERT OR IGNORE INTO trash_settings (id) VALUES (1);
+
+CREATE TABLE IF NOT EXISTS undo_tokens (
+    token TEXT PRIMARY KEY,
+    action TEXT NOT NULL,             -- JSON, e.g. {"type": "restore", "kind": "project", "id": 3}
+    description TEXT NOT NULL,
+    created_at DATETIME DEFAULT CURRENT_TIMESTAMP,
+    expires_at DATETIME NOT NULL,
+    used_at DATETIME
+);
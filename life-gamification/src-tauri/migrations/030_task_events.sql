@@ -0,0 +1,165 @@
+-- Migration 030: Task activity history and comments
+-- Triggers write a task_events row whenever a task is created, one of its
+-- fields changes, its status changes or it is tagged, whichever command did it.
+-- Syncs that change tasks on the user's behalf (GitHub, Reminders, Calendar,
+-- recurring instances) set task_event_context.source while they run, so their
+-- edits can be told apart from the user's. get_task_timeline merges these
+-- events with comments, time sessions and goal progress.
+
+CREATE TABLE IF NOT EXISTS task_events (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    task_id INTEGER NOT NULL,
+    kind TEXT NOT NULL,            -- created | updated | completed | reopened | archived | status_changed | tagged | untagged
+    field TEXT,                    -- for 'updated': title, description, due_date, start_date, category, priority, difficulty, estimate, project, parent
+    old_value TEXT,
+    new_value TEXT,
+    source TEXT NOT NULL DEFAULT 'user',  -- user | github | reminders | calendar | recurring
+    created_at DATETIME DEFAULT CURRENT_TIMESTAMP,
+    FOREIGN KEY (task_id) REFERENCES tasks(id) ON DELETE CASCADE
+);
+
+CREATE INDEX IF NOT EXISTS idx_task_events_task ON task_events(task_id, created_at);
+
+-- Who is changing tasks right now; NULL means the user
+CREATE TABLE IF NOT EXISTS task_event_context (
+    id INTEGER PRIMARY KEY CHECK (id = 1),
+    source TEXT
+);
+
+INSERT OR IGNORE INTO task_event_context (id) VALUES (1);
+
+CREATE TABLE IF NOT EXISTS task_comments (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    task_id INTEGER NOT NULL,
+    body TEXT NOT NULL,
+    created_at DATETIME DEFAULT CURRENT_TIMESTAMP,
+    updated_at DATETIME,           -- set when the comment is edited
+    FOREIGN KEY (task_id) REFERENCES tasks(id) ON DELETE CASCADE
+);
+
+CREATE INDEX IF NOT EXISTS idx_task_comments_task ON task_comments(task_id, created_at);
+
+CREATE TRIGGER IF NOT EXISTS task_events_created
+AFTER INSERT ON tasks
+BEGIN
+    INSERT INTO task_events (task_id, kind, new_value, source)
+    VALUES (NEW.id, 'created', NEW.title, COALESCE((SELECT source FROM task_event_context WHERE id = 1), 'user'));
+END;
+
+CREATE TRIGGER IF NOT EXISTS task_events_status
+AFTER UPDATE OF status ON tasks
+WHEN OLD.status IS NOT NEW.status
+BEGIN
+    INSERT INTO task_events (task_id, kind, old_value, new_value, source)
+    VALUES (
+        NEW.id,
+        CASE
+            WHEN NEW.status = 'completed' THEN 'completed'
+            WHEN NEW.status = 'archived' THEN 'archived'
+            WHEN OLD.status = 'completed' AND NEW.status = 'active' THEN 'reopened'
+            ELSE 'status_changed'
+        END,
+        OLD.status, NEW.status, COALESCE((SELECT source FROM task_event_context WHERE id = 1), 'user')
+    );
+END;
+
+CREATE TRIGGER IF NOT EXISTS task_events_title
+AFTER UPDATE OF title ON tasks
+WHEN OLD.title IS NOT NEW.title
+BEGIN
+    INSERT INTO task_events (task_id, kind, field, old_value, new_value, source)
+    VALUES (NEW.id, 'updated', 'title', OLD.title, NEW.title, COALESCE((SELECT source FROM task_event_context WHERE id = 1), 'user'));
+END;
+
+CREATE TRIGGER IF NOT EXISTS task_events_description
+AFTER UPDATE OF description ON tasks
+WHEN OLD.description IS NOT NEW.description
+BEGIN
+    INSERT INTO task_events (task_id, kind, field, old_value, new_value, source)
+    VALUES (NEW.id, 'updated', 'description', OLD.description, NEW.description, COALESCE((SELECT source FROM task_event_context WHERE id = 1), 'user'));
+END;
+
+CREATE TRIGGER IF NOT EXISTS task_events_due_date
+AFTER UPDATE OF due_date ON tasks
+WHEN OLD.due_date IS NOT NEW.due_date
+BEGIN
+    INSERT INTO task_events (task_id, kind, field, old_value, new_value, source)
+    VALUES (NEW.id, 'updated', 'due_date', OLD.due_date, NEW.due_date, COALESCE((SELECT source FROM task_event_context WHERE id = 1), 'user'));
+END;
+
+CREATE TRIGGER IF NOT EXISTS task_events_start_date
+AFTER UPDATE OF start_date ON tasks
+WHEN OLD.start_date IS NOT NEW.start_date
+BEGIN
+    INSERT INTO task_events (task_id, kind, field, old_value, new_value, source)
+    VALUES (NEW.id, 'updated', 'start_date', OLD.start_date, NEW.start_date, COALESCE((SELECT source FROM task_event_context WHERE id = 1), 'user'));
+END;
+
+CREATE TRIGGER IF NOT EXISTS task_events_category
+AFTER UPDATE OF category ON tasks
+WHEN OLD.category IS NOT NEW.category
+BEGIN
+    INSERT INTO task_events (task_id, kind, field, old_value, new_value, source)
+    VALUES (NEW.id, 'updated', 'category', OLD.category, NEW.category, COALESCE((SELECT source FROM task_event_context WHERE id = 1), 'user'));
+END;
+
+CREATE TRIGGER IF NOT EXISTS task_events_priority
+AFTER UPDATE OF priority ON tasks
+WHEN OLD.priority IS NOT NEW.priority
+BEGIN
+    INSERT INTO task_events (task_id, kind, field, old_value, new_value, source)
+    VALUES (NEW.id, 'updated', 'priority', OLD.priority, NEW.priority, COALESCE((SELECT source FROM task_event_context WHERE id = 1), 'user'));
+END;
+
+CREATE TRIGGER IF NOT EXISTS task_events_difficulty
+AFTER UPDATE OF difficulty ON tasks
+WHEN OLD.difficulty IS NOT NEW.difficulty
+BEGIN
+    INSERT INTO task_events (task_id, kind, field, old_value, new_value, source)
+    VALUES (NEW.id, 'updated', 'difficulty', OLD.difficulty, NEW.difficulty, COALESCE((SELECT source FROM task_event_context WHERE id = 1), 'user'));
+END;
+
+CREATE TRIGGER IF NOT EXISTS task_events_estimate
+AFTER UPDATE OF estimated_time_minutes ON tasks
+WHEN OLD.estimated_time_minutes IS NOT NEW.estimated_time_minutes
+BEGIN
+    INSERT INTO task_events (task_id, kind, field, old_value, new_value, source)
+    VALUES (NEW.id, 'updated', 'estimate', OLD.estimated_time_minutes, NEW.estimated_time_minutes, COALESCE((SELECT source FROM task_event_context WHERE id = 1), 'user'));
+END;
+
+CREATE TRIGGER IF NOT EXISTS task_events_project
+AFTER UPDATE OF project_id ON tasks
+WHEN OLD.project_id IS NOT NEW.project_id
+BEGIN
+    INSERT INTO task_events (task_id, kind, field, old_value, new_value, source)
+    VALUES (NEW.id, 'updated', 'project', (SELECT name FROM projects WHERE id = OLD.project_id), (SELECT name FROM projects WHERE id = NEW.project_id), COALESCE((SELECT source FROM task_event_context WHERE id = 1), 'user'));
+END;
+
+CREATE TRIGGER IF NOT EXISTS task_events_parent
+AFTER UPDATE OF parent_task_id ON tasks
+WHEN OLD.parent_task_id IS NOT NEW.parent_task_id
+BEGIN
+    INSERT INTO task_events (task_id, kind, field, old_value, new_value, source)
+    VALUES (NEW.id, 'updated', 'parent', (SELECT title FROM tasks WHERE id = OLD.parent_task_id), (SELECT title FROM tasks WHERE id = NEW.parent_task_id), COALESCE((SELECT source FROM task_event_context WHERE id = 1), 'user'));
+END;
+
+CREATE TRIGGER IF NOT EXISTS task_events_tagged
+AFTER INSERT ON task_tags
+BEGIN
+    INSERT INTO task_events (task_id, kind, new_value, source)
+    VALUES (NEW.task_id, 'tagged', (SELECT name FROM tags WHERE id = NEW.tag_id), COALESCE((SELECT source FROM task_event_context WHERE id = 1), 'user'));
+END;
+
+CREATE TRIGGER IF NOT EXISTS task_events_untagged
+AFTER DELETE ON task_tags
+BEGIN
+    INSERT INTO task_events (task_id, kind, old_value, source)
+    VALUES (OLD.task_id, 'untagged', (SELECT name FROM tags WHERE id = OLD.tag_id), COALESCE((SELECT source FROM task_event_context WHERE id = 1), 'user'));
+END;
+
+-- Existing tasks start their history with when they were created and completed
+INSERT INTO task_events (task_id, kind, new_value, created_at)
+SELECT id, 'created', title, COALESCE(created_at, CURRENT_TIMESTAMP) FROM tasks;
+
+INSERT INTO task_events (task_id, kind, old_value, new_value, created_at)
+SELECT id, 'completed', 'active', 'completed', completed_at FROM tasks WHERE status = 'completed' AND completed_at IS NOT NULL;
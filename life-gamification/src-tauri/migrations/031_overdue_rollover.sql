@@ -0,0 +1,41 @@
+-- Migration 031: Overdue rollover policies
+-- What the daily sweep does with tasks left overdue: roll their due date to
+-- today, decay their XP reward a percentage per overdue day down to a floor,
+-- and/or archive them after some days. A policy applies to one project or one
+-- category; the policy with neither is the default (all off out of the box).
+-- Project policies win over category policies, which win over the default.
+
+CREATE TABLE IF NOT EXISTS rollover_policies (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    project_id INTEGER,
+    category TEXT,                                    -- lowercase
+    roll_to_today INTEGER NOT NULL DEFAULT 0,
+    decay_percent_per_day REAL NOT NULL DEFAULT 0,    -- of the reward before any decay; 0 = off
+    decay_floor_percent REAL NOT NULL DEFAULT 50,     -- decay never goes below this share
+    archive_after_days INTEGER,                       -- NULL = never
+    updated_at DATETIME DEFAULT CURRENT_TIMESTAMP,
+    CHECK (project_id IS NULL OR category IS NULL),
+    CHECK (decay_percent_per_day BETWEEN 0 AND 100),
+    CHECK (decay_floor_percent BETWEEN 0 AND 100),
+    CHECK (archive_after_days IS NULL OR archive_after_days >= 1),
+    FOREIGN KEY (project_id) REFERENCES projects(id) ON DELETE CASCADE
+);
+
+CREATE UNIQUE INDEX IF NOT EXISTS idx_rollover_policies_scope
+    ON rollover_policies(COALESCE(project_id, 0), COALESCE(category, ''));
+
+INSERT OR IGNORE INTO rollover_policies (id) VALUES (1);
+
+-- Day a task first went overdue, and its XP reward before any decay; cleared
+-- once the task is rescheduled into the future
+ALTER TABLE tasks ADD COLUMN overdue_since DATE;
+ALTER TABLE tasks ADD COLUMN undecayed_xp_reward INTEGER;
+
+CREATE TRIGGER IF NOT EXISTS task_events_xp_reward
+AFTER UPDATE OF base_experience_reward ON tasks
+WHEN OLD.base_experience_reward IS NOT NEW.base_experience_reward
+BEGIN
+    INSERT INTO task_events (task_id, kind, field, old_value, new_value, source)
+    VALUES (NEW.id, 'updated', 'xp_reward', OLD.base_experience_reward, NEW.base_experience_reward,
+            COALESCE((SELECT source FROM task_event_context WHERE id = 1), 'user'));
+END;
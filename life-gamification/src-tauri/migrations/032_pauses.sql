@@ -0,0 +1,22 @@
+-- Migration 032: Vacation mode and paused recurring tasks
+-- A pause covers start_date..end_date (inclusive; NULL end = until resumed) for
+-- one recurring series, or for everything when recurring_task_id is NULL
+-- (vacation). While paused no instances are generated, no overdue damage or
+-- overdue notifications apply, and skipped occurrences don't break streaks.
+-- Rows are never deleted: resuming early shortens end_date and sets ended_at,
+-- and a pause called off before it started is marked cancelled_at.
+
+CREATE TABLE IF NOT EXISTS pauses (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    recurring_task_id INTEGER,                 -- NULL = vacation
+    start_date DATE NOT NULL,
+    end_date DATE,
+    reason TEXT,
+    created_at DATETIME DEFAULT CURRENT_TIMESTAMP,
+    ended_at DATETIME,
+    cancelled_at DATETIME,
+    CHECK (end_date IS NULL OR end_date >= start_date),
+    FOREIGN KEY (recurring_task_id) REFERENCES tasks(id) ON DELETE CASCADE
+);
+
+CREATE INDEX IF NOT EXISTS idx_pauses_recurring_task ON pauses(recurring_task_id);
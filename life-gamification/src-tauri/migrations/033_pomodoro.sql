@@ -0,0 +1,46 @@
+-- Migration 033: Pomodoro engine
+-- Cycle lengths and the XP paid per completed pomodoro, plus the engine's
+-- current phase. Work phases are ordinary 'pomodoro' timers (active_timers +
+-- time_sessions); breaks are logged as 'break' time_sessions on the same task.
+
+CREATE TABLE IF NOT EXISTS pomodoro_settings (
+    id INTEGER PRIMARY KEY CHECK (id = 1),
+    work_minutes INTEGER NOT NULL DEFAULT 25,
+    short_break_minutes INTEGER NOT NULL DEFAULT 5,
+    long_break_minutes INTEGER NOT NULL DEFAULT 15,
+    cycles_per_set INTEGER NOT NULL DEFAULT 4,       -- pomodoros before a long break
+    auto_start_work INTEGER NOT NULL DEFAULT 0,      -- start the next pomodoro when a break ends
+    completion_xp INTEGER NOT NULL DEFAULT 5,
+    updated_at DATETIME DEFAULT CURRENT_TIMESTAMP,
+    CHECK (work_minutes >= 1 AND short_break_minutes >= 1 AND long_break_minutes >= 1),
+    CHECK (cycles_per_set >= 1),
+    CHECK (completion_xp >= 0)
+);
+
+INSERT OR IGNORE INTO pomodoro_settings (id) VALUES (1);
+
+CREATE TABLE IF NOT EXISTS pomodoro_state (
+    id INTEGER PRIMARY KEY CHECK (id = 1),
+    phase TEXT NOT NULL DEFAULT 'idle' CHECK (phase IN ('idle', 'work', 'short_break', 'long_break')),
+    task_id INTEGER,
+    session_id INTEGER,                              -- time_sessions row of the current phase
+    phase_started_at DATETIME,
+    phase_ends_at DATETIME,
+    completed_in_set INTEGER NOT NULL DEFAULT 0,
+    updated_at DATETIME DEFAULT CURRENT_TIMESTAMP
+);
+
+INSERT OR IGNORE INTO pomodoro_state (id) VALUES (1);
+
+-- Breaks are logged against the task but are not time spent on it
+DROP TRIGGER IF EXISTS update_task_total_time;
+CREATE TRIGGER IF NOT EXISTS update_task_total_time
+AFTER UPDATE OF duration_seconds ON time_sessions
+WHEN NEW.duration_seconds IS NOT NULL AND OLD.duration_seconds IS NULL AND NEW.session_type != 'break'
+BEGIN
+    UPDATE tasks
+    SET
+        total_time_spent_seconds = COALESCE(total_time_spent_seconds, 0) + NEW.duration_seconds,
+        last_worked_at = NEW.end_time
+    WHERE id = NEW.task_id;
+END;
@@ -0,0 +1,34 @@
+-- Migration 034: Crash-safe timers
+-- The frontend sends a heartbeat while the app runs. A timer whose heartbeat
+-- went stale over a restart is held for review (stop at the last heartbeat,
+-- keep the full duration, or discard); a timer with no heartbeat for
+-- idle_pause_minutes while the app runs is paused as of its last heartbeat.
+-- Paused time is kept on the session and no longer counts towards its duration.
+
+ALTER TABLE active_timers ADD COLUMN last_heartbeat_at DATETIME;    -- NULL = start_time
+ALTER TABLE active_timers ADD COLUMN needs_review INTEGER NOT NULL DEFAULT 0;
+ALTER TABLE active_timers ADD COLUMN auto_paused INTEGER NOT NULL DEFAULT 0;
+
+ALTER TABLE time_sessions ADD COLUMN paused_seconds INTEGER NOT NULL DEFAULT 0;
+
+CREATE TABLE IF NOT EXISTS timer_settings (
+    id INTEGER PRIMARY KEY CHECK (id = 1),
+    idle_pause_minutes INTEGER DEFAULT 10,            -- NULL = never auto-pause
+    stale_after_minutes INTEGER NOT NULL DEFAULT 5,
+    updated_at DATETIME DEFAULT CURRENT_TIMESTAMP,
+    CHECK (idle_pause_minutes IS NULL OR idle_pause_minutes >= 1),
+    CHECK (stale_after_minutes >= 1)
+);
+
+INSERT OR IGNORE INTO timer_settings (id) VALUES (1);
+
+DROP TRIGGER IF EXISTS calculate_session_duration;
+CREATE TRIGGER IF NOT EXISTS calculate_session_duration
+AFTER UPDATE OF end_time ON time_sessions
+WHEN NEW.end_time IS NOT NULL AND OLD.end_time IS NULL
+BEGIN
+    UPDATE time_sessions
+    SET duration_seconds = MAX(0,
+        CAST((julianday(NEW.end_time) - julianday(NEW.start_time)) * 86400 AS INTEGER) - NEW.paused_seconds)
+    WHERE id = NEW.id;
+END;
@@ -0,0 +1,9 @@
+-- Migration 035: Tasks in the trash
+-- Deleting tasks moves them to the trash like projects and accounts: a task,
+-- its subtasks and its recurring instances get the same deleted_at and come
+-- back together. Listings and background jobs skip trashed tasks
+-- (trash::NOT_TRASHED); they are purged for good after the retention period.
+
+ALTER TABLE tasks ADD COLUMN deleted_at DATETIME;
+
+CREATE INDEX IF NOT EXISTS idx_tasks_deleted_at ON tasks(deleted_at) WHERE deleted_at IS NOT NULL;
@@ -0,0 +1,381 @@
+// Task activity history and comments.
+//
+// task_events is written by triggers (migration 030), so every command that
+// creates, edits, completes, reopens, archives or tags a task leaves a record
+// without having to remember to. Syncs acting on the user's behalf wrap their
+// writes in with_source so those events say where the change came from.
+// get_task_timeline merges the events with comments, time sessions and goal
+// progress into one chronological list.
+
+use rusqlite::Connection;
+use serde::Serialize;
+use tauri::State;
+
+use crate::database::DbConnection;
+
+pub const GITHUB: &str = "github";
+pub const REMINDERS: &str = "reminders";
+pub const CALENDAR: &str = "calendar";
+pub const RECURRING: &str = "recurring";
+pub const ROLLOVER: &str = "rollover";
+pub const CAPTURE: &str = "capture";
+
+const MAX_COMMENT_LEN: usize = 10_000;
+
+// ---------- Types ----------
+
+#[derive(Debug, Clone, Serialize)]
+pub struct TaskComment {
+    pub id: i64,
+    pub task_id: i64,
+    pub body: String,
+    pub created_at: String,
+    pub updated_at: Option<String>,
+}
+
+/// One entry of a task's timeline. `at` is always `YYYY-MM-DD HH:MM:SS` UTC.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum TimelineEntry {
+    Event {
+        id: i64,
+        kind: String,
+        field: Option<String>,
+        old_value: Option<String>,
+        new_value: Option<String>,
+        source: String,
+        at: String,
+    },
+    Comment {
+        id: i64,
+        body: String,
+        edited_at: Option<String>,
+        at: String,
+    },
+    TimeSession {
+        id: i64,
+        session_type: Option<String>,
+        end_time: Option<String>,
+        duration_seconds: Option<i64>,
+        notes: Option<String>,
+        at: String,
+    },
+    Progress {
+        id: i64,
+        delta: i64,
+        progress_after: i64,
+        kind: String,
+        note: Option<String>,
+        at: String,
+    },
+}
+
+impl TimelineEntry {
+    fn at(&self) -> &str {
+        match self {
+            TimelineEntry::Event { at, .. }
+            | TimelineEntry::Comment { at, .. }
+            | TimelineEntry::TimeSession { at, .. }
+            | TimelineEntry::Progress { at, .. } => at,
+        }
+    }
+}
+
+// ---------- Event source ----------
+
+/// Marks task changes as made by `source` (e.g. GITHUB) until dropped.
+pub struct SourceGuard<'a> {
+    conn: &'a Connection,
+}
+
+impl Drop for SourceGuard<'_> {
+    fn drop(&mut self) {
+        if let Err(e) = self.conn.execute("UPDATE task_event_context SET source = NULL WHERE id = 1", []) {
+            eprintln!("Failed to reset task event source: {}", e);
+        }
+    }
+}
+
+/// Attribute the task events written through `conn` to `source` for as long
+/// as the returned guard lives.
+pub fn with_source<'a>(conn: &'a Connection, source: &str) -> Result<SourceGuard<'a>, String> {
+    conn.execute("UPDATE task_event_context SET source = ?1 WHERE id = 1", [source])
+        .map_err(|e| format!("Failed to set task event source: {}", e))?;
+    Ok(SourceGuard { conn })
+}
+
+// ---------- Comments ----------
+
+fn clean_body(body: &str) -> Result<String, String> {
+    let body = body.trim();
+    if body.is_empty() {
+        return Err("Comment cannot be empty".to_string());
+    }
+    if body.len() > MAX_COMMENT_LEN {
+        return Err(format!("Comment is too long (max {} characters)", MAX_COMMENT_LEN));
+    }
+    Ok(body.to_string())
+}
+
+fn load_comment(conn: &Connection, comment_id: i64) -> Result<TaskComment, String> {
+    conn.query_row(
+        "SELECT id, task_id, body, created_at, updated_at FROM task_comments WHERE id = ?1",
+        [comment_id],
+        |row| {
+            Ok(TaskComment {
+                id: row.get(0)?,
+                task_id: row.get(1)?,
+                body: row.get(2)?,
+                created_at: row.get(3)?,
+                updated_at: row.get(4)?,
+            })
+        },
+    )
+    .map_err(|e| format!("Comment not found: {}", e))
+}
+
+pub fn add_comment(conn: &Connection, task_id: i64, body: &str) -> Result<TaskComment, String> {
+    let body = clean_body(body)?;
+    conn.query_row("SELECT 1 FROM tasks WHERE id = ?1 AND user_id = 1", [task_id], |_| Ok(()))
+        .map_err(|e| format!("Task not found: {}", e))?;
+    conn.execute(
+        "INSERT INTO task_comments (task_id, body) VALUES (?1, ?2)",
+        rusqlite::params![task_id, body],
+    )
+    .map_err(|e| format!("Failed to add comment: {}", e))?;
+    load_comment(conn, conn.last_insert_rowid())
+}
+
+// ---------- Timeline ----------
+
+/// Everything that happened to a task, oldest first. Entries at the same
+/// second keep the order events, comments, sessions, progress.
+pub fn timeline(conn: &Connection, task_id: i64) -> Result<Vec<TimelineEntry>, String> {
+    conn.query_row("SELECT 1 FROM tasks WHERE id = ?1 AND user_id = 1", [task_id], |_| Ok(()))
+        .map_err(|e| format!("Task not found: {}", e))?;
+
+    let mut entries = Vec::new();
+    let mut collect = |sql: &str, map: fn(&rusqlite::Row) -> rusqlite::Result<TimelineEntry>| -> Result<(), String> {
+        let mut stmt = conn.prepare(sql).map_err(|e| format!("Failed to prepare timeline query: {}", e))?;
+        let rows = stmt
+            .query_map([task_id], map)
+            .map_err(|e| format!("Failed to get timeline: {}", e))?
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|e| format!("Failed to read timeline: {}", e))?;
+        entries.extend(rows);
+        Ok(())
+    };
+
+    collect(
+        "SELECT id, kind, field, old_value, new_value, source, datetime(created_at)
+         FROM task_events WHERE task_id = ?1 ORDER BY id",
+        |row| {
+            Ok(TimelineEntry::Event {
+                id: row.get(0)?,
+                kind: row.get(1)?,
+                field: row.get(2)?,
+                old_value: row.get(3)?,
+                new_value: row.get(4)?,
+                source: row.get(5)?,
+                at: row.get(6)?,
+            })
+        },
+    )?;
+    collect(
+        "SELECT id, body, datetime(updated_at), datetime(created_at)
+         FROM task_comments WHERE task_id = ?1 ORDER BY id",
+        |row| {
+            Ok(TimelineEntry::Comment {
+                id: row.get(0)?,
+                body: row.get(1)?,
+                edited_at: row.get(2)?,
+                at: row.get(3)?,
+            })
+        },
+    )?;
+    collect(
+        "SELECT id, session_type, datetime(end_time), duration_seconds, notes, datetime(start_time)
+         FROM time_sessions WHERE task_id = ?1 ORDER BY id",
+        |row| {
+            Ok(TimelineEntry::TimeSession {
+                id: row.get(0)?,
+                session_type: row.get(1)?,
+                end_time: row.get(2)?,
+                duration_seconds: row.get(3)?,
+                notes: row.get(4)?,
+                at: row.get(5)?,
+            })
+        },
+    )?;
+    collect(
+        "SELECT id, delta, progress_after, kind, note, datetime(created_at)
+         FROM goal_progress_log WHERE task_id = ?1 ORDER BY id",
+        |row| {
+            Ok(TimelineEntry::Progress {
+                id: row.get(0)?,
+                delta: row.get(1)?,
+                progress_after: row.get(2)?,
+                kind: row.get(3)?,
+                note: row.get(4)?,
+                at: row.get(5)?,
+            })
+        },
+    )?;
+
+    // Stable, so same-second entries keep their per-source order
+    entries.sort_by(|a, b| a.at().cmp(b.at()));
+    Ok(entries)
+}
+
+// ---------- Commands ----------
+
+#[tauri::command]
+pub async fn add_task_comment(db: State<'_, DbConnection>, task_id: i64, body: String) -> Result<TaskComment, String> {
+    let conn = db.lock().await;
+    add_comment(&conn, task_id, &body)
+}
+
+#[tauri::command]
+pub async fn update_task_comment(db: State<'_, DbConnection>, comment_id: i64, body: String) -> Result<TaskComment, String> {
+    let body = clean_body(&body)?;
+    let conn = db.lock().await;
+    let updated = conn
+        .execute(
+            "UPDATE task_comments SET body = ?1, updated_at = CURRENT_TIMESTAMP WHERE id = ?2",
+            rusqlite::params![body, comment_id],
+        )
+        .map_err(|e| format!("Failed to update comment: {}", e))?;
+    if updated == 0 {
+        return Err(format!("Comment {} not found", comment_id));
+    }
+    load_comment(&conn, comment_id)
+}
+
+#[tauri::command]
+pub async fn delete_task_comment(db: State<'_, DbConnection>, comment_id: i64) -> Result<(), String> {
+    let conn = db.lock().await;
+    conn.execute("DELETE FROM task_comments WHERE id = ?1", [comment_id])
+        .map_err(|e| format!("Failed to delete comment: {}", e))?;
+    Ok(())
+}
+
+/// A task's events, comments, time sessions and goal progress, oldest first.
+#[tauri::command]
+pub async fn get_task_timeline(db: State<'_, DbConnection>, task_id: i64) -> Result<Vec<TimelineEntry>, String> {
+    let conn = db.lock().await;
+    timeline(&conn, task_id)
+}
+
+// ---------- Tests ----------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn test_conn() -> Connection {
+        let conn = crate::database::test_conn();
+        conn.execute_batch(
+            "INSERT INTO projects (id, user_id, name) VALUES (100, 1, 'Home');
+             INSERT INTO tasks (id, user_id, title, status) VALUES (1, 1, 'Fix sink', 'active');",
+        )
+        .unwrap();
+        conn
+    }
+
+    fn events(conn: &Connection) -> Vec<(String, Option<String>, Option<String>, Option<String>, String)> {
+        timeline(conn, 1)
+            .unwrap()
+            .into_iter()
+            .filter_map(|entry| match entry {
+                TimelineEntry::Event { kind, field, old_value, new_value, source, .. } => {
+                    Some((kind, field, old_value, new_value, source))
+                }
+                _ => None,
+            })
+            .collect()
+    }
+
+    fn event(kind: &str, field: Option<&str>, old: Option<&str>, new: Option<&str>, source: &str)
+        -> (String, Option<String>, Option<String>, Option<String>, String) {
+        (
+            kind.to_string(),
+            field.map(str::to_string),
+            old.map(str::to_string),
+            new.map(str::to_string),
+            source.to_string(),
+        )
+    }
+
+    #[test]
+    fn task_changes_are_recorded_with_their_source() {
+        let conn = test_conn();
+        conn.execute_batch(
+            "UPDATE tasks SET due_date = '2026-08-01' WHERE id = 1;
+             UPDATE tasks SET project_id = 100 WHERE id = 1;
+             UPDATE tasks SET priority = priority WHERE id = 1;",
+        )
+        .unwrap();
+        {
+            let _source = with_source(&conn, GITHUB).unwrap();
+            conn.execute("UPDATE tasks SET title = 'Fix kitchen sink' WHERE id = 1", []).unwrap();
+        }
+        conn.execute_batch(
+            "UPDATE tasks SET status = 'completed' WHERE id = 1;
+             UPDATE tasks SET status = 'active' WHERE id = 1;",
+        )
+        .unwrap();
+
+        assert_eq!(
+            events(&conn),
+            vec![
+                event("created", None, None, Some("Fix sink"), "user"),
+                event("updated", Some("due_date"), None, Some("2026-08-01"), "user"),
+                event("updated", Some("project"), None, Some("Home"), "user"),
+                event("updated", Some("title"), Some("Fix sink"), Some("Fix kitchen sink"), GITHUB),
+                event("completed", None, Some("active"), Some("completed"), "user"),
+                event("reopened", None, Some("completed"), Some("active"), "user"),
+            ]
+        );
+    }
+
+    #[test]
+    fn timeline_merges_comments_and_sessions_in_order() {
+        let conn = test_conn();
+        conn.execute_batch(
+            "UPDATE task_events SET created_at = '2026-05-01 09:00:00' WHERE task_id = 1;
+             INSERT INTO time_sessions (id, task_id, start_time, end_time, session_type)
+             VALUES (7, 1, '2026-05-01T10:00:00+00:00', '2026-05-01T10:25:00+00:00', 'focus');",
+        )
+        .unwrap();
+        let comment = add_comment(&conn, 1, "  Needs a new washer ").unwrap();
+        assert_eq!(comment.body, "Needs a new washer");
+        conn.execute("UPDATE task_comments SET created_at = '2026-05-01 09:30:00' WHERE id = ?1", [comment.id])
+            .unwrap();
+
+        let kinds: Vec<(&str, String)> = timeline(&conn, 1)
+            .unwrap()
+            .iter()
+            .map(|entry| {
+                let kind = match entry {
+                    TimelineEntry::Event { .. } => "event",
+                    TimelineEntry::Comment { .. } => "comment",
+                    TimelineEntry::TimeSession { .. } => "time_session",
+                    TimelineEntry::Progress { .. } => "progress",
+                };
+                (kind, entry.at().to_string())
+            })
+            .collect();
+        assert_eq!(
+            kinds,
+            vec![
+                ("event", "2026-05-01 09:00:00".to_string()),
+                ("comment", "2026-05-01 09:30:00".to_string()),
+                ("time_session", "2026-05-01 10:00:00".to_string()),
+            ]
+        );
+
+        assert!(add_comment(&conn, 1, "   ").is_err());
+        assert!(add_comment(&conn, 99, "Hello").is_err());
+        assert!(timeline(&conn, 99).is_err());
+    }
+}
@@ -0,0 +1,395 @@
+// Bulk task operations.
+//
+// bulk_update_tasks applies one action to many tasks, picked either by id or by
+// a view query (see views.rs), e.g. `source:github status:active`. The whole batch
+// runs in one transaction: every task gets a result in the report, and if any
+// of them fails nothing is applied. Deleted tasks go to the trash (with their
+// subtasks and recurring instances) and the report carries an undo token.
+
+use std::collections::HashSet;
+
+use chrono::{Duration, NaiveDate, Utc};
+use rusqlite::{Connection, OptionalExtension};
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, State};
+
+use crate::commands::trash::{self, TrashKind, UndoAction, UndoToken};
+use crate::commands::{progression, subtasks, views};
+use crate::database::DbConnection;
+
+/// Most tasks one bulk operation may touch.
+const MAX_BATCH: usize = 1000;
+
+// ---------- Types ----------
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum BulkAction {
+    /// Complete with the usual rewards, streaks and parent auto-completion.
+    Complete,
+    Archive,
+    Delete,
+    /// Move to a project, or out of any project with None.
+    MoveToProject { project_id: Option<i64> },
+    SetCategory { category: String },
+    /// Move due dates by a number of days (negative is earlier). Tasks without a
+    /// due date are left alone.
+    ShiftDueDate { days: i64 },
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct BulkTaskResult {
+    pub task_id: i64,
+    pub success: bool,
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct BulkReport {
+    /// False when any task failed and the whole batch was rolled back.
+    pub applied: bool,
+    pub succeeded: usize,
+    pub failed: usize,
+    pub results: Vec<BulkTaskResult>,
+    /// Set when tasks were moved to the trash.
+    pub undo_token: Option<UndoToken>,
+}
+
+// ---------- Pure helpers (unit-tested) ----------
+
+/// Shift the date part of a stored due date ("YYYY-MM-DD", optionally followed
+/// by a time) by `days`, keeping whatever follows it.
+pub fn shift_due_date(due_date: &str, days: i64) -> Option<String> {
+    let date = NaiveDate::parse_from_str(due_date.get(..10)?, "%Y-%m-%d").ok()?;
+    let shifted = date.checked_add_signed(Duration::days(days))?;
+    Some(format!("{}{}", shifted.format("%Y-%m-%d"), &due_date[10..]))
+}
+
+// ---------- Persistence ----------
+
+/// Remove a task and the rows that hang off it for good (trash.rs calls this
+/// when purging). Subtasks are kept and detached; instances of a recurring task
+/// go with it.
+pub fn delete_task_sync(conn: &Connection, task_id: i64) -> Result<(), String> {
+    let mut stmt = conn
+        .prepare("SELECT id FROM tasks WHERE parent_task_id = ?1")
+        .map_err(|e| format!("Failed to prepare query: {}", e))?;
+    let children = stmt
+        .query_map([task_id], |row| row.get(0))
+        .map_err(|e| format!("Failed to get subtasks: {}", e))?
+        .collect::<Result<Vec<i64>, _>>()
+        .map_err(|e| format!("Failed to read subtasks: {}", e))?;
+    for child in children {
+        subtasks::set_parent(conn, child, None)?;
+    }
+    subtasks::set_parent(conn, task_id, None)?;
+
+    let mut stmt = conn
+        .prepare("SELECT id FROM tasks WHERE parent_recurring_task_id = ?1")
+        .map_err(|e| format!("Failed to prepare query: {}", e))?;
+    let instances = stmt
+        .query_map([task_id], |row| row.get(0))
+        .map_err(|e| format!("Failed to get recurring instances: {}", e))?
+        .collect::<Result<Vec<i64>, _>>()
+        .map_err(|e| format!("Failed to read recurring instances: {}", e))?;
+    for instance in instances {
+        delete_task_sync(conn, instance)?;
+    }
+
+    for sql in [
+        "DELETE FROM task_dependencies WHERE task_id = ?1 OR blocked_by_task_id = ?1",
+        "DELETE FROM task_tags WHERE task_id = ?1",
+        "DELETE FROM task_progress WHERE task_id = ?1",
+        "DELETE FROM task_completions WHERE task_id = ?1",
+        "DELETE FROM goal_progress_log WHERE task_id = ?1",
+        "DELETE FROM goal_milestones WHERE task_id = ?1",
+        "DELETE FROM active_timers WHERE task_id = ?1",
+        "DELETE FROM time_sessions WHERE task_id = ?1",
+        "DELETE FROM scheduled_notifications WHERE task_id = ?1",
+        "DELETE FROM recurring_task_instances WHERE recurring_task_id = ?1 OR instance_task_id = ?1",
+        "UPDATE notification_history SET task_id = NULL WHERE task_id = ?1",
+        "UPDATE damage_events SET task_id = NULL WHERE task_id = ?1",
+        "DELETE FROM tasks WHERE id = ?1",
+        // Last, after the triggers above have logged their own events
+        "DELETE FROM task_events WHERE task_id = ?1",
+        "DELETE FROM task_comments WHERE task_id = ?1",
+    ] {
+        conn.execute(sql, [task_id])
+            .map_err(|e| format!("Failed to delete task {}: {}", task_id, e))?;
+    }
+    Ok(())
+}
+
+/// Apply `action` to one task. Returns the level-up a completion caused.
+fn apply(conn: &Connection, task_id: i64, action: &BulkAction) -> Result<Option<progression::LevelUp>, String> {
+    let (due_date, trashed): (Option<String>, bool) = conn
+        .query_row(
+            "SELECT due_date, deleted_at IS NOT NULL FROM tasks WHERE id = ?1 AND user_id = 1",
+            [task_id],
+            |row| Ok((row.get(0)?, row.get(1)?)),
+        )
+        .optional()
+        .map_err(|e| format!("Failed to get task: {}", e))?
+        .ok_or_else(|| format!("Task {} not found", task_id))?;
+    if trashed {
+        // Subtasks and instances of a task deleted earlier in this batch went with it
+        return match action {
+            BulkAction::Delete => Ok(None),
+            _ => Err(format!("Task {} is in the trash", task_id)),
+        };
+    }
+
+    let update = |sql: &str, value: &dyn rusqlite::ToSql| -> Result<(), String> {
+        conn.execute(sql, rusqlite::params![value, task_id])
+            .map(|_| ())
+            .map_err(|e| format!("Failed to update task {}: {}", task_id, e))
+    };
+    match action {
+        BulkAction::Complete => return crate::complete_task_sync(conn, task_id),
+        BulkAction::Archive => update("UPDATE tasks SET status = ?1 WHERE id = ?2", &"archived")?,
+        BulkAction::Delete => {
+            trash::trash_item(conn, TrashKind::Task, task_id)?;
+        }
+        BulkAction::MoveToProject { project_id } => update("UPDATE tasks SET project_id = ?1 WHERE id = ?2", project_id)?,
+        BulkAction::SetCategory { category } => update("UPDATE tasks SET category = ?1 WHERE id = ?2", &category.trim())?,
+        BulkAction::ShiftDueDate { days } => {
+            if let Some(due_date) = due_date {
+                let shifted = shift_due_date(&due_date, *days)
+                    .ok_or_else(|| format!("Task {} has an unreadable due date \"{}\"", task_id, due_date))?;
+                update("UPDATE tasks SET due_date = ?1 WHERE id = ?2", &shifted)?;
+            }
+        }
+    }
+    Ok(None)
+}
+
+/// Which of `task_ids` are already in the trash.
+fn trashed_tasks(conn: &Connection, task_ids: &[i64]) -> Result<HashSet<i64>, String> {
+    let ids = serde_json::to_string(task_ids).map_err(|e| format!("Failed to encode ids: {}", e))?;
+    let mut stmt = conn
+        .prepare("SELECT id FROM tasks WHERE deleted_at IS NOT NULL AND id IN (SELECT value FROM json_each(?1))")
+        .map_err(|e| format!("Failed to prepare query: {}", e))?;
+    let trashed = stmt
+        .query_map([ids], |row| row.get(0))
+        .map_err(|e| format!("Failed to get trashed tasks: {}", e))?
+        .collect::<Result<HashSet<i64>, _>>()
+        .map_err(|e| format!("Failed to read trashed tasks: {}", e))?;
+    Ok(trashed)
+}
+
+/// Check the action itself before touching any task.
+fn validate(conn: &Connection, action: &BulkAction) -> Result<(), String> {
+    match action {
+        BulkAction::SetCategory { category } if category.trim().is_empty() => {
+            Err("Category cannot be empty".to_string())
+        }
+        BulkAction::MoveToProject { project_id: Some(project_id) } => conn
+            .query_row("SELECT 1 FROM projects WHERE id = ?1 AND user_id = 1 AND deleted_at IS NULL", [project_id], |_| Ok(()))
+            .optional()
+            .map_err(|e| format!("Failed to get project: {}", e))?
+            .ok_or_else(|| format!("Project {} not found", project_id)),
+        _ => Ok(()),
+    }
+}
+
+/// Run a batch inside the caller's transaction. The caller commits only if the
+/// report says it was applied.
+pub fn bulk_update_sync(
+    conn: &Connection,
+    task_ids: &[i64],
+    action: &BulkAction,
+) -> Result<(BulkReport, Option<progression::LevelUp>), String> {
+    validate(conn, action)?;
+    let trashed_before = trashed_tasks(conn, task_ids)?;
+
+    let mut results = Vec::new();
+    let mut level_up = None;
+    for &task_id in task_ids {
+        if trashed_before.contains(&task_id) {
+            results.push(BulkTaskResult { task_id, success: false, error: Some(format!("Task {} is in the trash", task_id)) });
+            continue;
+        }
+        // A savepoint per task undoes a failed task's partial writes, so the
+        // tasks after it still run against a consistent state
+        let outcome = conn
+            .execute_batch("SAVEPOINT bulk_task")
+            .map_err(|e| format!("Failed to start savepoint: {}", e))
+            .and_then(|_| apply(conn, task_id, action));
+        let release = if outcome.is_ok() { "RELEASE bulk_task" } else { "ROLLBACK TO bulk_task; RELEASE bulk_task" };
+        conn.execute_batch(release)
+            .map_err(|e| format!("Failed to end savepoint: {}", e))?;
+
+        match outcome {
+            Ok(task_level_up) => {
+                level_up = progression::combine(level_up, task_level_up);
+                results.push(BulkTaskResult { task_id, success: true, error: None });
+            }
+            Err(e) => results.push(BulkTaskResult { task_id, success: false, error: Some(e) }),
+        }
+    }
+
+    let failed = results.iter().filter(|r| !r.success).count();
+    let undo_token = match action {
+        BulkAction::Delete if failed == 0 && !task_ids.is_empty() => Some(trash::issue_undo(
+            conn,
+            &UndoAction::RestoreTasks { ids: task_ids.to_vec() },
+            &format!("{} task(s) moved to trash", task_ids.len()),
+            Utc::now().naive_utc(),
+        )?),
+        _ => None,
+    };
+    let report = BulkReport {
+        applied: failed == 0,
+        succeeded: results.len() - failed,
+        failed,
+        results,
+        undo_token,
+    };
+    Ok((report, level_up))
+}
+
+// ---------- Commands ----------
+
+/// Apply one action to the tasks in `task_ids`, or to those matching `filter`
+/// (a view query). All-or-nothing; see BulkReport.
+#[tauri::command]
+pub async fn bulk_update_tasks(
+    app: AppHandle,
+    db: State<'_, DbConnection>,
+    task_ids: Option<Vec<i64>>,
+    filter: Option<String>,
+    action: BulkAction,
+) -> Result<BulkReport, String> {
+    let (report, level_up) = {
+        let conn = db.lock().await;
+        let mut task_ids = match (task_ids, filter) {
+            (Some(ids), None) => ids,
+            (None, Some(filter)) => views::matching_task_ids(&conn, &filter, Utc::now().naive_utc().date())?,
+            _ => return Err("Give either task ids or a filter".to_string()),
+        };
+        let mut seen = HashSet::new();
+        task_ids.retain(|id| seen.insert(*id));
+        if task_ids.len() > MAX_BATCH {
+            return Err(format!("Too many tasks ({}); the limit is {}", task_ids.len(), MAX_BATCH));
+        }
+
+        let tx = conn.unchecked_transaction()
+            .map_err(|e| format!("Failed to begin transaction: {}", e))?;
+        let (report, level_up) = bulk_update_sync(&tx, &task_ids, &action)?;
+        if !report.applied {
+            // Dropping the transaction rolls everything back
+            return Ok(report);
+        }
+        tx.commit()
+            .map_err(|e| format!("Failed to commit: {}", e))?;
+        (report, level_up)
+    };
+    progression::emit_level_up(&app, level_up);
+    Ok(report)
+}
+
+// ---------- Tests ----------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn test_conn() -> Connection {
+        let conn = crate::database::test_conn();
+        conn.execute_batch(
+            "INSERT INTO tasks (id, user_id, title, status, category, difficulty, base_experience_reward, gold_reward, due_date)
+             VALUES (1, 1, 'One', 'active', 'work', 3, 20, 10, '2026-07-01');
+             INSERT INTO tasks (id, user_id, title, status, category, difficulty, base_experience_reward, gold_reward, due_date)
+             VALUES (2, 1, 'Two', 'active', 'work', 3, 20, 10, '2026-07-30T09:00:00Z');
+             INSERT INTO tasks (id, user_id, title, status, category, difficulty, base_experience_reward, gold_reward)
+             VALUES (3, 1, 'Three', 'active', 'home', 3, 20, 10);",
+        )
+        .unwrap();
+        conn
+    }
+
+    fn column(conn: &Connection, sql: &str) -> Vec<Option<String>> {
+        let mut stmt = conn.prepare(sql).unwrap();
+        stmt.query_map([], |row| row.get(0)).unwrap().collect::<Result<_, _>>().unwrap()
+    }
+
+    #[test]
+    fn shifts_the_date_part_only() {
+        assert_eq!(shift_due_date("2026-07-30", 3).as_deref(), Some("2026-08-02"));
+        assert_eq!(shift_due_date("2026-07-01T09:00:00Z", -1).as_deref(), Some("2026-06-30T09:00:00Z"));
+        assert_eq!(shift_due_date("someday", 1), None);
+    }
+
+    #[test]
+    fn applies_to_every_task() {
+        let conn = test_conn();
+        let (report, _) = bulk_update_sync(&conn, &[1, 2, 3], &BulkAction::ShiftDueDate { days: 2 }).unwrap();
+        assert!(report.applied);
+        assert_eq!(report.succeeded, 3);
+        assert_eq!(
+            column(&conn, "SELECT due_date FROM tasks ORDER BY id"),
+            vec![Some("2026-07-03".to_string()), Some("2026-08-01T09:00:00Z".to_string()), None]
+        );
+
+        let (report, _) = bulk_update_sync(&conn, &[1, 2], &BulkAction::Complete).unwrap();
+        assert!(report.applied);
+        assert_eq!(
+            column(&conn, "SELECT status FROM tasks ORDER BY id"),
+            vec![Some("completed".to_string()), Some("completed".to_string()), Some("active".to_string())]
+        );
+
+        crate::commands::tags::add_tags(&conn, 3, &["x".to_string()]).unwrap();
+        let (report, _) = bulk_update_sync(&conn, &[3], &BulkAction::Delete).unwrap();
+        assert!(column(&conn, "SELECT title FROM tasks WHERE id = 3 AND deleted_at IS NULL").is_empty());
+        let undo = report.undo_token.expect("bulk delete can be undone");
+        trash::redeem_undo(&conn, &undo.token, Utc::now().naive_utc()).unwrap();
+        assert_eq!(column(&conn, "SELECT title FROM tasks WHERE deleted_at IS NULL AND id = 3"), vec![Some("Three".to_string())]);
+
+        // Purging the trash removes the task and what hangs off it
+        bulk_update_sync(&conn, &[3], &BulkAction::Delete).unwrap();
+        trash::purge_item(&conn, TrashKind::Task, 3).unwrap();
+        assert!(column(&conn, "SELECT title FROM tasks WHERE id = 3").is_empty());
+        assert!(column(&conn, "SELECT CAST(task_id AS TEXT) FROM task_tags").is_empty());
+    }
+
+    #[test]
+    fn deletes_subtasks_with_their_parent() {
+        let conn = test_conn();
+        conn.execute("UPDATE tasks SET parent_task_id = 1 WHERE id = 2", []).unwrap();
+        let (report, _) = bulk_update_sync(&conn, &[1, 2], &BulkAction::Delete).unwrap();
+        assert!(report.applied);
+        assert_eq!(column(&conn, "SELECT title FROM tasks WHERE deleted_at IS NULL"), vec![Some("Three".to_string())]);
+
+        // Already in the trash: nothing to delete
+        let (report, _) = bulk_update_sync(&conn, &[2], &BulkAction::Delete).unwrap();
+        assert!(!report.applied);
+        assert!(report.undo_token.is_none());
+
+        trash::restore_item(&conn, TrashKind::Task, 1).unwrap();
+        assert_eq!(column(&conn, "SELECT title FROM tasks WHERE deleted_at IS NULL ORDER BY id").len(), 3);
+    }
+
+    #[test]
+    fn reports_failures_per_task() {
+        let conn = test_conn();
+        let (report, _) = bulk_update_sync(&conn, &[1, 99, 3], &BulkAction::Archive).unwrap();
+        assert!(!report.applied);
+        assert_eq!((report.succeeded, report.failed), (2, 1));
+        assert_eq!(report.results[1].task_id, 99);
+        assert!(report.results[1].error.as_deref().unwrap().contains("not found"));
+
+        assert!(bulk_update_sync(&conn, &[1], &BulkAction::MoveToProject { project_id: Some(5) }).is_err());
+        assert!(bulk_update_sync(&conn, &[1], &BulkAction::SetCategory { category: " ".to_string() }).is_err());
+
+        // Only active tasks can be completed; a missing difficulty counts as the default
+        conn.execute_batch(
+            "UPDATE tasks SET status = 'failed' WHERE id = 1;
+             UPDATE tasks SET status = 'active', difficulty = NULL WHERE id = 3;",
+        )
+        .unwrap();
+        let (report, _) = bulk_update_sync(&conn, &[1, 3], &BulkAction::Complete).unwrap();
+        assert_eq!((report.succeeded, report.failed), (1, 1));
+        assert!(report.results[0].error.as_deref().unwrap().contains("only active tasks"));
+        assert!(report.results[1].success);
+    }
+}
@@ -11,6 +11,7 @@
 use serde::{Deserialize, Serialize};
 use std::process::Command;
 
+use crate::commands::{activity, economy};
 use crate::database::DbConnection;
 
 /// Dedicated calendar that pushed quests are written into (created on demand).
@@ -547,6 +548,7 @@ pub async fn import_calendar_events_as_tasks(
     // 3. Insert tasks for events not seen before (dedup on uid).
     let mut imported = 0i64;
     let conn = db.lock().await;
+    let _source = activity::with_source(&conn, activity::CALENDAR)?;
     for event in events {
         if event.calendar.as_deref() == Some(QUESTS_CALENDAR) {
             continue;
@@ -562,10 +564,9 @@ pub async fn import_calendar_events_as_tasks(
             continue;
         }
 
-        // Same reward formula as create_task with default difficulty 3.
-        let difficulty = 3i32;
-        let base_xp = 10 + (difficulty - 1) * 5;
-        let gold_reward = 1 + (difficulty - 1);
+        // Events carry no difficulty; price them as difficulty 3.
+        let difficulty = 3i64;
+        let (base_xp, gold_reward) = economy::task_rewards(&conn, difficulty)?;
 
         conn.execute(
             "INSERT INTO tasks (user_id, title, description, category, difficulty,
@@ -15,6 +15,8 @@ use serde_json::Value;
 use std::path::{Path, PathBuf};
 use tauri::State;
 
+use crate::commands::trash::{self, UndoAction, UndoToken};
+use crate::commands::{activity, economy, tags};
 use crate::database::DbConnection;
 
 // ---------- Types ----------
@@ -40,6 +42,8 @@ pub struct CaptureScanSummary {
     pub files_scanned: i64,
     pub tasks_created: i64,
     pub errors: Vec<String>,
+    /// Moves the captured tasks to the trash; set when tasks were created.
+    pub undo_token: Option<UndoToken>,
 }
 
 /// A task parsed out of an inbox file, before persistence.
@@ -52,6 +56,10 @@ pub struct ParsedCaptureTask {
     pub difficulty: Option<i64>,
     /// From an `@YYYY-MM-DD` token.
     pub due_date: Option<String>,
+    /// From a `^YYYY-MM-DD` token: hidden until that day.
+    pub start_date: Option<String>,
+    /// Every `#word` token, in order and without duplicates.
+    pub tags: Vec<String>,
 }
 
 // ---------- Parsing (pure functions, unit-tested against fixtures) ----------
@@ -66,9 +74,10 @@ fn parse_due_date(s: &str) -> Option<String> {
 /// Parse one captured line into a task.
 ///
 /// Whitespace-separated tokens are pulled out of the title:
-///   #word       -> category (lowercased; first wins)
+///   #word       -> tag (lowercased); the first one is also the category
 ///   !N          -> difficulty, only if 1 <= N <= 10
 ///   @YYYY-MM-DD -> due date, only if a valid calendar date
+///   ^YYYY-MM-DD -> start date (deferred until then), same rules
 /// Unrecognized/malformed tokens (e.g. `!99`, `@tomorrow`) stay in the title.
 /// Returns None for blank lines or lines that are nothing but tokens.
 pub fn parse_capture_line(line: &str) -> Option<ParsedCaptureTask> {
@@ -76,11 +85,19 @@ pub fn parse_capture_line(line: &str) -> Option<ParsedCaptureTask> {
     let mut category: Option<String> = None;
     let mut difficulty: Option<i64> = None;
     let mut due_date: Option<String> = None;
+    let mut start_date: Option<String> = None;
+    let mut tags: Vec<String> = Vec::new();
 
     for word in line.split_whitespace() {
         if let Some(rest) = word.strip_prefix('#') {
-            if !rest.is_empty() && category.is_none() {
-                category = Some(rest.to_lowercase());
+            if !rest.is_empty() {
+                let tag = rest.to_lowercase();
+                if category.is_none() {
+                    category = Some(tag.clone());
+                }
+                if !tags.contains(&tag) {
+                    tags.push(tag);
+                }
                 continue;
             }
         } else if let Some(rest) = word.strip_prefix('!') {
@@ -99,6 +116,13 @@ pub fn parse_capture_line(line: &str) -> Option<ParsedCaptureTask> {
                     continue;
                 }
             }
+        } else if let Some(rest) = word.strip_prefix('^') {
+            if start_date.is_none() {
+                if let Some(date) = parse_due_date(rest) {
+                    start_date = Some(date);
+                    continue;
+                }
+            }
         }
         title_words.push(word);
     }
@@ -112,6 +136,8 @@ pub fn parse_capture_line(line: &str) -> Option<ParsedCaptureTask> {
         category,
         difficulty,
         due_date,
+        start_date,
+        tags,
     })
 }
 
@@ -135,7 +161,8 @@ pub fn parse_capture_text(content: &str) -> Vec<ParsedCaptureTask> {
         .collect()
 }
 
-/// Parse a JSON inbox document: {"tasks":[{"title", "category"?, "difficulty"?, "due_date"?}]}.
+/// Parse a JSON inbox document:
+/// {"tasks":[{"title", "category"?, "difficulty"?, "due_date"?, "start_date"?, "tags"?: [..]}]}.
 /// Malformed documents error (the file moves to failed/); entries without a
 /// usable title are skipped; out-of-range difficulties are clamped to 1-10.
 pub fn parse_capture_json(content: &str) -> Result<Vec<ParsedCaptureTask>, String> {
@@ -168,6 +195,21 @@ pub fn parse_capture_json(content: &str) -> Result<Vec<ParsedCaptureTask>, Strin
                     .get("due_date")
                     .and_then(|d| d.as_str())
                     .and_then(parse_due_date),
+                start_date: entry
+                    .get("start_date")
+                    .and_then(|d| d.as_str())
+                    .and_then(parse_due_date),
+                tags: entry
+                    .get("tags")
+                    .and_then(|t| t.as_array())
+                    .map(|tags| {
+                        tags.iter()
+                            .filter_map(|t| t.as_str())
+                            .map(|t| t.trim().to_string())
+                            .filter(|t| !t.is_empty())
+                            .collect()
+                    })
+                    .unwrap_or_default(),
             })
         })
         .collect();
@@ -308,6 +350,7 @@ pub async fn scan_capture_inbox(
     db: State<'_, DbConnection>,
 ) -> Result<CaptureScanSummary, String> {
     let mut summary = CaptureScanSummary::default();
+    let mut created_ids: Vec<i64> = Vec::new();
 
     let folder = {
         let conn = db.lock().await;
@@ -409,17 +452,16 @@ pub async fn scan_capture_inbox(
 
         {
             let conn = db.lock().await;
+            let _source = activity::with_source(&conn, activity::CAPTURE)?;
             for task in &tasks {
                 let difficulty = task.difficulty.unwrap_or(3);
-                // Same reward formula as create_task in lib.rs / github.rs.
-                let base_xp = 10 + (difficulty - 1) * 5;
-                let gold_reward = 1 + (difficulty - 1);
+                let (base_xp, gold_reward) = economy::task_rewards(&conn, difficulty)?;
                 let category = task.category.as_deref().unwrap_or("general");
 
                 match conn.execute(
                     "INSERT INTO tasks (user_id, title, description, category, difficulty,
-                         base_experience_reward, gold_reward, due_date, status, priority, task_type)
-                     VALUES (1, ?1, NULL, ?2, ?3, ?4, ?5, ?6, 'active', 3, 'standard')",
+                         base_experience_reward, gold_reward, due_date, status, priority, task_type, start_date)
+                     VALUES (1, ?1, NULL, ?2, ?3, ?4, ?5, ?6, 'active', 3, 'standard', ?7)",
                     rusqlite::params![
                         task.title,
                         category,
@@ -427,9 +469,17 @@ pub async fn scan_capture_inbox(
                         base_xp as i32,
                         gold_reward as i32,
                         task.due_date,
+                        task.start_date.as_ref().map(|date| format!("{} 00:00:00", date)),
                     ],
                 ) {
-                    Ok(_) => created_here += 1,
+                    Ok(_) => {
+                        created_here += 1;
+                        let task_id = conn.last_insert_rowid();
+                        created_ids.push(task_id);
+                        if let Err(e) = tags::add_tags(&conn, task_id, &task.tags) {
+                            summary.errors.push(format!("{}: failed to tag \"{}\": {}", filename, task.title, e));
+                        }
+                    }
                     Err(e) => summary
                         .errors
                         .push(format!("{}: failed to create \"{}\": {}", filename, task.title, e)),
@@ -465,6 +515,14 @@ pub async fn scan_capture_inbox(
             [],
         )
         .map_err(|e| format!("Failed to stamp scan time: {}", e))?;
+        if !created_ids.is_empty() {
+            summary.undo_token = Some(trash::issue_undo(
+                &conn,
+                &UndoAction::TrashTasks { ids: created_ids },
+                &format!("Captured {} task(s)", summary.tasks_created),
+                chrono::Utc::now().naive_utc(),
+            )?);
+        }
     }
 
     Ok(summary)
@@ -506,6 +564,27 @@ mod tests {
         assert_eq!(task.difficulty, Some(2));
     }
 
+    #[test]
+    fn parses_start_dates() {
+        let task = parse_capture_line("Renew passport ^2026-09-01 @2026-10-01 ^2026-09-15").expect("should parse");
+        assert_eq!(task.title, "Renew passport ^2026-09-15");
+        assert_eq!(task.start_date.as_deref(), Some("2026-09-01"));
+        assert_eq!(task.due_date.as_deref(), Some("2026-10-01"));
+        assert_eq!(parse_capture_line("Later ^soon").unwrap().start_date, None);
+    }
+
+    #[test]
+    fn every_hashtag_becomes_a_tag() {
+        let task = parse_capture_line("Email #Work landlord #home #work #").expect("should parse");
+        assert_eq!(task.title, "Email landlord #");
+        assert_eq!(task.category.as_deref(), Some("work"));
+        assert_eq!(task.tags, vec!["work", "home"]);
+
+        let parsed = parse_capture_json(r#"{"tasks":[{"title":"ok","tags":["errands"," ",3]}]}"#)
+            .expect("should parse");
+        assert_eq!(parsed[0].tags, vec!["errands"]);
+    }
+
     #[test]
     fn invalid_tokens_stay_in_the_title() {
         // Out-of-range difficulty, non-numeric difficulty, invalid dates.
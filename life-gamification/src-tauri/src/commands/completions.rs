@@ -0,0 +1,278 @@
+// Task completions and reopening.
+//
+// complete_task / update_task_progress record a task_completions row holding the
+// state the completion is about to overwrite (recurring series streak, goal
+// progress). reopen_task uses that snapshot plus the reward ledger to undo one
+// completion exactly: every ledger entry for the task (completion rewards and the
+// health verification bonus) is offset by a reversal entry, streak and
+// longest_streak roll back, and the task returns to 'active'.
+
+use rusqlite::{Connection, OptionalExtension};
+use serde::Serialize;
+use tauri::State;
+
+use crate::commands::ledger;
+use crate::database::DbConnection;
+use crate::Task;
+
+// ---------- Types ----------
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ReopenSummary {
+    pub task: Task,
+    pub xp_reversed: i64,
+    pub gold_reversed: i64,
+    pub streak_restored: Option<i64>,
+}
+
+struct CompletionSnapshot {
+    id: i64,
+    parent_task_id: Option<i64>,
+    prev_current_streak: Option<i64>,
+    prev_longest_streak: Option<i64>,
+    prev_last_completed_date: Option<String>,
+    prev_streak_multiplier: Option<f64>,
+    prev_progress: Option<i64>,
+}
+
+// ---------- Recording ----------
+
+/// Snapshot what completing `task_id` is about to change. Must run before the
+/// recurring parent's streak fields are updated.
+pub fn record_completion(
+    conn: &Connection,
+    task_id: i64,
+    parent_task_id: Option<i64>,
+    prev_progress: Option<i64>,
+) -> Result<(), String> {
+    conn.execute(
+        "INSERT INTO task_completions (task_id, parent_task_id, prev_current_streak, prev_longest_streak,
+         prev_last_completed_date, prev_streak_multiplier, prev_progress)
+         SELECT ?1, ?2, p.current_streak, p.longest_streak, p.last_completed_date,
+                p.streak_bonus_multiplier, ?3
+         FROM (SELECT 1) LEFT JOIN tasks p ON p.id = ?2",
+        rusqlite::params![task_id, parent_task_id, prev_progress],
+    )
+    .map_err(|e| format!("Failed to record completion: {}", e))?;
+    Ok(())
+}
+
+fn latest_open_completion(conn: &Connection, task_id: i64) -> Result<Option<CompletionSnapshot>, String> {
+    conn.query_row(
+        "SELECT id, parent_task_id, prev_current_streak, prev_longest_streak, prev_last_completed_date,
+                prev_streak_multiplier, prev_progress
+         FROM task_completions
+         WHERE task_id = ?1 AND reopened_at IS NULL
+         ORDER BY id DESC LIMIT 1",
+        [task_id],
+        |row| {
+            Ok(CompletionSnapshot {
+                id: row.get(0)?,
+                parent_task_id: row.get(1)?,
+                prev_current_streak: row.get(2)?,
+                prev_longest_streak: row.get(3)?,
+                prev_last_completed_date: row.get(4)?,
+                prev_streak_multiplier: row.get(5)?,
+                prev_progress: row.get(6)?,
+            })
+        },
+    )
+    .optional()
+    .map_err(|e| format!("Failed to load completion: {}", e))
+}
+
+/// Undo the most recent completion of `task_id` inside the caller's transaction.
+/// Returns (xp reversed, gold reversed, restored streak).
+pub fn reopen_task_sync(conn: &Connection, task_id: i64) -> Result<(i64, i64, Option<i64>), String> {
+    let status: String = conn
+        .query_row("SELECT status FROM tasks WHERE id = ?1 AND user_id = 1", [task_id], |row| row.get(0))
+        .map_err(|e| format!("Task not found: {}", e))?;
+    if status != "completed" {
+        return Err("Only completed tasks can be reopened".to_string());
+    }
+
+    let snapshot = latest_open_completion(conn, task_id)?;
+
+    // A later occurrence of the same series was completed on top of this one, so
+    // its streak state can't be rolled back without discarding that completion.
+    if let Some(CompletionSnapshot { id, parent_task_id: Some(parent_id), .. }) = &snapshot {
+        let newer: bool = conn
+            .query_row(
+                "SELECT EXISTS(SELECT 1 FROM task_completions
+                 WHERE parent_task_id = ?1 AND id > ?2 AND reopened_at IS NULL)",
+                rusqlite::params![parent_id, id],
+                |row| row.get(0),
+            )
+            .map_err(|e| format!("Failed to check later completions: {}", e))?;
+        if newer {
+            return Err("A later occurrence of this recurring task is completed; reopen that one first".to_string());
+        }
+    }
+
+    let (xp_reversed, gold_reversed) = ledger::reverse_entries(
+        conn,
+        &[ledger::TASK_COMPLETION, ledger::GOAL_COMPLETION, ledger::VERIFICATION_BONUS],
+        &task_id.to_string(),
+        "Task reopened",
+    )?;
+
+    let mut streak_restored = None;
+    if let Some(snapshot) = &snapshot {
+        if let Some(parent_id) = snapshot.parent_task_id {
+            for id in [task_id, parent_id] {
+                conn.execute(
+                    "UPDATE tasks SET current_streak = ?2, longest_streak = ?3, last_completed_date = ?4,
+                     streak_bonus_multiplier = ?5 WHERE id = ?1",
+                    rusqlite::params![
+                        id,
+                        snapshot.prev_current_streak,
+                        snapshot.prev_longest_streak,
+                        snapshot.prev_last_completed_date,
+                        snapshot.prev_streak_multiplier,
+                    ],
+                )
+                .map_err(|e| format!("Failed to restore streak: {}", e))?;
+            }
+            streak_restored = Some(snapshot.prev_current_streak.unwrap_or(0));
+        }
+        if let Some(progress) = snapshot.prev_progress {
+            conn.execute(
+                "UPDATE task_progress SET current_progress = ?1 WHERE task_id = ?2",
+                rusqlite::params![progress, task_id],
+            )
+            .map_err(|e| format!("Failed to restore progress: {}", e))?;
+            conn.execute(
+                "INSERT INTO goal_progress_log (task_id, delta, progress_after, kind, note)
+                 SELECT ?1, ?2 - COALESCE((SELECT progress_after FROM goal_progress_log
+                                          WHERE task_id = ?1 ORDER BY id DESC LIMIT 1), 0),
+                        ?2, 'correction', 'Task reopened'",
+                rusqlite::params![task_id, progress],
+            )
+            .map_err(|e| format!("Failed to log progress: {}", e))?;
+        }
+        conn.execute(
+            "UPDATE task_completions SET reopened_at = CURRENT_TIMESTAMP WHERE id = ?1",
+            [snapshot.id],
+        )
+        .map_err(|e| format!("Failed to mark completion reopened: {}", e))?;
+    }
+
+    conn.execute(
+        "UPDATE tasks SET status = 'active', completed_at = NULL, verified = 0 WHERE id = ?1",
+        [task_id],
+    )
+    .map_err(|e| format!("Failed to reopen task: {}", e))?;
+
+    Ok((xp_reversed, gold_reversed, streak_restored))
+}
+
+// ---------- Commands ----------
+
+/// Reopen a completed task, taking back exactly what its completion granted.
+#[tauri::command]
+pub async fn reopen_task(db: State<'_, DbConnection>, task_id: i64) -> Result<ReopenSummary, String> {
+    let (xp_reversed, gold_reversed, streak_restored) = {
+        let conn = db.lock().await;
+        let tx = conn.unchecked_transaction()
+            .map_err(|e| format!("Failed to begin transaction: {}", e))?;
+        let result = reopen_task_sync(&tx, task_id)?;
+        tx.commit()
+            .map_err(|e| format!("Failed to commit: {}", e))?;
+        result
+    };
+
+    println!("Task {} reopened: reversed {} XP and {} gold", task_id, xp_reversed, gold_reversed);
+
+    Ok(ReopenSummary {
+        task: crate::get_task_by_id(db, task_id).await?,
+        xp_reversed,
+        gold_reversed,
+        streak_restored,
+    })
+}
+
+// ---------- Tests ----------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commands::ledger::{Reward, RewardSource};
+    use crate::database::test_conn;
+
+    fn balances(conn: &Connection) -> (i64, i64) {
+        conn.query_row("SELECT experience_points, gold FROM users WHERE id = 1", [], |row| Ok((row.get(0)?, row.get(1)?)))
+            .unwrap()
+    }
+
+    #[test]
+    fn reopening_a_recurring_instance_restores_streak_and_rewards() {
+        let conn = test_conn();
+        conn.execute_batch(
+            "INSERT INTO tasks (id, user_id, title, status, recurrence_pattern, current_streak, longest_streak,
+                                last_completed_date, streak_bonus_multiplier)
+             VALUES (1, 1, 'Run', 'active', 'daily', 6, 6, '2026-03-01', 1.0);
+             INSERT INTO tasks (id, user_id, title, status, task_type, parent_recurring_task_id, instance_date)
+             VALUES (2, 1, 'Run', 'active', 'recurring', 1, '2026-03-02');",
+        )
+        .unwrap();
+        let before = balances(&conn);
+
+        // What complete_task does for this instance
+        record_completion(&conn, 2, Some(1), None).unwrap();
+        conn.execute_batch(
+            "UPDATE tasks SET current_streak = 7, longest_streak = 7, last_completed_date = '2026-03-02',
+                              streak_bonus_multiplier = 2.0 WHERE id IN (1, 2);
+             UPDATE tasks SET status = 'completed', completed_at = CURRENT_TIMESTAMP WHERE id = 2;",
+        )
+        .unwrap();
+        let source = RewardSource::new(ledger::TASK_COMPLETION, 2);
+        ledger::grant_xp(&conn, &source, &Reward::flat(30).with("streak", 2.0), None).unwrap();
+        ledger::grant_gold(&conn, &source, &Reward::flat(4), None).unwrap();
+
+        assert_eq!(reopen_task_sync(&conn, 2).unwrap(), (60, 4, Some(6)));
+        assert_eq!(balances(&conn), before);
+
+        let (status, streak, longest, last): (String, i64, i64, String) = conn
+            .query_row(
+                "SELECT p.status, p.current_streak, p.longest_streak, p.last_completed_date
+                 FROM tasks p WHERE p.id = 1",
+                [],
+                |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?)),
+            )
+            .unwrap();
+        assert_eq!((status.as_str(), streak, longest, last.as_str()), ("active", 6, 6, "2026-03-01"));
+
+        let instance_status: String =
+            conn.query_row("SELECT status FROM tasks WHERE id = 2", [], |row| row.get(0)).unwrap();
+        assert_eq!(instance_status, "active");
+        assert!(reopen_task_sync(&conn, 2).is_err());
+    }
+
+    #[test]
+    fn reopening_a_goal_restores_progress_and_verification_bonus() {
+        let conn = test_conn();
+        conn.execute_batch(
+            "INSERT INTO tasks (id, user_id, title, status, task_type, verified)
+             VALUES (5, 1, 'Walk 10k steps', 'completed', 'goal', 1);
+             INSERT INTO task_progress (task_id, current_progress, target_progress) VALUES (5, 10, 10);",
+        )
+        .unwrap();
+        let before = balances(&conn);
+        record_completion(&conn, 5, None, Some(8)).unwrap();
+        ledger::grant_xp(&conn, &RewardSource::new(ledger::GOAL_COMPLETION, 5), &Reward::flat(20), None).unwrap();
+        ledger::grant_xp(&conn, &RewardSource::new(ledger::VERIFICATION_BONUS, 5), &Reward::flat(10), None).unwrap();
+
+        assert_eq!(reopen_task_sync(&conn, 5).unwrap(), (30, 0, None));
+        assert_eq!(balances(&conn), before);
+
+        let (progress, verified): (i64, i64) = conn
+            .query_row(
+                "SELECT tp.current_progress, t.verified FROM tasks t JOIN task_progress tp ON tp.task_id = t.id
+                 WHERE t.id = 5",
+                [],
+                |row| Ok((row.get(0)?, row.get(1)?)),
+            )
+            .unwrap();
+        assert_eq!((progress, verified), (8, 0));
+    }
+}
@@ -0,0 +1,566 @@
+// Overdue damage and defeat.
+//
+// Once a day the sweep looks at what the user let slip and takes HP for it:
+// every active task past its due date is hit once per overdue day, and every
+// recurring occurrence that was not completed is hit once, the day after it was
+// scheduled. Damage scales with difficulty and priority (damage_settings).
+//
+// At 0 HP the user is defeated: they lose a share of their gold, optionally
+// every recurring streak, and are revived at a share of max_health. Remaining
+// damage from that sweep is forgiven. Each hit and each defeat is recorded in
+// damage_events.
+//
+// The sweep runs hourly in the background and is idempotent per day; it catches
+// up on at most SWEEP_CATCH_UP_DAYS days the app was closed. Vacation days and
+// occurrences of paused series deal no damage (pauses.rs). Overdue rollover
+// policies (rollover.rs) are applied in the same transaction, after the damage.
+
+use chrono::{Duration, NaiveDate, Utc};
+use rusqlite::{Connection, OptionalExtension};
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Emitter, Manager, State};
+
+use crate::commands::ledger::{self, Reward, RewardSource};
+use crate::commands::pauses;
+use crate::commands::recurrence::parse_date;
+use crate::commands::rollover;
+use crate::database::DbConnection;
+
+/// Event emitted to the frontend when the user is defeated.
+pub const DEFEAT_EVENT: &str = "defeat";
+
+/// How many missed days a single sweep will catch up on.
+const SWEEP_CATCH_UP_DAYS: i64 = 7;
+
+// ---------- Types ----------
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DamageSettings {
+    pub enabled: bool,
+    /// HP per difficulty point, per missed day.
+    pub damage_per_difficulty: f64,
+    /// Each priority step above / below the default 3 adds / removes this share.
+    pub priority_weight: f64,
+    pub defeat_gold_loss_percent: f64,
+    pub defeat_resets_streaks: bool,
+    /// HP after a defeat, as a percentage of max_health.
+    pub revive_health_percent: f64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct DamageEvent {
+    pub id: i64,
+    pub task_id: Option<i64>,
+    pub task_title: Option<String>,
+    pub kind: String,
+    pub occurrence_date: Option<String>,
+    pub amount: i64,
+    pub health_before: i64,
+    pub health_after: i64,
+    pub gold_lost: i64,
+    pub reason: String,
+    pub created_at: String,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct SweepSummary {
+    pub days_swept: i64,
+    pub hits: i64,
+    pub damage_dealt: i64,
+    pub defeated: bool,
+    pub gold_lost: i64,
+    pub current_health: i64,
+    pub rollover: rollover::RolloverSummary,
+}
+
+/// Something the user missed on a given day.
+#[derive(Debug, Clone, PartialEq)]
+struct Miss {
+    task_id: i64,
+    /// The recurring series of a missed occurrence.
+    series: Option<i64>,
+    title: String,
+    kind: &'static str,
+    day: NaiveDate,
+    difficulty: i64,
+    priority: i64,
+}
+
+// ---------- Damage math (pure functions, unit-tested) ----------
+
+/// HP lost for one missed day of a task.
+pub fn damage_for(settings: &DamageSettings, difficulty: i64, priority: i64) -> i64 {
+    // A rate of 0 turns damage off; otherwise every miss costs at least 1 HP
+    if settings.damage_per_difficulty == 0.0 {
+        return 0;
+    }
+    let priority_factor = (1.0 + (priority - 3) as f64 * settings.priority_weight).max(0.0);
+    let damage = difficulty as f64 * settings.damage_per_difficulty * priority_factor;
+    (damage.round() as i64).max(1)
+}
+
+/// Days a sweep on `today` covers, given the last day already swept. A first
+/// sweep only covers today.
+pub fn days_to_sweep(last_sweep: Option<NaiveDate>, today: NaiveDate) -> Vec<NaiveDate> {
+    let first = match last_sweep {
+        Some(last) => (last + Duration::days(1)).max(today - Duration::days(SWEEP_CATCH_UP_DAYS - 1)),
+        None => today,
+    };
+    first.iter_days().take_while(|day| *day <= today).collect()
+}
+
+fn validate_settings(settings: &DamageSettings) -> Result<(), String> {
+    if settings.damage_per_difficulty < 0.0 || settings.priority_weight < 0.0 {
+        return Err("Damage settings cannot be negative".to_string());
+    }
+    if !(0.0..=100.0).contains(&settings.defeat_gold_loss_percent) {
+        return Err("defeat_gold_loss_percent must be between 0 and 100".to_string());
+    }
+    if !(1.0..=100.0).contains(&settings.revive_health_percent) {
+        return Err("revive_health_percent must be between 1 and 100".to_string());
+    }
+    Ok(())
+}
+
+// ---------- Persistence ----------
+
+pub fn load_settings(conn: &Connection) -> Result<DamageSettings, String> {
+    conn.query_row(
+        "SELECT enabled, damage_per_difficulty, priority_weight, defeat_gold_loss_percent,
+                defeat_resets_streaks, revive_health_percent
+         FROM damage_settings WHERE id = 1",
+        [],
+        |row| {
+            Ok(DamageSettings {
+                enabled: row.get(0)?,
+                damage_per_difficulty: row.get(1)?,
+                priority_weight: row.get(2)?,
+                defeat_gold_loss_percent: row.get(3)?,
+                defeat_resets_streaks: row.get(4)?,
+                revive_health_percent: row.get(5)?,
+            })
+        },
+    )
+    .map_err(|e| format!("Failed to load damage settings: {}", e))
+}
+
+/// Active tasks overdue on `day`, and recurring occurrences scheduled the day
+/// before that were never completed.
+fn misses_on(conn: &Connection, day: NaiveDate) -> Result<Vec<Miss>, String> {
+    let day_str = day.format("%Y-%m-%d").to_string();
+    let yesterday = (day - Duration::days(1)).format("%Y-%m-%d").to_string();
+    let mut stmt = conn
+        .prepare(
+            "SELECT id, title, parent_recurring_task_id, difficulty, priority
+             FROM tasks
+             WHERE user_id = 1 AND deleted_at IS NULL AND (
+                 (parent_recurring_task_id IS NULL AND status = 'active'
+                  AND due_date IS NOT NULL AND date(due_date) < ?1)
+                 OR (parent_recurring_task_id IS NOT NULL AND status IN ('active', 'failed')
+                     AND instance_date = ?2)
+             )",
+        )
+        .map_err(|e| format!("Failed to prepare query: {}", e))?;
+    let misses = stmt
+        .query_map(rusqlite::params![day_str, yesterday], |row| {
+            Ok(Miss {
+                task_id: row.get(0)?,
+                series: row.get(2)?,
+                title: row.get(1)?,
+                kind: if row.get::<_, Option<i64>>(2)?.is_some() { "missed_occurrence" } else { "overdue" },
+                day,
+                difficulty: row.get::<_, Option<i64>>(3)?.unwrap_or(5),
+                priority: row.get::<_, Option<i64>>(4)?.unwrap_or(3),
+            })
+        })
+        .map_err(|e| format!("Failed to find overdue tasks: {}", e))?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| format!("Failed to read overdue tasks: {}", e))?;
+    Ok(misses)
+}
+
+fn health(conn: &Connection) -> Result<(i64, i64), String> {
+    conn.query_row("SELECT current_health, max_health FROM users WHERE id = 1", [], |row| {
+        Ok((row.get(0)?, row.get(1)?))
+    })
+    .map_err(|e| format!("Failed to get health: {}", e))
+}
+
+/// Apply the defeat penalty and revive the user. Returns the gold lost.
+fn defeat(conn: &Connection, settings: &DamageSettings, day: NaiveDate) -> Result<i64, String> {
+    let (_, max_health) = health(conn)?;
+    let gold: i64 = conn
+        .query_row("SELECT gold FROM users WHERE id = 1", [], |row| row.get(0))
+        .map_err(|e| format!("Failed to get gold: {}", e))?;
+
+    let gold_lost = (gold.max(0) as f64 * settings.defeat_gold_loss_percent / 100.0).round() as i64;
+    if gold_lost > 0 {
+        ledger::grant_gold(conn, &RewardSource::untracked(ledger::DEFEAT), &Reward::flat(-gold_lost), Some("Defeated at 0 HP"))?;
+    }
+
+    let mut reason = format!("Defeated: lost {} gold", gold_lost);
+    if settings.defeat_resets_streaks {
+        let reset = conn
+            .execute(
+                "UPDATE tasks SET current_streak = 0, streak_bonus_multiplier = 1.0
+                 WHERE user_id = 1 AND recurrence_pattern IS NOT NULL AND parent_recurring_task_id IS NULL
+                   AND current_streak > 0",
+                [],
+            )
+            .map_err(|e| format!("Failed to reset streaks: {}", e))?;
+        if reset > 0 {
+            reason.push_str(&format!(" and {} streak(s)", reset));
+        }
+    }
+
+    let revived = ((max_health as f64 * settings.revive_health_percent / 100.0).round() as i64).clamp(1, max_health.max(1));
+    conn.execute("UPDATE users SET current_health = ?1 WHERE id = 1", [revived])
+        .map_err(|e| format!("Failed to revive user: {}", e))?;
+    conn.execute(
+        "INSERT INTO damage_events (task_id, kind, occurrence_date, amount, health_before, health_after, gold_lost, reason)
+         VALUES (NULL, 'defeat', ?1, 0, 0, ?2, ?3, ?4)",
+        rusqlite::params![day.format("%Y-%m-%d").to_string(), revived, gold_lost, reason],
+    )
+    .map_err(|e| format!("Failed to record defeat: {}", e))?;
+
+    Ok(gold_lost)
+}
+
+/// Run the sweep for every day since the last one, inside the caller's transaction.
+pub fn sweep_sync(conn: &Connection, today: NaiveDate) -> Result<SweepSummary, String> {
+    let settings = load_settings(conn)?;
+    let last_sweep: Option<String> = conn
+        .query_row("SELECT last_sweep_date FROM damage_settings WHERE id = 1", [], |row| row.get(0))
+        .optional()
+        .map_err(|e| format!("Failed to get last sweep: {}", e))?
+        .flatten();
+    let days = days_to_sweep(last_sweep.as_deref().and_then(parse_date), today);
+
+    let mut summary = SweepSummary {
+        days_swept: days.len() as i64,
+        current_health: health(conn)?.0,
+        ..Default::default()
+    };
+
+    if settings.enabled {
+        let pauses = pauses::PauseWindows::load(conn)?;
+        'days: for day in days {
+            if pauses.on_vacation(day) {
+                continue;
+            }
+            for miss in misses_on(conn, day)? {
+                if miss.series.is_some_and(|series| pauses.is_paused(Some(series), miss.day - Duration::days(1))) {
+                    continue;
+                }
+                let amount = damage_for(&settings, miss.difficulty, miss.priority);
+                if amount == 0 {
+                    continue;
+                }
+                let (before, _) = health(conn)?;
+                let after = (before - amount).max(0);
+                let reason = match miss.kind {
+                    "overdue" => format!("\"{}\" is overdue", miss.title),
+                    _ => format!("Missed \"{}\" on {}", miss.title, (miss.day - Duration::days(1)).format("%Y-%m-%d")),
+                };
+                let inserted = conn
+                    .execute(
+                        "INSERT OR IGNORE INTO damage_events (task_id, kind, occurrence_date, amount, health_before, health_after, reason)
+                         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+                        rusqlite::params![miss.task_id, miss.kind, day.format("%Y-%m-%d").to_string(), amount, before, after, reason],
+                    )
+                    .map_err(|e| format!("Failed to record damage: {}", e))?;
+                if inserted == 0 {
+                    continue; // already hit for this day
+                }
+                conn.execute("UPDATE users SET current_health = ?1 WHERE id = 1", [after])
+                    .map_err(|e| format!("Failed to apply damage: {}", e))?;
+                summary.hits += 1;
+                summary.damage_dealt += amount;
+
+                if after == 0 {
+                    summary.defeated = true;
+                    summary.gold_lost = defeat(conn, &settings, day)?;
+                    break 'days;
+                }
+            }
+        }
+    }
+
+    conn.execute(
+        "UPDATE damage_settings SET last_sweep_date = ?1 WHERE id = 1",
+        [today.format("%Y-%m-%d").to_string()],
+    )
+    .map_err(|e| format!("Failed to record sweep: {}", e))?;
+
+    summary.current_health = health(conn)?.0;
+    Ok(summary)
+}
+
+/// Catch up recurring instances, then sweep and roll over. The first sweep runs
+/// at startup, before the frontend generates instances, so occurrences missed
+/// while the app was closed have to exist before they can be evaluated.
+pub fn run_sweep_sync(conn: &Connection, today: NaiveDate) -> Result<SweepSummary, String> {
+    crate::generate_recurring_instances_sync(conn, today)?;
+    let tx = conn.unchecked_transaction()
+        .map_err(|e| format!("Failed to begin transaction: {}", e))?;
+    let mut summary = sweep_sync(&tx, today)?;
+    summary.rollover = rollover::sweep_sync(&tx, today)?;
+    tx.commit()
+        .map_err(|e| format!("Failed to commit: {}", e))?;
+    Ok(summary)
+}
+
+async fn sweep(app: &AppHandle, db: &DbConnection) -> Result<SweepSummary, String> {
+    let summary = {
+        let conn = db.lock().await;
+        run_sweep_sync(&conn, Utc::now().naive_utc().date())?
+    };
+
+    if summary.hits > 0 {
+        println!("Overdue sweep: {} hit(s), {} damage", summary.hits, summary.damage_dealt);
+    }
+    if summary.defeated {
+        if let Err(e) = app.emit(DEFEAT_EVENT, &summary) {
+            eprintln!("Failed to emit defeat event: {}", e);
+        }
+    }
+    Ok(summary)
+}
+
+/// Run the sweep every hour in the background; it only does work once per day.
+pub fn schedule_sweep(app: AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        let mut interval = tokio::time::interval(tokio::time::Duration::from_secs(3600));
+        loop {
+            interval.tick().await;
+            let db = app.state::<DbConnection>();
+            if let Err(e) = sweep(&app, &db).await {
+                eprintln!("Overdue sweep failed: {}", e);
+            }
+        }
+    });
+}
+
+// ---------- Commands ----------
+
+#[tauri::command]
+pub async fn run_overdue_sweep(app: AppHandle, db: State<'_, DbConnection>) -> Result<SweepSummary, String> {
+    sweep(&app, &db).await
+}
+
+#[tauri::command]
+pub async fn get_damage_events(db: State<'_, DbConnection>, limit: Option<i64>) -> Result<Vec<DamageEvent>, String> {
+    let conn = db.lock().await;
+    let mut stmt = conn
+        .prepare(
+            "SELECT e.id, e.task_id, t.title, e.kind, e.occurrence_date, e.amount, e.health_before,
+                    e.health_after, e.gold_lost, e.reason, e.created_at
+             FROM damage_events e
+             LEFT JOIN tasks t ON t.id = e.task_id
+             WHERE e.user_id = 1
+             ORDER BY e.id DESC
+             LIMIT ?1",
+        )
+        .map_err(|e| format!("Failed to prepare query: {}", e))?;
+    let events = stmt
+        .query_map([limit.unwrap_or(100)], |row| {
+            Ok(DamageEvent {
+                id: row.get(0)?,
+                task_id: row.get(1)?,
+                task_title: row.get(2)?,
+                kind: row.get(3)?,
+                occurrence_date: row.get(4)?,
+                amount: row.get(5)?,
+                health_before: row.get(6)?,
+                health_after: row.get(7)?,
+                gold_lost: row.get(8)?,
+                reason: row.get(9)?,
+                created_at: row.get(10)?,
+            })
+        })
+        .map_err(|e| format!("Failed to get damage events: {}", e))?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| format!("Failed to read damage events: {}", e))?;
+    Ok(events)
+}
+
+#[tauri::command]
+pub async fn get_damage_settings(db: State<'_, DbConnection>) -> Result<DamageSettings, String> {
+    let conn = db.lock().await;
+    load_settings(&conn)
+}
+
+#[tauri::command]
+pub async fn update_damage_settings(
+    db: State<'_, DbConnection>,
+    settings: DamageSettings,
+) -> Result<DamageSettings, String> {
+    validate_settings(&settings)?;
+    let conn = db.lock().await;
+    conn.execute(
+        "UPDATE damage_settings SET enabled = ?1, damage_per_difficulty = ?2, priority_weight = ?3,
+         defeat_gold_loss_percent = ?4, defeat_resets_streaks = ?5, revive_health_percent = ?6,
+         updated_at = CURRENT_TIMESTAMP
+         WHERE id = 1",
+        rusqlite::params![
+            settings.enabled,
+            settings.damage_per_difficulty,
+            settings.priority_weight,
+            settings.defeat_gold_loss_percent,
+            settings.defeat_resets_streaks,
+            settings.revive_health_percent,
+        ],
+    )
+    .map_err(|e| format!("Failed to save damage settings: {}", e))?;
+    Ok(settings)
+}
+
+// ---------- Tests ----------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::database::test_conn;
+
+    fn d(s: &str) -> NaiveDate {
+        parse_date(s).unwrap()
+    }
+
+    fn settings() -> DamageSettings {
+        DamageSettings {
+            enabled: true,
+            damage_per_difficulty: 1.0,
+            priority_weight: 0.25,
+            defeat_gold_loss_percent: 10.0,
+            defeat_resets_streaks: true,
+            revive_health_percent: 50.0,
+        }
+    }
+
+    #[test]
+    fn damage_scales_with_difficulty_and_priority() {
+        let s = settings();
+        assert_eq!(damage_for(&s, 4, 3), 4);
+        assert_eq!(damage_for(&s, 4, 5), 6);
+        assert_eq!(damage_for(&s, 4, 1), 2);
+        assert_eq!(damage_for(&s, 1, 1), 1); // never zero
+        let off = DamageSettings { damage_per_difficulty: 0.0, ..settings() };
+        assert_eq!(damage_for(&off, 10, 5), 0);
+    }
+
+    #[test]
+    fn sweep_window_catches_up_a_week_at_most() {
+        assert_eq!(days_to_sweep(None, d("2026-03-10")), vec![d("2026-03-10")]);
+        assert!(days_to_sweep(Some(d("2026-03-10")), d("2026-03-10")).is_empty());
+        assert_eq!(days_to_sweep(Some(d("2026-03-08")), d("2026-03-10")), vec![d("2026-03-09"), d("2026-03-10")]);
+        assert_eq!(days_to_sweep(Some(d("2026-01-01")), d("2026-03-10")).len(), SWEEP_CATCH_UP_DAYS as usize);
+    }
+
+    #[test]
+    fn sweep_hits_overdue_tasks_and_missed_occurrences_once_per_day() {
+        let conn = test_conn();
+        conn.execute_batch(
+            "UPDATE users SET current_health = 100, max_health = 100 WHERE id = 1;
+             INSERT INTO tasks (id, user_id, title, status, difficulty, priority, due_date)
+             VALUES (1, 1, 'Taxes', 'active', 4, 5, '2026-03-08');
+             INSERT INTO tasks (id, user_id, title, status, difficulty, priority, due_date)
+             VALUES (2, 1, 'Done', 'completed', 4, 5, '2026-03-08');
+             INSERT INTO tasks (id, user_id, title, status, recurrence_pattern) VALUES (3, 1, 'Run', 'active', 'daily');
+             INSERT INTO tasks (id, user_id, title, status, difficulty, priority, parent_recurring_task_id, instance_date)
+             VALUES (4, 1, 'Run', 'failed', 2, 3, 3, '2026-03-09');",
+        )
+        .unwrap();
+
+        let summary = sweep_sync(&conn, d("2026-03-10")).unwrap();
+        assert_eq!((summary.hits, summary.damage_dealt, summary.current_health), (2, 8, 92));
+
+        // Same day again: nothing new
+        let summary = sweep_sync(&conn, d("2026-03-10")).unwrap();
+        assert_eq!(summary.hits, 0);
+
+        // Next day: the overdue task hits again, the occurrence does not
+        let summary = sweep_sync(&conn, d("2026-03-11")).unwrap();
+        assert_eq!((summary.hits, summary.current_health), (1, 86));
+    }
+
+    #[test]
+    fn sweep_catches_up_occurrences_missed_while_closed() {
+        let conn = test_conn();
+        conn.execute_batch(
+            "UPDATE users SET current_health = 100, max_health = 100 WHERE id = 1;
+             UPDATE damage_settings SET last_sweep_date = '2026-03-08' WHERE id = 1;
+             INSERT INTO tasks (id, user_id, title, status, difficulty, priority, recurrence_pattern, created_at)
+             VALUES (3, 1, 'Run', 'active', 2, 3, 'daily', '2026-03-07 08:00:00');
+             INSERT INTO tasks (id, user_id, title, status, difficulty, priority, parent_recurring_task_id, instance_date)
+             VALUES (4, 1, 'Run', 'completed', 2, 3, 3, '2026-03-07');
+             INSERT INTO recurring_task_instances (recurring_task_id, instance_task_id, instance_date)
+             VALUES (3, 4, '2026-03-07');",
+        )
+        .unwrap();
+
+        // No instances exist for the 8th and 9th yet: the sweep generates them first
+        let summary = run_sweep_sync(&conn, d("2026-03-10")).unwrap();
+        assert_eq!((summary.hits, summary.current_health), (2, 96));
+        let failed: i64 = conn
+            .query_row("SELECT COUNT(*) FROM tasks WHERE parent_recurring_task_id = 3 AND status = 'failed'", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(failed, 2);
+    }
+
+    #[test]
+    fn sweep_spares_vacation_days_and_paused_series() {
+        let conn = test_conn();
+        conn.execute_batch(
+            "UPDATE users SET current_health = 100, max_health = 100 WHERE id = 1;
+             INSERT INTO tasks (id, user_id, title, status, difficulty, priority, due_date)
+             VALUES (1, 1, 'Taxes', 'active', 4, 5, '2026-03-08');
+             INSERT INTO tasks (id, user_id, title, status, recurrence_pattern) VALUES (3, 1, 'Run', 'active', 'daily');
+             INSERT INTO tasks (id, user_id, title, status, difficulty, priority, parent_recurring_task_id, instance_date)
+             VALUES (4, 1, 'Run', 'failed', 2, 3, 3, '2026-03-09');
+             INSERT INTO pauses (recurring_task_id, start_date, end_date) VALUES (3, '2026-03-09', '2026-03-09');
+             INSERT INTO pauses (recurring_task_id, start_date, end_date) VALUES (NULL, '2026-03-11', NULL);",
+        )
+        .unwrap();
+
+        // Only the overdue one-off task: the missed run was on a paused day
+        let summary = sweep_sync(&conn, d("2026-03-10")).unwrap();
+        assert_eq!((summary.hits, summary.current_health), (1, 94));
+
+        // On vacation nothing hits
+        let summary = sweep_sync(&conn, d("2026-03-12")).unwrap();
+        assert_eq!((summary.days_swept, summary.hits), (2, 0));
+    }
+
+    #[test]
+    fn defeat_costs_gold_and_streaks_then_revives() {
+        let conn = test_conn();
+        conn.execute_batch(
+            "UPDATE users SET current_health = 3, max_health = 100, gold = 200 WHERE id = 1;
+             INSERT INTO tasks (id, user_id, title, status, difficulty, priority, due_date)
+             VALUES (1, 1, 'Taxes', 'active', 10, 3, '2026-03-01');
+             INSERT INTO tasks (id, user_id, title, status, recurrence_pattern, current_streak, streak_bonus_multiplier)
+             VALUES (2, 1, 'Run', 'active', 'daily', 12, 2.0);",
+        )
+        .unwrap();
+
+        let summary = sweep_sync(&conn, d("2026-03-10")).unwrap();
+        assert!(summary.defeated);
+        assert_eq!((summary.gold_lost, summary.current_health), (20, 50));
+
+        let (gold, streak): (i64, i64) = conn
+            .query_row("SELECT u.gold, t.current_streak FROM users u, tasks t WHERE u.id = 1 AND t.id = 2", [], |row| {
+                Ok((row.get(0)?, row.get(1)?))
+            })
+            .unwrap();
+        assert_eq!((gold, streak), (180, 0));
+
+        let kinds: Vec<String> = conn
+            .prepare("SELECT kind FROM damage_events ORDER BY id")
+            .unwrap()
+            .query_map([], |row| row.get(0))
+            .unwrap()
+            .collect::<Result<_, _>>()
+            .unwrap();
+        assert_eq!(kinds, vec!["overdue", "defeat"]);
+    }
+}
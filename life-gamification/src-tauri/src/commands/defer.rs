@@ -0,0 +1,222 @@
+// Deferred tasks.
+//
+// A task's start_date hides it until then: get_tasks leaves deferred tasks out
+// unless asked, and they reappear on their own once the start date passes.
+// defer_task sets the start date from a duration ("3d", "2h", "1w2d") or an
+// explicit date, and can schedule a 'task_starting' notification for when the
+// task comes back, delivered through the usual notification queue.
+
+use chrono::{DateTime, Duration, NaiveDate, NaiveDateTime, Utc};
+use rusqlite::Connection;
+use tauri::State;
+
+use crate::database::DbConnection;
+use crate::Task;
+
+/// How start dates are stored, matching SQLite's datetime('now').
+pub const START_FORMAT: &str = "%Y-%m-%d %H:%M:%S";
+
+/// SQL condition for tasks that are not deferred right now.
+pub const NOT_DEFERRED: &str = "(t.start_date IS NULL OR datetime(t.start_date) <= datetime('now'))";
+
+// ---------- Parsing (pure functions, unit-tested) ----------
+
+/// A defer duration: one or more `<n><unit>` parts with units m, h, d, w
+/// (e.g. "30m", "3d", "1w2d").
+pub fn parse_duration(value: &str) -> Result<Duration, String> {
+    let invalid = || format!("Invalid duration \"{}\"; use e.g. 30m, 2h, 3d or 1w", value);
+    let mut total = Duration::zero();
+    let mut number = String::new();
+    for c in value.trim().to_lowercase().chars() {
+        if c.is_ascii_digit() {
+            number.push(c);
+            continue;
+        }
+        let n: i64 = number.parse().map_err(|_| invalid())?;
+        total += match c {
+            'm' => Duration::minutes(n),
+            'h' => Duration::hours(n),
+            'd' => Duration::days(n),
+            'w' => Duration::weeks(n),
+            _ => return Err(invalid()),
+        };
+        number.clear();
+    }
+    if !number.is_empty() || total <= Duration::zero() {
+        return Err(invalid());
+    }
+    Ok(total)
+}
+
+/// A start date: RFC 3339, `YYYY-MM-DD HH:MM[:SS]` (UTC), or a bare date
+/// meaning the start of that day.
+pub fn parse_start(value: &str) -> Result<NaiveDateTime, String> {
+    let value = value.trim();
+    if let Ok(dt) = DateTime::parse_from_rfc3339(value) {
+        return Ok(dt.with_timezone(&Utc).naive_utc());
+    }
+    for format in ["%Y-%m-%d %H:%M:%S", "%Y-%m-%dT%H:%M:%S", "%Y-%m-%d %H:%M", "%Y-%m-%dT%H:%M"] {
+        if let Ok(dt) = NaiveDateTime::parse_from_str(value, format) {
+            return Ok(dt);
+        }
+    }
+    NaiveDate::parse_from_str(value, "%Y-%m-%d")
+        .map(|date| date.and_hms_opt(0, 0, 0).expect("midnight is valid"))
+        .map_err(|_| format!("Invalid start date \"{}\"; use YYYY-MM-DD or YYYY-MM-DD HH:MM", value))
+}
+
+// ---------- Persistence ----------
+
+/// Set (or clear) a task's start date. Any pending "task is back" notification
+/// is replaced; a new one is only scheduled for a start date still ahead.
+pub fn set_start_date(conn: &Connection, task_id: i64, start: Option<NaiveDateTime>, notify: bool) -> Result<(), String> {
+    let (title, status): (String, String) = conn
+        .query_row("SELECT title, status FROM tasks WHERE id = ?1 AND user_id = 1", [task_id], |row| {
+            Ok((row.get(0)?, row.get(1)?))
+        })
+        .map_err(|e| format!("Task not found: {}", e))?;
+    if status != "active" {
+        return Err(format!("Only active tasks can be deferred (task {} is {})", task_id, status));
+    }
+
+    let returns_later = start.is_some_and(|dt| dt > Utc::now().naive_utc());
+    let start = start.map(|dt| dt.format(START_FORMAT).to_string());
+    conn.execute("UPDATE tasks SET start_date = ?1 WHERE id = ?2", rusqlite::params![start, task_id])
+        .map_err(|e| format!("Failed to defer task: {}", e))?;
+
+    conn.execute(
+        "UPDATE scheduled_notifications SET status = 'cancelled'
+         WHERE task_id = ?1 AND notification_type = 'task_starting' AND status IN ('pending', 'snoozed')",
+        [task_id],
+    )
+    .map_err(|e| format!("Failed to cancel notification: {}", e))?;
+    if let (Some(start), true) = (start, notify && returns_later) {
+        conn.execute(
+            "INSERT INTO scheduled_notifications (user_id, task_id, notification_type, title, message, scheduled_for, priority, status)
+             VALUES (1, ?1, 'task_starting', 'Task is back', ?2, ?3, 'medium', 'pending')",
+            rusqlite::params![task_id, format!("\"{}\" is back on your list", title), start],
+        )
+        .map_err(|e| format!("Failed to schedule notification: {}", e))?;
+    }
+    Ok(())
+}
+
+// ---------- Commands ----------
+
+/// Hide a task for `duration` (e.g. "3d") or until `until` (a date or datetime).
+#[tauri::command]
+pub async fn defer_task(
+    db: State<'_, DbConnection>,
+    task_id: i64,
+    duration: Option<String>,
+    until: Option<String>,
+    notify: Option<bool>,
+) -> Result<Task, String> {
+    let start = match (duration, until) {
+        (Some(duration), None) => Utc::now().naive_utc() + parse_duration(&duration)?,
+        (None, Some(until)) => parse_start(&until)?,
+        _ => return Err("Give either a duration or a date to defer until".to_string()),
+    };
+    let conn = db.lock().await;
+    let tx = conn.unchecked_transaction()
+        .map_err(|e| format!("Failed to begin transaction: {}", e))?;
+    set_start_date(&tx, task_id, Some(start), notify.unwrap_or(false))?;
+    tx.commit()
+        .map_err(|e| format!("Failed to commit: {}", e))?;
+    crate::fetch_task_sync(&conn, task_id)
+}
+
+/// Bring a deferred task back right away.
+#[tauri::command]
+pub async fn undefer_task(db: State<'_, DbConnection>, task_id: i64) -> Result<Task, String> {
+    let conn = db.lock().await;
+    let tx = conn.unchecked_transaction()
+        .map_err(|e| format!("Failed to begin transaction: {}", e))?;
+    set_start_date(&tx, task_id, None, false)?;
+    tx.commit()
+        .map_err(|e| format!("Failed to commit: {}", e))?;
+    crate::fetch_task_sync(&conn, task_id)
+}
+
+/// Active tasks that are currently deferred, soonest back first.
+#[tauri::command]
+pub async fn get_deferred_tasks(db: State<'_, DbConnection>) -> Result<Vec<Task>, String> {
+    let conn = db.lock().await;
+    let query = format!(
+        "{} WHERE t.user_id = 1 AND t.status = 'active' AND NOT {} ORDER BY datetime(t.start_date)",
+        crate::TASK_SELECT,
+        NOT_DEFERRED
+    );
+    let mut stmt = conn.prepare(&query)
+        .map_err(|e| format!("Failed to prepare query: {}", e))?;
+    let tasks = stmt
+        .query_map([], crate::task_from_row)
+        .map_err(|e| format!("Failed to get deferred tasks: {}", e))?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| format!("Failed to read tasks: {}", e))?;
+    Ok(tasks)
+}
+
+// ---------- Tests ----------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_durations() {
+        assert_eq!(parse_duration("3d").unwrap(), Duration::days(3));
+        assert_eq!(parse_duration("1w2d").unwrap(), Duration::days(9));
+        assert_eq!(parse_duration(" 90M ").unwrap(), Duration::minutes(90));
+        assert!(parse_duration("3").is_err());
+        assert!(parse_duration("0d").is_err());
+        assert!(parse_duration("tomorrow").is_err());
+    }
+
+    #[test]
+    fn parses_start_dates() {
+        let fmt = |value: &str| parse_start(value).unwrap().format(START_FORMAT).to_string();
+        assert_eq!(fmt("2026-07-10"), "2026-07-10 00:00:00");
+        assert_eq!(fmt("2026-07-10 09:30"), "2026-07-10 09:30:00");
+        assert_eq!(fmt("2026-07-10T09:30:00+02:00"), "2026-07-10 07:30:00");
+        assert!(parse_start("next week").is_err());
+    }
+
+    #[test]
+    fn deferring_hides_the_task_and_schedules_its_return() {
+        let conn = crate::database::test_conn();
+        conn.execute_batch(
+            "INSERT INTO tasks (id, user_id, title, status) VALUES (1, 1, 'Renew passport', 'active');
+             INSERT INTO tasks (id, user_id, title, status) VALUES (2, 1, 'Done already', 'completed');",
+        )
+        .unwrap();
+        let visible = |conn: &Connection| -> i64 {
+            conn.query_row(&format!("SELECT COUNT(*) FROM tasks t WHERE t.id = 1 AND {}", NOT_DEFERRED), [], |row| row.get(0))
+                .unwrap()
+        };
+        let pending = |conn: &Connection| -> i64 {
+            conn.query_row(
+                "SELECT COUNT(*) FROM scheduled_notifications WHERE task_id = 1 AND status = 'pending'",
+                [],
+                |row| row.get(0),
+            )
+            .unwrap()
+        };
+
+        let later = Utc::now().naive_utc() + Duration::days(3);
+        set_start_date(&conn, 1, Some(later), true).unwrap();
+        assert_eq!((visible(&conn), pending(&conn)), (0, 1));
+
+        // Re-deferring replaces the notification
+        set_start_date(&conn, 1, Some(later + Duration::days(1)), true).unwrap();
+        assert_eq!((visible(&conn), pending(&conn)), (0, 1));
+
+        // A start date in the past shows the task; nothing left to announce
+        set_start_date(&conn, 1, Some(later - Duration::days(4)), true).unwrap();
+        assert_eq!((visible(&conn), pending(&conn)), (1, 0));
+        set_start_date(&conn, 1, None, false).unwrap();
+        assert_eq!((visible(&conn), pending(&conn)), (1, 0));
+
+        assert!(set_start_date(&conn, 2, Some(later), false).is_err());
+    }
+}
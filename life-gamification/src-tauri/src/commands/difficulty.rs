@@ -0,0 +1,266 @@
+// Effective difficulty and difficulty recommendations.
+//
+// A task's difficulty is what the user picked; its effective difficulty is how
+// hard it is for *this* user, given the stat that governs its category (END for
+// health tasks, INT for work/learning, CHA for social). Tasks that have become
+// easy pay proportionally less XP, which shows up in the ledger as the
+// "effective_difficulty" multiplier.
+//
+// get_recommended_difficulty suggests a difficulty for a new task from the user's
+// history in the category: how long tasks took against their estimates
+// (time_sessions via total_time_spent_seconds) and how long they sat before
+// being completed.
+
+use rusqlite::Connection;
+use serde::Serialize;
+use tauri::State;
+
+use crate::commands::economy::{MAX_DIFFICULTY, MIN_DIFFICULTY};
+use crate::database::DbConnection;
+
+/// Most recent completed tasks in a category considered for a recommendation.
+const HISTORY_LIMIT: i64 = 50;
+
+/// Difficulty suggested when there is no usable history.
+const DEFAULT_DIFFICULTY: i64 = 5;
+
+// ---------- Types ----------
+
+/// The stats that make tasks easier (base values, as stored on the user).
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Stats {
+    pub strength: i64,
+    pub intelligence: i64,
+    pub endurance: i64,
+    pub charisma: i64,
+}
+
+/// One completed task from the user's history.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CompletedSample {
+    pub difficulty: i64,
+    pub estimated_minutes: Option<i64>,
+    pub spent_seconds: i64,
+    pub latency_days: Option<f64>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct DifficultyRecommendation {
+    pub category: String,
+    pub difficulty: i64,
+    pub sample_size: usize,
+    /// Median of time spent / estimated time, over tasks that had an estimate.
+    pub median_time_ratio: Option<f64>,
+    /// Median days from creation to completion.
+    pub median_latency_days: Option<f64>,
+    pub reasons: Vec<String>,
+}
+
+// ---------- Effective difficulty (pure functions, unit-tested) ----------
+
+/// How hard a task of `base_difficulty` in `category` is for a user with `stats`.
+/// Stats can make a task at most 50% easier, and never below difficulty 1.
+pub fn effective_difficulty(base_difficulty: i64, stats: &Stats, category: &str) -> f64 {
+    let stat_modifier = match category.to_lowercase().as_str() {
+        "exercise" | "health" | "fitness" => stats.endurance as f64 * 0.03, // 3% easier per END point
+        "study" | "learning" | "work" => stats.intelligence as f64 * 0.03,  // 3% easier per INT point
+        "social" => stats.charisma as f64 * 0.03,                           // 3% easier per CHA point
+        _ => ((stats.strength + stats.endurance) as f64 / 2.0) * 0.02,     // 2% easier per avg STR/END
+    };
+
+    let effective = base_difficulty as f64 * (1.0 - stat_modifier.clamp(0.0, 0.5));
+    effective.max(1.0)
+}
+
+/// XP multiplier for completing a task that is `effective_difficulty` hard for
+/// the user: 1.0 when stats don't help, down to 0.5.
+pub fn reward_multiplier(base_difficulty: i64, effective_difficulty: f64) -> f64 {
+    if base_difficulty <= 1 {
+        return 1.0;
+    }
+    (effective_difficulty / base_difficulty as f64).clamp(0.5, 1.0)
+}
+
+pub fn median(mut values: Vec<f64>) -> Option<f64> {
+    if values.is_empty() {
+        return None;
+    }
+    values.sort_by(|a, b| a.total_cmp(b));
+    let mid = values.len() / 2;
+    Some(if values.len().is_multiple_of(2) { (values[mid - 1] + values[mid]) / 2.0 } else { values[mid] })
+}
+
+/// Suggest a difficulty from completed tasks in a category. Starts from the
+/// user's typical difficulty there and moves it one step per signal: overrunning
+/// estimates or leaving tasks for a week means the work is harder than rated,
+/// beating estimates or finishing within a day means it's easier.
+pub fn recommend(category: &str, history: &[CompletedSample]) -> DifficultyRecommendation {
+    let mut reasons = Vec::new();
+
+    let typical = median(history.iter().map(|s| s.difficulty as f64).collect());
+    let mut difficulty = match typical {
+        Some(typical) => {
+            reasons.push(format!("Your completed {} tasks are typically difficulty {:.0}", category, typical));
+            typical.round() as i64
+        }
+        None => {
+            reasons.push(format!("No completed {} tasks yet", category));
+            DEFAULT_DIFFICULTY
+        }
+    };
+
+    let median_time_ratio = median(
+        history
+            .iter()
+            .filter_map(|s| match s.estimated_minutes {
+                Some(estimate) if estimate > 0 && s.spent_seconds > 0 => {
+                    Some(s.spent_seconds as f64 / (estimate * 60) as f64)
+                }
+                _ => None,
+            })
+            .collect(),
+    );
+    if let Some(ratio) = median_time_ratio {
+        if ratio > 1.25 {
+            difficulty += 1;
+            reasons.push(format!("Tasks take {:.0}% of their estimate", ratio * 100.0));
+        } else if ratio < 0.8 {
+            difficulty -= 1;
+            reasons.push(format!("Tasks finish in {:.0}% of their estimate", ratio * 100.0));
+        }
+    }
+
+    let median_latency_days = median(history.iter().filter_map(|s| s.latency_days).collect());
+    if let Some(days) = median_latency_days {
+        if days > 7.0 {
+            difficulty += 1;
+            reasons.push(format!("Tasks wait {:.0} days before they're done", days));
+        } else if days < 1.0 {
+            difficulty -= 1;
+            reasons.push("Tasks are usually done within a day".to_string());
+        }
+    }
+
+    DifficultyRecommendation {
+        category: category.to_string(),
+        difficulty: difficulty.clamp(MIN_DIFFICULTY, MAX_DIFFICULTY),
+        sample_size: history.len(),
+        median_time_ratio,
+        median_latency_days,
+        reasons,
+    }
+}
+
+// ---------- Persistence ----------
+
+/// The effective_difficulty XP multiplier for a task, using the user's current stats.
+pub fn task_reward_multiplier(conn: &Connection, base_difficulty: i64, category: &str) -> Result<f64, String> {
+    let user = crate::fetch_user_sync(conn)?;
+    let stats = Stats {
+        strength: user.strength,
+        intelligence: user.intelligence,
+        endurance: user.endurance,
+        charisma: user.charisma,
+    };
+    Ok(reward_multiplier(base_difficulty, effective_difficulty(base_difficulty, &stats, category)))
+}
+
+fn load_history(conn: &Connection, category: &str) -> Result<Vec<CompletedSample>, String> {
+    let mut stmt = conn
+        .prepare(
+            "SELECT difficulty, estimated_time_minutes, COALESCE(total_time_spent_seconds, 0),
+                    julianday(completed_at) - julianday(created_at)
+             FROM tasks
+             WHERE user_id = 1 AND status = 'completed' AND deleted_at IS NULL AND LOWER(category) = LOWER(?1)
+               AND completed_at IS NOT NULL
+             ORDER BY completed_at DESC
+             LIMIT ?2",
+        )
+        .map_err(|e| format!("Failed to prepare query: {}", e))?;
+    let history = stmt
+        .query_map(rusqlite::params![category, HISTORY_LIMIT], |row| {
+            Ok(CompletedSample {
+                difficulty: row.get(0)?,
+                estimated_minutes: row.get(1)?,
+                spent_seconds: row.get(2)?,
+                latency_days: row.get::<_, Option<f64>>(3)?.map(|days| days.max(0.0)),
+            })
+        })
+        .map_err(|e| format!("Failed to get task history: {}", e))?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| format!("Failed to read task history: {}", e))?;
+    Ok(history)
+}
+
+// ---------- Commands ----------
+
+#[tauri::command]
+pub async fn get_difficulty_recommendation(
+    db: State<'_, DbConnection>,
+    task_category: String,
+) -> Result<DifficultyRecommendation, String> {
+    let conn = db.lock().await;
+    let history = load_history(&conn, &task_category)?;
+    Ok(recommend(&task_category, &history))
+}
+
+// Get recommended task difficulty based on the user's history in the category
+#[tauri::command]
+pub async fn get_recommended_difficulty(db: State<'_, DbConnection>, task_category: String) -> Result<i64, String> {
+    Ok(get_difficulty_recommendation(db, task_category).await?.difficulty)
+}
+
+// ---------- Tests ----------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const STATS: Stats = Stats { strength: 10, intelligence: 10, endurance: 5, charisma: 0 };
+
+    fn sample(difficulty: i64, estimated_minutes: Option<i64>, spent_minutes: i64, latency_days: f64) -> CompletedSample {
+        CompletedSample {
+            difficulty,
+            estimated_minutes,
+            spent_seconds: spent_minutes * 60,
+            latency_days: Some(latency_days),
+        }
+    }
+
+    #[test]
+    fn effective_difficulty_uses_the_category_stat() {
+        assert!((effective_difficulty(10, &STATS, "work") - 7.0).abs() < 1e-9);
+        assert!((effective_difficulty(10, &STATS, "Health") - 8.5).abs() < 1e-9);
+        assert_eq!(effective_difficulty(10, &STATS, "social"), 10.0);
+        // Capped at 50% easier and never below 1
+        let strong = Stats { intelligence: 100, ..STATS };
+        assert_eq!(effective_difficulty(10, &strong, "learning"), 5.0);
+        assert_eq!(effective_difficulty(1, &strong, "learning"), 1.0);
+    }
+
+    #[test]
+    fn reward_multiplier_follows_effective_difficulty() {
+        assert!((reward_multiplier(10, 7.0) - 0.7).abs() < 1e-9);
+        assert_eq!(reward_multiplier(10, 10.0), 1.0);
+        assert_eq!(reward_multiplier(1, 1.0), 1.0);
+    }
+
+    #[test]
+    fn recommends_default_without_history() {
+        let rec = recommend("work", &[]);
+        assert_eq!((rec.difficulty, rec.sample_size), (DEFAULT_DIFFICULTY, 0));
+    }
+
+    #[test]
+    fn recommendation_follows_estimates_and_latency() {
+        // Overrunning estimates and taking over a week: one step harder each
+        let slow = [sample(4, Some(30), 60, 10.0), sample(4, Some(60), 90, 8.0), sample(5, None, 0, 9.0)];
+        let rec = recommend("work", &slow);
+        assert_eq!(rec.difficulty, 6);
+        assert_eq!(rec.median_time_ratio, Some(1.75));
+
+        // Beating estimates and finishing same day: one step easier each
+        let fast = [sample(3, Some(60), 30, 0.2), sample(3, Some(60), 40, 0.5)];
+        assert_eq!(recommend("work", &fast).difficulty, 1);
+    }
+}
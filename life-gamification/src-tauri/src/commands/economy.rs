@@ -0,0 +1,442 @@
+// Reward economy.
+//
+// One persisted, editable config (economy_settings) for every number that turns a
+// task into XP and gold: base rewards per difficulty, INT/LUCK scaling and its
+// cap, recurring streak tiers, the health verification bonus, goal milestone
+// payouts and per-completion caps. Task creation (manual, GitHub, reminders, capture, calendar) prices tasks
+// with task_rewards(); complete_task and goal completion build their rewards with
+// EconomyConfig::completion_rewards().
+//
+// Base rewards are stored on each task when it is created, so editing them only
+// affects tasks created afterwards. Bonuses, tiers and caps apply at completion.
+
+use rusqlite::Connection;
+use serde::{Deserialize, Serialize};
+use tauri::State;
+
+use crate::commands::difficulty;
+use crate::commands::ledger::Reward;
+use crate::database::DbConnection;
+
+pub const MIN_DIFFICULTY: i64 = 1;
+pub const MAX_DIFFICULTY: i64 = 10;
+
+// ---------- Types ----------
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DifficultyReward {
+    pub difficulty: i64,
+    pub xp: i64,
+    pub gold: i64,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct StreakTier {
+    /// Consecutive scheduled occurrences needed to reach this tier.
+    pub min_streak: i64,
+    pub multiplier: f64,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct EconomyConfig {
+    /// One entry per difficulty 1..=10.
+    pub base_rewards: Vec<DifficultyReward>,
+    pub xp_per_intelligence: f64,
+    pub gold_per_luck: f64,
+    /// Upper bound on the INT / LUCK bonus (1.0 = at most +100%); None = uncapped.
+    pub max_stat_bonus: Option<f64>,
+    pub streak_tiers: Vec<StreakTier>,
+    /// Extra share of base XP paid when a task is verified by health data.
+    pub verification_bonus: f64,
+    pub max_xp_per_completion: Option<i64>,
+    pub max_gold_per_completion: Option<i64>,
+    /// Progress percentages of a goal task that pay part of its reward early.
+    #[serde(default = "default_goal_milestones")]
+    pub goal_milestones: Vec<i64>,
+    /// Share of the base reward paid at each milestone (0 turns them off).
+    #[serde(default = "default_goal_milestone_share")]
+    pub goal_milestone_share: f64,
+}
+
+fn default_goal_milestones() -> Vec<i64> {
+    vec![25, 50, 75]
+}
+
+fn default_goal_milestone_share() -> f64 {
+    0.1
+}
+
+/// Everything besides the task's base reward that scales a completion reward.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Bonuses {
+    /// effective / base difficulty (see difficulty.rs), applied to XP.
+    pub difficulty_multiplier: f64,
+    /// INT / LUCK after skill tree and stat buffs.
+    pub intelligence: i64,
+    pub luck: i64,
+    pub streak_multiplier: f64,
+    pub xp_buff: f64,
+    pub gold_buff: f64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct RewardPreview {
+    pub difficulty: i64,
+    pub xp: Reward,
+    pub gold: Reward,
+}
+
+impl Default for EconomyConfig {
+    fn default() -> Self {
+        EconomyConfig {
+            // The original formula: 10 + 5 XP and 1 + 1 gold per difficulty step
+            base_rewards: (MIN_DIFFICULTY..=MAX_DIFFICULTY)
+                .map(|difficulty| DifficultyReward {
+                    difficulty,
+                    xp: 10 + (difficulty - 1) * 5,
+                    gold: 1 + (difficulty - 1),
+                })
+                .collect(),
+            xp_per_intelligence: 0.02,
+            gold_per_luck: 0.015,
+            max_stat_bonus: None,
+            streak_tiers: vec![
+                StreakTier { min_streak: 7, multiplier: 2.0 },
+                StreakTier { min_streak: 30, multiplier: 3.0 },
+            ],
+            verification_bonus: 0.5,
+            max_xp_per_completion: None,
+            max_gold_per_completion: None,
+            goal_milestones: default_goal_milestones(),
+            goal_milestone_share: default_goal_milestone_share(),
+        }
+    }
+}
+
+// ---------- Reward math (pure functions, unit-tested) ----------
+
+impl EconomyConfig {
+    /// Base (xp, gold) for a task of `difficulty`, clamped to the 1..=10 range.
+    pub fn base_rewards_for(&self, difficulty: i64) -> (i64, i64) {
+        let difficulty = difficulty.clamp(MIN_DIFFICULTY, MAX_DIFFICULTY);
+        self.base_rewards
+            .iter()
+            .find(|r| r.difficulty == difficulty)
+            .map(|r| (r.xp, r.gold))
+            .unwrap_or((0, 0))
+    }
+
+    /// XP multiplier earned by a streak of scheduled occurrences.
+    pub fn streak_multiplier(&self, streak: i64) -> f64 {
+        self.streak_tiers
+            .iter()
+            .filter(|tier| streak >= tier.min_streak)
+            .map(|tier| tier.multiplier)
+            .fold(1.0, f64::max)
+    }
+
+    /// XP and gold for completing a task worth `base_xp` / `base_gold`.
+    pub fn completion_rewards(&self, base_xp: i64, base_gold: i64, bonuses: &Bonuses) -> (Reward, Reward) {
+        let cap = self.max_stat_bonus.unwrap_or(f64::INFINITY);
+        let int_bonus = (bonuses.intelligence as f64 * self.xp_per_intelligence).clamp(0.0, cap);
+        let luck_bonus = (bonuses.luck as f64 * self.gold_per_luck).clamp(0.0, cap);
+
+        let xp = Reward::flat(base_xp)
+            .with("effective_difficulty", bonuses.difficulty_multiplier)
+            .with("intelligence", 1.0 + int_bonus)
+            .with("streak", bonuses.streak_multiplier)
+            .with("xp_buff", bonuses.xp_buff)
+            .capped(self.max_xp_per_completion);
+        let gold = Reward::flat(base_gold)
+            .with("luck", 1.0 + luck_bonus)
+            .with("gold_buff", bonuses.gold_buff)
+            .capped(self.max_gold_per_completion);
+        (xp, gold)
+    }
+
+    /// Bonus XP for a task verified against health data.
+    pub fn verification_reward(&self, base_xp: i64) -> Reward {
+        Reward::flat(base_xp).with("verification", self.verification_bonus)
+    }
+}
+
+fn validate_config(config: &EconomyConfig) -> Result<(), String> {
+    for difficulty in MIN_DIFFICULTY..=MAX_DIFFICULTY {
+        let entries = config.base_rewards.iter().filter(|r| r.difficulty == difficulty).count();
+        if entries != 1 {
+            return Err(format!("base_rewards needs exactly one entry for difficulty {}", difficulty));
+        }
+    }
+    if config.base_rewards.len() as i64 != MAX_DIFFICULTY - MIN_DIFFICULTY + 1 {
+        return Err(format!("base_rewards only covers difficulties {}-{}", MIN_DIFFICULTY, MAX_DIFFICULTY));
+    }
+    if config.base_rewards.iter().any(|r| r.xp < 0 || r.gold < 0) {
+        return Err("Base rewards cannot be negative".to_string());
+    }
+    if config.xp_per_intelligence < 0.0 || config.gold_per_luck < 0.0 || config.max_stat_bonus.is_some_and(|cap| cap < 0.0) {
+        return Err("Stat scaling cannot be negative".to_string());
+    }
+    if config.streak_tiers.iter().any(|t| t.min_streak < 1 || t.multiplier < 1.0) {
+        return Err("Streak tiers need min_streak >= 1 and multiplier >= 1.0".to_string());
+    }
+    if config.verification_bonus < 0.0 {
+        return Err("verification_bonus cannot be negative".to_string());
+    }
+    if config.max_xp_per_completion.is_some_and(|cap| cap < 0)
+        || config.max_gold_per_completion.is_some_and(|cap| cap < 0)
+    {
+        return Err("Reward caps cannot be negative".to_string());
+    }
+    if config.goal_milestones.iter().any(|percent| !(1..100).contains(percent)) {
+        return Err("Goal milestones must be percentages between 1 and 99".to_string());
+    }
+    if config.goal_milestone_share < 0.0
+        || config.goal_milestone_share * config.goal_milestones.len() as f64 > 1.0
+    {
+        return Err("Goal milestones cannot pay out more than the whole reward".to_string());
+    }
+    Ok(())
+}
+
+// ---------- Persistence ----------
+
+pub fn load_config(conn: &Connection) -> Result<EconomyConfig, String> {
+    let (base_rewards, xp_per_intelligence, gold_per_luck, max_stat_bonus, streak_tiers,
+         verification_bonus, max_xp_per_completion, max_gold_per_completion, goal_milestones,
+         goal_milestone_share) = conn
+        .query_row(
+            "SELECT base_rewards, xp_per_intelligence, gold_per_luck, max_stat_bonus, streak_tiers,
+                    verification_bonus, max_xp_per_completion, max_gold_per_completion, goal_milestones,
+                    goal_milestone_share
+             FROM economy_settings WHERE id = 1",
+            [],
+            |row| {
+                Ok((
+                    row.get::<_, Option<String>>(0)?,
+                    row.get::<_, f64>(1)?,
+                    row.get::<_, f64>(2)?,
+                    row.get::<_, Option<f64>>(3)?,
+                    row.get::<_, Option<String>>(4)?,
+                    row.get::<_, f64>(5)?,
+                    row.get::<_, Option<i64>>(6)?,
+                    row.get::<_, Option<i64>>(7)?,
+                    row.get::<_, Option<String>>(8)?,
+                    row.get::<_, f64>(9)?,
+                ))
+            },
+        )
+        .map_err(|e| format!("Failed to load economy settings: {}", e))?;
+
+    // NULL lists mean "never edited": fall back to the defaults
+    let defaults = EconomyConfig::default();
+    let base_rewards = match base_rewards {
+        Some(json) => serde_json::from_str(&json)
+            .map_err(|e| format!("Invalid base_rewards in economy settings: {}", e))?,
+        None => defaults.base_rewards,
+    };
+    let streak_tiers = match streak_tiers {
+        Some(json) => serde_json::from_str(&json)
+            .map_err(|e| format!("Invalid streak_tiers in economy settings: {}", e))?,
+        None => defaults.streak_tiers,
+    };
+    let goal_milestones = match goal_milestones {
+        Some(json) => serde_json::from_str(&json)
+            .map_err(|e| format!("Invalid goal_milestones in economy settings: {}", e))?,
+        None => defaults.goal_milestones,
+    };
+
+    Ok(EconomyConfig {
+        base_rewards,
+        xp_per_intelligence,
+        gold_per_luck,
+        max_stat_bonus,
+        streak_tiers,
+        verification_bonus,
+        max_xp_per_completion,
+        max_gold_per_completion,
+        goal_milestones,
+        goal_milestone_share,
+    })
+}
+
+fn save_config(conn: &Connection, config: &EconomyConfig) -> Result<(), String> {
+    let base_rewards = serde_json::to_string(&config.base_rewards)
+        .map_err(|e| format!("Failed to encode base_rewards: {}", e))?;
+    let streak_tiers = serde_json::to_string(&config.streak_tiers)
+        .map_err(|e| format!("Failed to encode streak_tiers: {}", e))?;
+    let goal_milestones = serde_json::to_string(&config.goal_milestones)
+        .map_err(|e| format!("Failed to encode goal_milestones: {}", e))?;
+    conn.execute(
+        "UPDATE economy_settings SET base_rewards = ?1, xp_per_intelligence = ?2, gold_per_luck = ?3,
+         max_stat_bonus = ?4, streak_tiers = ?5, verification_bonus = ?6, max_xp_per_completion = ?7,
+         max_gold_per_completion = ?8, goal_milestones = ?9, goal_milestone_share = ?10,
+         updated_at = CURRENT_TIMESTAMP
+         WHERE id = 1",
+        rusqlite::params![
+            base_rewards,
+            config.xp_per_intelligence,
+            config.gold_per_luck,
+            config.max_stat_bonus,
+            streak_tiers,
+            config.verification_bonus,
+            config.max_xp_per_completion,
+            config.max_gold_per_completion,
+            goal_milestones,
+            config.goal_milestone_share,
+        ],
+    )
+    .map_err(|e| format!("Failed to save economy settings: {}", e))?;
+    Ok(())
+}
+
+/// Base (xp, gold) to store on a new task of `difficulty`.
+pub fn task_rewards(conn: &Connection, difficulty: i64) -> Result<(i64, i64), String> {
+    Ok(load_config(conn)?.base_rewards_for(difficulty))
+}
+
+/// The user's current INT / LUCK (with skill tree and stat buffs) and active
+/// XP / gold buffs, alongside the task's own multipliers.
+pub fn user_bonuses(conn: &Connection, difficulty_multiplier: f64, streak_multiplier: f64) -> Result<Bonuses, String> {
+    let user = crate::fetch_user_sync(conn)?;
+    let (_, intelligence, _, _, luck) = crate::apply_stat_buffs_to_user_stats(&user);
+    let (xp_buff, gold_buff) = crate::active_reward_buffs(conn);
+    Ok(Bonuses {
+        difficulty_multiplier,
+        intelligence,
+        luck,
+        streak_multiplier,
+        xp_buff,
+        gold_buff,
+    })
+}
+
+// ---------- Commands ----------
+
+#[tauri::command]
+pub async fn get_economy_config(db: State<'_, DbConnection>) -> Result<EconomyConfig, String> {
+    let conn = db.lock().await;
+    load_config(&conn)
+}
+
+#[tauri::command]
+pub async fn update_economy_config(
+    db: State<'_, DbConnection>,
+    config: EconomyConfig,
+) -> Result<EconomyConfig, String> {
+    validate_config(&config)?;
+    let conn = db.lock().await;
+    save_config(&conn, &config)?;
+    Ok(config)
+}
+
+#[tauri::command]
+pub async fn reset_economy_config(db: State<'_, DbConnection>) -> Result<EconomyConfig, String> {
+    let config = EconomyConfig::default();
+    let conn = db.lock().await;
+    save_config(&conn, &config)?;
+    Ok(config)
+}
+
+/// What completing a hypothetical task of `difficulty` (in `category`, if given)
+/// would pay right now, with the user's current stats and buffs and an optional
+/// recurring streak.
+#[tauri::command]
+pub async fn preview_task_reward(
+    db: State<'_, DbConnection>,
+    difficulty: i64,
+    category: Option<String>,
+    streak: Option<i64>,
+) -> Result<RewardPreview, String> {
+    if !(MIN_DIFFICULTY..=MAX_DIFFICULTY).contains(&difficulty) {
+        return Err(format!("Difficulty must be between {} and {}", MIN_DIFFICULTY, MAX_DIFFICULTY));
+    }
+    let conn = db.lock().await;
+    let config = load_config(&conn)?;
+    let (base_xp, base_gold) = config.base_rewards_for(difficulty);
+    let difficulty_multiplier = match category.as_deref() {
+        Some(category) => difficulty::task_reward_multiplier(&conn, difficulty, category)?,
+        None => 1.0,
+    };
+    let bonuses = user_bonuses(&conn, difficulty_multiplier, config.streak_multiplier(streak.unwrap_or(0)))?;
+    let (xp, gold) = config.completion_rewards(base_xp, base_gold, &bonuses);
+    Ok(RewardPreview { difficulty, xp, gold })
+}
+
+// ---------- Tests ----------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn no_bonuses() -> Bonuses {
+        Bonuses {
+            difficulty_multiplier: 1.0,
+            intelligence: 0,
+            luck: 0,
+            streak_multiplier: 1.0,
+            xp_buff: 1.0,
+            gold_buff: 1.0,
+        }
+    }
+
+    #[test]
+    fn defaults_match_the_original_formula() {
+        let config = EconomyConfig::default();
+        assert!(validate_config(&config).is_ok());
+        assert_eq!(config.base_rewards_for(1), (10, 1));
+        assert_eq!(config.base_rewards_for(5), (30, 5));
+        assert_eq!(config.base_rewards_for(10), (55, 10));
+        assert_eq!(config.base_rewards_for(42), (55, 10));
+    }
+
+    #[test]
+    fn streak_tiers() {
+        let config = EconomyConfig::default();
+        assert_eq!(config.streak_multiplier(1), 1.0);
+        assert_eq!(config.streak_multiplier(7), 2.0);
+        assert_eq!(config.streak_multiplier(29), 2.0);
+        assert_eq!(config.streak_multiplier(30), 3.0);
+    }
+
+    #[test]
+    fn stat_bonuses_and_caps() {
+        let mut config = EconomyConfig::default();
+        let bonuses = Bonuses { intelligence: 10, luck: 20, ..no_bonuses() };
+        let (xp, gold) = config.completion_rewards(100, 10, &bonuses);
+        assert_eq!((xp.final_amount, gold.final_amount), (120, 13));
+
+        // The INT bonus is uncapped by default
+        let bonuses = Bonuses { intelligence: 500, streak_multiplier: 2.0, ..no_bonuses() };
+        let (xp, _) = config.completion_rewards(100, 10, &bonuses);
+        assert_eq!(xp.final_amount, 2200);
+
+        // INT bonus stops at max_stat_bonus; the completion cap is applied last
+        config.max_stat_bonus = Some(1.0);
+        let (xp, _) = config.completion_rewards(100, 10, &bonuses);
+        assert_eq!(xp.final_amount, 400);
+        config.max_xp_per_completion = Some(250);
+        let (xp, _) = config.completion_rewards(100, 10, &bonuses);
+        assert_eq!(xp.final_amount, 250);
+    }
+
+    #[test]
+    fn rejects_incomplete_difficulty_table() {
+        let mut config = EconomyConfig::default();
+        config.base_rewards.pop();
+        assert!(validate_config(&config).is_err());
+    }
+
+    #[test]
+    fn persists_config() {
+        let conn = crate::database::test_conn();
+        assert_eq!(load_config(&conn).unwrap(), EconomyConfig::default());
+
+        let mut config = EconomyConfig::default();
+        config.base_rewards[2].xp = 99;
+        config.streak_tiers.push(StreakTier { min_streak: 100, multiplier: 5.0 });
+        config.max_stat_bonus = Some(1.5);
+        save_config(&conn, &config).unwrap();
+        assert_eq!(load_config(&conn).unwrap(), config);
+        assert_eq!(task_rewards(&conn, 3).unwrap(), (99, 3));
+    }
+}
@@ -0,0 +1,363 @@
+// Estimate accuracy.
+//
+// Compares each completed task's estimate (update_estimated_time) with the time
+// actually tracked on it (time_sessions, via total_time_spent_seconds). The
+// ratio actual / estimated is the task's estimation multiplier; the user's
+// personal multiplier is the median over their tasks, reported per category,
+// per difficulty and per month so they can see whether they're getting better.
+//
+// suggest_estimate proposes minutes for a new task from the most similar
+// completed tasks: same category, difficulty and project, and shared title
+// words.
+
+use std::collections::{BTreeMap, HashSet};
+
+use rusqlite::Connection;
+use serde::{Deserialize, Serialize};
+use tauri::State;
+
+use crate::commands::difficulty::median;
+use crate::database::DbConnection;
+
+/// Most recent completed tasks considered for the report and suggestions.
+const HISTORY_LIMIT: i64 = 500;
+
+/// Similar tasks a suggestion is based on.
+const SIMILAR_LIMIT: usize = 5;
+
+/// A candidate needs at least this similarity score to count as similar.
+const MIN_SIMILARITY: f64 = 2.0;
+
+// ---------- Types ----------
+
+/// One completed task that had both an estimate and tracked time.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct EstimateSample {
+    pub task_id: i64,
+    pub title: String,
+    pub category: String,
+    pub difficulty: i64,
+    pub project_id: Option<i64>,
+    pub estimated_minutes: i64,
+    pub actual_minutes: f64,
+    /// YYYY-MM-DD
+    pub completed_on: String,
+}
+
+impl EstimateSample {
+    pub fn multiplier(&self) -> f64 {
+        self.actual_minutes / self.estimated_minutes as f64
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct GroupAccuracy {
+    pub key: String,
+    pub sample_size: usize,
+    pub estimated_minutes: i64,
+    pub actual_minutes: f64,
+    /// Median actual / estimated over the group's tasks.
+    pub multiplier: f64,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct EstimateReport {
+    /// Median actual / estimated over all tasks; None without history.
+    pub multiplier: Option<f64>,
+    pub sample_size: usize,
+    pub tasks: Vec<EstimateSample>,
+    pub by_category: Vec<GroupAccuracy>,
+    pub by_difficulty: Vec<GroupAccuracy>,
+    /// One point per month (key YYYY-MM), oldest first.
+    pub trend: Vec<GroupAccuracy>,
+    /// Whether the latest month's multiplier is closer to 1.0 than the first
+    /// month's; None with fewer than two months.
+    pub improving: Option<bool>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct EstimateSuggestionRequest {
+    pub title: String,
+    pub category: String,
+    pub difficulty: Option<i64>,
+    pub project_id: Option<i64>,
+    /// The user's own guess, scaled by their personal multiplier.
+    pub estimated_minutes: Option<i64>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct EstimateSuggestion {
+    /// Median actual minutes of the similar tasks.
+    pub suggested_minutes: Option<i64>,
+    /// The user's guess times their personal multiplier.
+    pub adjusted_minutes: Option<i64>,
+    pub multiplier: Option<f64>,
+    pub similar: Vec<EstimateSample>,
+    pub reasons: Vec<String>,
+}
+
+// ---------- Analysis (pure functions, unit-tested) ----------
+
+fn group_by<K: Ord + ToString>(samples: &[EstimateSample], key: impl Fn(&EstimateSample) -> K) -> Vec<GroupAccuracy> {
+    let mut groups: BTreeMap<K, Vec<&EstimateSample>> = BTreeMap::new();
+    for sample in samples {
+        groups.entry(key(sample)).or_default().push(sample);
+    }
+    groups
+        .into_iter()
+        .filter_map(|(key, group)| {
+            Some(GroupAccuracy {
+                key: key.to_string(),
+                sample_size: group.len(),
+                estimated_minutes: group.iter().map(|s| s.estimated_minutes).sum(),
+                actual_minutes: group.iter().map(|s| s.actual_minutes).sum(),
+                multiplier: median(group.iter().map(|s| s.multiplier()).collect())?,
+            })
+        })
+        .collect()
+}
+
+pub fn build_report(samples: Vec<EstimateSample>) -> EstimateReport {
+    let trend = group_by(&samples, |s| s.completed_on.get(..7).unwrap_or_default().to_string());
+    let improving = match (trend.first(), trend.last()) {
+        (Some(first), Some(last)) if trend.len() > 1 => {
+            Some((last.multiplier - 1.0).abs() < (first.multiplier - 1.0).abs())
+        }
+        _ => None,
+    };
+    EstimateReport {
+        multiplier: median(samples.iter().map(|s| s.multiplier()).collect()),
+        sample_size: samples.len(),
+        by_category: group_by(&samples, |s| s.category.to_lowercase()),
+        by_difficulty: group_by(&samples, |s| s.difficulty),
+        trend,
+        improving,
+        tasks: samples,
+    }
+}
+
+fn title_words(title: &str) -> HashSet<String> {
+    title
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|word| word.chars().count() >= 3)
+        .map(str::to_lowercase)
+        .collect()
+}
+
+/// How alike a completed task is to the one being created: 2 for the same
+/// category, 1 for the same project, 1 for the same difficulty (0.5 one step
+/// off), plus up to 3 for the share of title words in common.
+pub fn similarity(request: &EstimateSuggestionRequest, sample: &EstimateSample) -> f64 {
+    let mut score = 0.0;
+    if sample.category.eq_ignore_ascii_case(&request.category) {
+        score += 2.0;
+    }
+    if request.project_id.is_some() && sample.project_id == request.project_id {
+        score += 1.0;
+    }
+    if let Some(difficulty) = request.difficulty {
+        match (sample.difficulty - difficulty).abs() {
+            0 => score += 1.0,
+            1 => score += 0.5,
+            _ => {}
+        }
+    }
+    let wanted = title_words(&request.title);
+    let have = title_words(&sample.title);
+    let union = wanted.union(&have).count();
+    if union > 0 {
+        score += 3.0 * wanted.intersection(&have).count() as f64 / union as f64;
+    }
+    score
+}
+
+pub fn suggest(request: &EstimateSuggestionRequest, history: &[EstimateSample]) -> EstimateSuggestion {
+    let mut reasons = Vec::new();
+    let multiplier = median(history.iter().map(|s| s.multiplier()).collect());
+
+    let mut scored: Vec<(f64, &EstimateSample)> = history
+        .iter()
+        .map(|s| (similarity(request, s), s))
+        .filter(|(score, _)| *score >= MIN_SIMILARITY)
+        .collect();
+    // Stable sort keeps the most recent first among equals
+    scored.sort_by(|a, b| b.0.total_cmp(&a.0));
+    let similar: Vec<EstimateSample> = scored.into_iter().take(SIMILAR_LIMIT).map(|(_, s)| s.clone()).collect();
+
+    let suggested_minutes = median(similar.iter().map(|s| s.actual_minutes).collect()).map(|m| m.round().max(1.0) as i64);
+    match suggested_minutes {
+        Some(minutes) => reasons.push(format!("{} similar tasks took a median of {} minutes", similar.len(), minutes)),
+        None => reasons.push("No similar completed tasks with tracked time yet".to_string()),
+    }
+
+    let adjusted_minutes = match (request.estimated_minutes.filter(|m| *m > 0), multiplier) {
+        (Some(guess), Some(multiplier)) => {
+            reasons.push(format!("Your tasks take {:.0}% of their estimate", multiplier * 100.0));
+            Some((guess as f64 * multiplier).round().max(1.0) as i64)
+        }
+        _ => None,
+    };
+
+    EstimateSuggestion { suggested_minutes, adjusted_minutes, multiplier, similar, reasons }
+}
+
+// ---------- Persistence ----------
+
+/// Completed tasks with an estimate and tracked time, most recent first.
+pub fn load_samples(conn: &Connection) -> Result<Vec<EstimateSample>, String> {
+    let mut stmt = conn
+        .prepare(
+            "SELECT id, title, COALESCE(category, 'general'), COALESCE(difficulty, 5), project_id,
+                    estimated_time_minutes, total_time_spent_seconds, date(completed_at)
+             FROM tasks
+             WHERE user_id = 1 AND status = 'completed' AND deleted_at IS NULL AND completed_at IS NOT NULL
+               AND estimated_time_minutes > 0 AND total_time_spent_seconds > 0
+             ORDER BY completed_at DESC
+             LIMIT ?1",
+        )
+        .map_err(|e| format!("Failed to prepare query: {}", e))?;
+    let samples = stmt
+        .query_map([HISTORY_LIMIT], |row| {
+            Ok(EstimateSample {
+                task_id: row.get(0)?,
+                title: row.get(1)?,
+                category: row.get(2)?,
+                difficulty: row.get(3)?,
+                project_id: row.get(4)?,
+                estimated_minutes: row.get(5)?,
+                actual_minutes: row.get::<_, i64>(6)? as f64 / 60.0,
+                completed_on: row.get(7)?,
+            })
+        })
+        .map_err(|e| format!("Failed to get task history: {}", e))?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| format!("Failed to read task history: {}", e))?;
+    Ok(samples)
+}
+
+// ---------- Commands ----------
+
+#[tauri::command]
+pub async fn get_estimate_report(db: State<'_, DbConnection>) -> Result<EstimateReport, String> {
+    let conn = db.lock().await;
+    Ok(build_report(load_samples(&conn)?))
+}
+
+#[tauri::command]
+pub async fn suggest_estimate(
+    db: State<'_, DbConnection>,
+    request: EstimateSuggestionRequest,
+) -> Result<EstimateSuggestion, String> {
+    let conn = db.lock().await;
+    Ok(suggest(&request, &load_samples(&conn)?))
+}
+
+// ---------- Tests ----------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample(task_id: i64, title: &str, category: &str, difficulty: i64, estimated: i64, actual: f64, completed_on: &str) -> EstimateSample {
+        EstimateSample {
+            task_id,
+            title: title.to_string(),
+            category: category.to_string(),
+            difficulty,
+            project_id: None,
+            estimated_minutes: estimated,
+            actual_minutes: actual,
+            completed_on: completed_on.to_string(),
+        }
+    }
+
+    fn request(title: &str, category: &str, estimated_minutes: Option<i64>) -> EstimateSuggestionRequest {
+        EstimateSuggestionRequest {
+            title: title.to_string(),
+            category: category.to_string(),
+            difficulty: Some(5),
+            project_id: None,
+            estimated_minutes,
+        }
+    }
+
+    fn close(a: f64, b: f64) -> bool {
+        (a - b).abs() < 1e-9
+    }
+
+    #[test]
+    fn report_groups_multipliers_and_trend() {
+        let report = build_report(vec![
+            sample(4, "Report", "Work", 5, 60, 66.0, "2026-03-20"),
+            sample(3, "Gym", "health", 3, 60, 60.0, "2026-03-02"),
+            sample(2, "Report", "work", 5, 30, 60.0, "2026-02-10"),
+            sample(1, "Email", "work", 2, 10, 30.0, "2026-02-01"),
+        ]);
+        assert_eq!(report.sample_size, 4);
+        assert!(close(report.multiplier.unwrap(), 1.55));
+
+        let work = report.by_category.iter().find(|g| g.key == "work").unwrap();
+        assert_eq!((work.sample_size, work.estimated_minutes), (3, 100));
+        assert!(close(work.multiplier, 2.0));
+        let fives = report.by_difficulty.iter().find(|g| g.key == "5").unwrap();
+        assert!(close(fives.multiplier, 1.55));
+
+        let months: Vec<&str> = report.trend.iter().map(|g| g.key.as_str()).collect();
+        assert_eq!(months, vec!["2026-02", "2026-03"]);
+        assert!(close(report.trend[0].multiplier, 2.5) && close(report.trend[1].multiplier, 1.05));
+        assert_eq!(report.improving, Some(true));
+
+        assert_eq!(build_report(Vec::new()).multiplier, None);
+    }
+
+    #[test]
+    fn suggests_from_similar_tasks() {
+        let history = vec![
+            sample(1, "Write weekly report", "work", 5, 30, 50.0, "2026-03-01"),
+            sample(2, "Write monthly report", "work", 5, 30, 40.0, "2026-03-02"),
+            sample(3, "Go running", "health", 5, 30, 30.0, "2026-03-03"),
+            sample(4, "Plan sprint", "work", 9, 60, 90.0, "2026-03-04"),
+        ];
+        let suggestion = suggest(&request("Write quarterly report", "Work", Some(20)), &history);
+        let ids: Vec<i64> = suggestion.similar.iter().map(|s| s.task_id).collect();
+        // Title overlap ranks the reports first; a different category alone isn't similar enough
+        assert_eq!(ids, vec![1, 2, 4]);
+        assert_eq!(suggestion.suggested_minutes, Some(50));
+        // Median multiplier over all history is (1.333 + 1.5) / 2
+        assert_eq!(suggestion.adjusted_minutes, Some(28));
+
+        let none = suggest(&request("Anything", "social", None), &history);
+        assert_eq!((none.suggested_minutes, none.adjusted_minutes), (None, None));
+        assert!(none.similar.is_empty());
+    }
+
+    #[test]
+    fn loads_completed_tasks_with_estimates_and_time() {
+        let conn = crate::database::test_conn();
+        conn.execute_batch(
+            "INSERT INTO tasks (id, user_id, title, status, category, difficulty, estimated_time_minutes, completed_at)
+             VALUES (1, 1, 'Done', 'completed', 'work', 4, 30, '2026-03-01 10:00:00'),
+                    (2, 1, 'No estimate', 'completed', 'work', 4, NULL, '2026-03-01 10:00:00'),
+                    (3, 1, 'Open', 'active', 'work', 4, 30, NULL),
+                    (4, 1, 'Unrated', 'completed', 'work', NULL, 20, '2026-02-01 10:00:00');
+             INSERT INTO time_sessions (task_id, user_id, start_time, end_time, duration_seconds, session_type) VALUES
+                 (1, 1, '2026-03-01T09:00:00+00:00', '2026-03-01T09:45:00+00:00', 2700, 'focus'),
+                 (1, 1, '2026-03-01T09:45:00+00:00', '2026-03-01T09:50:00+00:00', 300, 'break'),
+                 (2, 1, '2026-03-01T08:00:00+00:00', '2026-03-01T08:45:00+00:00', 2700, 'focus'),
+                 (3, 1, '2026-03-01T07:00:00+00:00', '2026-03-01T07:45:00+00:00', 2700, 'focus'),
+                 (4, 1, '2026-02-01T09:00:00+00:00', '2026-02-01T09:20:00+00:00', 1200, 'focus');",
+        )
+        .unwrap();
+        for task_id in 1..=4 {
+            crate::commands::sessions::recompute_task_time(&conn, task_id).unwrap();
+        }
+
+        let samples = load_samples(&conn).unwrap();
+        assert_eq!(samples.len(), 2);
+        assert_eq!(samples[0].task_id, 1);
+        assert_eq!(samples[0].completed_on, "2026-03-01");
+        assert!((samples[0].actual_minutes - 45.0).abs() < 0.1);
+        // A task without a difficulty counts at the default
+        assert_eq!((samples[1].task_id, samples[1].difficulty), (4, 5));
+    }
+}
@@ -8,8 +8,12 @@ use serde::Serialize;
 use std::collections::HashMap;
 use tauri::State;
 
+use crate::commands::trash::{self, TrashKind, UndoAction, UndoToken};
 use crate::database::DbConnection;
 
+/// SQL condition leaving out transactions of accounts in the trash.
+const LIVE_ACCOUNT: &str = "account_id NOT IN (SELECT id FROM accounts WHERE deleted_at IS NOT NULL)";
+
 // ---------- Types ----------
 
 #[derive(Debug, Clone, Serialize)]
@@ -41,6 +45,11 @@ pub struct ImportSummary {
     pub imported: i64,
     pub duplicates: i64,
     pub skipped: i64,
+    /// Ids of the transactions this import added.
+    #[serde(skip)]
+    pub imported_ids: Vec<i64>,
+    /// Takes the whole import back out again (see trash.rs).
+    pub undo_token: Option<UndoToken>,
 }
 
 #[derive(Debug, Clone, Serialize)]
@@ -478,6 +487,7 @@ pub fn import_csv_into(
 
         if inserted > 0 {
             summary.imported += 1;
+            summary.imported_ids.push(tx.last_insert_rowid());
         } else {
             summary.duplicates += 1;
         }
@@ -494,7 +504,10 @@ pub fn import_csv_into(
 pub async fn finance_get_accounts(db: State<'_, DbConnection>) -> Result<Vec<FinanceAccount>, String> {
     let conn = db.lock().await;
     let mut stmt = conn
-        .prepare("SELECT id, name, kind, created_at, balance_cents, currency FROM accounts ORDER BY name")
+        .prepare(
+            "SELECT id, name, kind, created_at, balance_cents, currency FROM accounts
+             WHERE deleted_at IS NULL ORDER BY name",
+        )
         .map_err(|e| format!("Failed to prepare accounts query: {}", e))?;
     let accounts: Result<Vec<FinanceAccount>, _> = stmt
         .query_map([], |row| {
@@ -526,6 +539,16 @@ pub async fn finance_create_account(
         return Err("Account kind must be bank, credit, or brokerage".to_string());
     }
     let conn = db.lock().await;
+    let trashed: bool = conn
+        .query_row(
+            "SELECT EXISTS(SELECT 1 FROM accounts WHERE name = ?1 AND deleted_at IS NOT NULL)",
+            [&name],
+            |row| row.get(0),
+        )
+        .map_err(|e| format!("Failed to check account: {}", e))?;
+    if trashed {
+        return Err(format!("An account named \"{}\" is in the trash; restore it or delete it for good", name));
+    }
     conn.execute(
         "INSERT INTO accounts (name, kind) VALUES (?1, ?2)",
         rusqlite::params![name, kind],
@@ -549,15 +572,22 @@ pub async fn finance_create_account(
     .map_err(|e| format!("Failed to load account: {}", e))
 }
 
+/// Move an account (and with it, its transactions) to the trash.
 #[tauri::command]
-pub async fn finance_delete_account(db: State<'_, DbConnection>, account_id: i64) -> Result<(), String> {
+pub async fn finance_delete_account(db: State<'_, DbConnection>, account_id: i64) -> Result<UndoToken, String> {
     let conn = db.lock().await;
-    // Cascade delete this account's transactions (FKs are off by default in SQLite).
-    conn.execute("DELETE FROM transactions WHERE account_id = ?1", [account_id])
-        .map_err(|e| format!("Failed to delete account transactions: {}", e))?;
-    conn.execute("DELETE FROM accounts WHERE id = ?1", [account_id])
-        .map_err(|e| format!("Failed to delete account: {}", e))?;
-    Ok(())
+    let tx = conn.unchecked_transaction()
+        .map_err(|e| format!("Failed to begin transaction: {}", e))?;
+    let name = trash::trash_item(&tx, TrashKind::FinanceAccount, account_id)?;
+    let undo = trash::issue_undo(
+        &tx,
+        &UndoAction::Restore { kind: TrashKind::FinanceAccount, id: account_id },
+        &format!("Account \"{}\" moved to trash", name),
+        chrono::Utc::now().naive_utc(),
+    )?;
+    tx.commit()
+        .map_err(|e| format!("Failed to commit: {}", e))?;
+    Ok(undo)
 }
 
 #[tauri::command]
@@ -568,7 +598,22 @@ pub async fn import_transactions_csv(
     source_label: String,
 ) -> Result<ImportSummary, String> {
     let conn = db.lock().await;
-    import_csv_into(&conn, account_id, &csv_content, &source_label)
+    let trashed: bool = conn
+        .query_row("SELECT deleted_at IS NOT NULL FROM accounts WHERE id = ?1", [account_id], |row| row.get(0))
+        .unwrap_or(false);
+    if trashed {
+        return Err(format!("Account {} is in the trash", account_id));
+    }
+    let mut summary = import_csv_into(&conn, account_id, &csv_content, &source_label)?;
+    if !summary.imported_ids.is_empty() {
+        summary.undo_token = Some(trash::issue_undo(
+            &conn,
+            &UndoAction::DeleteTransactions { ids: summary.imported_ids.clone() },
+            &format!("Imported {} transactions from {}", summary.imported, source_label),
+            chrono::Utc::now().naive_utc(),
+        )?);
+    }
+    Ok(summary)
 }
 
 #[tauri::command]
@@ -582,7 +627,7 @@ pub async fn finance_get_transactions(
     let conn = db.lock().await;
     let mut sql = String::from(
         "SELECT t.id, t.account_id, a.name, t.date, t.amount_cents, t.merchant, t.description, t.category
-         FROM transactions t JOIN accounts a ON a.id = t.account_id WHERE 1=1",
+         FROM transactions t JOIN accounts a ON a.id = t.account_id WHERE a.deleted_at IS NULL",
     );
     let mut params: Vec<Box<dyn rusqlite::types::ToSql>> = Vec::new();
     if let Some(month) = month.filter(|m| !m.is_empty()) {
@@ -632,23 +677,27 @@ pub async fn finance_get_spending_summary(
 
     let (spent_cents, income_cents, transaction_count): (i64, i64, i64) = conn
         .query_row(
-            "SELECT
-                COALESCE(SUM(CASE WHEN amount_cents < 0 THEN -amount_cents ELSE 0 END), 0),
-                COALESCE(SUM(CASE WHEN amount_cents > 0 THEN amount_cents ELSE 0 END), 0),
-                COUNT(*)
-             FROM transactions WHERE substr(date, 1, 7) = ?1",
+            &format!(
+                "SELECT
+                    COALESCE(SUM(CASE WHEN amount_cents < 0 THEN -amount_cents ELSE 0 END), 0),
+                    COALESCE(SUM(CASE WHEN amount_cents > 0 THEN amount_cents ELSE 0 END), 0),
+                    COUNT(*)
+                 FROM transactions WHERE substr(date, 1, 7) = ?1 AND {}",
+                LIVE_ACCOUNT
+            ),
             [&month],
             |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)),
         )
         .map_err(|e| format!("Failed to compute summary: {}", e))?;
 
     let mut stmt = conn
-        .prepare(
+        .prepare(&format!(
             "SELECT category, SUM(-amount_cents) AS spent
              FROM transactions
-             WHERE substr(date, 1, 7) = ?1 AND amount_cents < 0
+             WHERE substr(date, 1, 7) = ?1 AND amount_cents < 0 AND {}
              GROUP BY category ORDER BY spent DESC",
-        )
+            LIVE_ACCOUNT
+        ))
         .map_err(|e| format!("Failed to prepare category query: {}", e))?;
     let by_category: Result<Vec<CategorySpend>, _> = stmt
         .query_map([&month], |row| {
@@ -678,13 +727,15 @@ pub async fn finance_get_monthly_totals(
 ) -> Result<Vec<MonthlyTotal>, String> {
     let conn = db.lock().await;
     let mut stmt = conn
-        .prepare(
+        .prepare(&format!(
             "SELECT substr(date, 1, 7) AS month,
                 COALESCE(SUM(CASE WHEN amount_cents < 0 THEN -amount_cents ELSE 0 END), 0),
                 COALESCE(SUM(CASE WHEN amount_cents > 0 THEN amount_cents ELSE 0 END), 0)
              FROM transactions
+             WHERE {}
              GROUP BY month ORDER BY month DESC LIMIT ?1",
-        )
+            LIVE_ACCOUNT
+        ))
         .map_err(|e| format!("Failed to prepare monthly totals query: {}", e))?;
     let totals: Result<Vec<MonthlyTotal>, _> = stmt
         .query_map([months.max(1)], |row| {
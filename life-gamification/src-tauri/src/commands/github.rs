@@ -5,8 +5,10 @@ use rusqlite::OptionalExtension;
 use serde::{Deserialize, Serialize};
 use std::collections::HashSet;
 use std::process::Command;
-use tauri::State;
+use tauri::{AppHandle, State};
 
+use crate::commands::trash::{self, UndoAction, UndoToken};
+use crate::commands::{activity, economy, tags};
 use crate::database::DbConnection;
 
 // ---------- Types ----------
@@ -41,6 +43,8 @@ pub struct GithubSyncSummary {
     pub tasks_updated: i64,
     pub tasks_completed: i64,
     pub errors: Vec<String>,
+    /// Moves the imported tasks to the trash; set when issues were imported.
+    pub undo_token: Option<UndoToken>,
 }
 
 #[derive(Debug, Clone, Serialize)]
@@ -141,6 +145,19 @@ fn difficulty_from_labels(labels: &[GhLabel]) -> i64 {
     3
 }
 
+/// Issue labels carried over as task tags; "difficulty:N" labels are already
+/// reflected in the task's difficulty.
+fn tags_from_labels(labels: &[GhLabel]) -> Vec<String> {
+    labels
+        .iter()
+        .map(|label| label.name.clone())
+        .filter(|name| {
+            let name = name.to_lowercase();
+            !name.starts_with("difficulty:") && !name.starts_with("difficulty ")
+        })
+        .collect()
+}
+
 /// Keep imported descriptions to a sane length for task cards.
 fn truncate_body(body: &str) -> String {
     const MAX: usize = 500;
@@ -316,7 +333,7 @@ pub async fn github_get_task_links(db: State<'_, DbConnection>) -> Result<Vec<Gi
     let mut stmt = conn
         .prepare(
             "SELECT id, github_repo, github_issue_number FROM tasks
-             WHERE github_repo IS NOT NULL AND github_issue_number IS NOT NULL",
+             WHERE github_repo IS NOT NULL AND github_issue_number IS NOT NULL AND deleted_at IS NULL",
         )
         .map_err(|e| format!("Failed to prepare task links query: {}", e))?;
 
@@ -356,7 +373,7 @@ pub async fn github_close_issue(repo: String, issue_number: i64) -> Result<(), S
 /// refresh titles of already-imported tasks, and auto-complete tasks whose issue
 /// was closed on GitHub (granting XP via the normal complete_task path).
 #[tauri::command]
-pub async fn github_sync(db: State<'_, DbConnection>) -> Result<GithubSyncSummary, String> {
+pub async fn github_sync(app: AppHandle, db: State<'_, DbConnection>) -> Result<GithubSyncSummary, String> {
     let mut summary = GithubSyncSummary::default();
 
     // Snapshot enabled repos, then release the lock while shelling out to gh.
@@ -373,6 +390,7 @@ pub async fn github_sync(db: State<'_, DbConnection>) -> Result<GithubSyncSummar
     };
 
     let mut tasks_to_complete: Vec<i64> = Vec::new();
+    let mut imported_ids: Vec<i64> = Vec::new();
 
     for (repo_id, repo_full) in repos {
         let issues: Vec<GhIssue> = match run_gh(&[
@@ -401,11 +419,13 @@ pub async fn github_sync(db: State<'_, DbConnection>) -> Result<GithubSyncSummar
 
         {
             let conn = db.lock().await;
+            let _source = activity::with_source(&conn, activity::GITHUB)?;
 
             for issue in &issues {
                 let existing: Option<(i64, String, String)> = conn
                     .query_row(
-                        "SELECT id, status, title FROM tasks WHERE github_repo = ?1 AND github_issue_number = ?2",
+                        "SELECT id, CASE WHEN deleted_at IS NULL THEN status ELSE 'trashed' END, title
+                         FROM tasks WHERE github_repo = ?1 AND github_issue_number = ?2",
                         rusqlite::params![repo_full, issue.number],
                         |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)),
                     )
@@ -423,13 +443,15 @@ pub async fn github_sync(db: State<'_, DbConnection>) -> Result<GithubSyncSummar
                             .map_err(|e| format!("Failed to update task {}: {}", task_id, e))?;
                             summary.tasks_updated += 1;
                         }
-                        // Completed/archived tasks are left alone (reopened issues are out of scope).
+                        // Labels added on GitHub show up as tags; tags are never removed here.
+                        if status == "active" {
+                            tags::add_tags(&conn, task_id, &tags_from_labels(&issue.labels))?;
+                        }
+                        // Completed/archived/trashed tasks are left alone (reopened issues are out of scope).
                     }
                     None => {
                         let difficulty = difficulty_from_labels(&issue.labels);
-                        // Same reward formula as create_task in lib.rs.
-                        let base_xp = 10 + (difficulty - 1) * 5;
-                        let gold_reward = 1 + (difficulty - 1);
+                        let (base_xp, gold_reward) = economy::task_rewards(&conn, difficulty)?;
                         let description = issue
                             .body
                             .as_deref()
@@ -454,6 +476,9 @@ pub async fn github_sync(db: State<'_, DbConnection>) -> Result<GithubSyncSummar
                             ],
                         )
                         .map_err(|e| format!("Failed to import issue {}#{}: {}", repo_full, issue.number, e))?;
+                        let task_id = conn.last_insert_rowid();
+                        tags::add_tags(&conn, task_id, &tags_from_labels(&issue.labels))?;
+                        imported_ids.push(task_id);
                         summary.issues_imported += 1;
                     }
                 }
@@ -464,7 +489,8 @@ pub async fn github_sync(db: State<'_, DbConnection>) -> Result<GithubSyncSummar
             let mut stmt = conn
                 .prepare(
                     "SELECT id, github_issue_number FROM tasks
-                     WHERE github_repo = ?1 AND github_issue_number IS NOT NULL AND status = 'active'",
+                     WHERE github_repo = ?1 AND github_issue_number IS NOT NULL AND status = 'active'
+                       AND deleted_at IS NULL",
                 )
                 .map_err(|e| format!("Failed to prepare stale-tasks query: {}", e))?;
             let stale: Vec<(i64, i64)> = stmt
@@ -492,7 +518,7 @@ pub async fn github_sync(db: State<'_, DbConnection>) -> Result<GithubSyncSummar
 
     // Reuse the standard completion path (XP/gold/streak logic) for closed issues.
     for task_id in tasks_to_complete {
-        match crate::complete_task(db.clone(), task_id).await {
+        match crate::complete_task(app.clone(), db.clone(), task_id).await {
             Ok(_) => summary.tasks_completed += 1,
             Err(e) => summary.errors.push(format!("Failed to complete task {}: {}", task_id, e)),
         }
@@ -502,6 +528,14 @@ pub async fn github_sync(db: State<'_, DbConnection>) -> Result<GithubSyncSummar
         let conn = db.lock().await;
         conn.execute("UPDATE github_settings SET last_sync_at = CURRENT_TIMESTAMP WHERE id = 1", [])
             .map_err(|e| format!("Failed to stamp sync time: {}", e))?;
+        if !imported_ids.is_empty() {
+            summary.undo_token = Some(trash::issue_undo(
+                &conn,
+                &UndoAction::TrashTasks { ids: imported_ids },
+                &format!("Imported {} GitHub issue(s)", summary.issues_imported),
+                chrono::Utc::now().naive_utc(),
+            )?);
+        }
     }
 
     Ok(summary)
@@ -0,0 +1,450 @@
+// Goal task progress.
+//
+// Every change to a goal task's progress goes through record_progress: it is
+// logged in goal_progress_log (increments from update_task_progress, absolute
+// corrections from set_task_progress, which may also go down), so the history
+// can be listed and charted day by day. Crossing one of the configured
+// milestones (25/50/75% by default) pays a share of the task's base reward
+// early; reaching the target completes the task and pays the rest. Milestones
+// are paid once per task and are not taken back when progress goes down.
+
+use chrono::{Duration, NaiveDate, Utc};
+use rusqlite::{Connection, OptionalExtension};
+use serde::Serialize;
+use tauri::{AppHandle, State};
+
+use crate::commands::ledger::{self, RewardSource};
+use crate::commands::{completions, difficulty, economy, progression, subtasks};
+use crate::database::DbConnection;
+use crate::Task;
+
+// ---------- Types ----------
+
+/// How a progress change is given.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ProgressChange {
+    /// Add to the current progress (negative to undo some).
+    By(i64),
+    /// Set the progress outright, e.g. to correct a miscount.
+    To(i64),
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ProgressEntry {
+    pub id: i64,
+    pub delta: i64,
+    pub progress_after: i64,
+    pub kind: String,
+    pub note: Option<String>,
+    pub created_at: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct MilestoneReached {
+    pub percent: i64,
+    pub base_xp: i64,
+    pub base_gold: i64,
+    pub reached_at: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct GoalProgressHistory {
+    pub task_id: i64,
+    pub current_progress: i64,
+    pub target_progress: i64,
+    pub entries: Vec<ProgressEntry>,
+    pub milestones: Vec<MilestoneReached>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ProgressPoint {
+    pub date: String,
+    pub progress: i64,
+    pub percent: f64,
+}
+
+// ---------- Milestones and series (pure functions, unit-tested) ----------
+
+/// Milestones (percentages) reached at `progress` that haven't been paid yet.
+pub fn newly_reached(milestones: &[i64], paid: &[i64], progress: i64, target: i64) -> Vec<i64> {
+    if target <= 0 {
+        return Vec::new();
+    }
+    let mut reached: Vec<i64> = milestones
+        .iter()
+        .copied()
+        .filter(|percent| progress * 100 >= percent * target && !paid.contains(percent))
+        .collect();
+    reached.sort_unstable();
+    reached.dedup();
+    reached
+}
+
+/// One point per day from the first logged change through `until`, carrying
+/// the last known progress over days without changes. `changes` must be in
+/// the order they happened.
+pub fn daily_series(changes: &[(NaiveDate, i64)], until: NaiveDate, target: i64) -> Vec<ProgressPoint> {
+    let Some(&(first, _)) = changes.first() else {
+        return Vec::new();
+    };
+    let mut points = Vec::new();
+    let mut progress = 0;
+    let mut pending = changes.iter().peekable();
+    let mut date = first;
+    while date <= until {
+        while let Some(&&(_, value)) = pending.peek().filter(|(day, _)| *day <= date) {
+            progress = value;
+            pending.next();
+        }
+        let percent = if target > 0 { progress as f64 * 100.0 / target as f64 } else { 0.0 };
+        points.push(ProgressPoint {
+            date: date.format("%Y-%m-%d").to_string(),
+            progress,
+            percent: (percent * 10.0).round() / 10.0,
+        });
+        date += Duration::days(1);
+    }
+    points
+}
+
+// ---------- Recording ----------
+
+/// Apply a progress change to a goal task inside the caller's transaction:
+/// log it, pay any milestone newly reached, and complete the task when the
+/// target is reached. Changing a completed goal is a no-op; reopen it first.
+pub fn record_progress(
+    conn: &Connection,
+    task_id: i64,
+    change: ProgressChange,
+    note: Option<&str>,
+) -> Result<Option<progression::LevelUp>, String> {
+    let (task_type, status, current, target): (String, String, Option<i64>, Option<i64>) = conn
+        .query_row(
+            "SELECT t.task_type, t.status, tp.current_progress, tp.target_progress
+             FROM tasks t
+             LEFT JOIN task_progress tp ON t.id = tp.task_id
+             WHERE t.id = ?1 AND t.user_id = 1",
+            [task_id],
+            |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?)),
+        )
+        .map_err(|e| format!("Task not found: {}", e))?;
+
+    if task_type != "goal" {
+        return Err("Task is not a goal-based task".to_string());
+    }
+    if matches!(change, ProgressChange::To(value) if value < 0) {
+        return Err("Progress cannot be negative".to_string());
+    }
+    if status == "completed" {
+        return Ok(None);
+    }
+    let target = target.ok_or("Goal task has no target")?;
+    let current = current.unwrap_or(0);
+
+    let (new_current, kind) = match change {
+        ProgressChange::By(amount) => ((current + amount).max(0), "increment"),
+        ProgressChange::To(value) => (value, "correction"),
+    };
+
+    conn.execute(
+        "UPDATE task_progress SET current_progress = ?1, last_updated = CURRENT_TIMESTAMP WHERE task_id = ?2",
+        rusqlite::params![new_current, task_id],
+    )
+    .map_err(|e| format!("Failed to update progress: {}", e))?;
+    conn.execute(
+        "INSERT INTO goal_progress_log (task_id, delta, progress_after, kind, note) VALUES (?1, ?2, ?3, ?4, ?5)",
+        rusqlite::params![task_id, new_current - current, new_current, kind, note],
+    )
+    .map_err(|e| format!("Failed to log progress: {}", e))?;
+
+    let (xp_reward, gold_reward, difficulty, category): (i64, i64, i64, String) = conn
+        .query_row(
+            "SELECT base_experience_reward, gold_reward, difficulty, category FROM tasks WHERE id = ?1",
+            [task_id],
+            |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?)),
+        )
+        .map_err(|e| format!("Failed to get rewards: {}", e))?;
+    let config = economy::load_config(conn)?;
+    let difficulty_multiplier = difficulty::task_reward_multiplier(conn, difficulty, &category)?;
+    let bonuses = economy::user_bonuses(conn, difficulty_multiplier, 1.0)?;
+    let source = |source_type| RewardSource::new(source_type, task_id);
+
+    // Reaching the target pays whatever the milestones haven't, so milestones
+    // are only paid on their own short of it
+    if new_current < target {
+        let paid = paid_milestones(conn, task_id)?;
+        let mut level_up = None;
+        for percent in newly_reached(&config.goal_milestones, &paid, new_current, target) {
+            let base_xp = (xp_reward as f64 * config.goal_milestone_share).round() as i64;
+            let base_gold = (gold_reward as f64 * config.goal_milestone_share).round() as i64;
+            conn.execute(
+                "INSERT INTO goal_milestones (task_id, percent, base_xp, base_gold) VALUES (?1, ?2, ?3, ?4)",
+                rusqlite::params![task_id, percent, base_xp, base_gold],
+            )
+            .map_err(|e| format!("Failed to record milestone: {}", e))?;
+            let (xp, gold) = config.completion_rewards(base_xp, base_gold, &bonuses);
+            let note = format!("{}% milestone", percent);
+            ledger::grant_gold(conn, &source(ledger::GOAL_MILESTONE), &gold, Some(&note))?;
+            level_up = progression::combine(level_up, ledger::grant_xp(conn, &source(ledger::GOAL_MILESTONE), &xp, Some(&note))?);
+            println!("Goal task {} reached {}%: awarded {} XP and {} gold", task_id, percent, xp.final_amount, gold.final_amount);
+        }
+        return Ok(level_up);
+    }
+
+    conn.execute(
+        "UPDATE tasks SET status = 'completed', completed_at = CURRENT_TIMESTAMP WHERE id = ?1",
+        [task_id],
+    )
+    .map_err(|e| format!("Failed to complete task: {}", e))?;
+    completions::record_completion(conn, task_id, None, Some(current))?;
+
+    let (paid_xp, paid_gold): (i64, i64) = conn
+        .query_row(
+            "SELECT COALESCE(SUM(base_xp), 0), COALESCE(SUM(base_gold), 0) FROM goal_milestones WHERE task_id = ?1",
+            [task_id],
+            |row| Ok((row.get(0)?, row.get(1)?)),
+        )
+        .map_err(|e| format!("Failed to sum milestones: {}", e))?;
+    let (xp, gold) = config.completion_rewards((xp_reward - paid_xp).max(0), (gold_reward - paid_gold).max(0), &bonuses);
+    ledger::grant_gold(conn, &source(ledger::GOAL_COMPLETION), &gold, None)?;
+    let level_up = ledger::grant_xp(conn, &source(ledger::GOAL_COMPLETION), &xp, None)?;
+    println!("Goal task completed! Awarded {} XP and {} gold", xp.final_amount, gold.final_amount);
+
+    // Reaching the goal may finish the parent's last open subtask
+    let parent_level_up = match subtasks::parent_ready_to_complete(conn, task_id)? {
+        Some(parent_id) => crate::complete_task_sync(conn, parent_id)?,
+        None => None,
+    };
+    Ok(progression::combine(level_up, parent_level_up))
+}
+
+fn paid_milestones(conn: &Connection, task_id: i64) -> Result<Vec<i64>, String> {
+    let mut stmt = conn
+        .prepare("SELECT percent FROM goal_milestones WHERE task_id = ?1")
+        .map_err(|e| format!("Failed to prepare query: {}", e))?;
+    let paid = stmt
+        .query_map([task_id], |row| row.get(0))
+        .map_err(|e| format!("Failed to get milestones: {}", e))?
+        .collect::<Result<Vec<i64>, _>>()
+        .map_err(|e| format!("Failed to read milestones: {}", e))?;
+    Ok(paid)
+}
+
+fn load_progress(conn: &Connection, task_id: i64) -> Result<(i64, i64), String> {
+    conn.query_row(
+        "SELECT tp.current_progress, tp.target_progress
+         FROM task_progress tp JOIN tasks t ON t.id = tp.task_id
+         WHERE tp.task_id = ?1 AND t.user_id = 1",
+        [task_id],
+        |row| Ok((row.get::<_, Option<i64>>(0)?.unwrap_or(0), row.get(1)?)),
+    )
+    .optional()
+    .map_err(|e| format!("Failed to get progress: {}", e))?
+    .ok_or_else(|| format!("Task {} is not a goal task", task_id))
+}
+
+fn load_history(conn: &Connection, task_id: i64) -> Result<GoalProgressHistory, String> {
+    let (current_progress, target_progress) = load_progress(conn, task_id)?;
+
+    let mut stmt = conn
+        .prepare(
+            "SELECT id, delta, progress_after, kind, note, created_at
+             FROM goal_progress_log WHERE task_id = ?1 ORDER BY created_at, id",
+        )
+        .map_err(|e| format!("Failed to prepare query: {}", e))?;
+    let entries = stmt
+        .query_map([task_id], |row| {
+            Ok(ProgressEntry {
+                id: row.get(0)?,
+                delta: row.get(1)?,
+                progress_after: row.get(2)?,
+                kind: row.get(3)?,
+                note: row.get(4)?,
+                created_at: row.get(5)?,
+            })
+        })
+        .map_err(|e| format!("Failed to get progress history: {}", e))?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| format!("Failed to read progress history: {}", e))?;
+
+    let mut stmt = conn
+        .prepare(
+            "SELECT percent, base_xp, base_gold, reached_at
+             FROM goal_milestones WHERE task_id = ?1 ORDER BY percent",
+        )
+        .map_err(|e| format!("Failed to prepare query: {}", e))?;
+    let milestones = stmt
+        .query_map([task_id], |row| {
+            Ok(MilestoneReached {
+                percent: row.get(0)?,
+                base_xp: row.get(1)?,
+                base_gold: row.get(2)?,
+                reached_at: row.get(3)?,
+            })
+        })
+        .map_err(|e| format!("Failed to get milestones: {}", e))?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| format!("Failed to read milestones: {}", e))?;
+
+    Ok(GoalProgressHistory { task_id, current_progress, target_progress, entries, milestones })
+}
+
+fn progress_series(conn: &Connection, task_id: i64, today: NaiveDate) -> Result<Vec<ProgressPoint>, String> {
+    let history = load_history(conn, task_id)?;
+    let changes = history
+        .entries
+        .iter()
+        .filter_map(|entry| {
+            let date = NaiveDate::parse_from_str(entry.created_at.get(..10)?, "%Y-%m-%d").ok()?;
+            Some((date, entry.progress_after))
+        })
+        .collect::<Vec<_>>();
+
+    // A finished goal's line stops on the day it was completed
+    let completed_on: Option<String> = conn
+        .query_row(
+            "SELECT date(completed_at) FROM tasks WHERE id = ?1 AND status = 'completed'",
+            [task_id],
+            |row| row.get(0),
+        )
+        .optional()
+        .map_err(|e| format!("Failed to get task: {}", e))?
+        .flatten();
+    let until = completed_on
+        .and_then(|date| NaiveDate::parse_from_str(&date, "%Y-%m-%d").ok())
+        .unwrap_or(today);
+
+    Ok(daily_series(&changes, until, history.target_progress))
+}
+
+// ---------- Commands ----------
+
+/// Set a goal task's progress outright, e.g. to fix a miscount. Logged as a
+/// correction; it may lower the progress.
+#[tauri::command]
+pub async fn set_task_progress(
+    app: AppHandle,
+    db: State<'_, DbConnection>,
+    task_id: i64,
+    progress: i64,
+    note: Option<String>,
+) -> Result<Task, String> {
+    let conn = db.lock().await;
+    let tx = conn.unchecked_transaction()
+        .map_err(|e| format!("Failed to begin transaction: {}", e))?;
+    let level_up = record_progress(&tx, task_id, ProgressChange::To(progress), note.as_deref())?;
+    tx.commit()
+        .map_err(|e| format!("Failed to commit: {}", e))?;
+    progression::emit_level_up(&app, level_up);
+    crate::fetch_task_sync(&conn, task_id)
+}
+
+/// Every logged change to a goal task's progress, oldest first, and the
+/// milestones it has reached.
+#[tauri::command]
+pub async fn get_goal_progress_history(db: State<'_, DbConnection>, task_id: i64) -> Result<GoalProgressHistory, String> {
+    let conn = db.lock().await;
+    load_history(&conn, task_id)
+}
+
+/// A goal task's progress at the end of each day, from its first logged change
+/// through today (or the day it was completed).
+#[tauri::command]
+pub async fn get_goal_progress_series(db: State<'_, DbConnection>, task_id: i64) -> Result<Vec<ProgressPoint>, String> {
+    let conn = db.lock().await;
+    progress_series(&conn, task_id, Utc::now().naive_utc().date())
+}
+
+// ---------- Tests ----------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(value: &str) -> NaiveDate {
+        NaiveDate::parse_from_str(value, "%Y-%m-%d").unwrap()
+    }
+
+    #[test]
+    fn finds_newly_reached_milestones() {
+        let milestones = [25, 50, 75];
+        assert_eq!(newly_reached(&milestones, &[], 4, 20), Vec::<i64>::new());
+        assert_eq!(newly_reached(&milestones, &[], 5, 20), vec![25]);
+        assert_eq!(newly_reached(&milestones, &[25], 16, 20), vec![50, 75]);
+        assert_eq!(newly_reached(&milestones, &[25, 50], 9, 20), Vec::<i64>::new());
+        assert_eq!(newly_reached(&milestones, &[], 5, 0), Vec::<i64>::new());
+    }
+
+    #[test]
+    fn daily_series_carries_progress_forward() {
+        let changes = [
+            (date("2026-05-01"), 2),
+            (date("2026-05-01"), 3),
+            (date("2026-05-03"), 1),
+            (date("2026-05-04"), 5),
+        ];
+        let series = daily_series(&changes, date("2026-05-05"), 20);
+        let values: Vec<(&str, i64)> = series.iter().map(|p| (p.date.as_str(), p.progress)).collect();
+        assert_eq!(
+            values,
+            vec![("2026-05-01", 3), ("2026-05-02", 3), ("2026-05-03", 1), ("2026-05-04", 5), ("2026-05-05", 5)]
+        );
+        assert_eq!(series[4].percent, 25.0);
+        assert!(daily_series(&[], date("2026-05-05"), 20).is_empty());
+    }
+
+    #[test]
+    fn progress_pays_milestones_once_and_the_rest_on_completion() {
+        let conn = crate::database::test_conn();
+        conn.execute_batch(
+            "INSERT INTO tasks (id, user_id, title, status, task_type, base_experience_reward, gold_reward)
+             VALUES (1, 1, 'Read 20 books', 'active', 'goal', 100, 20);
+             INSERT INTO task_progress (task_id, current_progress, target_progress) VALUES (1, 0, 20);
+             INSERT INTO tasks (id, user_id, title, status) VALUES (2, 1, 'Not a goal', 'active');",
+        )
+        .unwrap();
+        let base_paid = |source_type: &str| -> i64 {
+            conn.query_row(
+                "SELECT COALESCE(SUM(base_amount), 0) FROM reward_ledger
+                 WHERE source_type = ?1 AND source_id = '1' AND currency = 'xp'",
+                [source_type],
+                |row| row.get(0),
+            )
+            .unwrap()
+        };
+
+        record_progress(&conn, 1, ProgressChange::By(6), Some("Two novels")).unwrap();
+        record_progress(&conn, 1, ProgressChange::By(-2), None).unwrap();
+        // Dropping back below a milestone and crossing it again doesn't pay twice
+        record_progress(&conn, 1, ProgressChange::To(11), Some("Recounted")).unwrap();
+        assert_eq!(base_paid(ledger::GOAL_MILESTONE), 20);
+
+        record_progress(&conn, 1, ProgressChange::By(-50), None).unwrap();
+        assert_eq!(load_progress(&conn, 1).unwrap(), (0, 20));
+
+        record_progress(&conn, 1, ProgressChange::By(20), None).unwrap();
+        assert_eq!(base_paid(ledger::GOAL_COMPLETION), 80);
+        let status: String = conn.query_row("SELECT status FROM tasks WHERE id = 1", [], |row| row.get(0)).unwrap();
+        assert_eq!(status, "completed");
+
+        // A completed goal ignores further progress
+        record_progress(&conn, 1, ProgressChange::By(1), None).unwrap();
+        let history = load_history(&conn, 1).unwrap();
+        let kinds: Vec<(&str, i64, i64)> =
+            history.entries.iter().map(|e| (e.kind.as_str(), e.delta, e.progress_after)).collect();
+        assert_eq!(
+            kinds,
+            vec![
+                ("increment", 6, 6),
+                ("increment", -2, 4),
+                ("correction", 7, 11),
+                ("increment", -11, 0),
+                ("increment", 20, 20),
+            ]
+        );
+        assert_eq!(history.milestones.iter().map(|m| m.percent).collect::<Vec<_>>(), vec![25, 50]);
+
+        assert!(record_progress(&conn, 2, ProgressChange::By(1), None).is_err());
+        assert!(record_progress(&conn, 1, ProgressChange::To(-1), None).is_err());
+    }
+}
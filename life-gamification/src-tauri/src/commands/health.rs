@@ -11,8 +11,11 @@ use serde_json::Value;
 use std::collections::hash_map::DefaultHasher;
 use std::hash::{Hash, Hasher};
 use std::path::PathBuf;
-use tauri::State;
+use tauri::{AppHandle, State};
 
+use crate::commands::economy;
+use crate::commands::ledger::{self, RewardSource};
+use crate::commands::progression;
 use crate::database::DbConnection;
 
 // ---------- Types ----------
@@ -234,6 +237,7 @@ struct VerifyCandidate {
 /// same-day workouts. Matching tasks are completed via the standard reward path,
 /// then granted 0.5x of base XP as a verification bonus and flagged verified.
 async fn verify_fitness_tasks_inner(
+    app: &AppHandle,
     db: &State<'_, DbConnection>,
 ) -> Result<(Vec<VerifiedTask>, Vec<String>), String> {
     let today = chrono::Local::now().format("%Y-%m-%d").to_string();
@@ -247,7 +251,7 @@ async fn verify_fitness_tasks_inner(
                 "SELECT id, title, COALESCE(verify_workout_type, 'any'),
                         COALESCE(verify_min_minutes, 0), due_date, base_experience_reward
                  FROM tasks
-                 WHERE user_id = 1 AND status = 'active' AND COALESCE(verified, 0) = 0
+                 WHERE user_id = 1 AND status = 'active' AND deleted_at IS NULL AND COALESCE(verified, 0) = 0
                    AND lower(category) IN ('fitness', 'health')
                    AND (verify_workout_type IS NOT NULL OR verify_min_minutes IS NOT NULL)",
             )
@@ -303,23 +307,22 @@ async fn verify_fitness_tasks_inner(
     let mut errors = Vec::new();
     for cand in matched {
         // Standard completion path first (normal XP/gold/streak/buff logic).
-        if let Err(e) = crate::complete_task(db.clone(), cand.id).await {
+        if let Err(e) = crate::complete_task(app.clone(), db.clone(), cand.id).await {
             errors.push(format!("Failed to complete task {}: {}", cand.id, e));
             continue;
         }
 
-        // Verification bonus: 0.5x extra of base XP, and mark the task verified.
-        let bonus_xp = (cand.base_xp as f64 * 0.5).round() as i64;
-        {
+        // Verification bonus: an extra share of base XP, and mark the task verified.
+        let bonus_xp = {
             let conn = db.lock().await;
-            conn.execute(
-                "UPDATE users SET experience_points = experience_points + ?1 WHERE id = 1",
-                [bonus_xp],
-            )
-            .map_err(|e| format!("Failed to grant bonus XP for task {}: {}", cand.id, e))?;
+            let bonus = economy::load_config(&conn)?.verification_reward(cand.base_xp);
+            let level_up = ledger::grant_xp(&conn, &RewardSource::new(ledger::VERIFICATION_BONUS, cand.id), &bonus, None)
+                .map_err(|e| format!("Failed to grant bonus XP for task {}: {}", cand.id, e))?;
             conn.execute("UPDATE tasks SET verified = 1 WHERE id = ?1", [cand.id])
                 .map_err(|e| format!("Failed to mark task {} verified: {}", cand.id, e))?;
-        }
+            progression::emit_level_up(app, level_up);
+            bonus.final_amount
+        };
 
         verified.push(VerifiedTask {
             task_id: cand.id,
@@ -422,7 +425,7 @@ pub async fn get_workouts(
 /// Scan the configured folder for new/changed *.json exports, import their
 /// workouts, then auto-verify matching open fitness tasks.
 #[tauri::command]
-pub async fn scan_health_folder(db: State<'_, DbConnection>) -> Result<HealthScanSummary, String> {
+pub async fn scan_health_folder(app: AppHandle, db: State<'_, DbConnection>) -> Result<HealthScanSummary, String> {
     let mut summary = HealthScanSummary::default();
 
     let folder = {
@@ -565,7 +568,7 @@ pub async fn scan_health_folder(db: State<'_, DbConnection>) -> Result<HealthSca
         .map_err(|e| format!("Failed to stamp scan time: {}", e))?;
     }
 
-    let (verified, mut verify_errors) = verify_fitness_tasks_inner(&db).await?;
+    let (verified, mut verify_errors) = verify_fitness_tasks_inner(&app, &db).await?;
     summary.tasks_verified = verified.len() as i64;
     summary.errors.append(&mut verify_errors);
 
@@ -574,8 +577,8 @@ pub async fn scan_health_folder(db: State<'_, DbConnection>) -> Result<HealthSca
 
 /// Manually re-run task verification against already-imported workouts.
 #[tauri::command]
-pub async fn verify_fitness_tasks(db: State<'_, DbConnection>) -> Result<Vec<VerifiedTask>, String> {
-    let (verified, errors) = verify_fitness_tasks_inner(&db).await?;
+pub async fn verify_fitness_tasks(app: AppHandle, db: State<'_, DbConnection>) -> Result<Vec<VerifiedTask>, String> {
+    let (verified, errors) = verify_fitness_tasks_inner(&app, &db).await?;
     for e in errors {
         eprintln!("verify_fitness_tasks: {}", e);
     }
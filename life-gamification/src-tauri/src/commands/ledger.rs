@@ -0,0 +1,456 @@
+// Reward ledger: the single place XP and gold balances change.
+//
+// Callers describe a reward as a base amount plus the multipliers that apply to
+// it (INT, LUCK, streak, buffs, ...). grant_xp / grant_gold append a row to the
+// append-only reward_ledger table and then move the balance on users, so every
+// point of XP or gold can be traced back to its source. XP goes on through the
+// level-up pipeline in progression.rs.
+
+use rusqlite::Connection;
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, State};
+
+use crate::commands::progression::{self, LevelUp};
+use crate::database::DbConnection;
+
+// Source types recorded in reward_ledger.source_type
+pub const TASK_COMPLETION: &str = "task_completion";
+pub const GOAL_COMPLETION: &str = "goal_completion";
+pub const VERIFICATION_BONUS: &str = "verification_bonus";
+pub const ACHIEVEMENT: &str = "achievement";
+pub const PURCHASE: &str = "purchase";
+pub const MANUAL: &str = "manual";
+pub const RECONCILIATION: &str = "reconciliation";
+pub const DEFEAT: &str = "defeat";
+pub const GOAL_MILESTONE: &str = "goal_milestone";
+pub const POMODORO: &str = "pomodoro";
+
+// ---------- Types ----------
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Multiplier {
+    pub name: String,
+    pub value: f64,
+}
+
+/// A reward before it is granted: base amount, multipliers in the order they
+/// were applied, and the resulting (rounded) final amount.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Reward {
+    pub base_amount: i64,
+    pub multipliers: Vec<Multiplier>,
+    pub final_amount: i64,
+}
+
+#[derive(Debug, Clone)]
+pub struct RewardSource {
+    pub source_type: &'static str,
+    pub source_id: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct LedgerEntry {
+    pub id: i64,
+    pub currency: String,
+    pub source_type: String,
+    pub source_id: Option<String>,
+    pub base_amount: i64,
+    pub multipliers: Vec<Multiplier>,
+    pub final_amount: i64,
+    pub note: Option<String>,
+    /// Set on reversal entries (e.g. from reopen_task): the entry being offset.
+    pub reverses_entry_id: Option<i64>,
+    pub created_at: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct LedgerReconciliation {
+    pub ledger_xp: i64,
+    pub balance_xp: i64,
+    pub ledger_gold: i64,
+    pub balance_gold: i64,
+    pub in_balance: bool,
+    /// True when drift was found and an adjusting entry was written.
+    pub repaired: bool,
+}
+
+// ---------- Reward math (pure functions, unit-tested) ----------
+
+impl Reward {
+    pub fn flat(amount: i64) -> Self {
+        Reward {
+            base_amount: amount,
+            multipliers: Vec::new(),
+            final_amount: amount,
+        }
+    }
+
+    /// Apply another multiplier. Identity multipliers are not recorded.
+    pub fn with(mut self, name: &str, value: f64) -> Self {
+        if (value - 1.0).abs() > f64::EPSILON {
+            self.multipliers.push(Multiplier { name: name.to_string(), value });
+            let product: f64 = self.multipliers.iter().map(|m| m.value).product();
+            self.final_amount = (self.base_amount as f64 * product).round() as i64;
+        }
+        self
+    }
+
+    /// Limit the final amount to `max`, recording the cut as a "cap" multiplier.
+    pub fn capped(mut self, max: Option<i64>) -> Self {
+        if let Some(max) = max {
+            if self.final_amount > max {
+                let value = if self.final_amount == 0 { 1.0 } else { max as f64 / self.final_amount as f64 };
+                self.multipliers.push(Multiplier { name: "cap".to_string(), value });
+                self.final_amount = max;
+            }
+        }
+        self
+    }
+}
+
+impl RewardSource {
+    pub fn new(source_type: &'static str, source_id: impl ToString) -> Self {
+        RewardSource {
+            source_type,
+            source_id: Some(source_id.to_string()),
+        }
+    }
+
+    pub fn untracked(source_type: &'static str) -> Self {
+        RewardSource { source_type, source_id: None }
+    }
+}
+
+// ---------- Granting ----------
+
+fn record(
+    conn: &Connection,
+    currency: &str,
+    source: &RewardSource,
+    reward: &Reward,
+    note: Option<&str>,
+    reverses_entry_id: Option<i64>,
+) -> Result<i64, String> {
+    let multipliers = serde_json::to_string(&reward.multipliers)
+        .map_err(|e| format!("Failed to encode multipliers: {}", e))?;
+    conn.execute(
+        "INSERT INTO reward_ledger (user_id, currency, source_type, source_id, base_amount,
+         multipliers, final_amount, note, reverses_entry_id)
+         VALUES (1, ?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+        rusqlite::params![
+            currency,
+            source.source_type,
+            source.source_id,
+            reward.base_amount,
+            multipliers,
+            reward.final_amount,
+            note,
+            reverses_entry_id,
+        ],
+    )
+    .map_err(|e| format!("Failed to record reward: {}", e))?;
+    Ok(conn.last_insert_rowid())
+}
+
+/// Record an XP reward and apply it through the level-up pipeline.
+pub fn grant_xp(
+    conn: &Connection,
+    source: &RewardSource,
+    reward: &Reward,
+    note: Option<&str>,
+) -> Result<Option<LevelUp>, String> {
+    grant_xp_entry(conn, source, reward, note, None)
+}
+
+fn grant_xp_entry(
+    conn: &Connection,
+    source: &RewardSource,
+    reward: &Reward,
+    note: Option<&str>,
+    reverses_entry_id: Option<i64>,
+) -> Result<Option<LevelUp>, String> {
+    // XP never goes below zero; record what was actually removed.
+    let mut reward = reward.clone();
+    if reward.final_amount < 0 {
+        let current: i64 = conn
+            .query_row("SELECT experience_points FROM users WHERE id = 1", [], |row| row.get(0))
+            .map_err(|e| format!("Failed to get user: {}", e))?;
+        reward.final_amount = reward.final_amount.max(-current);
+    }
+    record(conn, "xp", source, &reward, note, reverses_entry_id)?;
+    progression::apply_experience(conn, reward.final_amount)
+}
+
+/// Record a gold change (negative for spending) and apply it to the balance.
+pub fn grant_gold(
+    conn: &Connection,
+    source: &RewardSource,
+    reward: &Reward,
+    note: Option<&str>,
+) -> Result<(), String> {
+    grant_gold_entry(conn, source, reward, note, None)
+}
+
+fn grant_gold_entry(
+    conn: &Connection,
+    source: &RewardSource,
+    reward: &Reward,
+    note: Option<&str>,
+    reverses_entry_id: Option<i64>,
+) -> Result<(), String> {
+    record(conn, "gold", source, reward, note, reverses_entry_id)?;
+    conn.execute(
+        "UPDATE users SET gold = gold + ?1 WHERE id = 1",
+        [reward.final_amount],
+    )
+    .map_err(|e| format!("Failed to update gold: {}", e))?;
+    Ok(())
+}
+
+/// Offset every not-yet-reversed entry recorded for `source_id` under one of
+/// `source_types` with an equal and opposite entry. Returns the (xp, gold)
+/// actually taken back.
+pub fn reverse_entries(
+    conn: &Connection,
+    source_types: &[&'static str],
+    source_id: &str,
+    note: &str,
+) -> Result<(i64, i64), String> {
+    let mut reversed = (0, 0);
+    for source_type in source_types {
+        let mut stmt = conn
+            .prepare(
+                "SELECT id, currency, final_amount FROM reward_ledger l
+                 WHERE user_id = 1 AND source_type = ?1 AND source_id = ?2
+                   AND reverses_entry_id IS NULL
+                   AND NOT EXISTS (SELECT 1 FROM reward_ledger r WHERE r.reverses_entry_id = l.id)
+                 ORDER BY id",
+            )
+            .map_err(|e| format!("Failed to prepare reversal query: {}", e))?;
+        let entries: Vec<(i64, String, i64)> = stmt
+            .query_map(rusqlite::params![source_type, source_id], |row| {
+                Ok((row.get(0)?, row.get(1)?, row.get(2)?))
+            })
+            .map_err(|e| format!("Failed to query entries to reverse: {}", e))?
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|e| format!("Failed to collect entries to reverse: {}", e))?;
+        drop(stmt);
+
+        let source = RewardSource::new(source_type, source_id);
+        for (entry_id, currency, amount) in entries {
+            let reward = Reward::flat(-amount);
+            if currency == "xp" {
+                let before: i64 = conn
+                    .query_row("SELECT experience_points FROM users WHERE id = 1", [], |row| row.get(0))
+                    .map_err(|e| format!("Failed to get user: {}", e))?;
+                grant_xp_entry(conn, &source, &reward, Some(note), Some(entry_id))?;
+                reversed.0 += before.min(amount);
+            } else {
+                grant_gold_entry(conn, &source, &reward, Some(note), Some(entry_id))?;
+                reversed.1 += amount;
+            }
+        }
+    }
+    Ok(reversed)
+}
+
+fn ledger_totals(conn: &Connection) -> Result<(i64, i64), String> {
+    conn.query_row(
+        "SELECT COALESCE(SUM(CASE WHEN currency = 'xp' THEN final_amount END), 0),
+                COALESCE(SUM(CASE WHEN currency = 'gold' THEN final_amount END), 0)
+         FROM reward_ledger WHERE user_id = 1",
+        [],
+        |row| Ok((row.get(0)?, row.get(1)?)),
+    )
+    .map_err(|e| format!("Failed to total reward ledger: {}", e))
+}
+
+/// Compare the ledger totals with the balances on users. With `repair`, any
+/// drift (from a write that bypassed the ledger) is booked as a reconciliation
+/// entry so the two agree again.
+pub fn reconcile(conn: &Connection, repair: bool) -> Result<LedgerReconciliation, String> {
+    let (ledger_xp, ledger_gold) = ledger_totals(conn)?;
+    let (balance_xp, balance_gold): (i64, i64) = conn
+        .query_row("SELECT experience_points, gold FROM users WHERE id = 1", [], |row| {
+            Ok((row.get(0)?, row.get(1)?))
+        })
+        .map_err(|e| format!("Failed to get user: {}", e))?;
+
+    let in_balance = ledger_xp == balance_xp && ledger_gold == balance_gold;
+    let repaired = repair && !in_balance;
+    if repaired {
+        let source = RewardSource::untracked(RECONCILIATION);
+        let note = Some("Balance changed outside the ledger");
+        if balance_xp != ledger_xp {
+            record(conn, "xp", &source, &Reward::flat(balance_xp - ledger_xp), note, None)?;
+        }
+        if balance_gold != ledger_gold {
+            record(conn, "gold", &source, &Reward::flat(balance_gold - ledger_gold), note, None)?;
+        }
+    }
+
+    Ok(LedgerReconciliation {
+        ledger_xp,
+        balance_xp,
+        ledger_gold,
+        balance_gold,
+        in_balance,
+        repaired,
+    })
+}
+
+// ---------- Commands ----------
+
+/// Ledger entries, newest first, optionally filtered by currency and source.
+#[tauri::command]
+pub async fn get_reward_ledger(
+    db: State<'_, DbConnection>,
+    currency: Option<String>,
+    source_type: Option<String>,
+    source_id: Option<String>,
+    limit: Option<i64>,
+) -> Result<Vec<LedgerEntry>, String> {
+    let conn = db.lock().await;
+    let mut stmt = conn
+        .prepare(
+            "SELECT id, currency, source_type, source_id, base_amount, multipliers, final_amount,
+                    note, reverses_entry_id, created_at
+             FROM reward_ledger
+             WHERE user_id = 1
+               AND (?1 IS NULL OR currency = ?1)
+               AND (?2 IS NULL OR source_type = ?2)
+               AND (?3 IS NULL OR source_id = ?3)
+             ORDER BY id DESC
+             LIMIT ?4",
+        )
+        .map_err(|e| format!("Failed to prepare ledger query: {}", e))?;
+
+    let rows = stmt
+        .query_map(
+            rusqlite::params![currency, source_type, source_id, limit.unwrap_or(200)],
+            |row| {
+                let multipliers: String = row.get(5)?;
+                Ok(LedgerEntry {
+                    id: row.get(0)?,
+                    currency: row.get(1)?,
+                    source_type: row.get(2)?,
+                    source_id: row.get(3)?,
+                    base_amount: row.get(4)?,
+                    multipliers: serde_json::from_str(&multipliers).unwrap_or_default(),
+                    final_amount: row.get(6)?,
+                    note: row.get(7)?,
+                    reverses_entry_id: row.get(8)?,
+                    created_at: row.get(9)?,
+                })
+            },
+        )
+        .map_err(|e| format!("Failed to query ledger: {}", e))?;
+
+    rows.collect::<Result<Vec<_>, _>>()
+        .map_err(|e| format!("Failed to collect ledger: {}", e))
+}
+
+#[tauri::command]
+pub async fn reconcile_reward_ledger(
+    app: AppHandle,
+    db: State<'_, DbConnection>,
+    repair: Option<bool>,
+) -> Result<LedgerReconciliation, String> {
+    let conn = db.lock().await;
+    let tx = conn.unchecked_transaction()
+        .map_err(|e| format!("Failed to begin transaction: {}", e))?;
+    let report = reconcile(&tx, repair.unwrap_or(false))?;
+    // Re-level against the (possibly corrected) XP balance
+    let level_up = progression::apply_experience(&tx, 0)?;
+    tx.commit()
+        .map_err(|e| format!("Failed to commit: {}", e))?;
+    progression::emit_level_up(&app, level_up);
+    Ok(report)
+}
+
+// ---------- Tests ----------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::database::test_conn;
+
+    #[test]
+    fn multipliers_compound_and_skip_identity() {
+        let reward = Reward::flat(30).with("intelligence", 1.2).with("streak", 1.0).with("xp_buff", 1.5);
+        assert_eq!(reward.multipliers.len(), 2);
+        assert_eq!(reward.final_amount, 54);
+
+        let reward = Reward::flat(7).with("luck", 1.15);
+        assert_eq!(reward.final_amount, 8);
+    }
+
+    #[test]
+    fn grants_are_recorded_and_reconcile() {
+        let conn = test_conn();
+        let source = RewardSource::new(TASK_COMPLETION, 42);
+        grant_xp(&conn, &source, &Reward::flat(30).with("intelligence", 1.2), None).unwrap();
+        grant_gold(&conn, &source, &Reward::flat(5), None).unwrap();
+        grant_gold(&conn, &RewardSource::new(PURCHASE, "health_potion"), &Reward::flat(-3), None).unwrap();
+
+        let (xp, gold): (i64, i64) = conn
+            .query_row("SELECT experience_points, gold FROM users WHERE id = 1", [], |row| Ok((row.get(0)?, row.get(1)?)))
+            .unwrap();
+        let report = reconcile(&conn, false).unwrap();
+        assert!(report.in_balance);
+        assert_eq!((report.ledger_xp, report.ledger_gold), (xp, gold));
+
+        let multipliers: String = conn
+            .query_row(
+                "SELECT multipliers FROM reward_ledger WHERE currency = 'xp' AND source_id = '42'",
+                [],
+                |row| row.get(0),
+            )
+            .unwrap();
+        assert_eq!(multipliers, r#"[{"name":"intelligence","value":1.2}]"#);
+    }
+
+    #[test]
+    fn negative_xp_is_clamped_in_the_ledger() {
+        let conn = test_conn();
+        let before = reconcile(&conn, false).unwrap().ledger_xp;
+        grant_xp(&conn, &RewardSource::untracked(MANUAL), &Reward::flat(-(before + 500)), None).unwrap();
+        let report = reconcile(&conn, false).unwrap();
+        assert_eq!(report.balance_xp, 0);
+        assert!(report.in_balance);
+    }
+
+    #[test]
+    fn drift_is_repaired_with_an_adjusting_entry() {
+        let conn = test_conn();
+        conn.execute("UPDATE users SET gold = gold + 17 WHERE id = 1", []).unwrap();
+        assert!(!reconcile(&conn, false).unwrap().in_balance);
+        assert!(reconcile(&conn, true).unwrap().repaired);
+        assert!(reconcile(&conn, false).unwrap().in_balance);
+    }
+
+    #[test]
+    fn reversal_offsets_each_entry_once() {
+        let conn = test_conn();
+        let source = RewardSource::new(TASK_COMPLETION, 7);
+        let before = reconcile(&conn, false).unwrap();
+        grant_xp(&conn, &source, &Reward::flat(40).with("streak", 2.0), None).unwrap();
+        grant_gold(&conn, &source, &Reward::flat(6), None).unwrap();
+        grant_xp(&conn, &RewardSource::new(VERIFICATION_BONUS, 7), &Reward::flat(20), None).unwrap();
+
+        let reversed = reverse_entries(&conn, &[TASK_COMPLETION, VERIFICATION_BONUS], "7", "test").unwrap();
+        assert_eq!(reversed, (100, 6));
+        assert_eq!(reverse_entries(&conn, &[TASK_COMPLETION, VERIFICATION_BONUS], "7", "test").unwrap(), (0, 0));
+
+        let after = reconcile(&conn, false).unwrap();
+        assert!(after.in_balance);
+        assert_eq!((after.balance_xp, after.balance_gold), (before.balance_xp, before.balance_gold));
+    }
+
+    #[test]
+    fn ledger_rows_cannot_be_rewritten() {
+        let conn = test_conn();
+        assert!(conn.execute("UPDATE reward_ledger SET final_amount = 0", []).is_err());
+        assert!(conn.execute("DELETE FROM reward_ledger", []).is_err());
+    }
+}
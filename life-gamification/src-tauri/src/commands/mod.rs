@@ -1,9 +1,34 @@
+pub mod activity;
 pub mod avatar;
+pub mod bulk;
 pub mod calendar;
 pub mod capture;
+pub mod completions;
 pub mod connections;
+pub mod damage;
+pub mod defer;
+pub mod difficulty;
+pub mod economy;
+pub mod estimates;
 pub mod finance;
 pub mod github;
+pub mod goals;
 pub mod health;
+pub mod ledger;
+pub mod pauses;
+pub mod pomodoro;
+pub mod progression;
+pub mod recurrence;
 pub mod reminders;
+pub mod rollover;
+pub mod scoring;
+pub mod search;
+pub mod sessions;
 pub mod simplefin;
+pub mod subtasks;
+pub mod tags;
+pub mod templates;
+pub mod timers;
+pub mod timesheet;
+pub mod trash;
+pub mod views;
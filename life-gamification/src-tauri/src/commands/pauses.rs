@@ -0,0 +1,351 @@
+// Vacation mode and paused recurring series.
+//
+// A pause covers a date range for one recurring series, or for everything
+// (vacation). Paused days are treated as if nothing was scheduled:
+// generate_recurring_instances skips them, the damage sweep deals no damage for
+// them, overdue and streak-risk notifications scheduled in them are held back,
+// and the streak of a series carries over the paused occurrences.
+//
+// Pauses can't be backdated and are never deleted, so get_pauses is a complete
+// history: resuming early shortens the range and a pause called off before it
+// started stays on record as cancelled.
+
+use chrono::{Duration, NaiveDate, Utc};
+use rusqlite::{Connection, OptionalExtension};
+use serde::{Deserialize, Serialize};
+use tauri::State;
+
+use crate::commands::recurrence::parse_date;
+use crate::database::DbConnection;
+
+/// SQL condition for scheduled_notifications rows held back by a pause: pending
+/// overdue / streak-risk notifications for a paused day.
+pub const SILENCED_NOTIFICATION: &str = "(scheduled_notifications.status IN ('pending', 'snoozed')
+     AND scheduled_notifications.notification_type IN ('overdue', 'streak_risk')
+     AND EXISTS (SELECT 1 FROM pauses p
+                 WHERE p.cancelled_at IS NULL
+                   AND date(scheduled_notifications.scheduled_for)
+                       BETWEEN p.start_date AND COALESCE(p.end_date, '9999-12-31')
+                   AND (p.recurring_task_id IS NULL
+                        OR p.recurring_task_id = scheduled_notifications.task_id
+                        OR p.recurring_task_id = (SELECT t.parent_recurring_task_id FROM tasks t
+                                                  WHERE t.id = scheduled_notifications.task_id))))";
+
+// ---------- Types ----------
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Pause {
+    pub id: i64,
+    /// None for vacation mode.
+    pub recurring_task_id: Option<i64>,
+    pub task_title: Option<String>,
+    pub start_date: String,
+    pub end_date: Option<String>,
+    pub reason: Option<String>,
+    pub created_at: String,
+    pub ended_at: Option<String>,
+    pub cancelled_at: Option<String>,
+    /// In effect today.
+    pub active: bool,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct CreatePauseRequest {
+    pub recurring_task_id: Option<i64>,
+    /// Defaults to today; can't be in the past.
+    pub start_date: Option<String>,
+    /// Inclusive; None pauses until resumed.
+    pub end_date: Option<String>,
+    pub reason: Option<String>,
+}
+
+/// Date ranges in effect, for checking many days at once.
+#[derive(Debug, Clone, Default)]
+pub struct PauseWindows {
+    windows: Vec<(Option<i64>, NaiveDate, Option<NaiveDate>)>,
+}
+
+impl PauseWindows {
+    pub fn load(conn: &Connection) -> Result<Self, String> {
+        let mut stmt = conn
+            .prepare("SELECT recurring_task_id, start_date, end_date FROM pauses WHERE cancelled_at IS NULL")
+            .map_err(|e| format!("Failed to prepare pauses query: {}", e))?;
+        let rows = stmt
+            .query_map([], |row| {
+                Ok((row.get::<_, Option<i64>>(0)?, row.get::<_, String>(1)?, row.get::<_, Option<String>>(2)?))
+            })
+            .map_err(|e| format!("Failed to get pauses: {}", e))?
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|e| format!("Failed to read pauses: {}", e))?;
+        let windows = rows
+            .into_iter()
+            .filter_map(|(series, start, end)| Some((series, parse_date(&start)?, end.as_deref().and_then(parse_date))))
+            .collect();
+        Ok(PauseWindows { windows })
+    }
+
+    /// Whether `day` is paused for `series`, or for everything if `series` is None.
+    pub fn is_paused(&self, series: Option<i64>, day: NaiveDate) -> bool {
+        self.windows.iter().any(|(paused, start, end)| {
+            (paused.is_none() || *paused == series) && *start <= day && end.is_none_or(|end| day <= end)
+        })
+    }
+
+    pub fn on_vacation(&self, day: NaiveDate) -> bool {
+        self.is_paused(None, day)
+    }
+}
+
+// ---------- Persistence ----------
+
+const PAUSE_SELECT: &str = "SELECT p.id, p.recurring_task_id, t.title, p.start_date, p.end_date, p.reason,
+                                   p.created_at, p.ended_at, p.cancelled_at,
+                                   p.cancelled_at IS NULL AND p.start_date <= ?1
+                                       AND COALESCE(p.end_date, '9999-12-31') >= ?1
+                            FROM pauses p
+                            LEFT JOIN tasks t ON t.id = p.recurring_task_id";
+
+fn pause_from_row(row: &rusqlite::Row) -> rusqlite::Result<Pause> {
+    Ok(Pause {
+        id: row.get(0)?,
+        recurring_task_id: row.get(1)?,
+        task_title: row.get(2)?,
+        start_date: row.get(3)?,
+        end_date: row.get(4)?,
+        reason: row.get(5)?,
+        created_at: row.get(6)?,
+        ended_at: row.get(7)?,
+        cancelled_at: row.get(8)?,
+        active: row.get(9)?,
+    })
+}
+
+fn fetch_pause(conn: &Connection, today: NaiveDate, pause_id: i64) -> Result<Pause, String> {
+    conn.query_row(
+        &format!("{} WHERE p.id = ?2", PAUSE_SELECT),
+        rusqlite::params![today.format("%Y-%m-%d").to_string(), pause_id],
+        pause_from_row,
+    )
+    .optional()
+    .map_err(|e| format!("Failed to get pause: {}", e))?
+    .ok_or_else(|| format!("Pause {} not found", pause_id))
+}
+
+pub fn create_pause_sync(conn: &Connection, today: NaiveDate, request: &CreatePauseRequest) -> Result<Pause, String> {
+    let start = match request.start_date.as_deref() {
+        Some(raw) => parse_date(raw).ok_or_else(|| format!("Invalid start date: {}", raw))?,
+        None => today,
+    };
+    let end = match request.end_date.as_deref() {
+        Some(raw) => Some(parse_date(raw).ok_or_else(|| format!("Invalid end date: {}", raw))?),
+        None => None,
+    };
+    if start < today {
+        return Err("A pause can't start in the past".to_string());
+    }
+    if end.is_some_and(|end| end < start) {
+        return Err("A pause can't end before it starts".to_string());
+    }
+    if let Some(task_id) = request.recurring_task_id {
+        let recurring: bool = conn
+            .query_row(
+                "SELECT recurrence_pattern IS NOT NULL AND parent_recurring_task_id IS NULL FROM tasks WHERE id = ?1",
+                [task_id],
+                |row| row.get(0),
+            )
+            .map_err(|_| format!("Task {} not found", task_id))?;
+        if !recurring {
+            return Err("Only recurring tasks can be paused".to_string());
+        }
+    }
+
+    let start_str = start.format("%Y-%m-%d").to_string();
+    let end_str = end.map(|end| end.format("%Y-%m-%d").to_string());
+    let overlapping: bool = conn
+        .query_row(
+            "SELECT EXISTS (SELECT 1 FROM pauses
+                            WHERE recurring_task_id IS ?1 AND cancelled_at IS NULL
+                              AND start_date <= COALESCE(?3, '9999-12-31')
+                              AND COALESCE(end_date, '9999-12-31') >= ?2)",
+            rusqlite::params![request.recurring_task_id, start_str, end_str],
+            |row| row.get(0),
+        )
+        .map_err(|e| format!("Failed to check pauses: {}", e))?;
+    if overlapping {
+        return Err("This overlaps an existing pause".to_string());
+    }
+
+    let reason = request.reason.as_deref().map(str::trim).filter(|r| !r.is_empty());
+    conn.execute(
+        "INSERT INTO pauses (recurring_task_id, start_date, end_date, reason) VALUES (?1, ?2, ?3, ?4)",
+        rusqlite::params![request.recurring_task_id, start_str, end_str, reason],
+    )
+    .map_err(|e| format!("Failed to create pause: {}", e))?;
+    fetch_pause(conn, today, conn.last_insert_rowid())
+}
+
+/// Resume a pause in effect (it ends yesterday, so today counts again) or call
+/// off one that hasn't started yet.
+pub fn end_pause_sync(conn: &Connection, today: NaiveDate, pause_id: i64) -> Result<Pause, String> {
+    let pause = fetch_pause(conn, today, pause_id)?;
+    let start = parse_date(&pause.start_date).unwrap_or(today);
+    let end = pause.end_date.as_deref().and_then(parse_date);
+    if pause.cancelled_at.is_some() || end.is_some_and(|end| end < today) {
+        return Err("This pause is already over".to_string());
+    }
+
+    if start >= today {
+        conn.execute("UPDATE pauses SET cancelled_at = CURRENT_TIMESTAMP WHERE id = ?1", [pause_id])
+            .map_err(|e| format!("Failed to cancel pause: {}", e))?;
+    } else {
+        conn.execute(
+            "UPDATE pauses SET end_date = ?2, ended_at = CURRENT_TIMESTAMP WHERE id = ?1",
+            rusqlite::params![pause_id, (today - Duration::days(1)).format("%Y-%m-%d").to_string()],
+        )
+        .map_err(|e| format!("Failed to end pause: {}", e))?;
+    }
+    fetch_pause(conn, today, pause_id)
+}
+
+// ---------- Commands ----------
+
+/// Pause one recurring series, or everything if no task is given.
+#[tauri::command]
+pub async fn create_pause(db: State<'_, DbConnection>, request: CreatePauseRequest) -> Result<Pause, String> {
+    let conn = db.lock().await;
+    create_pause_sync(&conn, Utc::now().naive_utc().date(), &request)
+}
+
+#[tauri::command]
+pub async fn end_pause(db: State<'_, DbConnection>, pause_id: i64) -> Result<Pause, String> {
+    let conn = db.lock().await;
+    end_pause_sync(&conn, Utc::now().naive_utc().date(), pause_id)
+}
+
+/// Every pause ever made, newest first; for one series, its pauses and vacations.
+#[tauri::command]
+pub async fn get_pauses(db: State<'_, DbConnection>, recurring_task_id: Option<i64>) -> Result<Vec<Pause>, String> {
+    let conn = db.lock().await;
+    let today = Utc::now().naive_utc().date().format("%Y-%m-%d").to_string();
+    let mut stmt = conn
+        .prepare(&format!(
+            "{} WHERE ?2 IS NULL OR p.recurring_task_id IS NULL OR p.recurring_task_id = ?2
+             ORDER BY p.start_date DESC, p.id DESC",
+            PAUSE_SELECT
+        ))
+        .map_err(|e| format!("Failed to prepare pauses query: {}", e))?;
+    let pauses = stmt
+        .query_map(rusqlite::params![today, recurring_task_id], pause_from_row)
+        .map_err(|e| format!("Failed to get pauses: {}", e))?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| format!("Failed to read pauses: {}", e))?;
+    Ok(pauses)
+}
+
+// ---------- Tests ----------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn d(s: &str) -> NaiveDate {
+        NaiveDate::parse_from_str(s, "%Y-%m-%d").unwrap()
+    }
+
+    fn test_conn() -> Connection {
+        let conn = crate::database::test_conn();
+        conn.execute_batch(
+            "INSERT INTO tasks (id, user_id, title, status, recurrence_pattern) VALUES
+                 (1, 1, 'Stretch', 'active', '{\"frequency\":\"daily\"}'),
+                 (2, 1, 'One-off', 'active', NULL);",
+        )
+        .unwrap();
+        conn
+    }
+
+    fn request(task: Option<i64>, start: &str, end: Option<&str>) -> CreatePauseRequest {
+        CreatePauseRequest {
+            recurring_task_id: task,
+            start_date: Some(start.to_string()),
+            end_date: end.map(str::to_string),
+            reason: None,
+        }
+    }
+
+    #[test]
+    fn windows_cover_their_series_and_vacations_cover_everything() {
+        let windows = PauseWindows {
+            windows: vec![(Some(1), d("2026-07-01"), Some(d("2026-07-03"))), (None, d("2026-08-01"), None)],
+        };
+        assert!(windows.is_paused(Some(1), d("2026-07-03")));
+        assert!(!windows.is_paused(Some(2), d("2026-07-03")));
+        assert!(!windows.is_paused(Some(1), d("2026-07-04")));
+        assert!(windows.is_paused(Some(2), d("2026-09-01")));
+        assert!(windows.on_vacation(d("2026-08-01")));
+        assert!(!windows.on_vacation(d("2026-07-02")));
+    }
+
+    #[test]
+    fn pauses_are_validated() {
+        let conn = test_conn();
+        let today = d("2026-07-01");
+        assert!(create_pause_sync(&conn, today, &request(None, "2026-06-30", None)).is_err());
+        assert!(create_pause_sync(&conn, today, &request(None, "2026-07-05", Some("2026-07-04"))).is_err());
+        assert!(create_pause_sync(&conn, today, &request(Some(2), "2026-07-01", None)).is_err());
+
+        create_pause_sync(&conn, today, &request(Some(1), "2026-07-01", Some("2026-07-10"))).unwrap();
+        assert!(create_pause_sync(&conn, today, &request(Some(1), "2026-07-10", None)).is_err());
+        // A vacation is a separate scope
+        create_pause_sync(&conn, today, &request(None, "2026-07-05", Some("2026-07-06"))).unwrap();
+    }
+
+    #[test]
+    fn ending_a_pause_keeps_it_on_record() {
+        let conn = test_conn();
+        let running = create_pause_sync(&conn, d("2026-07-01"), &request(None, "2026-07-01", None)).unwrap();
+        let upcoming = create_pause_sync(&conn, d("2026-07-01"), &request(Some(1), "2026-08-01", None)).unwrap();
+        assert!(running.active && !upcoming.active);
+
+        let resumed = end_pause_sync(&conn, d("2026-07-05"), running.id).unwrap();
+        assert_eq!(resumed.end_date.as_deref(), Some("2026-07-04"));
+        assert!(resumed.ended_at.is_some() && !resumed.active);
+        assert!(end_pause_sync(&conn, d("2026-07-05"), running.id).is_err());
+
+        let cancelled = end_pause_sync(&conn, d("2026-07-05"), upcoming.id).unwrap();
+        assert!(cancelled.cancelled_at.is_some());
+
+        let windows = PauseWindows::load(&conn).unwrap();
+        assert!(windows.on_vacation(d("2026-07-04")));
+        assert!(!windows.on_vacation(d("2026-07-05")));
+        assert!(!windows.is_paused(Some(1), d("2026-08-02")));
+        let count: i64 = conn.query_row("SELECT COUNT(*) FROM pauses", [], |row| row.get(0)).unwrap();
+        assert_eq!(count, 2);
+    }
+
+    #[test]
+    fn vacations_and_the_trash_hold_back_pending_notifications() {
+        let conn = test_conn();
+        conn.execute_batch(
+            "INSERT INTO scheduled_notifications (id, task_id, notification_type, title, message, scheduled_for) VALUES
+                 (1, 2, 'overdue', 'Overdue', 'One-off is overdue', datetime('now', '+1 minute')),
+                 (2, 1, 'streak_risk', 'Streak', 'Stretch streak at risk', datetime('now', '+1 minute')),
+                 (3, 2, 'due_soon', 'Due soon', 'One-off is due soon', datetime('now', '+1 minute')),
+                 (4, NULL, 'daily_agenda', 'Today', 'Your agenda', datetime('now', '+1 minute'));",
+        )
+        .unwrap();
+        let pending = |conn: &Connection| -> Vec<i64> {
+            let mut ids: Vec<i64> =
+                crate::get_pending_notifications_sync(conn).unwrap().into_iter().map(|n| n.id).collect();
+            ids.sort();
+            ids
+        };
+        assert_eq!(pending(&conn), vec![1, 2, 3, 4]);
+
+        conn.execute("INSERT INTO pauses (start_date) VALUES (date('now', '-1 day'))", []).unwrap();
+        assert_eq!(pending(&conn), vec![3, 4]);
+
+        // A task in the trash takes its reminders with it, not the ones without a task
+        crate::commands::trash::trash_item(&conn, crate::commands::trash::TrashKind::Task, 2).unwrap();
+        assert_eq!(pending(&conn), vec![4]);
+    }
+}
@@ -0,0 +1,582 @@
+// Pomodoro engine.
+//
+// Starting a timer with session_type 'pomodoro' hands the cycle to this engine,
+// so it survives a reload: work for work_minutes, then a short break, and after
+// cycles_per_set pomodoros a long break. A background tick moves the engine on
+// and emits every transition as PHASE_EVENT.
+//
+// A work phase is the ordinary pomodoro timer (a 'pomodoro' time_session plus
+// its active_timers row), so pausing the timer holds the phase and stop_timer
+// abandons it. Breaks are logged as 'break' time_sessions on the same task.
+// Each pomodoro that runs its full length pays completion_xp into the ledger as
+// a 'pomodoro' reward, separate from the task's completion reward.
+//
+// A break always follows a finished pomodoro. The next pomodoro starts on its
+// own only with auto_start_work, and never retroactively: if the app was closed
+// past the end of a break, the engine goes idle instead.
+
+use chrono::{DateTime, Duration, Utc};
+use rusqlite::{Connection, OptionalExtension};
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Emitter, Manager, State};
+
+use crate::commands::ledger::{self, Reward, RewardSource};
+use crate::commands::progression::{self, LevelUp};
+use crate::database::DbConnection;
+
+/// Event emitted to the frontend on every phase transition.
+pub const PHASE_EVENT: &str = "pomodoro-phase";
+
+/// How often the background tick checks whether the current phase is over.
+const TICK_SECS: u64 = 15;
+
+// ---------- Types ----------
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Phase {
+    Idle,
+    Work,
+    ShortBreak,
+    LongBreak,
+}
+
+impl Phase {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Phase::Idle => "idle",
+            Phase::Work => "work",
+            Phase::ShortBreak => "short_break",
+            Phase::LongBreak => "long_break",
+        }
+    }
+
+    fn parse(value: &str) -> Phase {
+        match value {
+            "work" => Phase::Work,
+            "short_break" => Phase::ShortBreak,
+            "long_break" => Phase::LongBreak,
+            _ => Phase::Idle,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PomodoroSettings {
+    pub work_minutes: i64,
+    pub short_break_minutes: i64,
+    pub long_break_minutes: i64,
+    /// Pomodoros before a long break.
+    pub cycles_per_set: i64,
+    /// Start the next pomodoro as soon as a break ends.
+    pub auto_start_work: bool,
+    pub completion_xp: i64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct PomodoroState {
+    pub phase: Phase,
+    pub task_id: Option<i64>,
+    /// The time_sessions row of the current phase.
+    pub session_id: Option<i64>,
+    pub phase_started_at: Option<String>,
+    /// When the current phase ends; while idle, when the last one ended.
+    pub phase_ends_at: Option<String>,
+    pub completed_in_set: i64,
+    pub remaining_seconds: Option<i64>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct PhaseChange {
+    pub from: Phase,
+    pub to: Phase,
+    pub task_id: Option<i64>,
+    pub completed_in_set: i64,
+    pub phase_ends_at: Option<String>,
+    pub xp_awarded: i64,
+}
+
+// ---------- Cycle (pure functions, unit-tested) ----------
+
+/// The phase that follows `phase`, and the pomodoros completed in the set after it.
+pub fn phase_after(phase: Phase, completed_in_set: i64, settings: &PomodoroSettings) -> (Phase, i64) {
+    match phase {
+        Phase::Work if completed_in_set + 1 >= settings.cycles_per_set => (Phase::LongBreak, completed_in_set + 1),
+        Phase::Work => (Phase::ShortBreak, completed_in_set + 1),
+        Phase::ShortBreak => (Phase::Work, completed_in_set),
+        Phase::LongBreak => (Phase::Work, 0),
+        Phase::Idle => (Phase::Idle, completed_in_set),
+    }
+}
+
+pub fn phase_length(phase: Phase, settings: &PomodoroSettings) -> Duration {
+    Duration::minutes(match phase {
+        Phase::Work => settings.work_minutes,
+        Phase::ShortBreak => settings.short_break_minutes,
+        Phase::LongBreak => settings.long_break_minutes,
+        Phase::Idle => 0,
+    })
+}
+
+fn validate(settings: &PomodoroSettings) -> Result<(), String> {
+    if settings.work_minutes < 1 || settings.short_break_minutes < 1 || settings.long_break_minutes < 1 {
+        return Err("Pomodoro phases must last at least a minute".to_string());
+    }
+    if settings.cycles_per_set < 1 {
+        return Err("A set needs at least one pomodoro".to_string());
+    }
+    if settings.completion_xp < 0 {
+        return Err("Pomodoro XP cannot be negative".to_string());
+    }
+    Ok(())
+}
+
+fn parse_time(value: &str) -> Option<DateTime<Utc>> {
+    DateTime::parse_from_rfc3339(value).ok().map(|t| t.with_timezone(&Utc))
+}
+
+// ---------- Persistence ----------
+
+pub fn load_settings(conn: &Connection) -> Result<PomodoroSettings, String> {
+    conn.query_row(
+        "SELECT work_minutes, short_break_minutes, long_break_minutes, cycles_per_set, auto_start_work, completion_xp
+         FROM pomodoro_settings WHERE id = 1",
+        [],
+        |row| {
+            Ok(PomodoroSettings {
+                work_minutes: row.get(0)?,
+                short_break_minutes: row.get(1)?,
+                long_break_minutes: row.get(2)?,
+                cycles_per_set: row.get(3)?,
+                auto_start_work: row.get(4)?,
+                completion_xp: row.get(5)?,
+            })
+        },
+    )
+    .map_err(|e| format!("Failed to load pomodoro settings: {}", e))
+}
+
+pub fn load_state(conn: &Connection, now: DateTime<Utc>) -> Result<PomodoroState, String> {
+    let mut state = conn
+        .query_row(
+            "SELECT phase, task_id, session_id, phase_started_at, phase_ends_at, completed_in_set
+             FROM pomodoro_state WHERE id = 1",
+            [],
+            |row| {
+                Ok(PomodoroState {
+                    phase: Phase::parse(&row.get::<_, String>(0)?),
+                    task_id: row.get(1)?,
+                    session_id: row.get(2)?,
+                    phase_started_at: row.get(3)?,
+                    phase_ends_at: row.get(4)?,
+                    completed_in_set: row.get(5)?,
+                    remaining_seconds: None,
+                })
+            },
+        )
+        .map_err(|e| format!("Failed to load pomodoro state: {}", e))?;
+    if state.phase != Phase::Idle {
+        state.remaining_seconds = state
+            .phase_ends_at
+            .as_deref()
+            .and_then(parse_time)
+            .map(|ends| (ends - now).num_seconds().max(0));
+    }
+    Ok(state)
+}
+
+fn save_state(
+    conn: &Connection,
+    phase: Phase,
+    task_id: Option<i64>,
+    session_id: Option<i64>,
+    started_at: Option<&str>,
+    ends_at: Option<&str>,
+    completed_in_set: i64,
+) -> Result<(), String> {
+    conn.execute(
+        "UPDATE pomodoro_state SET phase = ?1, task_id = ?2, session_id = ?3, phase_started_at = ?4,
+                phase_ends_at = ?5, completed_in_set = ?6, updated_at = CURRENT_TIMESTAMP
+         WHERE id = 1",
+        rusqlite::params![phase.as_str(), task_id, session_id, started_at, ends_at, completed_in_set],
+    )
+    .map_err(|e| format!("Failed to save pomodoro state: {}", e))?;
+    Ok(())
+}
+
+fn open_session(conn: &Connection, task_id: i64, session_type: &str, start: &str) -> Result<i64, String> {
+    conn.execute(
+        "INSERT INTO time_sessions (task_id, user_id, start_time, session_type) VALUES (?1, 1, ?2, ?3)",
+        rusqlite::params![task_id, start, session_type],
+    )
+    .map_err(|e| format!("Failed to create time session: {}", e))?;
+    Ok(conn.last_insert_rowid())
+}
+
+fn close_session(conn: &Connection, session_id: i64, end: &str, completed: bool) -> Result<(), String> {
+    conn.execute(
+        "UPDATE time_sessions SET end_time = ?2, is_completed = ?3 WHERE id = ?1 AND end_time IS NULL",
+        rusqlite::params![session_id, end, completed],
+    )
+    .map_err(|e| format!("Failed to close time session: {}", e))?;
+    Ok(())
+}
+
+/// Hand a pomodoro timer that start_timer just created to the engine. A break
+/// in progress is cut short; the set carries on unless the engine sat idle
+/// longer than a long break.
+pub fn begin_work(conn: &Connection, task_id: i64, session_id: i64, start: &str) -> Result<(), String> {
+    let settings = load_settings(conn)?;
+    let started = parse_time(start).ok_or_else(|| format!("Invalid start time: {}", start))?;
+    let state = load_state(conn, started)?;
+
+    let mut completed = state.completed_in_set;
+    match state.phase {
+        Phase::ShortBreak | Phase::LongBreak => {
+            if let Some(break_session) = state.session_id {
+                close_session(conn, break_session, start, false)?;
+            }
+            if state.phase == Phase::LongBreak {
+                completed = 0;
+            }
+        }
+        _ => {
+            let last_ended = state.phase_ends_at.as_deref().and_then(parse_time);
+            if last_ended.is_none_or(|ended| started - ended > phase_length(Phase::LongBreak, &settings)) {
+                completed = 0;
+            }
+        }
+    }
+    let ends = (started + phase_length(Phase::Work, &settings)).to_rfc3339();
+    save_state(conn, Phase::Work, Some(task_id), Some(session_id), Some(start), Some(&ends), completed)
+}
+
+/// Called when a timer is stopped by hand: a pomodoro cut short earns nothing
+/// and leaves the engine idle.
+pub fn abandon_work(conn: &Connection, session_id: i64, now: &str) -> Result<(), String> {
+    let state = load_state(conn, Utc::now())?;
+    if state.phase == Phase::Work && state.session_id == Some(session_id) {
+        save_state(conn, Phase::Idle, state.task_id, None, None, Some(now), state.completed_in_set)?;
+    }
+    Ok(())
+}
+
+/// Finish every phase that is over by `now`, starting the phases that follow,
+/// inside the caller's transaction.
+pub fn advance_sync(conn: &Connection, now: DateTime<Utc>) -> Result<(Vec<PhaseChange>, Option<LevelUp>), String> {
+    let settings = load_settings(conn)?;
+    let mut changes = Vec::new();
+    let mut level_up = None;
+
+    loop {
+        let state = load_state(conn, now)?;
+        let (Some(task_id), Some(session_id), Some(mut ends)) =
+            (state.task_id, state.session_id, state.phase_ends_at.as_deref().and_then(parse_time))
+        else {
+            break;
+        };
+        if state.phase == Phase::Idle {
+            break;
+        }
+
+        if state.phase == Phase::Work {
+            // Time spent paused doesn't count towards the pomodoro
+            // and a timer held for review after a restart (timers.rs) doesn't finish
+            let timer: Option<(bool, i64)> = conn
+                .query_row(
+                    "SELECT is_paused OR needs_review, COALESCE(total_paused_seconds, 0)
+                     FROM active_timers WHERE session_id = ?1",
+                    [session_id],
+                    |row| Ok((row.get(0)?, row.get(1)?)),
+                )
+                .optional()
+                .map_err(|e| format!("Failed to get pomodoro timer: {}", e))?;
+            match timer {
+                None => {
+                    save_state(conn, Phase::Idle, Some(task_id), None, None, Some(&now.to_rfc3339()), state.completed_in_set)?;
+                    break;
+                }
+                Some((true, _)) => break,
+                Some((false, paused)) => {
+                    ends += Duration::seconds(paused);
+                    conn.execute("UPDATE time_sessions SET paused_seconds = ?1 WHERE id = ?2", [paused, session_id])
+                        .map_err(|e| format!("Failed to record paused time: {}", e))?;
+                }
+            }
+        }
+        if ends > now {
+            break;
+        }
+
+        let ended_at = ends.to_rfc3339();
+        close_session(conn, session_id, &ended_at, true)?;
+        let mut xp_awarded = 0;
+        if state.phase == Phase::Work {
+            conn.execute("DELETE FROM active_timers WHERE session_id = ?1", [session_id])
+                .map_err(|e| format!("Failed to finish pomodoro timer: {}", e))?;
+            if settings.completion_xp > 0 {
+                let granted = ledger::grant_xp(
+                    conn,
+                    &RewardSource::new(ledger::POMODORO, session_id),
+                    &Reward::flat(settings.completion_xp),
+                    Some("Pomodoro completed"),
+                )?;
+                level_up = progression::combine(level_up, granted);
+                xp_awarded = settings.completion_xp;
+            }
+        }
+
+        let (mut next, completed) = phase_after(state.phase, state.completed_in_set, &settings);
+        let next_ends = ends + phase_length(next, &settings);
+        if next == Phase::Work {
+            let timer_running: bool = conn
+                .query_row("SELECT EXISTS (SELECT 1 FROM active_timers WHERE user_id = 1)", [], |row| row.get(0))
+                .map_err(|e| format!("Failed to check timers: {}", e))?;
+            if !settings.auto_start_work || next_ends <= now || timer_running {
+                next = Phase::Idle;
+            }
+        }
+
+        let next_session = match next {
+            Phase::Idle => None,
+            Phase::Work => {
+                let session = open_session(conn, task_id, "pomodoro", &ended_at)?;
+                conn.execute(
+                    "INSERT INTO active_timers (task_id, user_id, session_id, start_time) VALUES (?1, 1, ?2, ?3)",
+                    rusqlite::params![task_id, session, ended_at],
+                )
+                .map_err(|e| format!("Failed to create active timer: {}", e))?;
+                Some(session)
+            }
+            _ => Some(open_session(conn, task_id, "break", &ended_at)?),
+        };
+        let (started_at, ends_at) = match next {
+            Phase::Idle => (None, ended_at.clone()),
+            _ => (Some(ended_at.as_str()), next_ends.to_rfc3339()),
+        };
+        save_state(conn, next, Some(task_id), next_session, started_at, Some(&ends_at), completed)?;
+
+        changes.push(PhaseChange {
+            from: state.phase,
+            to: next,
+            task_id: Some(task_id),
+            completed_in_set: completed,
+            phase_ends_at: (next != Phase::Idle).then_some(ends_at),
+            xp_awarded,
+        });
+    }
+
+    Ok((changes, level_up))
+}
+
+/// End whatever phase is running, without reward, and start the next set afresh.
+pub fn stop_sync(conn: &Connection, now: DateTime<Utc>) -> Result<PomodoroState, String> {
+    let state = load_state(conn, now)?;
+    let now_str = now.to_rfc3339();
+    if let Some(session_id) = state.session_id {
+        close_session(conn, session_id, &now_str, false)?;
+        conn.execute("DELETE FROM active_timers WHERE session_id = ?1", [session_id])
+            .map_err(|e| format!("Failed to stop pomodoro timer: {}", e))?;
+    }
+    save_state(conn, Phase::Idle, state.task_id, None, None, Some(&now_str), 0)?;
+    load_state(conn, now)
+}
+
+async fn advance(app: &AppHandle, db: &DbConnection) -> Result<PomodoroState, String> {
+    let now = Utc::now();
+    let (state, changes, level_up) = {
+        let conn = db.lock().await;
+        let tx = conn.unchecked_transaction()
+            .map_err(|e| format!("Failed to begin transaction: {}", e))?;
+        let (changes, level_up) = advance_sync(&tx, now)?;
+        tx.commit()
+            .map_err(|e| format!("Failed to commit: {}", e))?;
+        (load_state(&conn, now)?, changes, level_up)
+    };
+
+    for change in &changes {
+        if let Err(e) = app.emit(PHASE_EVENT, change) {
+            eprintln!("Failed to emit pomodoro event: {}", e);
+        }
+    }
+    progression::emit_level_up(app, level_up);
+    Ok(state)
+}
+
+/// Move the engine on every few seconds in the background.
+pub fn schedule_ticks(app: AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        let mut interval = tokio::time::interval(tokio::time::Duration::from_secs(TICK_SECS));
+        loop {
+            interval.tick().await;
+            let db = app.state::<DbConnection>();
+            if let Err(e) = advance(&app, &db).await {
+                eprintln!("Pomodoro tick failed: {}", e);
+            }
+        }
+    });
+}
+
+// ---------- Commands ----------
+
+#[tauri::command]
+pub async fn get_pomodoro_state(app: AppHandle, db: State<'_, DbConnection>) -> Result<PomodoroState, String> {
+    advance(&app, &db).await
+}
+
+#[tauri::command]
+pub async fn stop_pomodoro(db: State<'_, DbConnection>) -> Result<PomodoroState, String> {
+    let conn = db.lock().await;
+    let tx = conn.unchecked_transaction()
+        .map_err(|e| format!("Failed to begin transaction: {}", e))?;
+    let state = stop_sync(&tx, Utc::now())?;
+    tx.commit()
+        .map_err(|e| format!("Failed to commit: {}", e))?;
+    Ok(state)
+}
+
+#[tauri::command]
+pub async fn get_pomodoro_settings(db: State<'_, DbConnection>) -> Result<PomodoroSettings, String> {
+    let conn = db.lock().await;
+    load_settings(&conn)
+}
+
+/// New lengths apply from the next phase on.
+#[tauri::command]
+pub async fn update_pomodoro_settings(
+    db: State<'_, DbConnection>,
+    settings: PomodoroSettings,
+) -> Result<PomodoroSettings, String> {
+    validate(&settings)?;
+    let conn = db.lock().await;
+    conn.execute(
+        "UPDATE pomodoro_settings SET work_minutes = ?1, short_break_minutes = ?2, long_break_minutes = ?3,
+                cycles_per_set = ?4, auto_start_work = ?5, completion_xp = ?6, updated_at = CURRENT_TIMESTAMP
+         WHERE id = 1",
+        rusqlite::params![
+            settings.work_minutes,
+            settings.short_break_minutes,
+            settings.long_break_minutes,
+            settings.cycles_per_set,
+            settings.auto_start_work,
+            settings.completion_xp,
+        ],
+    )
+    .map_err(|e| format!("Failed to update pomodoro settings: {}", e))?;
+    load_settings(&conn)
+}
+
+// ---------- Tests ----------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn settings() -> PomodoroSettings {
+        PomodoroSettings {
+            work_minutes: 25,
+            short_break_minutes: 5,
+            long_break_minutes: 15,
+            cycles_per_set: 2,
+            auto_start_work: true,
+            completion_xp: 5,
+        }
+    }
+
+    fn at(minutes: i64) -> DateTime<Utc> {
+        parse_time("2026-05-04T09:00:00+00:00").unwrap() + Duration::minutes(minutes)
+    }
+
+    fn test_conn() -> Connection {
+        let conn = crate::database::test_conn();
+        conn.execute("INSERT INTO tasks (id, user_id, title, status) VALUES (1, 1, 'Write', 'active')", [])
+            .unwrap();
+        conn
+    }
+
+    /// What start_timer does for a 'pomodoro' session.
+    fn start(conn: &Connection, minutes: i64) -> i64 {
+        let start = at(minutes).to_rfc3339();
+        let session = open_session(conn, 1, "pomodoro", &start).unwrap();
+        conn.execute(
+            "INSERT INTO active_timers (task_id, user_id, session_id, start_time) VALUES (1, 1, ?1, ?2)",
+            rusqlite::params![session, start],
+        )
+        .unwrap();
+        begin_work(conn, 1, session, &start).unwrap();
+        session
+    }
+
+    #[test]
+    fn cycles_through_short_and_long_breaks() {
+        let s = settings();
+        assert_eq!(phase_after(Phase::Work, 0, &s), (Phase::ShortBreak, 1));
+        assert_eq!(phase_after(Phase::ShortBreak, 1, &s), (Phase::Work, 1));
+        assert_eq!(phase_after(Phase::Work, 1, &s), (Phase::LongBreak, 2));
+        assert_eq!(phase_after(Phase::LongBreak, 2, &s), (Phase::Work, 0));
+        assert_eq!(phase_length(Phase::LongBreak, &s), Duration::minutes(15));
+        assert!(validate(&PomodoroSettings { cycles_per_set: 0, ..s }).is_err());
+    }
+
+    #[test]
+    fn engine_runs_a_set_and_rewards_each_pomodoro() {
+        let conn = test_conn();
+        conn.execute_batch("UPDATE pomodoro_settings SET cycles_per_set = 2, auto_start_work = 1").unwrap();
+        start(&conn, 0);
+
+        assert!(advance_sync(&conn, at(24)).unwrap().0.is_empty());
+        let (changes, _) = advance_sync(&conn, at(25)).unwrap();
+        assert_eq!((changes[0].from, changes[0].to, changes[0].xp_awarded), (Phase::Work, Phase::ShortBreak, 5));
+        let timers: i64 = conn.query_row("SELECT COUNT(*) FROM active_timers", [], |row| row.get(0)).unwrap();
+        assert_eq!(timers, 0);
+
+        // Break over: the next pomodoro starts on its own and, once done, a long break follows
+        let (changes, _) = advance_sync(&conn, at(31)).unwrap();
+        assert_eq!((changes[0].from, changes[0].to), (Phase::ShortBreak, Phase::Work));
+        let (changes, _) = advance_sync(&conn, at(56)).unwrap();
+        assert_eq!((changes[0].to, changes[0].completed_in_set), (Phase::LongBreak, 2));
+        let state = load_state(&conn, at(56)).unwrap();
+        assert_eq!((state.completed_in_set, state.remaining_seconds), (2, Some(14 * 60)));
+
+        let (breaks, break_seconds, worked): (i64, i64, i64) = conn
+            .query_row(
+                "SELECT COUNT(*), SUM(duration_seconds),
+                        (SELECT total_time_spent_seconds FROM tasks WHERE id = 1)
+                 FROM time_sessions WHERE session_type = 'break' AND end_time IS NOT NULL",
+                [],
+                |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)),
+            )
+            .unwrap();
+        // Breaks don't add to the task's time (durations come from julianday, so allow rounding)
+        assert_eq!((breaks, break_seconds), (1, 300));
+        assert!((worked - 50 * 60).abs() <= 2, "worked {}", worked);
+        let xp: i64 = conn
+            .query_row("SELECT SUM(final_amount) FROM reward_ledger WHERE source_type = 'pomodoro'", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(xp, 10);
+    }
+
+    #[test]
+    fn engine_never_starts_work_retroactively_or_while_paused() {
+        let conn = test_conn();
+        conn.execute_batch("UPDATE pomodoro_settings SET auto_start_work = 1").unwrap();
+        start(&conn, 0);
+
+        conn.execute("UPDATE active_timers SET is_paused = 1", []).unwrap();
+        assert!(advance_sync(&conn, at(40)).unwrap().0.is_empty());
+        conn.execute("UPDATE active_timers SET is_paused = 0, total_paused_seconds = 600", []).unwrap();
+        assert!(advance_sync(&conn, at(34)).unwrap().0.is_empty());
+
+        // Closed for hours: the pomodoro and its break finish, then the engine idles
+        let (changes, _) = advance_sync(&conn, at(300)).unwrap();
+        let phases: Vec<Phase> = changes.iter().map(|c| c.to).collect();
+        assert_eq!(phases, vec![Phase::ShortBreak, Phase::Idle]);
+
+        // Stopping the timer by hand abandons the pomodoro without XP
+        let session = start(&conn, 400);
+        conn.execute("DELETE FROM active_timers", []).unwrap();
+        abandon_work(&conn, session, &at(410).to_rfc3339()).unwrap();
+        assert_eq!(load_state(&conn, at(410)).unwrap().phase, Phase::Idle);
+        assert!(advance_sync(&conn, at(430)).unwrap().0.is_empty());
+    }
+}
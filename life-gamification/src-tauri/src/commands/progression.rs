@@ -0,0 +1,324 @@
+// Level-up pipeline.
+//
+// Every XP grant goes through apply_experience(): it moves users.experience_points,
+// recomputes level / experience_to_next_level from the configurable curve in
+// progression_settings, and for each level reached for the first time awards skill
+// points (user_skill_stats.available_skill_points) and max_health growth. Commands
+// hand the resulting LevelUp to emit_level_up() so the UI can celebrate it.
+
+use rusqlite::Connection;
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Emitter, State};
+
+use crate::database::DbConnection;
+
+/// Event emitted to the frontend whenever an XP grant crosses a level boundary.
+pub const LEVEL_UP_EVENT: &str = "level-up";
+
+/// Hard ceiling so a degenerate curve can never loop forever.
+const MAX_LEVEL: i64 = 1000;
+
+// ---------- Types ----------
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ProgressionSettings {
+    /// XP needed to go from level 1 to level 2.
+    pub base_xp_per_level: i64,
+    /// Each level costs this many times the previous one (1.0 = flat curve).
+    pub level_growth: f64,
+    pub skill_points_per_level: i64,
+    pub max_health_per_level: i64,
+}
+
+impl Default for ProgressionSettings {
+    fn default() -> Self {
+        ProgressionSettings {
+            base_xp_per_level: 100,
+            level_growth: 1.0,
+            skill_points_per_level: 1,
+            max_health_per_level: 10,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct LevelUp {
+    pub previous_level: i64,
+    pub new_level: i64,
+    pub levels_gained: i64,
+    pub skill_points_awarded: i64,
+    pub max_health_gained: i64,
+    pub max_health: i64,
+    pub experience_points: i64,
+    pub experience_to_next_level: i64,
+}
+
+// ---------- Level curve (pure functions, unit-tested) ----------
+
+/// XP it costs to advance from `level` to `level + 1`.
+pub fn xp_to_advance(settings: &ProgressionSettings, level: i64) -> i64 {
+    let cost = settings.base_xp_per_level as f64 * settings.level_growth.powi((level - 1).clamp(0, i32::MAX as i64) as i32);
+    (cost.round() as i64).max(1)
+}
+
+/// Level reached with `total_xp` lifetime XP, and the XP still needed for the next one.
+pub fn level_for_xp(settings: &ProgressionSettings, total_xp: i64) -> (i64, i64) {
+    let mut level = 1;
+    let mut remaining = total_xp.max(0);
+    while level < MAX_LEVEL {
+        let cost = xp_to_advance(settings, level);
+        if remaining < cost {
+            return (level, cost - remaining);
+        }
+        remaining -= cost;
+        level += 1;
+    }
+    (MAX_LEVEL, 0)
+}
+
+fn validate_settings(settings: &ProgressionSettings) -> Result<(), String> {
+    if settings.base_xp_per_level < 1 {
+        return Err("base_xp_per_level must be at least 1".to_string());
+    }
+    if !(1.0..=3.0).contains(&settings.level_growth) {
+        return Err("level_growth must be between 1.0 and 3.0".to_string());
+    }
+    if settings.skill_points_per_level < 0 || settings.max_health_per_level < 0 {
+        return Err("Per-level rewards cannot be negative".to_string());
+    }
+    Ok(())
+}
+
+// ---------- Pipeline ----------
+
+pub fn load_settings(conn: &Connection) -> Result<ProgressionSettings, String> {
+    conn.query_row(
+        "SELECT base_xp_per_level, level_growth, skill_points_per_level, max_health_per_level
+         FROM progression_settings WHERE id = 1",
+        [],
+        |row| {
+            Ok(ProgressionSettings {
+                base_xp_per_level: row.get(0)?,
+                level_growth: row.get(1)?,
+                skill_points_per_level: row.get(2)?,
+                max_health_per_level: row.get(3)?,
+            })
+        },
+    )
+    .map_err(|e| format!("Failed to load progression settings: {}", e))
+}
+
+/// Add (or, when negative, remove) XP for the user and run the level-up pipeline.
+/// Returns a LevelUp when the user's level went up.
+pub fn apply_experience(conn: &Connection, amount: i64) -> Result<Option<LevelUp>, String> {
+    let settings = load_settings(conn)?;
+
+    conn.execute(
+        "UPDATE users SET experience_points = MAX(0, experience_points + ?1) WHERE id = 1",
+        [amount],
+    )
+    .map_err(|e| format!("Failed to update experience: {}", e))?;
+
+    let (total_xp, previous_level, rewarded_through, max_health): (i64, i64, Option<i64>, i64) = conn
+        .query_row(
+            "SELECT experience_points, level, highest_level_rewarded, max_health FROM users WHERE id = 1",
+            [],
+            |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?)),
+        )
+        .map_err(|e| format!("Failed to get user: {}", e))?;
+
+    let (new_level, to_next) = level_for_xp(&settings, total_xp);
+    let rewarded_through = rewarded_through.unwrap_or(previous_level);
+
+    conn.execute(
+        "UPDATE users SET level = ?1, experience_to_next_level = ?2,
+         highest_level_rewarded = MAX(COALESCE(highest_level_rewarded, 0), ?1) WHERE id = 1",
+        rusqlite::params![new_level, to_next],
+    )
+    .map_err(|e| format!("Failed to update level: {}", e))?;
+
+    if new_level <= previous_level {
+        return Ok(None);
+    }
+
+    // Only levels never reached before pay out
+    let new_levels = (new_level - rewarded_through).max(0);
+    let skill_points = new_levels * settings.skill_points_per_level;
+    let health_gain = new_levels * settings.max_health_per_level;
+
+    if skill_points > 0 {
+        conn.execute(
+            "INSERT OR IGNORE INTO user_skill_stats (user_id, available_skill_points) VALUES (1, 10)",
+            [],
+        )
+        .map_err(|e| format!("Failed to create skill stats: {}", e))?;
+        conn.execute(
+            "UPDATE user_skill_stats SET available_skill_points = available_skill_points + ?1 WHERE user_id = 1",
+            [skill_points],
+        )
+        .map_err(|e| format!("Failed to award skill points: {}", e))?;
+    }
+    if health_gain > 0 {
+        conn.execute(
+            "UPDATE users SET max_health = max_health + ?1, current_health = current_health + ?1 WHERE id = 1",
+            [health_gain],
+        )
+        .map_err(|e| format!("Failed to grow max health: {}", e))?;
+    }
+
+    println!("Level up! {} -> {} (+{} skill points, +{} max HP)",
+        previous_level, new_level, skill_points, health_gain);
+
+    Ok(Some(LevelUp {
+        previous_level,
+        new_level,
+        levels_gained: new_level - previous_level,
+        skill_points_awarded: skill_points,
+        max_health_gained: health_gain,
+        max_health: max_health + health_gain,
+        experience_points: total_xp,
+        experience_to_next_level: to_next,
+    }))
+}
+
+/// Merge two level-ups from the same operation (e.g. a subtask and the parent it
+/// auto-completed) into one event spanning both.
+pub fn combine(first: Option<LevelUp>, second: Option<LevelUp>) -> Option<LevelUp> {
+    match (first, second) {
+        (Some(a), Some(b)) => Some(LevelUp {
+            previous_level: a.previous_level,
+            levels_gained: b.new_level - a.previous_level,
+            skill_points_awarded: a.skill_points_awarded + b.skill_points_awarded,
+            max_health_gained: a.max_health_gained + b.max_health_gained,
+            ..b
+        }),
+        (a, b) => a.or(b),
+    }
+}
+
+/// Emit a level-up to the frontend, if there was one.
+pub fn emit_level_up(app: &AppHandle, level_up: Option<LevelUp>) {
+    if let Some(level_up) = level_up {
+        if let Err(e) = app.emit(LEVEL_UP_EVENT, level_up) {
+            eprintln!("Failed to emit level-up event: {}", e);
+        }
+    }
+}
+
+// ---------- Commands ----------
+
+#[tauri::command]
+pub async fn get_progression_settings(db: State<'_, DbConnection>) -> Result<ProgressionSettings, String> {
+    let conn = db.lock().await;
+    load_settings(&conn)
+}
+
+/// Replace the level curve and re-level the user against it. Levels newly reached
+/// under the new curve pay out like any other level-up.
+#[tauri::command]
+pub async fn update_progression_settings(
+    app: AppHandle,
+    db: State<'_, DbConnection>,
+    settings: ProgressionSettings,
+) -> Result<ProgressionSettings, String> {
+    validate_settings(&settings)?;
+
+    let conn = db.lock().await;
+    let tx = conn.unchecked_transaction()
+        .map_err(|e| format!("Failed to begin transaction: {}", e))?;
+    tx.execute(
+        "UPDATE progression_settings SET base_xp_per_level = ?1, level_growth = ?2,
+         skill_points_per_level = ?3, max_health_per_level = ?4, updated_at = CURRENT_TIMESTAMP
+         WHERE id = 1",
+        rusqlite::params![
+            settings.base_xp_per_level,
+            settings.level_growth,
+            settings.skill_points_per_level,
+            settings.max_health_per_level,
+        ],
+    )
+    .map_err(|e| format!("Failed to save progression settings: {}", e))?;
+    let level_up = apply_experience(&tx, 0)?;
+    tx.commit()
+        .map_err(|e| format!("Failed to commit: {}", e))?;
+
+    emit_level_up(&app, level_up);
+    Ok(settings)
+}
+
+// ---------- Tests ----------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::database::test_conn;
+
+    fn skill_points(conn: &Connection) -> i64 {
+        conn.query_row("SELECT available_skill_points FROM user_skill_stats WHERE user_id = 1", [], |row| row.get(0))
+            .unwrap()
+    }
+
+    #[test]
+    fn flat_curve_matches_legacy_formula() {
+        let s = ProgressionSettings::default();
+        assert_eq!(level_for_xp(&s, 0), (1, 100));
+        assert_eq!(level_for_xp(&s, 99), (1, 1));
+        assert_eq!(level_for_xp(&s, 100), (2, 100));
+        assert_eq!(level_for_xp(&s, 1234), (13, 66));
+    }
+
+    #[test]
+    fn growing_curve_gets_steeper() {
+        let s = ProgressionSettings { level_growth: 1.5, ..ProgressionSettings::default() };
+        assert_eq!(xp_to_advance(&s, 1), 100);
+        assert_eq!(xp_to_advance(&s, 2), 150);
+        assert_eq!(xp_to_advance(&s, 3), 225);
+        assert_eq!(level_for_xp(&s, 250), (3, 225));
+    }
+
+    #[test]
+    fn rejects_bad_settings() {
+        let base = ProgressionSettings::default();
+        assert!(validate_settings(&base).is_ok());
+        assert!(validate_settings(&ProgressionSettings { base_xp_per_level: 0, ..base.clone() }).is_err());
+        assert!(validate_settings(&ProgressionSettings { level_growth: 0.5, ..base.clone() }).is_err());
+        assert!(validate_settings(&ProgressionSettings { skill_points_per_level: -1, ..base }).is_err());
+    }
+
+    #[test]
+    fn crossing_levels_awards_points_and_health() {
+        let conn = test_conn();
+        let points_before = skill_points(&conn);
+
+        assert_eq!(apply_experience(&conn, 50).unwrap(), None);
+
+        let level_up = apply_experience(&conn, 200).unwrap().expect("level up");
+        assert_eq!(level_up.previous_level, 1);
+        assert_eq!(level_up.new_level, 3);
+        assert_eq!(level_up.skill_points_awarded, 2);
+        assert_eq!(level_up.max_health_gained, 20);
+        assert_eq!(level_up.experience_to_next_level, 50);
+        assert_eq!(skill_points(&conn), points_before + 2);
+
+        let (level, max_health): (i64, i64) = conn
+            .query_row("SELECT level, max_health FROM users WHERE id = 1", [], |row| Ok((row.get(0)?, row.get(1)?)))
+            .unwrap();
+        assert_eq!((level, max_health), (3, 120));
+    }
+
+    #[test]
+    fn regaining_lost_levels_pays_nothing() {
+        let conn = test_conn();
+        apply_experience(&conn, 250).unwrap();
+        let points = skill_points(&conn);
+
+        assert_eq!(apply_experience(&conn, -200).unwrap(), None);
+        let level: i64 = conn.query_row("SELECT level FROM users WHERE id = 1", [], |row| row.get(0)).unwrap();
+        assert_eq!(level, 1);
+
+        let level_up = apply_experience(&conn, 200).unwrap().expect("level up");
+        assert_eq!(level_up.skill_points_awarded, 0);
+        assert_eq!(level_up.max_health_gained, 0);
+        assert_eq!(skill_points(&conn), points);
+    }
+}
@@ -0,0 +1,475 @@
+// Recurrence engine for recurring tasks.
+//
+// A recurring parent task stores its schedule as JSON in tasks.recurrence_pattern
+// (the frontend's RecurrencePattern shape, plus a few extra fields). This module
+// turns that pattern into concrete occurrence dates: generate_recurring_instances
+// uses it to create one instance per scheduled date (catching up on dates missed
+// while the app was closed), and complete_task uses it so a streak counts
+// consecutive *scheduled* occurrences rather than consecutive calendar days.
+
+use chrono::{Datelike, Duration, NaiveDate, Utc};
+use rusqlite::Connection;
+use serde::{Deserialize, Serialize};
+
+use crate::commands::{economy, pauses};
+
+/// How far back generate_recurring_instances will catch up on missed occurrences.
+pub const CATCH_UP_DAYS: i64 = 30;
+
+/// Upper bound on days scanned when walking a schedule, so a malformed pattern
+/// can never spin forever.
+const MAX_SCAN_DAYS: i64 = 366 * 20;
+
+// ---------- Types ----------
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Frequency {
+    Daily,
+    Weekly,
+    Monthly,
+    /// Every `interval` days; kept separate from Daily because the frontend offers it.
+    Custom,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RecurrencePattern {
+    pub frequency: Frequency,
+    /// Every N days / weeks / months.
+    #[serde(default = "default_interval")]
+    pub interval: u32,
+    /// Weekly only: days of the week, 0 = Sunday .. 6 = Saturday.
+    #[serde(default)]
+    pub weekdays: Vec<u32>,
+    /// Monthly only: day of the month, clamped to the last day in short months.
+    #[serde(default)]
+    pub month_day: Option<u32>,
+    /// Monthly only: which week of the month (1..=5, or -1 for the last),
+    /// combined with `month_weekday` for schedules like "last Friday".
+    #[serde(default)]
+    pub month_week: Option<i32>,
+    #[serde(default)]
+    pub month_weekday: Option<u32>,
+    /// First scheduled date; defaults to the parent task's creation date.
+    #[serde(default)]
+    pub start_date: Option<String>,
+    /// Last date (inclusive) an occurrence may fall on.
+    #[serde(default)]
+    pub end_date: Option<String>,
+    /// Stop after this many occurrences.
+    #[serde(default, alias = "max_completions")]
+    pub max_occurrences: Option<u32>,
+}
+
+fn default_interval() -> u32 {
+    1
+}
+
+/// Streak state to write back to a recurring parent and the completed instance.
+#[derive(Debug, Clone, PartialEq)]
+pub struct StreakUpdate {
+    pub current: i64,
+    pub longest: i64,
+    pub last_completed: String,
+    pub multiplier: f64,
+}
+
+// ---------- Parsing (pure functions, unit-tested) ----------
+
+/// Parse a "YYYY-MM-DD" date, tolerating a trailing time or ISO timestamp.
+pub fn parse_date(raw: &str) -> Option<NaiveDate> {
+    let raw = raw.trim();
+    NaiveDate::parse_from_str(raw.get(..10)?, "%Y-%m-%d").ok()
+}
+
+impl RecurrencePattern {
+    pub fn simple(frequency: Frequency) -> Self {
+        RecurrencePattern {
+            frequency,
+            interval: 1,
+            weekdays: Vec::new(),
+            month_day: None,
+            month_week: None,
+            month_weekday: None,
+            start_date: None,
+            end_date: None,
+            max_occurrences: None,
+        }
+    }
+
+    /// Parse the stored pattern. Accepts the JSON object written by the frontend
+    /// as well as legacy shorthand strings such as "daily" or "weekly".
+    pub fn parse(raw: &str) -> Result<Self, String> {
+        let raw = raw.trim();
+        let pattern = if raw.starts_with('{') {
+            serde_json::from_str::<RecurrencePattern>(raw)
+                .map_err(|e| format!("Invalid recurrence pattern: {}", e))?
+        } else {
+            let shorthand = raw.trim_matches('"').to_lowercase();
+            let frequency = match shorthand.as_str() {
+                "daily" => Frequency::Daily,
+                "weekly" => Frequency::Weekly,
+                "monthly" => Frequency::Monthly,
+                "custom" => Frequency::Custom,
+                other => return Err(format!("Unknown recurrence pattern '{}'", other)),
+            };
+            RecurrencePattern::simple(frequency)
+        };
+        pattern.validate()?;
+        Ok(pattern)
+    }
+
+    fn validate(&self) -> Result<(), String> {
+        if self.interval == 0 {
+            return Err("Recurrence interval must be at least 1".to_string());
+        }
+        if self.weekdays.iter().any(|d| *d > 6) {
+            return Err("Recurrence weekdays must be 0 (Sunday) to 6 (Saturday)".to_string());
+        }
+        if let Some(day) = self.month_day {
+            if !(1..=31).contains(&day) {
+                return Err("Recurrence month_day must be 1 to 31".to_string());
+            }
+        }
+        match (self.month_week, self.month_weekday) {
+            (Some(week), Some(weekday)) => {
+                if !(week == -1 || (1..=5).contains(&week)) {
+                    return Err("Recurrence month_week must be 1 to 5, or -1 for the last week".to_string());
+                }
+                if weekday > 6 {
+                    return Err("Recurrence month_weekday must be 0 (Sunday) to 6 (Saturday)".to_string());
+                }
+            }
+            (None, None) => {}
+            _ => return Err("Recurrence month_week and month_weekday must be set together".to_string()),
+        }
+        for (name, value) in [("start_date", &self.start_date), ("end_date", &self.end_date)] {
+            if let Some(raw) = value {
+                if parse_date(raw).is_none() {
+                    return Err(format!("Recurrence {} must be YYYY-MM-DD", name));
+                }
+            }
+        }
+        if self.max_occurrences == Some(0) {
+            return Err("Recurrence max_occurrences must be at least 1".to_string());
+        }
+        Ok(())
+    }
+
+    /// The first date of the series: the pattern's start_date if set, otherwise
+    /// the date the parent task was created.
+    pub fn anchor(&self, created: NaiveDate) -> NaiveDate {
+        self.start_date.as_deref().and_then(parse_date).unwrap_or(created)
+    }
+
+    fn end_date(&self) -> Option<NaiveDate> {
+        self.end_date.as_deref().and_then(parse_date)
+    }
+
+    /// Whether `date` matches the schedule, ignoring end_date / max_occurrences.
+    pub fn matches(&self, anchor: NaiveDate, date: NaiveDate) -> bool {
+        if date < anchor {
+            return false;
+        }
+        let interval = self.interval.max(1) as i64;
+        match self.frequency {
+            Frequency::Daily | Frequency::Custom => (date - anchor).num_days() % interval == 0,
+            Frequency::Weekly => {
+                let weeks = (week_start(date) - week_start(anchor)).num_days() / 7;
+                if weeks % interval != 0 {
+                    return false;
+                }
+                let weekday = date.weekday().num_days_from_sunday();
+                if self.weekdays.is_empty() {
+                    weekday == anchor.weekday().num_days_from_sunday()
+                } else {
+                    self.weekdays.contains(&weekday)
+                }
+            }
+            Frequency::Monthly => {
+                let months = month_index(date) - month_index(anchor);
+                if months % interval != 0 {
+                    return false;
+                }
+                if let (Some(week), Some(weekday)) = (self.month_week, self.month_weekday) {
+                    nth_weekday_of_month(date.year(), date.month(), week, weekday) == Some(date)
+                } else {
+                    let day = self.month_day.unwrap_or_else(|| anchor.day());
+                    date.day() == day.min(days_in_month(date.year(), date.month()))
+                }
+            }
+        }
+    }
+
+    /// All scheduled dates in `[from, to]`, honoring end_date and max_occurrences.
+    pub fn occurrences_between(&self, anchor: NaiveDate, from: NaiveDate, to: NaiveDate) -> Vec<NaiveDate> {
+        let to = match self.end_date() {
+            Some(end) => to.min(end),
+            None => to,
+        };
+        if to < anchor || to < from {
+            return Vec::new();
+        }
+
+        // max_occurrences counts from the anchor, so walk the whole series when it is set.
+        let mut day = if self.max_occurrences.is_some() { anchor } else { from.max(anchor) };
+        let mut seen: u32 = 0;
+        let mut dates = Vec::new();
+        while day <= to && (day - anchor).num_days() <= MAX_SCAN_DAYS {
+            if self.matches(anchor, day) {
+                seen += 1;
+                if let Some(max) = self.max_occurrences {
+                    if seen > max {
+                        break;
+                    }
+                }
+                if day >= from {
+                    dates.push(day);
+                }
+            }
+            day += Duration::days(1);
+        }
+        dates
+    }
+
+    /// The latest scheduled occurrence strictly before `date`, if any.
+    pub fn previous_occurrence(&self, anchor: NaiveDate, date: NaiveDate) -> Option<NaiveDate> {
+        let mut day = date - Duration::days(1);
+        while day >= anchor {
+            if self.matches(anchor, day) {
+                return Some(day);
+            }
+            day -= Duration::days(1);
+        }
+        None
+    }
+
+    /// True once every occurrence of the series lies on or before `date`.
+    pub fn is_finished(&self, anchor: NaiveDate, date: NaiveDate) -> bool {
+        if let Some(end) = self.end_date() {
+            if date >= end {
+                return true;
+            }
+        }
+        match self.max_occurrences {
+            Some(max) => self.occurrences_between(anchor, anchor, date).len() as u32 >= max,
+            None => false,
+        }
+    }
+}
+
+/// Streak after completing the occurrence scheduled on `occurrence`.
+///
+/// The streak continues only if the previous *scheduled* occurrence was the last
+/// one completed, so a Mon/Wed/Fri habit does not break over Tuesday. Occurrences
+/// for which `paused` is true are passed over the same way. Completing the same
+/// occurrence again (or an older one) leaves the streak unchanged.
+pub fn next_streak(
+    pattern: &RecurrencePattern,
+    anchor: NaiveDate,
+    last_completed: Option<NaiveDate>,
+    current_streak: i64,
+    occurrence: NaiveDate,
+    paused: impl Fn(NaiveDate) -> bool,
+) -> i64 {
+    let mut previous = pattern.previous_occurrence(anchor, occurrence);
+    while let Some(day) = previous.filter(|day| paused(*day)) {
+        previous = pattern.previous_occurrence(anchor, day);
+    }
+    match last_completed {
+        Some(last) if last >= occurrence => current_streak.max(1),
+        Some(last) if previous == Some(last) || (previous < Some(last) && paused(last)) => current_streak + 1,
+        _ => 1,
+    }
+}
+
+// ---------- Persistence helpers ----------
+
+/// Compute the streak for completing an instance of `parent_id` that was
+/// scheduled on `instance_date` (today if the instance has no date).
+pub fn streak_after_completion(
+    conn: &Connection,
+    parent_id: i64,
+    instance_date: Option<&str>,
+) -> Result<StreakUpdate, String> {
+    let (raw_pattern, created_at, current, longest, last_completed) = conn
+        .query_row(
+            "SELECT recurrence_pattern, created_at, current_streak, longest_streak, last_completed_date
+             FROM tasks WHERE id = ?1",
+            [parent_id],
+            |row| {
+                Ok((
+                    row.get::<_, Option<String>>(0)?,
+                    row.get::<_, Option<String>>(1)?,
+                    row.get::<_, Option<i64>>(2)?,
+                    row.get::<_, Option<i64>>(3)?,
+                    row.get::<_, Option<String>>(4)?,
+                ))
+            },
+        )
+        .map_err(|e| format!("Recurring parent task not found: {}", e))?;
+
+    let today = Utc::now().naive_utc().date();
+    let occurrence = instance_date.and_then(parse_date).unwrap_or(today);
+    let pattern = raw_pattern
+        .as_deref()
+        .and_then(|raw| RecurrencePattern::parse(raw).ok())
+        .unwrap_or_else(|| RecurrencePattern::simple(Frequency::Daily));
+    let anchor = pattern.anchor(created_at.as_deref().and_then(parse_date).unwrap_or(occurrence));
+    let last = last_completed.as_deref().and_then(parse_date);
+
+    let pauses = pauses::PauseWindows::load(conn)?;
+    let streak = next_streak(&pattern, anchor, last, current.unwrap_or(0), occurrence, |day| {
+        pauses.is_paused(Some(parent_id), day)
+    });
+    Ok(StreakUpdate {
+        current: streak,
+        longest: longest.unwrap_or(0).max(streak),
+        last_completed: last.map_or(occurrence, |l| l.max(occurrence)).format("%Y-%m-%d").to_string(),
+        multiplier: economy::load_config(conn)?.streak_multiplier(streak),
+    })
+}
+
+fn week_start(date: NaiveDate) -> NaiveDate {
+    date - Duration::days(date.weekday().num_days_from_sunday() as i64)
+}
+
+fn month_index(date: NaiveDate) -> i64 {
+    date.year() as i64 * 12 + date.month0() as i64
+}
+
+fn days_in_month(year: i32, month: u32) -> u32 {
+    let (next_year, next_month) = if month == 12 { (year + 1, 1) } else { (year, month + 1) };
+    NaiveDate::from_ymd_opt(next_year, next_month, 1)
+        .and_then(|d| d.pred_opt())
+        .map(|d| d.day())
+        .unwrap_or(28)
+}
+
+/// The `week`-th `weekday` (0 = Sunday) of a month; `week == -1` means the last one.
+fn nth_weekday_of_month(year: i32, month: u32, week: i32, weekday: u32) -> Option<NaiveDate> {
+    if week == -1 {
+        let last = NaiveDate::from_ymd_opt(year, month, days_in_month(year, month))?;
+        let back = (last.weekday().num_days_from_sunday() + 7 - weekday) % 7;
+        return Some(last - Duration::days(back as i64));
+    }
+    let first = NaiveDate::from_ymd_opt(year, month, 1)?;
+    let forward = (weekday + 7 - first.weekday().num_days_from_sunday()) % 7;
+    let date = first + Duration::days(forward as i64 + 7 * (week as i64 - 1));
+    (date.month() == month).then_some(date)
+}
+
+// ---------- Tests ----------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn d(s: &str) -> NaiveDate {
+        parse_date(s).unwrap()
+    }
+
+    #[test]
+    fn parses_json_and_legacy_shorthand() {
+        let p = RecurrencePattern::parse(r#"{"frequency":"weekly","interval":1,"weekdays":[1,3,5]}"#).unwrap();
+        assert_eq!(p.frequency, Frequency::Weekly);
+        assert_eq!(p.weekdays, vec![1, 3, 5]);
+
+        let legacy = RecurrencePattern::parse("daily").unwrap();
+        assert_eq!(legacy, RecurrencePattern::simple(Frequency::Daily));
+
+        let aliased = RecurrencePattern::parse(r#"{"frequency":"daily","max_completions":5}"#).unwrap();
+        assert_eq!(aliased.max_occurrences, Some(5));
+        assert_eq!(aliased.interval, 1);
+    }
+
+    #[test]
+    fn rejects_invalid_patterns() {
+        assert!(RecurrencePattern::parse("fortnightly").is_err());
+        assert!(RecurrencePattern::parse(r#"{"frequency":"daily","interval":0}"#).is_err());
+        assert!(RecurrencePattern::parse(r#"{"frequency":"weekly","weekdays":[7]}"#).is_err());
+        assert!(RecurrencePattern::parse(r#"{"frequency":"monthly","month_week":-1}"#).is_err());
+        assert!(RecurrencePattern::parse(r#"{"frequency":"daily","end_date":"soon"}"#).is_err());
+    }
+
+    #[test]
+    fn weekly_on_specific_weekdays() {
+        // 2026-03-02 is a Monday.
+        let p = RecurrencePattern::parse(r#"{"frequency":"weekly","weekdays":[1,3,5]}"#).unwrap();
+        let dates = p.occurrences_between(d("2026-03-02"), d("2026-03-02"), d("2026-03-08"));
+        assert_eq!(dates, vec![d("2026-03-02"), d("2026-03-04"), d("2026-03-06")]);
+    }
+
+    #[test]
+    fn every_other_week_skips_odd_weeks() {
+        let p = RecurrencePattern::parse(r#"{"frequency":"weekly","interval":2,"weekdays":[2]}"#).unwrap();
+        let dates = p.occurrences_between(d("2026-03-02"), d("2026-03-01"), d("2026-03-31"));
+        assert_eq!(dates, vec![d("2026-03-03"), d("2026-03-17"), d("2026-03-31")]);
+    }
+
+    #[test]
+    fn every_n_days() {
+        let p = RecurrencePattern::parse(r#"{"frequency":"custom","interval":3}"#).unwrap();
+        let dates = p.occurrences_between(d("2026-01-30"), d("2026-01-30"), d("2026-02-06"));
+        assert_eq!(dates, vec![d("2026-01-30"), d("2026-02-02"), d("2026-02-05")]);
+    }
+
+    #[test]
+    fn monthly_day_clamps_to_short_months() {
+        let p = RecurrencePattern::parse(r#"{"frequency":"monthly","month_day":31}"#).unwrap();
+        let dates = p.occurrences_between(d("2026-01-01"), d("2026-01-01"), d("2026-04-30"));
+        assert_eq!(dates, vec![d("2026-01-31"), d("2026-02-28"), d("2026-03-31"), d("2026-04-30")]);
+    }
+
+    #[test]
+    fn monthly_last_friday() {
+        let p = RecurrencePattern::parse(r#"{"frequency":"monthly","month_week":-1,"month_weekday":5}"#).unwrap();
+        let dates = p.occurrences_between(d("2026-01-01"), d("2026-01-01"), d("2026-03-31"));
+        assert_eq!(dates, vec![d("2026-01-30"), d("2026-02-27"), d("2026-03-27")]);
+    }
+
+    #[test]
+    fn ends_after_n_occurrences_or_end_date() {
+        let p = RecurrencePattern::parse(r#"{"frequency":"daily","max_occurrences":3}"#).unwrap();
+        let anchor = d("2026-05-01");
+        assert_eq!(p.occurrences_between(anchor, d("2026-05-02"), d("2026-05-10")).len(), 2);
+        assert_eq!(p.occurrences_between(anchor, d("2026-05-03"), d("2026-05-04")), vec![d("2026-05-03")]);
+        assert!(p.is_finished(anchor, d("2026-05-03")));
+
+        let p = RecurrencePattern::parse(r#"{"frequency":"daily","end_date":"2026-05-02"}"#).unwrap();
+        assert_eq!(p.occurrences_between(anchor, anchor, d("2026-05-10")).len(), 2);
+        assert!(p.is_finished(anchor, d("2026-05-02")));
+        assert!(!p.is_finished(anchor, d("2026-05-01")));
+    }
+
+    #[test]
+    fn start_date_overrides_creation_anchor() {
+        let p = RecurrencePattern::parse(r#"{"frequency":"daily","start_date":"2026-06-10"}"#).unwrap();
+        assert_eq!(p.anchor(d("2026-06-01")), d("2026-06-10"));
+        assert!(p.occurrences_between(d("2026-06-10"), d("2026-06-01"), d("2026-06-09")).is_empty());
+    }
+
+    #[test]
+    fn streak_counts_consecutive_scheduled_occurrences() {
+        // Mon/Wed/Fri habit: Monday -> Wednesday is consecutive despite the gap day.
+        let p = RecurrencePattern::parse(r#"{"frequency":"weekly","weekdays":[1,3,5]}"#).unwrap();
+        let anchor = d("2026-03-02");
+        assert_eq!(next_streak(&p, anchor, None, 0, d("2026-03-02"), |_| false), 1);
+        assert_eq!(next_streak(&p, anchor, Some(d("2026-03-02")), 1, d("2026-03-04"), |_| false), 2);
+        // Skipping Wednesday breaks it.
+        assert_eq!(next_streak(&p, anchor, Some(d("2026-03-02")), 2, d("2026-03-06"), |_| false), 1);
+        // Re-completing an already counted occurrence keeps the streak.
+        assert_eq!(next_streak(&p, anchor, Some(d("2026-03-04")), 2, d("2026-03-04"), |_| false), 2);
+    }
+
+    #[test]
+    fn paused_occurrences_do_not_break_the_streak() {
+        let p = RecurrencePattern::simple(Frequency::Daily);
+        let anchor = d("2026-03-01");
+        let paused = |day: NaiveDate| day >= d("2026-03-05") && day <= d("2026-03-09");
+        assert_eq!(next_streak(&p, anchor, Some(d("2026-03-04")), 4, d("2026-03-10"), paused), 5);
+        // Completing an occurrence generated on the first paused day also counts
+        assert_eq!(next_streak(&p, anchor, Some(d("2026-03-05")), 5, d("2026-03-10"), paused), 6);
+        assert_eq!(next_streak(&p, anchor, Some(d("2026-03-03")), 3, d("2026-03-10"), paused), 1);
+    }
+}
@@ -14,8 +14,9 @@ use rusqlite::OptionalExtension;
 use serde::{Deserialize, Serialize};
 use std::collections::HashSet;
 use std::process::Command;
-use tauri::State;
+use tauri::{AppHandle, State};
 
+use crate::commands::{activity, economy};
 use crate::database::DbConnection;
 
 /// Max reminders imported per list per sync (JXA gets slow on huge lists).
@@ -336,7 +337,7 @@ pub async fn mark_reminder_completed(
 /// 2. Complete in-app tasks whose reminder was checked off (or deleted) in Reminders.
 /// 3. Check off reminders whose task was completed in-app (catch-up for missed backflow).
 #[tauri::command]
-pub async fn sync_reminders(db: State<'_, DbConnection>) -> Result<RemindersSyncSummary, String> {
+pub async fn sync_reminders(app: AppHandle, db: State<'_, DbConnection>) -> Result<RemindersSyncSummary, String> {
     let mut summary = RemindersSyncSummary::default();
 
     // 1. Enabled lists (DB), then release the lock for the slow JXA fetch.
@@ -359,12 +360,14 @@ pub async fn sync_reminders(db: State<'_, DbConnection>) -> Result<RemindersSync
 
     {
         let conn = db.lock().await;
+        let _source = activity::with_source(&conn, activity::REMINDERS)?;
 
         // 2. Upsert tasks for incomplete reminders (dedup on reminder_id).
         for reminder in &reminders {
             let existing: Option<(i64, String, String)> = conn
                 .query_row(
-                    "SELECT id, status, title FROM tasks WHERE reminder_id = ?1",
+                    "SELECT id, CASE WHEN deleted_at IS NULL THEN status ELSE 'trashed' END, title
+                     FROM tasks WHERE reminder_id = ?1",
                     [&reminder.id],
                     |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)),
                 )
@@ -390,10 +393,9 @@ pub async fn sync_reminders(db: State<'_, DbConnection>) -> Result<RemindersSync
                     }
                 }
                 None => {
-                    // Same reward formula as create_task with default difficulty 3.
+                    // Reminders carry no difficulty; price them as difficulty 3.
                     let difficulty = 3i64;
-                    let base_xp = 10 + (difficulty - 1) * 5;
-                    let gold_reward = 1 + (difficulty - 1);
+                    let (base_xp, gold_reward) = economy::task_rewards(&conn, difficulty)?;
                     let priority = task_priority_from_reminder(reminder.priority);
                     let description = reminder
                         .body
@@ -430,7 +432,7 @@ pub async fn sync_reminders(db: State<'_, DbConnection>) -> Result<RemindersSync
         let mut stmt = conn
             .prepare(
                 "SELECT id, reminder_id, reminder_list FROM tasks
-                 WHERE reminder_id IS NOT NULL AND status = 'active'",
+                 WHERE reminder_id IS NOT NULL AND status = 'active' AND deleted_at IS NULL",
             )
             .map_err(|e| format!("Failed to prepare stale-tasks query: {}", e))?;
         let stale: Vec<(i64, String, String)> = stmt
@@ -450,7 +452,7 @@ pub async fn sync_reminders(db: State<'_, DbConnection>) -> Result<RemindersSync
     // Reuse the standard completion path (XP/gold/streak logic) for reminders
     // checked off in Reminders.app.
     for task_id in tasks_to_complete {
-        match crate::complete_task(db.clone(), task_id).await {
+        match crate::complete_task(app.clone(), db.clone(), task_id).await {
             Ok(_) => summary.tasks_completed += 1,
             Err(e) => summary
                 .errors
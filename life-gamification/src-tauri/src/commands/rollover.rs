@@ -0,0 +1,436 @@
+// Overdue rollover policies.
+//
+// Left alone, an overdue task keeps its full reward forever. A rollover policy
+// (per project, per category, or the default) says what the daily sweep does
+// instead: roll the due date forward to today, decay the XP reward by a
+// percentage of its original value per overdue day down to a floor, and/or
+// archive the task after N overdue days. Overdue days count from the day the
+// task first went overdue (tasks.overdue_since), so rolling doesn't reset them.
+// Like overdue damage, they leave out vacation days (see pauses.rs) and the
+// days a task was deferred (see defer.rs), and the sweep leaves deferred tasks
+// alone and does nothing at all on a vacation day.
+//
+// The sweep runs right after the overdue damage sweep (see damage.rs), so a
+// rolled task still costs HP for each day it was missed. Its changes go through
+// activity::with_source and show up in the task's timeline as 'rollover'.
+
+use chrono::NaiveDate;
+use rusqlite::{Connection, OptionalExtension};
+use serde::{Deserialize, Serialize};
+use tauri::State;
+
+use crate::commands::pauses::PauseWindows;
+use crate::commands::recurrence::parse_date;
+use crate::commands::{activity, defer};
+use crate::database::DbConnection;
+
+// ---------- Types ----------
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RolloverPolicy {
+    #[serde(default)]
+    pub id: i64,
+    /// The project this policy is for; with category also None, the default.
+    pub project_id: Option<i64>,
+    pub category: Option<String>,
+    pub roll_to_today: bool,
+    /// Share of the original XP reward lost per overdue day (0 = no decay).
+    pub decay_percent_per_day: f64,
+    /// Decay never takes the reward below this share of the original.
+    pub decay_floor_percent: f64,
+    pub archive_after_days: Option<i64>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct RolloverSummary {
+    pub rolled: i64,
+    pub decayed: i64,
+    pub archived: i64,
+}
+
+/// What the sweep should do to one overdue task today.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Decision {
+    Archive,
+    Keep { xp: i64, roll: bool },
+}
+
+// ---------- Policy (pure functions, unit-tested) ----------
+
+/// XP left after `days` overdue days of decay.
+pub fn decayed_reward(original: i64, percent_per_day: f64, floor_percent: f64, days: i64) -> i64 {
+    let remaining = (100.0 - percent_per_day * days.max(0) as f64).max(floor_percent).min(100.0);
+    (original as f64 * remaining / 100.0).round() as i64
+}
+
+/// Apply a policy to a task `days` past the day it first went overdue.
+pub fn decide(policy: &RolloverPolicy, original_xp: i64, days: i64) -> Decision {
+    if policy.archive_after_days.is_some_and(|limit| days >= limit) {
+        return Decision::Archive;
+    }
+    let xp = if policy.decay_percent_per_day > 0.0 {
+        decayed_reward(original_xp, policy.decay_percent_per_day, policy.decay_floor_percent, days)
+    } else {
+        original_xp
+    };
+    Decision::Keep { xp, roll: policy.roll_to_today }
+}
+
+/// The due date moved to `today`, keeping any time of day it had.
+pub fn rolled_due_date(due: &str, today: NaiveDate) -> String {
+    format!("{}{}", today.format("%Y-%m-%d"), due.get(10..).unwrap_or(""))
+}
+
+/// Overdue days from `since` up to (not including) `today`, leaving out
+/// vacation days.
+pub fn overdue_days(since: NaiveDate, today: NaiveDate, pauses: &PauseWindows) -> i64 {
+    since.iter_days().take_while(|day| *day < today).filter(|day| !pauses.on_vacation(*day)).count() as i64
+}
+
+fn validate(policy: &RolloverPolicy) -> Result<(), String> {
+    if policy.project_id.is_some() && policy.category.is_some() {
+        return Err("A rollover policy is for a project or a category, not both".to_string());
+    }
+    if !(0.0..=100.0).contains(&policy.decay_percent_per_day) || !(0.0..=100.0).contains(&policy.decay_floor_percent) {
+        return Err("Decay percentages must be between 0 and 100".to_string());
+    }
+    if policy.archive_after_days.is_some_and(|days| days < 1) {
+        return Err("Archiving needs at least one overdue day".to_string());
+    }
+    Ok(())
+}
+
+// ---------- Persistence ----------
+
+fn policy_from_row(row: &rusqlite::Row) -> rusqlite::Result<RolloverPolicy> {
+    Ok(RolloverPolicy {
+        id: row.get(0)?,
+        project_id: row.get(1)?,
+        category: row.get(2)?,
+        roll_to_today: row.get(3)?,
+        decay_percent_per_day: row.get(4)?,
+        decay_floor_percent: row.get(5)?,
+        archive_after_days: row.get(6)?,
+    })
+}
+
+const POLICY_SELECT: &str = "SELECT id, project_id, category, roll_to_today, decay_percent_per_day,
+                                    decay_floor_percent, archive_after_days
+                             FROM rollover_policies";
+
+pub fn load_policies(conn: &Connection) -> Result<Vec<RolloverPolicy>, String> {
+    let mut stmt = conn
+        .prepare(&format!("{} ORDER BY project_id IS NOT NULL, category IS NOT NULL, category, project_id", POLICY_SELECT))
+        .map_err(|e| format!("Failed to prepare policies query: {}", e))?;
+    let policies = stmt
+        .query_map([], policy_from_row)
+        .map_err(|e| format!("Failed to get rollover policies: {}", e))?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| format!("Failed to read rollover policies: {}", e))?;
+    Ok(policies)
+}
+
+/// The policy for a task: its project's, else its category's, else the default.
+fn policy_for<'a>(policies: &'a [RolloverPolicy], project_id: Option<i64>, category: &str) -> Option<&'a RolloverPolicy> {
+    let category = category.to_lowercase();
+    policies
+        .iter()
+        .find(|p| p.project_id.is_some() && p.project_id == project_id)
+        .or_else(|| policies.iter().find(|p| p.category.as_deref() == Some(category.as_str())))
+        .or_else(|| policies.iter().find(|p| p.project_id.is_none() && p.category.is_none()))
+}
+
+// ---------- Sweep ----------
+
+/// Apply rollover policies to every overdue one-off task, inside the caller's
+/// transaction. Safe to run more than once a day.
+pub fn sweep_sync(conn: &Connection, today: NaiveDate) -> Result<RolloverSummary, String> {
+    let pauses = PauseWindows::load(conn)?;
+    if pauses.on_vacation(today) {
+        return Ok(RolloverSummary::default());
+    }
+    let _source = activity::with_source(conn, activity::ROLLOVER)?;
+    let today_str = today.format("%Y-%m-%d").to_string();
+
+    // Rescheduled into the future: no longer overdue, start counting afresh
+    // with the reward the task had before it decayed
+    conn.execute(
+        "UPDATE tasks SET overdue_since = NULL, undecayed_xp_reward = NULL,
+             base_experience_reward = CASE WHEN status = 'active' AND undecayed_xp_reward IS NOT NULL
+                                           THEN undecayed_xp_reward ELSE base_experience_reward END
+         WHERE overdue_since IS NOT NULL AND (status != 'active' OR due_date IS NULL OR date(due_date) > ?1)",
+        [&today_str],
+    )
+    .map_err(|e| format!("Failed to reset rollover tracking: {}", e))?;
+
+    // Start tracking tasks that went overdue since the last sweep
+    conn.execute(
+        "UPDATE tasks SET overdue_since = date(due_date), undecayed_xp_reward = base_experience_reward
+         WHERE user_id = 1 AND status = 'active' AND overdue_since IS NULL
+           AND recurrence_pattern IS NULL AND parent_recurring_task_id IS NULL
+           AND due_date IS NOT NULL AND date(due_date) < ?1",
+        [&today_str],
+    )
+    .map_err(|e| format!("Failed to track overdue tasks: {}", e))?;
+
+    let policies = load_policies(conn)?;
+    let mut stmt = conn
+        .prepare(&format!(
+            "SELECT t.id, t.project_id, t.category, t.due_date, MAX(t.overdue_since, COALESCE(date(t.start_date), '')),
+                    t.undecayed_xp_reward, t.base_experience_reward
+             FROM tasks t
+             WHERE t.user_id = 1 AND t.status = 'active' AND t.overdue_since IS NOT NULL AND t.deleted_at IS NULL
+               AND {}",
+            defer::NOT_DEFERRED
+        ))
+        .map_err(|e| format!("Failed to prepare overdue query: {}", e))?;
+    let overdue = stmt
+        .query_map([], |row| {
+            Ok((
+                row.get::<_, i64>(0)?,
+                row.get::<_, Option<i64>>(1)?,
+                row.get::<_, Option<String>>(2)?.unwrap_or_default(),
+                row.get::<_, String>(3)?,
+                row.get::<_, String>(4)?,
+                row.get::<_, Option<i64>>(5)?,
+                row.get::<_, Option<i64>>(6)?.unwrap_or(0),
+            ))
+        })
+        .map_err(|e| format!("Failed to find overdue tasks: {}", e))?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| format!("Failed to read overdue tasks: {}", e))?;
+
+    let mut summary = RolloverSummary::default();
+    for (task_id, project_id, category, due, since, original_xp, current_xp) in overdue {
+        let Some(policy) = policy_for(&policies, project_id, &category) else { continue };
+        let Some(since) = parse_date(&since) else { continue };
+        let days = overdue_days(since, today, &pauses);
+
+        match decide(policy, original_xp.unwrap_or(current_xp), days) {
+            Decision::Archive => {
+                conn.execute("UPDATE tasks SET status = 'archived' WHERE id = ?1", [task_id])
+                    .map_err(|e| format!("Failed to archive task {}: {}", task_id, e))?;
+                summary.archived += 1;
+            }
+            Decision::Keep { xp, roll } => {
+                if xp != current_xp {
+                    conn.execute("UPDATE tasks SET base_experience_reward = ?1 WHERE id = ?2", rusqlite::params![xp, task_id])
+                        .map_err(|e| format!("Failed to decay reward of task {}: {}", task_id, e))?;
+                    summary.decayed += 1;
+                }
+                if roll && parse_date(&due).is_some_and(|d| d < today) {
+                    conn.execute(
+                        "UPDATE tasks SET due_date = ?1 WHERE id = ?2",
+                        rusqlite::params![rolled_due_date(&due, today), task_id],
+                    )
+                    .map_err(|e| format!("Failed to roll task {}: {}", task_id, e))?;
+                    summary.rolled += 1;
+                }
+            }
+        }
+    }
+    Ok(summary)
+}
+
+// ---------- Commands ----------
+
+/// The default policy first, then category and project policies.
+#[tauri::command]
+pub async fn get_rollover_policies(db: State<'_, DbConnection>) -> Result<Vec<RolloverPolicy>, String> {
+    let conn = db.lock().await;
+    load_policies(&conn)
+}
+
+/// Create or replace the policy for a project, a category, or (with neither)
+/// the default policy.
+#[tauri::command]
+pub async fn set_rollover_policy(db: State<'_, DbConnection>, policy: RolloverPolicy) -> Result<RolloverPolicy, String> {
+    let policy = RolloverPolicy {
+        category: policy
+            .category
+            .map(|c| c.trim().to_lowercase())
+            .filter(|c| !c.is_empty()),
+        ..policy
+    };
+    validate(&policy)?;
+    let conn = db.lock().await;
+    if let Some(project_id) = policy.project_id {
+        conn.query_row("SELECT 1 FROM projects WHERE id = ?1 AND deleted_at IS NULL", [project_id], |_| Ok(()))
+            .map_err(|_| format!("Project {} not found", project_id))?;
+    }
+    conn.execute(
+        "INSERT INTO rollover_policies (project_id, category, roll_to_today, decay_percent_per_day,
+                                        decay_floor_percent, archive_after_days)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6)
+         ON CONFLICT(COALESCE(project_id, 0), COALESCE(category, '')) DO UPDATE SET
+             roll_to_today = excluded.roll_to_today, decay_percent_per_day = excluded.decay_percent_per_day,
+             decay_floor_percent = excluded.decay_floor_percent, archive_after_days = excluded.archive_after_days,
+             updated_at = CURRENT_TIMESTAMP",
+        rusqlite::params![
+            policy.project_id,
+            policy.category,
+            policy.roll_to_today,
+            policy.decay_percent_per_day,
+            policy.decay_floor_percent,
+            policy.archive_after_days,
+        ],
+    )
+    .map_err(|e| format!("Failed to save rollover policy: {}", e))?;
+    conn.query_row(
+        &format!("{} WHERE COALESCE(project_id, 0) = COALESCE(?1, 0) AND COALESCE(category, '') = COALESCE(?2, '')", POLICY_SELECT),
+        rusqlite::params![policy.project_id, policy.category],
+        policy_from_row,
+    )
+    .optional()
+    .map_err(|e| format!("Failed to load rollover policy: {}", e))?
+    .ok_or_else(|| "Rollover policy not saved".to_string())
+}
+
+/// Remove a project or category policy; its tasks fall back to the next one.
+#[tauri::command]
+pub async fn delete_rollover_policy(db: State<'_, DbConnection>, policy_id: i64) -> Result<(), String> {
+    let conn = db.lock().await;
+    let deleted = conn
+        .execute(
+            "DELETE FROM rollover_policies WHERE id = ?1 AND (project_id IS NOT NULL OR category IS NOT NULL)",
+            [policy_id],
+        )
+        .map_err(|e| format!("Failed to delete rollover policy: {}", e))?;
+    if deleted == 0 {
+        return Err("Only project and category policies can be deleted".to_string());
+    }
+    Ok(())
+}
+
+// ---------- Tests ----------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn d(s: &str) -> NaiveDate {
+        NaiveDate::parse_from_str(s, "%Y-%m-%d").unwrap()
+    }
+
+    fn policy() -> RolloverPolicy {
+        RolloverPolicy {
+            id: 0,
+            project_id: None,
+            category: None,
+            roll_to_today: false,
+            decay_percent_per_day: 0.0,
+            decay_floor_percent: 50.0,
+            archive_after_days: None,
+        }
+    }
+
+    #[test]
+    fn decays_down_to_the_floor() {
+        assert_eq!(decayed_reward(100, 10.0, 50.0, 0), 100);
+        assert_eq!(decayed_reward(100, 10.0, 50.0, 3), 70);
+        assert_eq!(decayed_reward(100, 10.0, 50.0, 9), 50);
+        assert_eq!(decayed_reward(25, 5.0, 0.0, 3), 21);
+    }
+
+    #[test]
+    fn decides_per_policy() {
+        assert_eq!(decide(&policy(), 40, 5), Decision::Keep { xp: 40, roll: false });
+        let decaying = RolloverPolicy { decay_percent_per_day: 25.0, roll_to_today: true, ..policy() };
+        assert_eq!(decide(&decaying, 40, 1), Decision::Keep { xp: 30, roll: true });
+        let archiving = RolloverPolicy { archive_after_days: Some(7), ..decaying };
+        assert_eq!(decide(&archiving, 40, 6), Decision::Keep { xp: 20, roll: true });
+        assert_eq!(decide(&archiving, 40, 7), Decision::Archive);
+
+        assert_eq!(rolled_due_date("2026-06-01", d("2026-06-05")), "2026-06-05");
+        assert_eq!(rolled_due_date("2026-06-01T17:00:00Z", d("2026-06-05")), "2026-06-05T17:00:00Z");
+    }
+
+    #[test]
+    fn project_policies_beat_category_policies_beat_the_default() {
+        let policies = vec![
+            RolloverPolicy { id: 1, ..policy() },
+            RolloverPolicy { id: 2, category: Some("work".to_string()), ..policy() },
+            RolloverPolicy { id: 3, project_id: Some(9), ..policy() },
+        ];
+        assert_eq!(policy_for(&policies, Some(9), "work").map(|p| p.id), Some(3));
+        assert_eq!(policy_for(&policies, Some(4), "Work").map(|p| p.id), Some(2));
+        assert_eq!(policy_for(&policies, None, "health").map(|p| p.id), Some(1));
+    }
+
+    #[test]
+    fn sweep_rolls_decays_and_archives() {
+        let conn = crate::database::test_conn();
+        conn.execute_batch(
+            "UPDATE rollover_policies SET roll_to_today = 1, decay_percent_per_day = 10 WHERE id = 1;
+             INSERT INTO rollover_policies (category, archive_after_days) VALUES ('errands', 3);
+             INSERT INTO tasks (id, user_id, title, status, category, due_date, base_experience_reward) VALUES
+                 (1, 1, 'Write report', 'active', 'work', '2026-06-08', 100),
+                 (2, 1, 'Return parcel', 'active', 'errands', '2026-06-08', 20),
+                 (3, 1, 'Future', 'active', 'work', '2026-06-20', 100);",
+        )
+        .unwrap();
+        let task = |id: i64| -> (String, String, i64) {
+            conn.query_row(
+                "SELECT status, due_date, base_experience_reward FROM tasks WHERE id = ?1",
+                [id],
+                |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)),
+            )
+            .unwrap()
+        };
+
+        assert_eq!(sweep_sync(&conn, d("2026-06-10")).unwrap(), RolloverSummary { rolled: 1, decayed: 1, archived: 0 });
+        assert_eq!(task(1), ("active".to_string(), "2026-06-10".to_string(), 80));
+        assert_eq!(task(2), ("active".to_string(), "2026-06-08".to_string(), 20));
+        assert_eq!(task(3).2, 100);
+        // Running again the same day changes nothing
+        assert_eq!(sweep_sync(&conn, d("2026-06-10")).unwrap(), RolloverSummary::default());
+
+        // Days keep counting from the original due date after a roll
+        assert_eq!(sweep_sync(&conn, d("2026-06-11")).unwrap(), RolloverSummary { rolled: 1, decayed: 1, archived: 1 });
+        assert_eq!(task(1), ("active".to_string(), "2026-06-11".to_string(), 70));
+        assert_eq!(task(2).0, "archived");
+
+        let sources: Vec<String> = conn
+            .prepare("SELECT DISTINCT source FROM task_events WHERE task_id = 1 AND kind = 'updated'")
+            .unwrap()
+            .query_map([], |row| row.get(0))
+            .unwrap()
+            .collect::<Result<_, _>>()
+            .unwrap();
+        assert_eq!(sources, vec![activity::ROLLOVER.to_string()]);
+
+        // Rescheduling into the future gives the decayed reward back
+        conn.execute("UPDATE tasks SET due_date = '2026-06-20' WHERE id = 1", []).unwrap();
+        assert_eq!(sweep_sync(&conn, d("2026-06-12")).unwrap(), RolloverSummary::default());
+        assert_eq!(task(1), ("active".to_string(), "2026-06-20".to_string(), 100));
+    }
+
+    #[test]
+    fn vacations_and_deferrals_hold_the_overdue_count() {
+        let conn = crate::database::test_conn();
+        conn.execute_batch(
+            "UPDATE rollover_policies SET decay_percent_per_day = 10 WHERE id = 1;
+             INSERT INTO pauses (start_date, end_date) VALUES ('2026-06-07', '2026-06-10');
+             INSERT INTO tasks (id, user_id, title, status, due_date, base_experience_reward, start_date) VALUES
+                 (1, 1, 'Write report', 'active', '2026-06-08', 100, NULL),
+                 (2, 1, 'Later', 'active', '2026-06-05', 100, '2999-01-01 00:00:00');",
+        )
+        .unwrap();
+        let reward = |id: i64| -> i64 {
+            conn.query_row("SELECT base_experience_reward FROM tasks WHERE id = ?1", [id], |row| row.get(0)).unwrap()
+        };
+
+        // Nothing moves while the vacation lasts
+        assert_eq!(sweep_sync(&conn, d("2026-06-10")).unwrap(), RolloverSummary::default());
+        // The vacation days up to the due date and after it don't count
+        assert_eq!(sweep_sync(&conn, d("2026-06-12")).unwrap(), RolloverSummary { decayed: 1, ..Default::default() });
+        assert_eq!(reward(1), 90);
+        // Deferred tasks are left alone
+        assert_eq!(reward(2), 100);
+
+        // Once it starts, days count from the start date rather than the due date
+        conn.execute("UPDATE tasks SET start_date = '2026-06-11 00:00:00' WHERE id = 2", []).unwrap();
+        sweep_sync(&conn, d("2026-06-12")).unwrap();
+        assert_eq!(reward(2), 90);
+    }
+}
@@ -0,0 +1,321 @@
+// Task scoring and "what should I do now?".
+//
+// score_task turns what we know about an open task into a score plus the
+// reasons behind it: how close (or past) the due date is, its priority, its
+// difficulty given the user's current HP, whether its estimate fits in the
+// free time left today, the streak at stake on a recurring occurrence, and an
+// approaching project deadline. get_next_actions ranks every actionable task
+// (active, not deferred, not blocked, without open subtasks) by that score.
+
+use chrono::{Local, NaiveDate, NaiveTime};
+use rusqlite::Connection;
+use serde::Serialize;
+use tauri::State;
+
+use crate::commands::{defer, economy, recurrence, trash};
+use crate::database::DbConnection;
+use crate::Task;
+
+/// Local hour the day is assumed to end when working out free time.
+pub const DAY_END_HOUR: u32 = 22;
+
+const DEFAULT_ACTIONS: usize = 5;
+const MAX_ACTIONS: usize = 50;
+
+// ---------- Types ----------
+
+/// What the scorer looks at for one task.
+#[derive(Debug, Clone, Default)]
+pub struct TaskFacts {
+    pub priority: i64,
+    pub difficulty: i64,
+    pub due: Option<NaiveDate>,
+    pub estimate_minutes: Option<i64>,
+    /// Current streak of the series, for a recurring occurrence.
+    pub streak: Option<i64>,
+    pub project: Option<(String, NaiveDate)>,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct ScoringContext {
+    pub today: NaiveDate,
+    /// current_health / max_health.
+    pub health: f64,
+    pub free_minutes: i64,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ScoreReason {
+    pub factor: &'static str,
+    pub points: f64,
+    pub detail: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct NextAction {
+    pub task: Task,
+    pub score: f64,
+    pub reasons: Vec<ScoreReason>,
+}
+
+// ---------- Scoring (pure functions, unit-tested) ----------
+
+fn days_phrase(days: i64) -> String {
+    if days == 1 { "1 day".to_string() } else { format!("{} days", days) }
+}
+
+/// Score a task; higher means do it sooner. Only factors that moved the
+/// score are listed in the reasons.
+pub fn score_task(facts: &TaskFacts, ctx: &ScoringContext) -> (f64, Vec<ScoreReason>) {
+    let mut reasons = Vec::new();
+    let mut add = |factor: &'static str, points: f64, detail: String| {
+        if points != 0.0 {
+            reasons.push(ScoreReason { factor, points, detail });
+        }
+    };
+
+    if let Some(due) = facts.due {
+        let days = (due - ctx.today).num_days();
+        let (points, detail) = match days {
+            d if d < 0 => ((40 + 5 * -d).min(60) as f64, format!("Overdue by {}", days_phrase(-d))),
+            0 => (35.0, "Due today".to_string()),
+            1 => (25.0, "Due tomorrow".to_string()),
+            d if d <= 7 => ((20 - 2 * d) as f64, format!("Due in {}", days_phrase(d))),
+            _ => (0.0, String::new()),
+        };
+        add("due_date", points, detail);
+    }
+
+    let priority = facts.priority.clamp(1, 5);
+    add("priority", ((priority - 3) * 8) as f64, format!("Priority {}", priority));
+
+    let difficulty = facts.difficulty.clamp(economy::MIN_DIFFICULTY, economy::MAX_DIFFICULTY);
+    let percent = (ctx.health * 100.0).round();
+    if ctx.health < 0.5 && difficulty >= 7 {
+        add("health", -5.0 * (difficulty - 5) as f64, format!("Hard task while HP is at {}%", percent));
+    } else if ctx.health < 0.5 && difficulty <= 3 {
+        add("health", 5.0, format!("Easy win while HP is at {}%", percent));
+    } else if ctx.health >= 0.8 && difficulty >= 7 {
+        add("health", 5.0, format!("HP is at {}%, a good time for a hard task", percent));
+    }
+
+    if let Some(estimate) = facts.estimate_minutes.filter(|&m| m > 0) {
+        if estimate > ctx.free_minutes {
+            add(
+                "free_time",
+                -15.0,
+                format!("Needs {} min, {} min free today", estimate, ctx.free_minutes.max(0)),
+            );
+        } else if estimate <= 15 {
+            add("free_time", 5.0, format!("Quick: about {} min", estimate));
+        }
+    }
+
+    if let Some(streak) = facts.streak.filter(|&s| s > 0) {
+        if facts.due.is_some_and(|due| due <= ctx.today) {
+            add("streak", (10 + 2 * streak).min(30) as f64, format!("{}-occurrence streak at risk", streak));
+        }
+    }
+
+    if let Some((name, due)) = &facts.project {
+        let days = (*due - ctx.today).num_days();
+        let points = match days {
+            d if d <= 0 => 15,
+            d if d <= 7 => 15 - d,
+            _ => 0,
+        };
+        let detail = if days < 0 {
+            format!("Project \"{}\" is past its deadline", name)
+        } else {
+            format!("Project \"{}\" is due in {}", name, days_phrase(days))
+        };
+        add("project_deadline", points as f64, detail);
+    }
+
+    let score = reasons.iter().map(|r| r.points).sum();
+    (score, reasons)
+}
+
+/// Minutes from `now` until DAY_END_HOUR, never negative.
+pub fn free_minutes_until_day_end(now: NaiveTime) -> i64 {
+    let end = NaiveTime::from_hms_opt(DAY_END_HOUR, 0, 0).expect("valid day end");
+    (end - now).num_minutes().max(0)
+}
+
+// ---------- Ranking ----------
+
+/// Every actionable task with its score, best first.
+pub fn rank_tasks(conn: &Connection, ctx: &ScoringContext) -> Result<Vec<(i64, f64, Vec<ScoreReason>)>, String> {
+    let query = format!(
+        "SELECT t.id, t.priority, t.difficulty, COALESCE(t.due_date, t.instance_date), t.estimated_time_minutes,
+                CASE WHEN t.parent_recurring_task_id IS NOT NULL THEN p.current_streak END,
+                pr.name, pr.due_date
+         FROM tasks t
+         LEFT JOIN tasks p ON p.id = t.parent_recurring_task_id
+         LEFT JOIN projects pr ON pr.id = t.project_id AND pr.deleted_at IS NULL
+         WHERE t.user_id = 1 AND t.status = 'active' AND {} AND {}
+           AND NOT (t.recurrence_pattern IS NOT NULL AND t.parent_recurring_task_id IS NULL)
+           AND NOT EXISTS (SELECT 1 FROM tasks c WHERE c.parent_task_id = t.id AND c.status = 'active')
+           AND NOT EXISTS (SELECT 1 FROM task_dependencies d JOIN tasks b ON b.id = d.blocked_by_task_id
+                           WHERE d.task_id = t.id AND b.status = 'active')",
+        defer::NOT_DEFERRED,
+        trash::NOT_TRASHED
+    );
+    let mut stmt = conn.prepare(&query)
+        .map_err(|e| format!("Failed to prepare scoring query: {}", e))?;
+    let rows = stmt
+        .query_map([], |row| {
+            let project = match (row.get::<_, Option<String>>(6)?, row.get::<_, Option<String>>(7)?) {
+                (Some(name), Some(due)) => recurrence::parse_date(&due).map(|due| (name, due)),
+                _ => None,
+            };
+            Ok((
+                row.get::<_, i64>(0)?,
+                TaskFacts {
+                    priority: row.get::<_, Option<i64>>(1)?.unwrap_or(3),
+                    difficulty: row.get::<_, Option<i64>>(2)?.unwrap_or(5),
+                    due: row.get::<_, Option<String>>(3)?.as_deref().and_then(recurrence::parse_date),
+                    estimate_minutes: row.get(4)?,
+                    streak: row.get(5)?,
+                    project,
+                },
+            ))
+        })
+        .map_err(|e| format!("Failed to get tasks to score: {}", e))?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| format!("Failed to read tasks to score: {}", e))?;
+
+    let mut ranked: Vec<(i64, f64, Vec<ScoreReason>)> = rows
+        .into_iter()
+        .map(|(id, facts)| {
+            let (score, reasons) = score_task(&facts, ctx);
+            (id, score, reasons)
+        })
+        .collect();
+    // Ties go to the older task
+    ranked.sort_by(|a, b| b.1.total_cmp(&a.1).then(a.0.cmp(&b.0)));
+    Ok(ranked)
+}
+
+fn current_context(conn: &Connection, free_minutes: Option<i64>) -> Result<ScoringContext, String> {
+    let user = crate::fetch_user_sync(conn)?;
+    // Due dates and free time both refer to the user's local day
+    let now = Local::now().naive_local();
+    Ok(ScoringContext {
+        today: now.date(),
+        health: if user.max_health > 0 { user.current_health as f64 / user.max_health as f64 } else { 1.0 },
+        free_minutes: free_minutes.unwrap_or_else(|| free_minutes_until_day_end(now.time())),
+    })
+}
+
+// ---------- Commands ----------
+
+/// The `n` tasks most worth doing now, with the reasons for each score.
+/// `free_minutes` overrides the time assumed free for the rest of today.
+#[tauri::command]
+pub async fn get_next_actions(
+    db: State<'_, DbConnection>,
+    n: Option<usize>,
+    free_minutes: Option<i64>,
+) -> Result<Vec<NextAction>, String> {
+    let conn = db.lock().await;
+    let ctx = current_context(&conn, free_minutes)?;
+    rank_tasks(&conn, &ctx)?
+        .into_iter()
+        .take(n.unwrap_or(DEFAULT_ACTIONS).clamp(1, MAX_ACTIONS))
+        .map(|(id, score, reasons)| {
+            Ok(NextAction { task: crate::fetch_task_sync(&conn, id)?, score, reasons })
+        })
+        .collect()
+}
+
+// ---------- Tests ----------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(value: &str) -> NaiveDate {
+        NaiveDate::parse_from_str(value, "%Y-%m-%d").unwrap()
+    }
+
+    fn ctx() -> ScoringContext {
+        ScoringContext { today: date("2026-06-10"), health: 0.7, free_minutes: 120 }
+    }
+
+    fn factors(facts: &TaskFacts, ctx: &ScoringContext) -> Vec<(&'static str, f64)> {
+        score_task(facts, ctx).1.iter().map(|r| (r.factor, r.points)).collect()
+    }
+
+    #[test]
+    fn urgency_grows_towards_and_past_the_due_date() {
+        let score = |due: &str| {
+            let facts = TaskFacts { priority: 3, difficulty: 3, due: Some(date(due)), ..Default::default() };
+            score_task(&facts, &ctx()).0
+        };
+        assert_eq!(score("2026-06-30"), 0.0);
+        assert_eq!(score("2026-06-15"), 10.0);
+        assert_eq!(score("2026-06-11"), 25.0);
+        assert_eq!(score("2026-06-10"), 35.0);
+        assert_eq!(score("2026-06-08"), 50.0);
+        assert_eq!(score("2026-05-01"), 60.0);
+    }
+
+    #[test]
+    fn scores_health_free_time_streaks_and_projects() {
+        let hard = TaskFacts { priority: 5, difficulty: 9, estimate_minutes: Some(180), ..Default::default() };
+        assert_eq!(factors(&hard, &ctx()), vec![("priority", 16.0), ("free_time", -15.0)]);
+        let low_hp = ScoringContext { health: 0.3, ..ctx() };
+        assert_eq!(factors(&hard, &low_hp), vec![("priority", 16.0), ("health", -20.0), ("free_time", -15.0)]);
+        let full_hp = ScoringContext { health: 0.9, ..ctx() };
+        assert_eq!(factors(&hard, &full_hp), vec![("priority", 16.0), ("health", 5.0), ("free_time", -15.0)]);
+
+        // The default difficulty sits in the middle of the 1..=10 scale.
+        let medium = TaskFacts { priority: 3, difficulty: 5, ..Default::default() };
+        assert_eq!(factors(&medium, &low_hp), vec![]);
+        assert_eq!(factors(&medium, &full_hp), vec![]);
+
+        let quick = TaskFacts { priority: 3, difficulty: 1, estimate_minutes: Some(10), ..Default::default() };
+        assert_eq!(factors(&quick, &low_hp), vec![("health", 5.0), ("free_time", 5.0)]);
+
+        let occurrence = TaskFacts { priority: 3, difficulty: 3, due: Some(date("2026-06-10")), streak: Some(12), ..Default::default() };
+        assert_eq!(factors(&occurrence, &ctx()), vec![("due_date", 35.0), ("streak", 30.0)]);
+
+        let in_project = TaskFacts {
+            priority: 2,
+            difficulty: 3,
+            project: Some(("Launch".to_string(), date("2026-06-13"))),
+            ..Default::default()
+        };
+        let (score, reasons) = score_task(&in_project, &ctx());
+        assert_eq!(score, 4.0);
+        assert_eq!(reasons[1].detail, "Project \"Launch\" is due in 3 days");
+    }
+
+    #[test]
+    fn free_time_runs_until_the_end_of_the_day() {
+        assert_eq!(free_minutes_until_day_end(NaiveTime::from_hms_opt(20, 30, 0).unwrap()), 90);
+        assert_eq!(free_minutes_until_day_end(NaiveTime::from_hms_opt(23, 0, 0).unwrap()), 0);
+    }
+
+    #[test]
+    fn ranks_only_actionable_tasks() {
+        let conn = crate::database::test_conn();
+        conn.execute_batch(
+            "INSERT INTO tasks (id, user_id, title, status, priority, due_date) VALUES
+                 (1, 1, 'Someday', 'active', 3, NULL),
+                 (2, 1, 'Pay rent', 'active', 4, '2026-06-10'),
+                 (3, 1, 'Blocked', 'active', 5, '2026-06-09'),
+                 (4, 1, 'Deferred', 'active', 5, '2026-06-09'),
+                 (5, 1, 'Parent', 'active', 5, '2026-06-09'),
+                 (6, 1, 'Child', 'active', 3, NULL),
+                 (7, 1, 'Done', 'completed', 5, '2026-06-09');
+             UPDATE tasks SET start_date = '2999-01-01 00:00:00' WHERE id = 4;
+             UPDATE tasks SET parent_task_id = 5 WHERE id = 6;
+             INSERT INTO task_dependencies (task_id, blocked_by_task_id) VALUES (3, 1);",
+        )
+        .unwrap();
+        let ids: Vec<i64> = rank_tasks(&conn, &ctx()).unwrap().into_iter().map(|(id, _, _)| id).collect();
+        assert_eq!(ids, vec![2, 1, 6]);
+    }
+}
@@ -0,0 +1,311 @@
+// Full-text task search.
+//
+// tasks_fts (migration 023) indexes title, description, category, project name
+// and import source of every task, kept in sync by triggers. search_tasks takes
+// a query like `taxes category:work status:completed "annual report"`:
+//
+//   word / "a phrase"   matched anywhere, words as prefixes (`tax` finds "taxes")
+//   title: description: category: project: source:   match within that field
+//   status: priority: difficulty: type:   exact filters on the task itself
+//
+// Results are ranked with bm25 (title matches weigh most) and come with the
+// title and a description snippet highlighted.
+
+use rusqlite::types::Value;
+use rusqlite::Connection;
+use serde::Serialize;
+use tauri::State;
+
+use crate::commands::trash;
+use crate::database::DbConnection;
+use crate::Task;
+
+/// Marks wrapped around matched text in highlights and snippets.
+pub const HIGHLIGHT_START: &str = "<mark>";
+pub const HIGHLIGHT_END: &str = "</mark>";
+
+const DEFAULT_LIMIT: i64 = 50;
+
+/// FTS columns that can be targeted with `field:value`.
+const TEXT_FIELDS: [&str; 5] = ["title", "description", "category", "project", "source"];
+
+// ---------- Types ----------
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ParsedQuery {
+    /// FTS5 MATCH expression; None when the query only has task filters.
+    pub match_expr: Option<String>,
+    pub status: Option<String>,
+    pub priority: Option<i64>,
+    pub difficulty: Option<i64>,
+    pub task_type: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct TaskSearchResult {
+    pub task: Task,
+    /// Title with matches wrapped in <mark>.
+    pub title_highlight: String,
+    /// Short excerpt of the description around the matches, if any matched.
+    pub snippet: Option<String>,
+    /// bm25 score; lower is a better match.
+    pub rank: f64,
+}
+
+// ---------- Query parsing (pure, unit-tested) ----------
+
+/// Split a query into words and quoted phrases.
+fn tokenize(query: &str) -> Vec<String> {
+    let mut tokens = Vec::new();
+    let mut current = String::new();
+    let mut in_quotes = false;
+    for c in query.chars() {
+        match c {
+            '"' => {
+                in_quotes = !in_quotes;
+                current.push(c);
+            }
+            c if c.is_whitespace() && !in_quotes => {
+                if !current.is_empty() {
+                    tokens.push(std::mem::take(&mut current));
+                }
+            }
+            c => current.push(c),
+        }
+    }
+    if !current.is_empty() {
+        tokens.push(current);
+    }
+    tokens
+}
+
+/// A phrase for the MATCH expression. Bare words become prefix queries; quoted
+/// phrases match exactly.
+fn fts_phrase(value: &str) -> Option<String> {
+    let quoted = value.len() >= 2 && value.starts_with('"') && value.ends_with('"');
+    let text = value.trim_matches('"').replace('"', "");
+    if text.trim().is_empty() {
+        return None;
+    }
+    Some(if quoted {
+        format!("\"{}\"", text)
+    } else {
+        format!("\"{}\"*", text)
+    })
+}
+
+pub fn parse_query(query: &str) -> Result<ParsedQuery, String> {
+    let mut parsed = ParsedQuery::default();
+    let mut phrases = Vec::new();
+
+    for token in tokenize(query) {
+        let field = token
+            .split_once(':')
+            .filter(|(field, value)| !field.starts_with('"') && !value.is_empty())
+            .map(|(field, value)| (field.to_lowercase(), value.to_string()));
+
+        match field {
+            Some((field, value)) if TEXT_FIELDS.contains(&field.as_str()) => {
+                if let Some(phrase) = fts_phrase(&value) {
+                    phrases.push(format!("{} : {}", field, phrase));
+                }
+            }
+            Some((field, value)) if field == "status" => parsed.status = Some(value.to_lowercase()),
+            Some((field, value)) if field == "type" => parsed.task_type = Some(value.to_lowercase()),
+            Some((field, value)) if field == "priority" || field == "difficulty" => {
+                let number = value
+                    .parse::<i64>()
+                    .map_err(|_| format!("{} must be a number, got \"{}\"", field, value))?;
+                if field == "priority" {
+                    parsed.priority = Some(number);
+                } else {
+                    parsed.difficulty = Some(number);
+                }
+            }
+            // Anything else (including unknown `foo:bar`) is searched as text
+            _ => {
+                for part in token.split(':') {
+                    if let Some(phrase) = fts_phrase(part) {
+                        phrases.push(phrase);
+                    }
+                }
+            }
+        }
+    }
+
+    if !phrases.is_empty() {
+        parsed.match_expr = Some(phrases.join(" AND "));
+    }
+    Ok(parsed)
+}
+
+// ---------- Search ----------
+
+/// Add a parameter and return its placeholder.
+fn bind(params: &mut Vec<Value>, value: Value) -> String {
+    params.push(value);
+    format!("?{}", params.len())
+}
+
+pub fn search_tasks_sync(conn: &Connection, query: &str, limit: i64) -> Result<Vec<TaskSearchResult>, String> {
+    let parsed = parse_query(query)?;
+
+    let mut conditions = vec!["t.user_id = 1".to_string(), trash::NOT_TRASHED.to_string()];
+    let mut params: Vec<Value> = Vec::new();
+
+    if let Some(match_expr) = &parsed.match_expr {
+        let p = bind(&mut params, Value::Text(match_expr.clone()));
+        conditions.push(format!("tasks_fts MATCH {}", p));
+    }
+    match &parsed.status {
+        Some(status) => {
+            let p = bind(&mut params, Value::Text(status.clone()));
+            conditions.push(format!("t.status = {}", p));
+        }
+        None => conditions.push("t.status != 'archived'".to_string()),
+    }
+    if let Some(priority) = parsed.priority {
+        let p = bind(&mut params, Value::Integer(priority));
+        conditions.push(format!("t.priority = {}", p));
+    }
+    if let Some(difficulty) = parsed.difficulty {
+        let p = bind(&mut params, Value::Integer(difficulty));
+        conditions.push(format!("t.difficulty = {}", p));
+    }
+    if let Some(task_type) = &parsed.task_type {
+        let p = bind(&mut params, Value::Text(task_type.clone()));
+        conditions.push(format!("COALESCE(t.task_type, 'standard') = {}", p));
+    }
+    let limit_param = bind(&mut params, Value::Integer(limit));
+
+    // Without text to match, list the filtered tasks in the usual order
+    let (select, order) = if parsed.match_expr.is_some() {
+        (
+            format!(
+                "SELECT t.id,
+                        highlight(tasks_fts, 0, '{start}', '{end}'),
+                        CASE WHEN highlight(tasks_fts, 1, '{start}', '{end}') LIKE '%{start}%'
+                             THEN snippet(tasks_fts, 1, '{start}', '{end}', '…', 12) END,
+                        bm25(tasks_fts, 10.0, 3.0, 2.0, 2.0, 1.0)",
+                start = HIGHLIGHT_START,
+                end = HIGHLIGHT_END,
+            ),
+            "ORDER BY 4",
+        )
+    } else {
+        (
+            "SELECT t.id, t.title, NULL, 0.0".to_string(),
+            "ORDER BY t.priority DESC, t.due_date ASC",
+        )
+    };
+    let sql = format!(
+        "{} FROM tasks_fts JOIN tasks t ON t.id = tasks_fts.rowid WHERE {} {} LIMIT {}",
+        select,
+        conditions.join(" AND "),
+        order,
+        limit_param,
+    );
+
+    let mut stmt = conn.prepare(&sql)
+        .map_err(|e| format!("Failed to prepare search: {}", e))?;
+    let hits = stmt
+        .query_map(rusqlite::params_from_iter(params), |row| {
+            Ok((
+                row.get::<_, i64>(0)?,
+                row.get::<_, String>(1)?,
+                row.get::<_, Option<String>>(2)?,
+                row.get::<_, f64>(3)?,
+            ))
+        })
+        .map_err(|e| format!("Search failed: {}", e))?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| format!("Search failed: {}", e))?;
+
+    hits.into_iter()
+        .map(|(id, title_highlight, snippet, rank)| {
+            Ok(TaskSearchResult {
+                task: crate::fetch_task_sync(conn, id)?,
+                title_highlight,
+                snippet,
+                rank,
+            })
+        })
+        .collect()
+}
+
+// ---------- Commands ----------
+
+#[tauri::command]
+pub async fn search_tasks(
+    db: State<'_, DbConnection>,
+    query: String,
+    limit: Option<i64>,
+) -> Result<Vec<TaskSearchResult>, String> {
+    let conn = db.lock().await;
+    search_tasks_sync(&conn, &query, limit.unwrap_or(DEFAULT_LIMIT).clamp(1, 500))
+}
+
+// ---------- Tests ----------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::database::test_conn;
+
+    fn ids(results: &[TaskSearchResult]) -> Vec<i64> {
+        results.iter().map(|r| r.task.id).collect()
+    }
+
+    #[test]
+    fn parses_terms_fields_and_filters() {
+        let parsed = parse_query("tax category:work status:Completed \"annual report\" priority:5").unwrap();
+        assert_eq!(
+            parsed.match_expr.as_deref(),
+            Some("\"tax\"* AND category : \"work\"* AND \"annual report\"")
+        );
+        assert_eq!(parsed.status.as_deref(), Some("completed"));
+        assert_eq!(parsed.priority, Some(5));
+
+        // Unknown fields are plain text; quotes can't break out of a phrase
+        let parsed = parse_query("re:invoice \"").unwrap();
+        assert_eq!(parsed.match_expr.as_deref(), Some("\"re\"* AND \"invoice\"*"));
+        assert!(parse_query("priority:high").is_err());
+        assert_eq!(parse_query("status:active").unwrap().match_expr, None);
+    }
+
+    #[test]
+    fn index_follows_tasks_and_projects() {
+        let conn = test_conn();
+        conn.execute_batch(
+            "INSERT INTO projects (id, user_id, name) VALUES (100, 1, 'Household');
+             INSERT INTO tasks (id, user_id, title, description, category, status, project_id)
+             VALUES (1, 1, 'File taxes', 'Gather receipts for the accountant', 'work', 'active', 100);
+             INSERT INTO tasks (id, user_id, title, category, status, github_repo)
+             VALUES (2, 1, 'Fix login bug', 'work', 'completed', 'acme/web');
+             INSERT INTO tasks (id, user_id, title, category, status)
+             VALUES (3, 1, 'Taxi to airport', 'personal', 'active');",
+        )
+        .unwrap();
+
+        // Prefix matching, ranked, highlighted
+        let results = search_tasks_sync(&conn, "tax", 10).unwrap();
+        assert_eq!(ids(&results).len(), 2);
+        assert!(results.iter().any(|r| r.title_highlight == "File <mark>taxes</mark>"));
+
+        let results = search_tasks_sync(&conn, "receipts", 10).unwrap();
+        assert_eq!(ids(&results), vec![1]);
+        assert!(results[0].snippet.as_deref().unwrap().contains("<mark>receipts</mark>"));
+
+        assert_eq!(ids(&search_tasks_sync(&conn, "source:acme", 10).unwrap()), vec![2]);
+        assert_eq!(ids(&search_tasks_sync(&conn, "category:work status:completed", 10).unwrap()), vec![2]);
+        assert_eq!(ids(&search_tasks_sync(&conn, "project:household", 10).unwrap()), vec![1]);
+
+        // Triggers keep the index current
+        conn.execute("UPDATE tasks SET title = 'Book flight' WHERE id = 3", []).unwrap();
+        assert_eq!(ids(&search_tasks_sync(&conn, "tax", 10).unwrap()), vec![1]);
+        conn.execute("UPDATE projects SET name = 'Home admin' WHERE id = 100", []).unwrap();
+        assert_eq!(ids(&search_tasks_sync(&conn, "project:admin", 10).unwrap()), vec![1]);
+        conn.execute("DELETE FROM tasks WHERE id = 1", []).unwrap();
+        assert!(search_tasks_sync(&conn, "receipts", 10).unwrap().is_empty());
+    }
+}
@@ -0,0 +1,460 @@
+// Manual time entries.
+//
+// Timers are not the only source of truth: a forgotten timer has to be fixed by
+// hand. These commands add a manual session, move a session's start and end,
+// split a session (optionally handing the second part to another task) and merge
+// two sessions of the same task. Finished sessions may never overlap each other
+// or the running timer.
+//
+// Durations are written directly (end - start - paused_seconds) rather than via
+// the calculate_session_duration trigger, which only fires when a timer stops,
+// and each affected task's total_time_spent_seconds is recomputed from its
+// sessions so TimeStats and task totals stay in step.
+
+use chrono::{DateTime, NaiveDateTime, Utc};
+use rusqlite::{Connection, OptionalExtension};
+use tauri::State;
+
+use crate::database::DbConnection;
+use crate::TimeSession;
+
+// ---------- Time parsing (pure functions, unit-tested) ----------
+
+/// Session times are stored as RFC 3339; older rows may use SQLite's
+/// "YYYY-MM-DD HH:MM:SS" (UTC).
+pub fn parse_time(value: &str) -> Option<DateTime<Utc>> {
+    DateTime::parse_from_rfc3339(value)
+        .map(|t| t.with_timezone(&Utc))
+        .ok()
+        .or_else(|| NaiveDateTime::parse_from_str(value, "%Y-%m-%d %H:%M:%S").ok().map(|t| t.and_utc()))
+}
+
+/// Split `paused` seconds between two parts of a session in proportion to
+/// their lengths.
+pub fn split_paused(paused: i64, first_secs: i64, second_secs: i64) -> (i64, i64) {
+    let total = first_secs + second_secs;
+    if total <= 0 {
+        return (paused, 0);
+    }
+    let first = (paused as f64 * first_secs as f64 / total as f64).round() as i64;
+    (first, paused - first)
+}
+
+fn parse_input(value: &str, label: &str) -> Result<DateTime<Utc>, String> {
+    parse_time(value).ok_or_else(|| format!("Invalid {} time: {}", label, value))
+}
+
+// ---------- Persistence ----------
+
+/// A finished session, as this module edits it.
+struct SessionRow {
+    id: i64,
+    task_id: i64,
+    start: DateTime<Utc>,
+    end: DateTime<Utc>,
+    paused_seconds: i64,
+    session_type: String,
+    notes: Option<String>,
+}
+
+fn fetch_session(conn: &Connection, session_id: i64) -> Result<TimeSession, String> {
+    conn.query_row(
+        "SELECT id, task_id, user_id, start_time, end_time, duration_seconds, session_type, is_completed, notes, tags, created_at
+         FROM time_sessions WHERE id = ?1",
+        [session_id],
+        |row| {
+            Ok(TimeSession {
+                id: row.get(0)?,
+                task_id: row.get(1)?,
+                user_id: row.get(2)?,
+                start_time: row.get(3)?,
+                end_time: row.get(4)?,
+                duration_seconds: row.get(5)?,
+                session_type: row.get(6)?,
+                is_completed: row.get(7)?,
+                notes: row.get(8)?,
+                tags: row.get(9)?,
+                created_at: row.get(10)?,
+            })
+        },
+    )
+    .map_err(|e| format!("Failed to retrieve session: {}", e))
+}
+
+fn load_finished(conn: &Connection, session_id: i64) -> Result<SessionRow, String> {
+    let (task_id, start, end, paused_seconds, session_type, notes) = conn
+        .query_row(
+            "SELECT task_id, start_time, end_time, paused_seconds, session_type, notes
+             FROM time_sessions WHERE id = ?1 AND user_id = 1",
+            [session_id],
+            |row| {
+                Ok((
+                    row.get::<_, i64>(0)?,
+                    row.get::<_, String>(1)?,
+                    row.get::<_, Option<String>>(2)?,
+                    row.get::<_, i64>(3)?,
+                    row.get::<_, Option<String>>(4)?,
+                    row.get::<_, Option<String>>(5)?,
+                ))
+            },
+        )
+        .optional()
+        .map_err(|e| format!("Failed to get session: {}", e))?
+        .ok_or_else(|| format!("Session {} not found", session_id))?;
+    let end = end.ok_or_else(|| "Stop the timer before editing its session".to_string())?;
+    Ok(SessionRow {
+        id: session_id,
+        task_id,
+        start: parse_input(&start, "start")?,
+        end: parse_input(&end, "end")?,
+        paused_seconds,
+        session_type: session_type.unwrap_or_else(|| "focus".to_string()),
+        notes,
+    })
+}
+
+fn ensure_task(conn: &Connection, task_id: i64) -> Result<(), String> {
+    let trashed: bool = conn
+        .query_row("SELECT deleted_at IS NOT NULL FROM tasks WHERE id = ?1 AND user_id = 1", [task_id], |row| row.get(0))
+        .map_err(|_| format!("Task {} not found", task_id))?;
+    if trashed {
+        return Err(format!("Task {} is in the trash", task_id));
+    }
+    Ok(())
+}
+
+/// Reject a span that is empty, in the future, or overlaps another session
+/// (the running timer counts up to now). `except` are sessions being replaced.
+fn validate_span(conn: &Connection, start: DateTime<Utc>, end: DateTime<Utc>, except: &[i64], now: DateTime<Utc>) -> Result<(), String> {
+    if end <= start {
+        return Err("A session must end after it starts".to_string());
+    }
+    if end > now {
+        return Err("A session can't end in the future".to_string());
+    }
+    let except = except.iter().map(|id| id.to_string()).collect::<Vec<_>>().join(",");
+    let overlapping: Option<(i64, String)> = conn
+        .query_row(
+            &format!(
+                "SELECT s.id, t.title FROM time_sessions s LEFT JOIN tasks t ON t.id = s.task_id
+                 WHERE s.user_id = 1 AND s.id NOT IN ({})
+                   AND julianday(s.start_time) < julianday(?2)
+                   AND julianday(COALESCE(s.end_time, ?3)) > julianday(?1)
+                 ORDER BY julianday(s.start_time) LIMIT 1",
+                except
+            ),
+            rusqlite::params![start.to_rfc3339(), end.to_rfc3339(), now.to_rfc3339()],
+            |row| Ok((row.get(0)?, row.get::<_, Option<String>>(1)?.unwrap_or_default())),
+        )
+        .optional()
+        .map_err(|e| format!("Failed to check for overlaps: {}", e))?;
+    match overlapping {
+        Some((id, title)) => Err(format!("This overlaps session {} on \"{}\"", id, title)),
+        None => Ok(()),
+    }
+}
+
+fn duration_secs(start: DateTime<Utc>, end: DateTime<Utc>, paused: i64) -> i64 {
+    ((end - start).num_seconds() - paused).max(0)
+}
+
+fn write_span(conn: &Connection, session_id: i64, task_id: i64, start: DateTime<Utc>, end: DateTime<Utc>, paused: i64) -> Result<(), String> {
+    conn.execute(
+        "UPDATE time_sessions SET task_id = ?2, start_time = ?3, end_time = ?4, paused_seconds = ?5, duration_seconds = ?6
+         WHERE id = ?1",
+        rusqlite::params![session_id, task_id, start.to_rfc3339(), end.to_rfc3339(), paused, duration_secs(start, end, paused)],
+    )
+    .map_err(|e| format!("Failed to update session: {}", e))?;
+    Ok(())
+}
+
+fn insert_span(
+    conn: &Connection,
+    task_id: i64,
+    start: DateTime<Utc>,
+    end: DateTime<Utc>,
+    paused: i64,
+    session_type: &str,
+    notes: Option<&str>,
+) -> Result<i64, String> {
+    conn.execute(
+        "INSERT INTO time_sessions (task_id, user_id, start_time, end_time, duration_seconds, paused_seconds,
+                                    session_type, is_completed, notes)
+         VALUES (?1, 1, ?2, ?3, ?4, ?5, ?6, 1, ?7)",
+        rusqlite::params![
+            task_id,
+            start.to_rfc3339(),
+            end.to_rfc3339(),
+            duration_secs(start, end, paused),
+            paused,
+            session_type,
+            notes,
+        ],
+    )
+    .map_err(|e| format!("Failed to create session: {}", e))?;
+    Ok(conn.last_insert_rowid())
+}
+
+/// Rebuild a task's time total and last-worked time from its sessions.
+pub fn recompute_task_time(conn: &Connection, task_id: i64) -> Result<(), String> {
+    conn.execute(
+        "UPDATE tasks SET
+             total_time_spent_seconds = (SELECT COALESCE(SUM(duration_seconds), 0) FROM time_sessions
+                                         WHERE task_id = ?1 AND end_time IS NOT NULL AND session_type != 'break'),
+             last_worked_at = (SELECT end_time FROM time_sessions
+                               WHERE task_id = ?1 AND end_time IS NOT NULL AND session_type != 'break'
+                               ORDER BY julianday(end_time) DESC LIMIT 1)
+         WHERE id = ?1",
+        [task_id],
+    )
+    .map_err(|e| format!("Failed to update task time: {}", e))?;
+    Ok(())
+}
+
+// ---------- Edits ----------
+
+pub fn add_session_sync(
+    conn: &Connection,
+    now: DateTime<Utc>,
+    task_id: i64,
+    start_time: &str,
+    end_time: &str,
+    notes: Option<&str>,
+) -> Result<TimeSession, String> {
+    ensure_task(conn, task_id)?;
+    let (start, end) = (parse_input(start_time, "start")?, parse_input(end_time, "end")?);
+    validate_span(conn, start, end, &[], now)?;
+    let session_id = insert_span(conn, task_id, start, end, 0, "manual", notes)?;
+    recompute_task_time(conn, task_id)?;
+    fetch_session(conn, session_id)
+}
+
+pub fn edit_session_sync(
+    conn: &Connection,
+    now: DateTime<Utc>,
+    session_id: i64,
+    start_time: Option<&str>,
+    end_time: Option<&str>,
+    notes: Option<&str>,
+) -> Result<TimeSession, String> {
+    let session = load_finished(conn, session_id)?;
+    let start = start_time.map(|s| parse_input(s, "start")).transpose()?.unwrap_or(session.start);
+    let end = end_time.map(|s| parse_input(s, "end")).transpose()?.unwrap_or(session.end);
+    validate_span(conn, start, end, &[session_id], now)?;
+
+    // Paused time can't exceed the new span
+    let paused = session.paused_seconds.min((end - start).num_seconds());
+    write_span(conn, session_id, session.task_id, start, end, paused)?;
+    if let Some(notes) = notes {
+        conn.execute("UPDATE time_sessions SET notes = ?1 WHERE id = ?2", rusqlite::params![notes, session_id])
+            .map_err(|e| format!("Failed to update notes: {}", e))?;
+    }
+    recompute_task_time(conn, session.task_id)?;
+    fetch_session(conn, session_id)
+}
+
+/// Split a session at `at`; the second part goes to `task_id` if given.
+pub fn split_session_sync(conn: &Connection, session_id: i64, at: &str, task_id: Option<i64>) -> Result<Vec<TimeSession>, String> {
+    let session = load_finished(conn, session_id)?;
+    let at = parse_input(at, "split")?;
+    if at <= session.start || at >= session.end {
+        return Err("Split point must fall inside the session".to_string());
+    }
+    let second_task = task_id.unwrap_or(session.task_id);
+    ensure_task(conn, second_task)?;
+
+    let (first_paused, second_paused) = split_paused(
+        session.paused_seconds,
+        (at - session.start).num_seconds(),
+        (session.end - at).num_seconds(),
+    );
+    write_span(conn, session_id, session.task_id, session.start, at, first_paused)?;
+    let second_id = insert_span(conn, second_task, at, session.end, second_paused, &session.session_type, session.notes.as_deref())?;
+
+    recompute_task_time(conn, session.task_id)?;
+    if second_task != session.task_id {
+        recompute_task_time(conn, second_task)?;
+    }
+    Ok(vec![fetch_session(conn, session_id)?, fetch_session(conn, second_id)?])
+}
+
+/// Merge two sessions of the same task into the earlier one. Any gap between
+/// them is kept as paused time, so the merged session counts exactly what the
+/// two did.
+pub fn merge_sessions_sync(conn: &Connection, now: DateTime<Utc>, first_id: i64, second_id: i64) -> Result<TimeSession, String> {
+    if first_id == second_id {
+        return Err("Pick two different sessions to merge".to_string());
+    }
+    let (a, b) = (load_finished(conn, first_id)?, load_finished(conn, second_id)?);
+    let (first, second) = if a.start <= b.start { (a, b) } else { (b, a) };
+    if first.task_id != second.task_id {
+        return Err("Only sessions of the same task can be merged".to_string());
+    }
+    // Nothing may sit between them
+    validate_span(conn, first.start, second.end, &[first.id, second.id], now)?;
+
+    let gap = (second.start - first.end).num_seconds().max(0);
+    let paused = first.paused_seconds + second.paused_seconds + gap;
+    write_span(conn, first.id, first.task_id, first.start, second.end, paused)?;
+    let notes = match (first.notes, second.notes) {
+        (Some(a), Some(b)) if !a.is_empty() && !b.is_empty() => Some(format!("{}\n{}", a, b)),
+        (a, b) => a.filter(|n| !n.is_empty()).or(b),
+    };
+    conn.execute("UPDATE time_sessions SET notes = ?1 WHERE id = ?2", rusqlite::params![notes, first.id])
+        .map_err(|e| format!("Failed to update notes: {}", e))?;
+    conn.execute("DELETE FROM time_sessions WHERE id = ?1", [second.id])
+        .map_err(|e| format!("Failed to delete merged session: {}", e))?;
+
+    recompute_task_time(conn, first.task_id)?;
+    fetch_session(conn, first.id)
+}
+
+// ---------- Commands ----------
+
+async fn in_transaction<T>(db: &DbConnection, edit: impl FnOnce(&Connection) -> Result<T, String>) -> Result<T, String> {
+    let conn = db.lock().await;
+    let tx = conn.unchecked_transaction()
+        .map_err(|e| format!("Failed to begin transaction: {}", e))?;
+    let result = edit(&tx)?;
+    tx.commit()
+        .map_err(|e| format!("Failed to commit: {}", e))?;
+    Ok(result)
+}
+
+/// Log time worked without a timer.
+#[tauri::command]
+pub async fn add_time_session(
+    db: State<'_, DbConnection>,
+    task_id: i64,
+    start_time: String,
+    end_time: String,
+    notes: Option<String>,
+) -> Result<TimeSession, String> {
+    in_transaction(&db, |conn| add_session_sync(conn, Utc::now(), task_id, &start_time, &end_time, notes.as_deref())).await
+}
+
+#[tauri::command]
+pub async fn update_time_session(
+    db: State<'_, DbConnection>,
+    session_id: i64,
+    start_time: Option<String>,
+    end_time: Option<String>,
+    notes: Option<String>,
+) -> Result<TimeSession, String> {
+    in_transaction(&db, |conn| {
+        edit_session_sync(conn, Utc::now(), session_id, start_time.as_deref(), end_time.as_deref(), notes.as_deref())
+    })
+    .await
+}
+
+#[tauri::command]
+pub async fn split_time_session(
+    db: State<'_, DbConnection>,
+    session_id: i64,
+    at: String,
+    task_id: Option<i64>,
+) -> Result<Vec<TimeSession>, String> {
+    in_transaction(&db, |conn| split_session_sync(conn, session_id, &at, task_id)).await
+}
+
+#[tauri::command]
+pub async fn merge_time_sessions(
+    db: State<'_, DbConnection>,
+    first_session_id: i64,
+    second_session_id: i64,
+) -> Result<TimeSession, String> {
+    in_transaction(&db, |conn| merge_sessions_sync(conn, Utc::now(), first_session_id, second_session_id)).await
+}
+
+// ---------- Tests ----------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Duration;
+
+    fn at(minutes: i64) -> String {
+        (parse_time("2026-05-04T09:00:00+00:00").unwrap() + Duration::minutes(minutes)).to_rfc3339()
+    }
+
+    fn now() -> DateTime<Utc> {
+        parse_time("2026-05-04T18:00:00+00:00").unwrap()
+    }
+
+    fn test_conn() -> Connection {
+        let conn = crate::database::test_conn();
+        conn.execute_batch(
+            "INSERT INTO tasks (id, user_id, title, status) VALUES (1, 1, 'Write', 'active'), (2, 1, 'Review', 'active');",
+        )
+        .unwrap();
+        conn
+    }
+
+    fn task_total(conn: &Connection, task_id: i64) -> i64 {
+        conn.query_row("SELECT total_time_spent_seconds FROM tasks WHERE id = ?1", [task_id], |row| row.get(0))
+            .unwrap()
+    }
+
+    #[test]
+    fn parses_both_stored_time_formats() {
+        assert_eq!(parse_time("2026-05-04 09:30:00"), parse_time("2026-05-04T09:30:00Z"));
+        assert_eq!(parse_time("2026-05-04T11:30:00+02:00"), parse_time("2026-05-04T09:30:00Z"));
+        assert!(parse_time("yesterday").is_none());
+        assert_eq!(split_paused(300, 600, 1200), (100, 200));
+        assert_eq!(split_paused(0, 600, 1200), (0, 0));
+    }
+
+    #[test]
+    fn manual_sessions_may_not_overlap() {
+        let conn = test_conn();
+        let session = add_session_sync(&conn, now(), 1, &at(0), &at(60), Some("Draft")).unwrap();
+        assert_eq!((session.duration_seconds, session.session_type.as_str()), (Some(3600), "manual"));
+        assert_eq!(task_total(&conn, 1), 3600);
+
+        assert!(add_session_sync(&conn, now(), 2, &at(30), &at(90), None).unwrap_err().contains("overlaps"));
+        assert!(add_session_sync(&conn, now(), 2, &at(90), &at(80), None).is_err());
+        assert!(add_session_sync(&conn, now(), 2, &at(600), &at(660), None).is_err());
+        // Touching end to start is fine
+        add_session_sync(&conn, now(), 2, &at(60), &at(90), None).unwrap();
+
+        // The running timer counts up to now
+        conn.execute_batch(&format!(
+            "INSERT INTO time_sessions (task_id, user_id, start_time, session_type) VALUES (2, 1, '{}', 'focus')",
+            at(300)
+        ))
+        .unwrap();
+        assert!(add_session_sync(&conn, now(), 1, &at(400), &at(420), None).is_err());
+
+        // Time can't be logged on a task in the trash
+        crate::commands::trash::trash_item(&conn, crate::commands::trash::TrashKind::Task, 1).unwrap();
+        assert!(add_session_sync(&conn, now(), 1, &at(200), &at(220), None).unwrap_err().contains("trash"));
+    }
+
+    #[test]
+    fn edits_move_time_between_tasks() {
+        let conn = test_conn();
+        let first = add_session_sync(&conn, now(), 1, &at(0), &at(60), None).unwrap();
+        let later = add_session_sync(&conn, now(), 1, &at(120), &at(150), None).unwrap();
+
+        // Forgot to stop: pull the end back
+        let edited = edit_session_sync(&conn, now(), first.id, None, Some(&at(45)), Some("Fixed")).unwrap();
+        assert_eq!((edited.duration_seconds, edited.notes.as_deref()), (Some(2700), Some("Fixed")));
+        assert!(edit_session_sync(&conn, now(), first.id, None, Some(&at(130)), None).is_err());
+
+        // Hand the last 15 minutes to another task
+        let parts = split_session_sync(&conn, first.id, &at(30), Some(2)).unwrap();
+        assert_eq!((parts[0].duration_seconds, parts[1].duration_seconds, parts[1].task_id), (Some(1800), Some(900), 2));
+        assert!(split_session_sync(&conn, first.id, &at(30), None).is_err());
+        assert_eq!((task_total(&conn, 1), task_total(&conn, 2)), (1800 + 1800, 900));
+
+        // Merging keeps the gap out of the total, and can't swallow another task's session
+        assert!(merge_sessions_sync(&conn, now(), first.id, parts[1].id).is_err());
+        assert!(merge_sessions_sync(&conn, now(), later.id, first.id).is_err());
+        let resumed = add_session_sync(&conn, now(), 1, &at(45), &at(60), None).unwrap();
+        let merged = merge_sessions_sync(&conn, now(), later.id, resumed.id).unwrap();
+        assert_eq!((merged.id, merged.duration_seconds), (resumed.id, Some(2700)));
+        assert_eq!(task_total(&conn, 1), 1800 + 2700);
+        let sessions: i64 = conn.query_row("SELECT COUNT(*) FROM time_sessions WHERE task_id = 1", [], |row| row.get(0)).unwrap();
+        assert_eq!(sessions, 2);
+    }
+}
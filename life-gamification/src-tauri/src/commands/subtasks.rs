@@ -0,0 +1,466 @@
+// Subtasks and "blocked by" dependencies.
+//
+// A task can be split into child tasks (tasks.parent_task_id) and can be blocked
+// by other tasks (task_dependencies). get_tasks marks a task `blocked` while any
+// of its blockers is unfinished, and complete_task completes a parent flagged
+// complete_with_subtasks once its last open child is done.
+//
+// Rewards: when a task gets its first child, its original XP/gold become a budget
+// (reward_budget_xp / reward_budget_gold). The parent keeps half of it and the
+// open children share the rest, minus what already-completed children were paid.
+// However many subtasks are added, the tree never pays more than the budget.
+
+use std::collections::{HashMap, HashSet};
+
+use rusqlite::{Connection, OptionalExtension};
+use serde::Serialize;
+use tauri::State;
+
+use crate::commands::economy;
+use crate::database::DbConnection;
+use crate::Task;
+
+/// Depth limit when walking parent chains, as a guard against corrupt data.
+const MAX_DEPTH: usize = 64;
+
+// ---------- Types ----------
+
+#[derive(Debug, Clone, Serialize)]
+pub struct TaskDependencies {
+    /// Tasks that must be finished before this one.
+    pub blocked_by: Vec<Task>,
+    /// Tasks waiting on this one.
+    pub blocking: Vec<Task>,
+}
+
+// ---------- Cycle detection (pure, unit-tested) ----------
+
+/// Would adding "`task_id` is blocked by `blocked_by`" close a cycle in `edges`
+/// (each edge is (task_id, blocked_by_task_id))?
+pub fn would_create_cycle(edges: &[(i64, i64)], task_id: i64, blocked_by: i64) -> bool {
+    if task_id == blocked_by {
+        return true;
+    }
+    let mut graph: HashMap<i64, Vec<i64>> = HashMap::new();
+    for &(task, blocker) in edges {
+        graph.entry(task).or_default().push(blocker);
+    }
+
+    // A cycle exists if task_id is already (transitively) blocking `blocked_by`
+    let mut stack = vec![blocked_by];
+    let mut seen = HashSet::new();
+    while let Some(current) = stack.pop() {
+        if current == task_id {
+            return true;
+        }
+        if seen.insert(current) {
+            if let Some(next) = graph.get(&current) {
+                stack.extend(next);
+            }
+        }
+    }
+    false
+}
+
+// ---------- Rewards ----------
+
+/// Redistribute `parent_id`'s reward budget between itself and its children.
+pub fn rebalance_rewards(conn: &Connection, parent_id: i64) -> Result<(), String> {
+    let (status, base_xp, base_gold, budget_xp, budget_gold): (String, i64, i64, Option<i64>, Option<i64>) = conn
+        .query_row(
+            "SELECT status, base_experience_reward, gold_reward, reward_budget_xp, reward_budget_gold
+             FROM tasks WHERE id = ?1",
+            [parent_id],
+            |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?, row.get(4)?)),
+        )
+        .map_err(|e| format!("Failed to get task {}: {}", parent_id, e))?;
+
+    let mut stmt = conn
+        .prepare(
+            "SELECT id, status, reward_budget_xp IS NOT NULL,
+                    COALESCE(reward_budget_xp, base_experience_reward), COALESCE(reward_budget_gold, gold_reward)
+             FROM tasks WHERE parent_task_id = ?1 AND status != 'archived' AND deleted_at IS NULL",
+        )
+        .map_err(|e| format!("Failed to prepare query: {}", e))?;
+    let children = stmt
+        .query_map([parent_id], |row| {
+            Ok((
+                row.get::<_, i64>(0)?,
+                row.get::<_, String>(1)?,
+                row.get::<_, bool>(2)?,
+                row.get::<_, i64>(3)?,
+                row.get::<_, i64>(4)?,
+            ))
+        })
+        .map_err(|e| format!("Failed to get subtasks: {}", e))?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| format!("Failed to read subtasks: {}", e))?;
+
+    if children.is_empty() {
+        // Last child gone: the parent earns its full rewards again
+        if let (Some(xp), Some(gold)) = (budget_xp, budget_gold) {
+            conn.execute(
+                "UPDATE tasks SET base_experience_reward = ?2, gold_reward = ?3,
+                 reward_budget_xp = NULL, reward_budget_gold = NULL WHERE id = ?1",
+                rusqlite::params![parent_id, xp, gold],
+            )
+            .map_err(|e| format!("Failed to restore rewards: {}", e))?;
+        }
+        return Ok(());
+    }
+
+    let budget_xp = budget_xp.unwrap_or(base_xp);
+    let budget_gold = budget_gold.unwrap_or(base_gold);
+    let parent_xp = budget_xp / 2;
+    let parent_gold = budget_gold / 2;
+
+    // A completed parent was already paid; only its children's shares can move
+    if status == "completed" {
+        conn.execute(
+            "UPDATE tasks SET reward_budget_xp = ?2, reward_budget_gold = ?3 WHERE id = ?1",
+            rusqlite::params![parent_id, budget_xp, budget_gold],
+        )
+    } else {
+        conn.execute(
+            "UPDATE tasks SET reward_budget_xp = ?2, reward_budget_gold = ?3,
+             base_experience_reward = ?4, gold_reward = ?5 WHERE id = ?1",
+            rusqlite::params![parent_id, budget_xp, budget_gold, parent_xp, parent_gold],
+        )
+    }
+    .map_err(|e| format!("Failed to update reward budget: {}", e))?;
+
+    let (paid_xp, paid_gold) = children
+        .iter()
+        .filter(|(_, status, ..)| status == "completed")
+        .fold((0, 0), |(xp, gold), (_, _, _, child_xp, child_gold)| (xp + child_xp, gold + child_gold));
+    let open: Vec<_> = children.iter().filter(|(_, status, ..)| status != "completed").collect();
+    if open.is_empty() {
+        return Ok(());
+    }
+    let share_xp = (budget_xp - parent_xp - paid_xp).max(0) / open.len() as i64;
+    let share_gold = (budget_gold - parent_gold - paid_gold).max(0) / open.len() as i64;
+
+    for &&(child_id, _, has_children, _, _) in &open {
+        if has_children {
+            conn.execute(
+                "UPDATE tasks SET reward_budget_xp = ?2, reward_budget_gold = ?3 WHERE id = ?1",
+                rusqlite::params![child_id, share_xp, share_gold],
+            )
+            .map_err(|e| format!("Failed to update subtask budget: {}", e))?;
+            rebalance_rewards(conn, child_id)?;
+        } else {
+            conn.execute(
+                "UPDATE tasks SET base_experience_reward = ?2, gold_reward = ?3 WHERE id = ?1",
+                rusqlite::params![child_id, share_xp, share_gold],
+            )
+            .map_err(|e| format!("Failed to update subtask rewards: {}", e))?;
+        }
+    }
+    Ok(())
+}
+
+/// Give a task that just left its parent its own difficulty-based rewards back.
+fn restore_own_rewards(conn: &Connection, task_id: i64) -> Result<(), String> {
+    let (difficulty, status, has_children): (i64, String, bool) = conn
+        .query_row(
+            "SELECT difficulty, status, reward_budget_xp IS NOT NULL FROM tasks WHERE id = ?1",
+            [task_id],
+            |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)),
+        )
+        .map_err(|e| format!("Failed to get task {}: {}", task_id, e))?;
+    if status == "completed" {
+        return Ok(());
+    }
+
+    let (xp, gold) = economy::task_rewards(conn, difficulty)?;
+    if has_children {
+        conn.execute(
+            "UPDATE tasks SET reward_budget_xp = ?2, reward_budget_gold = ?3 WHERE id = ?1",
+            rusqlite::params![task_id, xp, gold],
+        )
+        .map_err(|e| format!("Failed to restore rewards: {}", e))?;
+        rebalance_rewards(conn, task_id)
+    } else {
+        conn.execute(
+            "UPDATE tasks SET base_experience_reward = ?2, gold_reward = ?3 WHERE id = ?1",
+            rusqlite::params![task_id, xp, gold],
+        )
+        .map_err(|e| format!("Failed to restore rewards: {}", e))?;
+        Ok(())
+    }
+}
+
+// ---------- Hierarchy ----------
+
+/// Move `task_id` under `parent_id` (or detach it with None), rebalancing the
+/// rewards of both the old and the new parent.
+pub fn set_parent(conn: &Connection, task_id: i64, parent_id: Option<i64>) -> Result<(), String> {
+    let old_parent: Option<i64> = conn
+        .query_row("SELECT parent_task_id FROM tasks WHERE id = ?1 AND user_id = 1", [task_id], |row| row.get(0))
+        .map_err(|e| format!("Task not found: {}", e))?;
+    if old_parent == parent_id {
+        return Ok(());
+    }
+
+    if let Some(parent_id) = parent_id {
+        // Walk up from the new parent; meeting task_id means it would become its own ancestor
+        let mut current = Some(parent_id);
+        let mut depth = 0;
+        while let Some(id) = current {
+            if id == task_id {
+                return Err("A task cannot be a subtask of itself or of its own subtasks".to_string());
+            }
+            depth += 1;
+            if depth > MAX_DEPTH {
+                return Err("Subtask nesting is too deep".to_string());
+            }
+            current = conn
+                .query_row("SELECT parent_task_id FROM tasks WHERE id = ?1 AND user_id = 1", [id], |row| row.get(0))
+                .optional()
+                .map_err(|e| format!("Failed to get parent task: {}", e))?
+                .ok_or_else(|| format!("Parent task {} not found", id))?;
+        }
+    }
+
+    conn.execute("UPDATE tasks SET parent_task_id = ?2 WHERE id = ?1", rusqlite::params![task_id, parent_id])
+        .map_err(|e| format!("Failed to set parent task: {}", e))?;
+
+    if let Some(old_parent) = old_parent {
+        restore_own_rewards(conn, task_id)?;
+        rebalance_rewards(conn, old_parent)?;
+    }
+    if let Some(parent_id) = parent_id {
+        rebalance_rewards(conn, parent_id)?;
+    }
+    Ok(())
+}
+
+/// The parent of `child_id`, if completing the child left it with no open
+/// subtasks and it is set to complete with them.
+pub fn parent_ready_to_complete(conn: &Connection, child_id: i64) -> Result<Option<i64>, String> {
+    conn.query_row(
+        "SELECT p.id FROM tasks c JOIN tasks p ON p.id = c.parent_task_id
+         WHERE c.id = ?1 AND p.complete_with_subtasks = 1 AND p.status = 'active'
+           AND NOT EXISTS (SELECT 1 FROM tasks s WHERE s.parent_task_id = p.id
+                           AND s.status NOT IN ('completed', 'archived'))",
+        [child_id],
+        |row| row.get(0),
+    )
+    .optional()
+    .map_err(|e| format!("Failed to check parent task: {}", e))
+}
+
+fn query_tasks(conn: &Connection, filter: &str, id: i64) -> Result<Vec<Task>, String> {
+    let query = format!("{} WHERE {} ORDER BY t.priority DESC, t.due_date ASC, t.id", crate::TASK_SELECT, filter);
+    let mut stmt = conn.prepare(&query)
+        .map_err(|e| format!("Failed to prepare query: {}", e))?;
+    let tasks = stmt.query_map([id], crate::task_from_row)
+        .map_err(|e| format!("Failed to get tasks: {}", e))?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| format!("Failed to read tasks: {}", e))?;
+    Ok(tasks)
+}
+
+// ---------- Commands ----------
+
+#[tauri::command]
+pub async fn set_task_parent(
+    db: State<'_, DbConnection>,
+    task_id: i64,
+    parent_task_id: Option<i64>,
+) -> Result<Task, String> {
+    let conn = db.lock().await;
+    let tx = conn.unchecked_transaction()
+        .map_err(|e| format!("Failed to begin transaction: {}", e))?;
+    set_parent(&tx, task_id, parent_task_id)?;
+    tx.commit()
+        .map_err(|e| format!("Failed to commit: {}", e))?;
+    crate::fetch_task_sync(&conn, task_id)
+}
+
+#[tauri::command]
+pub async fn set_complete_with_subtasks(
+    db: State<'_, DbConnection>,
+    task_id: i64,
+    enabled: bool,
+) -> Result<Task, String> {
+    let conn = db.lock().await;
+    let updated = conn
+        .execute(
+            "UPDATE tasks SET complete_with_subtasks = ?2 WHERE id = ?1 AND user_id = 1",
+            rusqlite::params![task_id, enabled],
+        )
+        .map_err(|e| format!("Failed to update task: {}", e))?;
+    if updated == 0 {
+        return Err(format!("Task {} not found", task_id));
+    }
+    crate::fetch_task_sync(&conn, task_id)
+}
+
+#[tauri::command]
+pub async fn get_subtasks(db: State<'_, DbConnection>, task_id: i64) -> Result<Vec<Task>, String> {
+    let conn = db.lock().await;
+    query_tasks(&conn, "t.parent_task_id = ?1 AND t.status != 'archived'", task_id)
+}
+
+#[tauri::command]
+pub async fn add_task_dependency(
+    db: State<'_, DbConnection>,
+    task_id: i64,
+    blocked_by_task_id: i64,
+) -> Result<Task, String> {
+    let conn = db.lock().await;
+
+    let both_exist: bool = conn
+        .query_row(
+            "SELECT COUNT(*) = 2 FROM tasks WHERE id IN (?1, ?2) AND user_id = 1",
+            rusqlite::params![task_id, blocked_by_task_id],
+            |row| row.get(0),
+        )
+        .map_err(|e| format!("Failed to check tasks: {}", e))?;
+    if !both_exist {
+        return Err("Both tasks must exist".to_string());
+    }
+
+    let mut stmt = conn.prepare("SELECT task_id, blocked_by_task_id FROM task_dependencies")
+        .map_err(|e| format!("Failed to prepare query: {}", e))?;
+    let edges = stmt
+        .query_map([], |row| Ok((row.get::<_, i64>(0)?, row.get::<_, i64>(1)?)))
+        .map_err(|e| format!("Failed to get dependencies: {}", e))?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| format!("Failed to read dependencies: {}", e))?;
+    if would_create_cycle(&edges, task_id, blocked_by_task_id) {
+        return Err("This dependency would create a cycle".to_string());
+    }
+
+    conn.execute(
+        "INSERT OR IGNORE INTO task_dependencies (task_id, blocked_by_task_id) VALUES (?1, ?2)",
+        rusqlite::params![task_id, blocked_by_task_id],
+    )
+    .map_err(|e| format!("Failed to add dependency: {}", e))?;
+
+    crate::fetch_task_sync(&conn, task_id)
+}
+
+#[tauri::command]
+pub async fn remove_task_dependency(
+    db: State<'_, DbConnection>,
+    task_id: i64,
+    blocked_by_task_id: i64,
+) -> Result<Task, String> {
+    let conn = db.lock().await;
+    conn.execute(
+        "DELETE FROM task_dependencies WHERE task_id = ?1 AND blocked_by_task_id = ?2",
+        rusqlite::params![task_id, blocked_by_task_id],
+    )
+    .map_err(|e| format!("Failed to remove dependency: {}", e))?;
+    crate::fetch_task_sync(&conn, task_id)
+}
+
+#[tauri::command]
+pub async fn get_task_dependencies(db: State<'_, DbConnection>, task_id: i64) -> Result<TaskDependencies, String> {
+    let conn = db.lock().await;
+    Ok(TaskDependencies {
+        blocked_by: query_tasks(
+            &conn,
+            "t.id IN (SELECT blocked_by_task_id FROM task_dependencies WHERE task_id = ?1)",
+            task_id,
+        )?,
+        blocking: query_tasks(
+            &conn,
+            "t.id IN (SELECT task_id FROM task_dependencies WHERE blocked_by_task_id = ?1)",
+            task_id,
+        )?,
+    })
+}
+
+// ---------- Tests ----------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::database::test_conn;
+
+    fn rewards(conn: &Connection, id: i64) -> (i64, i64) {
+        conn.query_row(
+            "SELECT base_experience_reward, gold_reward FROM tasks WHERE id = ?1",
+            [id],
+            |row| Ok((row.get(0)?, row.get(1)?)),
+        )
+        .unwrap()
+    }
+
+    fn insert_task(conn: &Connection, id: i64, difficulty: i64) {
+        let (xp, gold) = economy::task_rewards(conn, difficulty).unwrap();
+        conn.execute(
+            "INSERT INTO tasks (id, user_id, title, status, difficulty, base_experience_reward, gold_reward)
+             VALUES (?1, 1, 'Task', 'active', ?2, ?3, ?4)",
+            rusqlite::params![id, difficulty, xp, gold],
+        )
+        .unwrap();
+    }
+
+    #[test]
+    fn detects_dependency_cycles() {
+        let edges = [(1, 2), (2, 3)];
+        assert!(would_create_cycle(&edges, 3, 1));
+        assert!(would_create_cycle(&edges, 2, 2));
+        assert!(!would_create_cycle(&edges, 1, 3));
+        assert!(!would_create_cycle(&edges, 4, 1));
+    }
+
+    #[test]
+    fn subtasks_share_the_parent_budget() {
+        let conn = test_conn();
+        insert_task(&conn, 1, 5); // 30 XP, 5 gold
+        for id in 2..=11 {
+            insert_task(&conn, id, 5);
+            set_parent(&conn, id, Some(1)).unwrap();
+        }
+
+        assert_eq!(rewards(&conn, 1), (15, 2));
+        let children_xp: i64 = (2..=11).map(|id| rewards(&conn, id).0).sum();
+        assert!(15 + children_xp <= 30);
+
+        // Completed children keep what they were paid; the rest share what's left
+        conn.execute("UPDATE tasks SET status = 'completed' WHERE id = 2", []).unwrap();
+        set_parent(&conn, 11, None).unwrap();
+        assert_eq!(rewards(&conn, 11), (30, 5));
+        let children_xp: i64 = (2..=10).map(|id| rewards(&conn, id).0).sum();
+        assert!(15 + children_xp <= 30);
+
+        for id in 2..=10 {
+            set_parent(&conn, id, None).unwrap();
+        }
+        assert_eq!(rewards(&conn, 1), (30, 5));
+    }
+
+    #[test]
+    fn rejects_parent_cycles() {
+        let conn = test_conn();
+        for id in 1..=3 {
+            insert_task(&conn, id, 1);
+        }
+        set_parent(&conn, 2, Some(1)).unwrap();
+        set_parent(&conn, 3, Some(2)).unwrap();
+        assert!(set_parent(&conn, 1, Some(3)).is_err());
+        assert!(set_parent(&conn, 1, Some(1)).is_err());
+    }
+
+    #[test]
+    fn parent_completes_after_last_open_subtask() {
+        let conn = test_conn();
+        for id in 1..=3 {
+            insert_task(&conn, id, 1);
+        }
+        set_parent(&conn, 2, Some(1)).unwrap();
+        set_parent(&conn, 3, Some(1)).unwrap();
+        conn.execute("UPDATE tasks SET status = 'completed' WHERE id = 2", []).unwrap();
+        assert_eq!(parent_ready_to_complete(&conn, 2).unwrap(), None);
+
+        conn.execute("UPDATE tasks SET complete_with_subtasks = 1 WHERE id = 1", []).unwrap();
+        assert_eq!(parent_ready_to_complete(&conn, 2).unwrap(), None);
+        conn.execute("UPDATE tasks SET status = 'completed' WHERE id = 3", []).unwrap();
+        assert_eq!(parent_ready_to_complete(&conn, 3).unwrap(), Some(1));
+    }
+}
@@ -0,0 +1,312 @@
+// Tags.
+//
+// Tasks keep their single category, and can additionally carry any number of
+// tags (tags / task_tags). Tags are created on first use, matched ignoring case,
+// and have a color for the UI. Capture (#word tokens) and GitHub import (issue
+// labels) tag the tasks they create; get_tasks can filter by tags.
+
+use rusqlite::{Connection, OptionalExtension};
+use serde::Serialize;
+use tauri::State;
+
+use crate::database::DbConnection;
+
+const MAX_TAG_LENGTH: usize = 50;
+
+// ---------- Types ----------
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Tag {
+    pub id: i64,
+    pub name: String,
+    pub color: String,
+    pub task_count: i64,
+}
+
+// ---------- Validation (pure functions, unit-tested) ----------
+
+/// Clean up a tag name: trim, drop a leading '#', collapse inner whitespace.
+/// None if nothing usable is left.
+pub fn normalize_tag(name: &str) -> Option<String> {
+    let name = name.trim().trim_start_matches('#');
+    let name = name.split_whitespace().collect::<Vec<_>>().join(" ");
+    if name.is_empty() {
+        return None;
+    }
+    Some(name.chars().take(MAX_TAG_LENGTH).collect())
+}
+
+/// Accept #RGB or #RRGGBB hex colors.
+pub fn valid_color(color: &str) -> bool {
+    color
+        .strip_prefix('#')
+        .is_some_and(|hex| (hex.len() == 3 || hex.len() == 6) && hex.chars().all(|c| c.is_ascii_hexdigit()))
+}
+
+// ---------- Persistence ----------
+
+/// Id of the tag called `name`, creating it if needed.
+pub fn ensure_tag(conn: &Connection, name: &str) -> Result<i64, String> {
+    let name = normalize_tag(name).ok_or("Tag name cannot be empty")?;
+    conn.execute("INSERT OR IGNORE INTO tags (user_id, name) VALUES (1, ?1)", [&name])
+        .map_err(|e| format!("Failed to create tag: {}", e))?;
+    conn.query_row("SELECT id FROM tags WHERE user_id = 1 AND name = ?1", [&name], |row| row.get(0))
+        .map_err(|e| format!("Failed to get tag: {}", e))
+}
+
+/// Add tags to a task, skipping blanks and tags it already has.
+pub fn add_tags(conn: &Connection, task_id: i64, names: &[String]) -> Result<(), String> {
+    for name in names {
+        if normalize_tag(name).is_none() {
+            continue;
+        }
+        let tag_id = ensure_tag(conn, name)?;
+        conn.execute(
+            "INSERT OR IGNORE INTO task_tags (task_id, tag_id) VALUES (?1, ?2)",
+            rusqlite::params![task_id, tag_id],
+        )
+        .map_err(|e| format!("Failed to tag task {}: {}", task_id, e))?;
+    }
+    Ok(())
+}
+
+/// Give `to_task_id` every tag of `from_task_id` (recurring instances inherit
+/// their template's tags).
+pub fn copy_tags(conn: &Connection, from_task_id: i64, to_task_id: i64) -> Result<(), String> {
+    conn.execute(
+        "INSERT OR IGNORE INTO task_tags (task_id, tag_id) SELECT ?2, tag_id FROM task_tags WHERE task_id = ?1",
+        rusqlite::params![from_task_id, to_task_id],
+    )
+    .map_err(|e| format!("Failed to copy tags: {}", e))?;
+    Ok(())
+}
+
+fn remove_tag(conn: &Connection, task_id: i64, name: &str) -> Result<(), String> {
+    conn.execute(
+        "DELETE FROM task_tags WHERE task_id = ?1
+         AND tag_id IN (SELECT id FROM tags WHERE user_id = 1 AND name = ?2)",
+        rusqlite::params![task_id, normalize_tag(name).unwrap_or_default()],
+    )
+    .map_err(|e| format!("Failed to untag task {}: {}", task_id, e))?;
+    Ok(())
+}
+
+/// Rename a tag. Renaming onto an existing tag merges the two.
+pub fn rename_tag_sync(conn: &Connection, tag_id: i64, new_name: &str) -> Result<i64, String> {
+    let new_name = normalize_tag(new_name).ok_or("Tag name cannot be empty")?;
+    let existing: Option<i64> = conn
+        .query_row(
+            "SELECT id FROM tags WHERE user_id = 1 AND name = ?1 AND id != ?2",
+            rusqlite::params![new_name, tag_id],
+            |row| row.get(0),
+        )
+        .optional()
+        .map_err(|e| format!("Failed to look up tag: {}", e))?;
+
+    match existing {
+        Some(target_id) => {
+            conn.execute(
+                "INSERT OR IGNORE INTO task_tags (task_id, tag_id) SELECT task_id, ?2 FROM task_tags WHERE tag_id = ?1",
+                rusqlite::params![tag_id, target_id],
+            )
+            .map_err(|e| format!("Failed to merge tags: {}", e))?;
+            conn.execute("DELETE FROM tags WHERE id = ?1", [tag_id])
+                .map_err(|e| format!("Failed to merge tags: {}", e))?;
+            Ok(target_id)
+        }
+        None => {
+            let updated = conn
+                .execute(
+                    "UPDATE tags SET name = ?1 WHERE id = ?2 AND user_id = 1",
+                    rusqlite::params![new_name, tag_id],
+                )
+                .map_err(|e| format!("Failed to rename tag: {}", e))?;
+            if updated == 0 {
+                return Err(format!("Tag {} not found", tag_id));
+            }
+            Ok(tag_id)
+        }
+    }
+}
+
+fn query_tags(conn: &Connection, filter: &str, id: Option<i64>) -> Result<Vec<Tag>, String> {
+    let query = format!(
+        "SELECT g.id, g.name, g.color,
+                (SELECT COUNT(*) FROM task_tags tt WHERE tt.tag_id = g.id)
+         FROM tags g
+         WHERE g.user_id = 1 AND {}
+         ORDER BY g.name COLLATE NOCASE",
+        filter
+    );
+    let mut stmt = conn.prepare(&query)
+        .map_err(|e| format!("Failed to prepare query: {}", e))?;
+    let tags = stmt
+        .query_map(rusqlite::params![id], |row| {
+            Ok(Tag {
+                id: row.get(0)?,
+                name: row.get(1)?,
+                color: row.get(2)?,
+                task_count: row.get(3)?,
+            })
+        })
+        .map_err(|e| format!("Failed to get tags: {}", e))?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| format!("Failed to read tags: {}", e))?;
+    Ok(tags)
+}
+
+fn task_tags(conn: &Connection, task_id: i64) -> Result<Vec<Tag>, String> {
+    query_tags(conn, "g.id IN (SELECT tag_id FROM task_tags WHERE task_id = ?1)", Some(task_id))
+}
+
+// ---------- Commands ----------
+
+#[tauri::command]
+pub async fn get_tags(db: State<'_, DbConnection>) -> Result<Vec<Tag>, String> {
+    let conn = db.lock().await;
+    query_tags(&conn, "?1 IS NULL", None)
+}
+
+#[tauri::command]
+pub async fn create_tag(db: State<'_, DbConnection>, name: String, color: Option<String>) -> Result<Tag, String> {
+    if let Some(color) = color.as_deref() {
+        if !valid_color(color) {
+            return Err(format!("Invalid color \"{}\"; use #RGB or #RRGGBB", color));
+        }
+    }
+    let conn = db.lock().await;
+    let tag_id = ensure_tag(&conn, &name)?;
+    if let Some(color) = color {
+        conn.execute("UPDATE tags SET color = ?1 WHERE id = ?2", rusqlite::params![color, tag_id])
+            .map_err(|e| format!("Failed to set tag color: {}", e))?;
+    }
+    query_tags(&conn, "g.id = ?1", Some(tag_id))?
+        .pop()
+        .ok_or_else(|| "Tag not found".to_string())
+}
+
+#[tauri::command]
+pub async fn rename_tag(db: State<'_, DbConnection>, tag_id: i64, name: String) -> Result<Tag, String> {
+    let conn = db.lock().await;
+    let tx = conn.unchecked_transaction()
+        .map_err(|e| format!("Failed to begin transaction: {}", e))?;
+    let tag_id = rename_tag_sync(&tx, tag_id, &name)?;
+    tx.commit()
+        .map_err(|e| format!("Failed to commit: {}", e))?;
+    query_tags(&conn, "g.id = ?1", Some(tag_id))?
+        .pop()
+        .ok_or_else(|| "Tag not found".to_string())
+}
+
+#[tauri::command]
+pub async fn set_tag_color(db: State<'_, DbConnection>, tag_id: i64, color: String) -> Result<Tag, String> {
+    if !valid_color(&color) {
+        return Err(format!("Invalid color \"{}\"; use #RGB or #RRGGBB", color));
+    }
+    let conn = db.lock().await;
+    conn.execute(
+        "UPDATE tags SET color = ?1 WHERE id = ?2 AND user_id = 1",
+        rusqlite::params![color, tag_id],
+    )
+    .map_err(|e| format!("Failed to set tag color: {}", e))?;
+    query_tags(&conn, "g.id = ?1", Some(tag_id))?
+        .pop()
+        .ok_or_else(|| format!("Tag {} not found", tag_id))
+}
+
+/// Delete a tag and remove it from every task.
+#[tauri::command]
+pub async fn delete_tag(db: State<'_, DbConnection>, tag_id: i64) -> Result<(), String> {
+    let conn = db.lock().await;
+    let tx = conn.unchecked_transaction()
+        .map_err(|e| format!("Failed to begin transaction: {}", e))?;
+    tx.execute("DELETE FROM task_tags WHERE tag_id = ?1", [tag_id])
+        .map_err(|e| format!("Failed to untag tasks: {}", e))?;
+    tx.execute("DELETE FROM tags WHERE id = ?1 AND user_id = 1", [tag_id])
+        .map_err(|e| format!("Failed to delete tag: {}", e))?;
+    tx.commit()
+        .map_err(|e| format!("Failed to commit: {}", e))?;
+    Ok(())
+}
+
+/// Tag a task (creating the tag if needed) and return the task's tags.
+#[tauri::command]
+pub async fn add_task_tag(db: State<'_, DbConnection>, task_id: i64, tag: String) -> Result<Vec<Tag>, String> {
+    let conn = db.lock().await;
+    crate::fetch_task_sync(&conn, task_id)?;
+    add_tags(&conn, task_id, &[tag])?;
+    task_tags(&conn, task_id)
+}
+
+#[tauri::command]
+pub async fn remove_task_tag(db: State<'_, DbConnection>, task_id: i64, tag: String) -> Result<Vec<Tag>, String> {
+    let conn = db.lock().await;
+    remove_tag(&conn, task_id, &tag)?;
+    task_tags(&conn, task_id)
+}
+
+#[tauri::command]
+pub async fn get_task_tags(db: State<'_, DbConnection>, task_id: i64) -> Result<Vec<Tag>, String> {
+    let conn = db.lock().await;
+    task_tags(&conn, task_id)
+}
+
+// ---------- Tests ----------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn test_conn() -> Connection {
+        let conn = crate::database::test_conn();
+        conn.execute_batch(
+            "INSERT INTO tasks (id, user_id, title, status) VALUES (1, 1, 'One', 'active');
+             INSERT INTO tasks (id, user_id, title, status) VALUES (2, 1, 'Two', 'active');",
+        )
+        .unwrap();
+        conn
+    }
+
+    fn names(tags: &[Tag]) -> Vec<&str> {
+        tags.iter().map(|t| t.name.as_str()).collect()
+    }
+
+    #[test]
+    fn normalizes_names_and_colors() {
+        assert_eq!(normalize_tag("  #Deep   Work "), Some("Deep Work".to_string()));
+        assert_eq!(normalize_tag("#"), None);
+        assert!(valid_color("#abc") && valid_color("#A1B2C3"));
+        assert!(!valid_color("red") && !valid_color("#12345g"));
+    }
+
+    #[test]
+    fn tags_are_shared_and_case_insensitive() {
+        let conn = test_conn();
+        add_tags(&conn, 1, &["Errands".to_string(), "home".to_string()]).unwrap();
+        add_tags(&conn, 2, &["errands".to_string(), " ".to_string()]).unwrap();
+
+        let all = query_tags(&conn, "?1 IS NULL", None).unwrap();
+        assert_eq!(names(&all), vec!["Errands", "home"]);
+        assert_eq!(all[0].task_count, 2);
+
+        remove_tag(&conn, 1, "ERRANDS").unwrap();
+        assert_eq!(names(&task_tags(&conn, 1).unwrap()), vec!["home"]);
+    }
+
+    #[test]
+    fn renaming_onto_an_existing_tag_merges() {
+        let conn = test_conn();
+        add_tags(&conn, 1, &["chores".to_string()]).unwrap();
+        add_tags(&conn, 2, &["housework".to_string(), "chores".to_string()]).unwrap();
+        let housework = ensure_tag(&conn, "housework").unwrap();
+        let chores = ensure_tag(&conn, "chores").unwrap();
+
+        assert_eq!(rename_tag_sync(&conn, housework, "Chores").unwrap(), chores);
+        let all = query_tags(&conn, "?1 IS NULL", None).unwrap();
+        assert_eq!(names(&all), vec!["chores"]);
+        assert_eq!(all[0].task_count, 2);
+
+        assert_eq!(rename_tag_sync(&conn, chores, "Housework").unwrap(), chores);
+        assert_eq!(names(&task_tags(&conn, 2).unwrap()), vec!["Housework"]);
+    }
+}
@@ -0,0 +1,507 @@
+// Task templates.
+//
+// A template stores what a recurring kind of quest looks like (title, category,
+// difficulty, estimate, project, tags) plus an ordered checklist. Instantiating
+// it creates the task and one subtask per checklist item, with the task set to
+// complete once its checklist is done.
+//
+// Title, description and checklist items can use {{name}} placeholders, filled
+// in from the variables passed to instantiate_template. {{date}} (today) and
+// {{weekday}} are always available.
+
+use std::collections::HashMap;
+
+use chrono::{NaiveDate, Utc};
+use rusqlite::{Connection, OptionalExtension};
+use serde::{Deserialize, Serialize};
+use tauri::State;
+
+use crate::commands::economy::{self, MAX_DIFFICULTY, MIN_DIFFICULTY};
+use crate::commands::{subtasks, tags};
+use crate::database::DbConnection;
+use crate::Task;
+
+/// Placeholders filled in without being passed.
+const BUILTIN_VARIABLES: [&str; 2] = ["date", "weekday"];
+
+// ---------- Types ----------
+
+#[derive(Debug, Clone, Serialize)]
+pub struct TaskTemplate {
+    pub id: i64,
+    pub name: String,
+    pub title: String,
+    pub description: Option<String>,
+    pub category: String,
+    pub difficulty: i64,
+    pub priority: i64,
+    pub estimated_minutes: Option<i64>,
+    pub project_id: Option<i64>,
+    pub tags: Vec<String>,
+    pub checklist: Vec<String>,
+    /// Placeholders that need a value when instantiating (builtins excluded).
+    pub variables: Vec<String>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct TemplateRequest {
+    pub name: String,
+    pub title: String,
+    pub description: Option<String>,
+    pub category: Option<String>,
+    pub difficulty: Option<i64>,
+    pub priority: Option<i64>,
+    pub estimated_minutes: Option<i64>,
+    pub project_id: Option<i64>,
+    pub tags: Option<Vec<String>>,
+    pub checklist: Option<Vec<String>>,
+}
+
+// ---------- Placeholders (pure functions, unit-tested) ----------
+
+/// Names of the {{placeholders}} in `text`, in order of appearance.
+fn placeholders(text: &str) -> Vec<String> {
+    let mut names = Vec::new();
+    let mut rest = text;
+    while let Some(start) = rest.find("{{") {
+        let after = &rest[start + 2..];
+        match after.find("}}") {
+            Some(end) => {
+                let name = after[..end].trim();
+                if !name.is_empty() {
+                    names.push(name.to_string());
+                }
+                rest = &after[end + 2..];
+            }
+            None => break,
+        }
+    }
+    names
+}
+
+/// Placeholders across several texts that the caller has to supply.
+pub fn template_variables(texts: &[&str]) -> Vec<String> {
+    let mut names: Vec<String> = Vec::new();
+    for name in texts.iter().flat_map(|text| placeholders(text)) {
+        if !BUILTIN_VARIABLES.contains(&name.as_str()) && !names.contains(&name) {
+            names.push(name);
+        }
+    }
+    names
+}
+
+/// Values for the builtin placeholders on `today`.
+pub fn builtin_values(today: NaiveDate) -> HashMap<String, String> {
+    HashMap::from([
+        ("date".to_string(), today.format("%Y-%m-%d").to_string()),
+        ("weekday".to_string(), today.format("%A").to_string()),
+    ])
+}
+
+/// Replace every {{name}} in `text`. Text after an unclosed "{{" is kept as is.
+pub fn substitute(text: &str, values: &HashMap<String, String>) -> Result<String, String> {
+    let mut out = String::new();
+    let mut rest = text;
+    while let Some(start) = rest.find("{{") {
+        let after = &rest[start + 2..];
+        let Some(end) = after.find("}}") else { break };
+        let name = after[..end].trim();
+        let value = values
+            .get(name)
+            .ok_or_else(|| format!("No value given for {{{{{}}}}}", name))?;
+        out.push_str(&rest[..start]);
+        out.push_str(value);
+        rest = &after[end + 2..];
+    }
+    out.push_str(rest);
+    Ok(out)
+}
+
+// ---------- Persistence ----------
+
+fn load_template(conn: &Connection, template_id: i64) -> Result<TaskTemplate, String> {
+    let mut template = conn
+        .query_row(
+            "SELECT id, name, title, description, category, difficulty, priority,
+                    estimated_time_minutes, project_id, tags
+             FROM task_templates WHERE id = ?1 AND user_id = 1",
+            [template_id],
+            |row| {
+                Ok(TaskTemplate {
+                    id: row.get(0)?,
+                    name: row.get(1)?,
+                    title: row.get(2)?,
+                    description: row.get(3)?,
+                    category: row.get(4)?,
+                    difficulty: row.get(5)?,
+                    priority: row.get(6)?,
+                    estimated_minutes: row.get(7)?,
+                    project_id: row.get(8)?,
+                    tags: serde_json::from_str(&row.get::<_, String>(9)?).unwrap_or_default(),
+                    checklist: Vec::new(),
+                    variables: Vec::new(),
+                })
+            },
+        )
+        .optional()
+        .map_err(|e| format!("Failed to get template: {}", e))?
+        .ok_or_else(|| format!("Template {} not found", template_id))?;
+
+    let mut stmt = conn
+        .prepare("SELECT text FROM task_template_items WHERE template_id = ?1 ORDER BY position")
+        .map_err(|e| format!("Failed to prepare query: {}", e))?;
+    template.checklist = stmt
+        .query_map([template_id], |row| row.get(0))
+        .map_err(|e| format!("Failed to get checklist: {}", e))?
+        .collect::<Result<Vec<String>, _>>()
+        .map_err(|e| format!("Failed to read checklist: {}", e))?;
+
+    let mut texts = vec![template.title.as_str()];
+    texts.extend(template.description.as_deref());
+    texts.extend(template.checklist.iter().map(String::as_str));
+    template.variables = template_variables(&texts);
+    Ok(template)
+}
+
+/// Insert or (with `template_id`) replace a template; returns its id.
+fn save_template(conn: &Connection, template_id: Option<i64>, request: &TemplateRequest) -> Result<i64, String> {
+    let name = request.name.trim();
+    if name.is_empty() {
+        return Err("Template name cannot be empty".to_string());
+    }
+    if request.title.trim().is_empty() {
+        return Err("Template title cannot be empty".to_string());
+    }
+    let difficulty = request.difficulty.unwrap_or(5);
+    if !(MIN_DIFFICULTY..=MAX_DIFFICULTY).contains(&difficulty) {
+        return Err(format!("Difficulty must be between {} and {}", MIN_DIFFICULTY, MAX_DIFFICULTY));
+    }
+    let tags: Vec<String> = request
+        .tags
+        .iter()
+        .flatten()
+        .filter_map(|tag| tags::normalize_tag(tag))
+        .collect();
+    let tags = serde_json::to_string(&tags).map_err(|e| format!("Failed to encode tags: {}", e))?;
+
+    let result = match template_id {
+        Some(id) => conn.execute(
+            "UPDATE task_templates SET name = ?1, title = ?2, description = ?3, category = ?4, difficulty = ?5,
+             priority = ?6, estimated_time_minutes = ?7, project_id = ?8, tags = ?9, updated_at = CURRENT_TIMESTAMP
+             WHERE id = ?10 AND user_id = 1",
+            rusqlite::params![
+                name,
+                request.title.trim(),
+                request.description,
+                request.category.as_deref().unwrap_or("general"),
+                difficulty,
+                request.priority.unwrap_or(3),
+                request.estimated_minutes,
+                request.project_id,
+                tags,
+                id,
+            ],
+        ),
+        None => conn.execute(
+            "INSERT INTO task_templates (user_id, name, title, description, category, difficulty,
+             priority, estimated_time_minutes, project_id, tags)
+             VALUES (1, ?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)",
+            rusqlite::params![
+                name,
+                request.title.trim(),
+                request.description,
+                request.category.as_deref().unwrap_or("general"),
+                difficulty,
+                request.priority.unwrap_or(3),
+                request.estimated_minutes,
+                request.project_id,
+                tags,
+            ],
+        ),
+    };
+    match result {
+        Ok(0) => return Err(format!("Template {} not found", template_id.unwrap_or_default())),
+        Ok(_) => {}
+        Err(e) if e.to_string().contains("UNIQUE") => {
+            return Err(format!("A template named \"{}\" already exists", name))
+        }
+        Err(e) => return Err(format!("Failed to save template: {}", e)),
+    }
+    let template_id = template_id.unwrap_or_else(|| conn.last_insert_rowid());
+
+    conn.execute("DELETE FROM task_template_items WHERE template_id = ?1", [template_id])
+        .map_err(|e| format!("Failed to save checklist: {}", e))?;
+    let items = request.checklist.iter().flatten().map(|item| item.trim()).filter(|item| !item.is_empty());
+    for (position, item) in items.enumerate() {
+        conn.execute(
+            "INSERT INTO task_template_items (template_id, position, text) VALUES (?1, ?2, ?3)",
+            rusqlite::params![template_id, position as i64, item],
+        )
+        .map_err(|e| format!("Failed to save checklist: {}", e))?;
+    }
+    Ok(template_id)
+}
+
+fn insert_task(
+    conn: &Connection,
+    template: &TaskTemplate,
+    title: &str,
+    description: Option<&str>,
+    difficulty: i64,
+    estimated_minutes: Option<i64>,
+    due_date: Option<&str>,
+) -> Result<i64, String> {
+    let (base_xp, gold_reward) = economy::task_rewards(conn, difficulty)?;
+    conn.execute(
+        "INSERT INTO tasks (user_id, title, description, category, difficulty, base_experience_reward,
+         gold_reward, due_date, status, priority, task_type, project_id, estimated_time_minutes)
+         VALUES (1, ?1, ?2, ?3, ?4, ?5, ?6, ?7, 'active', ?8, 'standard', ?9, ?10)",
+        rusqlite::params![
+            title,
+            description,
+            template.category,
+            difficulty,
+            base_xp,
+            gold_reward,
+            due_date,
+            template.priority,
+            template.project_id,
+            estimated_minutes,
+        ],
+    )
+    .map_err(|e| format!("Failed to create task: {}", e))?;
+    Ok(conn.last_insert_rowid())
+}
+
+/// Create a task (and its checklist subtasks) from a template; returns the task id.
+pub fn instantiate_sync(
+    conn: &Connection,
+    template_id: i64,
+    variables: HashMap<String, String>,
+    due_date: Option<&str>,
+    today: NaiveDate,
+) -> Result<i64, String> {
+    let template = load_template(conn, template_id)?;
+    let missing: Vec<&str> = template
+        .variables
+        .iter()
+        .filter(|name| !variables.contains_key(*name))
+        .map(String::as_str)
+        .collect();
+    if !missing.is_empty() {
+        return Err(format!("Missing values for: {}", missing.join(", ")));
+    }
+    let mut values = builtin_values(today);
+    values.extend(variables);
+
+    let title = substitute(&template.title, &values)?;
+    let description = template.description.as_deref().map(|d| substitute(d, &values)).transpose()?;
+    let task_id = insert_task(
+        conn,
+        &template,
+        &title,
+        description.as_deref(),
+        template.difficulty,
+        template.estimated_minutes,
+        due_date,
+    )?;
+    tags::add_tags(conn, task_id, &template.tags)?;
+
+    if !template.checklist.is_empty() {
+        conn.execute("UPDATE tasks SET complete_with_subtasks = 1 WHERE id = ?1", [task_id])
+            .map_err(|e| format!("Failed to update task: {}", e))?;
+    }
+    for item in &template.checklist {
+        let item = substitute(item, &values)?;
+        let item_id = insert_task(conn, &template, &item, None, MIN_DIFFICULTY, None, due_date)?;
+        subtasks::set_parent(conn, item_id, Some(task_id))?;
+    }
+    Ok(task_id)
+}
+
+/// A template request describing an existing task, its subtasks as the checklist.
+fn request_from_task(conn: &Connection, task_id: i64, name: &str) -> Result<TemplateRequest, String> {
+    let task = crate::fetch_task_sync(conn, task_id)?;
+    let estimated_minutes: Option<i64> = conn
+        .query_row("SELECT estimated_time_minutes FROM tasks WHERE id = ?1", [task_id], |row| row.get(0))
+        .map_err(|e| format!("Failed to get task: {}", e))?;
+    let mut stmt = conn
+        .prepare("SELECT title FROM tasks WHERE parent_task_id = ?1 AND user_id = 1 ORDER BY id")
+        .map_err(|e| format!("Failed to prepare query: {}", e))?;
+    let checklist = stmt
+        .query_map([task_id], |row| row.get(0))
+        .map_err(|e| format!("Failed to get subtasks: {}", e))?
+        .collect::<Result<Vec<String>, _>>()
+        .map_err(|e| format!("Failed to read subtasks: {}", e))?;
+
+    Ok(TemplateRequest {
+        name: name.to_string(),
+        title: task.title,
+        description: task.description,
+        category: Some(task.category),
+        difficulty: Some(task.difficulty),
+        priority: Some(task.priority),
+        estimated_minutes,
+        project_id: task.project_id,
+        tags: Some(task.tags),
+        checklist: Some(checklist),
+    })
+}
+
+// ---------- Commands ----------
+
+#[tauri::command]
+pub async fn get_templates(db: State<'_, DbConnection>) -> Result<Vec<TaskTemplate>, String> {
+    let conn = db.lock().await;
+    let mut stmt = conn
+        .prepare("SELECT id FROM task_templates WHERE user_id = 1 ORDER BY name COLLATE NOCASE")
+        .map_err(|e| format!("Failed to prepare query: {}", e))?;
+    let ids = stmt
+        .query_map([], |row| row.get(0))
+        .map_err(|e| format!("Failed to get templates: {}", e))?
+        .collect::<Result<Vec<i64>, _>>()
+        .map_err(|e| format!("Failed to read templates: {}", e))?;
+    ids.into_iter().map(|id| load_template(&conn, id)).collect()
+}
+
+#[tauri::command]
+pub async fn create_template(db: State<'_, DbConnection>, template: TemplateRequest) -> Result<TaskTemplate, String> {
+    let conn = db.lock().await;
+    let tx = conn.unchecked_transaction()
+        .map_err(|e| format!("Failed to begin transaction: {}", e))?;
+    let template_id = save_template(&tx, None, &template)?;
+    tx.commit()
+        .map_err(|e| format!("Failed to commit: {}", e))?;
+    load_template(&conn, template_id)
+}
+
+#[tauri::command]
+pub async fn update_template(
+    db: State<'_, DbConnection>,
+    template_id: i64,
+    template: TemplateRequest,
+) -> Result<TaskTemplate, String> {
+    let conn = db.lock().await;
+    let tx = conn.unchecked_transaction()
+        .map_err(|e| format!("Failed to begin transaction: {}", e))?;
+    save_template(&tx, Some(template_id), &template)?;
+    tx.commit()
+        .map_err(|e| format!("Failed to commit: {}", e))?;
+    load_template(&conn, template_id)
+}
+
+#[tauri::command]
+pub async fn delete_template(db: State<'_, DbConnection>, template_id: i64) -> Result<(), String> {
+    let conn = db.lock().await;
+    let tx = conn.unchecked_transaction()
+        .map_err(|e| format!("Failed to begin transaction: {}", e))?;
+    tx.execute("DELETE FROM task_template_items WHERE template_id = ?1", [template_id])
+        .map_err(|e| format!("Failed to delete checklist: {}", e))?;
+    tx.execute("DELETE FROM task_templates WHERE id = ?1 AND user_id = 1", [template_id])
+        .map_err(|e| format!("Failed to delete template: {}", e))?;
+    tx.commit()
+        .map_err(|e| format!("Failed to commit: {}", e))?;
+    Ok(())
+}
+
+/// Save an existing task (with its subtasks as the checklist) as a template.
+#[tauri::command]
+pub async fn save_task_as_template(db: State<'_, DbConnection>, task_id: i64, name: String) -> Result<TaskTemplate, String> {
+    let conn = db.lock().await;
+    let tx = conn.unchecked_transaction()
+        .map_err(|e| format!("Failed to begin transaction: {}", e))?;
+    let request = request_from_task(&tx, task_id, &name)?;
+    let template_id = save_template(&tx, None, &request)?;
+    tx.commit()
+        .map_err(|e| format!("Failed to commit: {}", e))?;
+    load_template(&conn, template_id)
+}
+
+#[tauri::command]
+pub async fn instantiate_template(
+    db: State<'_, DbConnection>,
+    template_id: i64,
+    variables: Option<HashMap<String, String>>,
+    due_date: Option<String>,
+) -> Result<Task, String> {
+    let conn = db.lock().await;
+    let tx = conn.unchecked_transaction()
+        .map_err(|e| format!("Failed to begin transaction: {}", e))?;
+    let task_id = instantiate_sync(
+        &tx,
+        template_id,
+        variables.unwrap_or_default(),
+        due_date.as_deref(),
+        Utc::now().naive_utc().date(),
+    )?;
+    tx.commit()
+        .map_err(|e| format!("Failed to commit: {}", e))?;
+    crate::fetch_task_sync(&conn, task_id)
+}
+
+// ---------- Tests ----------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn today() -> NaiveDate {
+        NaiveDate::parse_from_str("2026-07-03", "%Y-%m-%d").unwrap()
+    }
+
+    fn request(name: &str) -> TemplateRequest {
+        TemplateRequest {
+            name: name.to_string(),
+            title: "Pack for {{destination}}".to_string(),
+            description: Some("Leaving {{ date }}".to_string()),
+            category: Some("personal".to_string()),
+            difficulty: Some(4),
+            priority: None,
+            estimated_minutes: Some(45),
+            project_id: None,
+            tags: Some(vec!["#Travel".to_string()]),
+            checklist: Some(vec!["Passport".to_string(), " ".to_string(), "Guidebook for {{destination}}".to_string()]),
+        }
+    }
+
+    #[test]
+    fn finds_and_fills_placeholders() {
+        assert_eq!(
+            template_variables(&["{{a}} and {{ b }}", "{{date}} {{a}} {{", "{{}}"]),
+            vec!["a", "b"]
+        );
+
+        let mut values = builtin_values(today());
+        values.insert("city".to_string(), "Lisbon".to_string());
+        assert_eq!(substitute("{{city}} on {{weekday}}, {{ date }}", &values).unwrap(), "Lisbon on Friday, 2026-07-03");
+        assert_eq!(substitute("keep {{ open", &values).unwrap(), "keep {{ open");
+        assert!(substitute("{{unknown}}", &values).is_err());
+    }
+
+    #[test]
+    fn instantiates_task_with_checklist_subtasks() {
+        let conn = crate::database::test_conn();
+        let template_id = save_template(&conn, None, &request("Trip")).unwrap();
+        let template = load_template(&conn, template_id).unwrap();
+        assert_eq!(template.checklist, vec!["Passport", "Guidebook for {{destination}}"]);
+        assert_eq!(template.variables, vec!["destination"]);
+        assert_eq!(template.tags, vec!["Travel"]);
+        assert!(save_template(&conn, None, &request("trip")).is_err());
+
+        assert!(instantiate_sync(&conn, template_id, HashMap::new(), None, today()).is_err());
+        let variables = HashMap::from([("destination".to_string(), "Oslo".to_string())]);
+        let task_id = instantiate_sync(&conn, template_id, variables, Some("2026-07-10"), today()).unwrap();
+
+        let task = crate::fetch_task_sync(&conn, task_id).unwrap();
+        assert_eq!(task.title, "Pack for Oslo");
+        assert_eq!(task.description.as_deref(), Some("Leaving 2026-07-03"));
+        assert_eq!(task.tags, vec!["Travel"]);
+        assert!(task.complete_with_subtasks);
+
+        // Saving the instance as a template round-trips the checklist in order
+        let copy = load_template(&conn, save_template(&conn, None, &request_from_task(&conn, task_id, "Oslo trip").unwrap()).unwrap()).unwrap();
+        assert_eq!(copy.checklist, vec!["Passport", "Guidebook for Oslo"]);
+        assert_eq!((copy.difficulty, copy.estimated_minutes), (4, Some(45)));
+        assert!(copy.variables.is_empty());
+    }
+}
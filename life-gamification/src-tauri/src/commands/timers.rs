@@ -0,0 +1,457 @@
+// Crash-safe timers.
+//
+// active_timers rows survive a restart, so a timer left running over a crash
+// or a sleep would count hours nobody worked. While the app runs the backend's
+// idle check records a heartbeat every IDLE_CHECK_SECS (clients may also call
+// timer_heartbeat), and two guards use it:
+//
+// - On startup, a running timer whose last heartbeat is older than
+//   stale_after_minutes is held for review (needs_review): the pomodoro engine
+//   won't finish it and heartbeats no longer move it, until the user picks a
+//   StaleResolution.
+// - While running, a timer with no heartbeat for idle_pause_minutes (the
+//   machine slept or the process was suspended between two checks) is paused
+//   as of its last heartbeat, so the idle gap is not counted, and
+//   IDLE_PAUSE_EVENT is emitted.
+//
+// Paused time is written to time_sessions.paused_seconds when a timer stops and
+// is left out of the session's duration.
+
+use chrono::{DateTime, Duration, Utc};
+use rusqlite::{Connection, OptionalExtension};
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Emitter, Manager, State};
+
+use crate::commands::pomodoro;
+use crate::database::DbConnection;
+
+/// Event emitted to the frontend when a timer is paused for lack of heartbeats.
+pub const IDLE_PAUSE_EVENT: &str = "timer-auto-paused";
+
+/// How often the idle check runs.
+const IDLE_CHECK_SECS: u64 = 30;
+
+// ---------- Types ----------
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TimerSettings {
+    /// Pause a running timer after this long without a heartbeat (None = never).
+    pub idle_pause_minutes: Option<i64>,
+    /// A timer whose heartbeat is older than this on startup is held for review.
+    pub stale_after_minutes: i64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct StaleTimer {
+    pub timer_id: i64,
+    pub task_id: i64,
+    pub task_title: String,
+    pub session_id: i64,
+    pub start_time: String,
+    pub last_heartbeat_at: String,
+    /// Time between the last heartbeat and now.
+    pub unaccounted_seconds: i64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum StaleResolution {
+    /// Stop the timer as of its last heartbeat.
+    StopAtHeartbeat,
+    /// Count the whole gap and keep the timer running.
+    KeepFullDuration,
+    /// Throw the session away.
+    Discard,
+}
+
+/// The running timer's state, as far as this module cares.
+struct TimerRow {
+    id: i64,
+    session_id: i64,
+    start_time: DateTime<Utc>,
+    last_heartbeat: DateTime<Utc>,
+    is_paused: bool,
+    paused_at: Option<DateTime<Utc>>,
+    total_paused_seconds: i64,
+    needs_review: bool,
+}
+
+// ---------- Time accounting (pure functions, unit-tested) ----------
+
+/// Seconds a timer stopped at `end` spent paused, never more than it ran.
+pub fn paused_seconds(
+    start: DateTime<Utc>,
+    end: DateTime<Utc>,
+    total_paused_seconds: i64,
+    paused_at: Option<DateTime<Utc>>,
+) -> i64 {
+    let current_pause = paused_at.map_or(0, |paused_at| (end - paused_at).num_seconds().max(0));
+    (total_paused_seconds + current_pause).clamp(0, (end - start).num_seconds().max(0))
+}
+
+/// Whether a heartbeat last seen at `last_heartbeat` is older than `minutes`.
+pub fn silent_for(last_heartbeat: DateTime<Utc>, now: DateTime<Utc>, minutes: i64) -> bool {
+    now - last_heartbeat >= Duration::minutes(minutes)
+}
+
+fn parse_time(value: &str) -> Option<DateTime<Utc>> {
+    DateTime::parse_from_rfc3339(value).ok().map(|t| t.with_timezone(&Utc))
+}
+
+// ---------- Persistence ----------
+
+pub fn load_settings(conn: &Connection) -> Result<TimerSettings, String> {
+    conn.query_row(
+        "SELECT idle_pause_minutes, stale_after_minutes FROM timer_settings WHERE id = 1",
+        [],
+        |row| Ok(TimerSettings { idle_pause_minutes: row.get(0)?, stale_after_minutes: row.get(1)? }),
+    )
+    .map_err(|e| format!("Failed to load timer settings: {}", e))
+}
+
+fn load_timer(conn: &Connection) -> Result<Option<TimerRow>, String> {
+    let row = conn
+        .query_row(
+            "SELECT id, session_id, start_time, COALESCE(last_heartbeat_at, start_time), is_paused, paused_at,
+                    COALESCE(total_paused_seconds, 0), needs_review
+             FROM active_timers WHERE user_id = 1",
+            [],
+            |row| {
+                Ok((
+                    row.get::<_, i64>(0)?,
+                    row.get::<_, i64>(1)?,
+                    row.get::<_, String>(2)?,
+                    row.get::<_, String>(3)?,
+                    row.get::<_, bool>(4)?,
+                    row.get::<_, Option<String>>(5)?,
+                    row.get::<_, i64>(6)?,
+                    row.get::<_, bool>(7)?,
+                ))
+            },
+        )
+        .optional()
+        .map_err(|e| format!("Failed to get active timer: {}", e))?;
+    let Some((id, session_id, start, heartbeat, is_paused, paused_at, total_paused_seconds, needs_review)) = row else {
+        return Ok(None);
+    };
+    let start_time = parse_time(&start).ok_or_else(|| format!("Invalid timer start: {}", start))?;
+    Ok(Some(TimerRow {
+        id,
+        session_id,
+        start_time,
+        last_heartbeat: parse_time(&heartbeat).unwrap_or(start_time),
+        is_paused,
+        paused_at: paused_at.as_deref().and_then(parse_time),
+        total_paused_seconds,
+        needs_review,
+    }))
+}
+
+/// Stop the running timer as of `end`, leaving paused time out of the session.
+/// Returns the session id.
+pub fn stop_active_timer(conn: &Connection, end: DateTime<Utc>, notes: Option<&str>) -> Result<i64, String> {
+    let timer = load_timer(conn)?.ok_or_else(|| "No active timer found".to_string())?;
+    let paused = paused_seconds(timer.start_time, end, timer.total_paused_seconds, timer.paused_at.filter(|_| timer.is_paused));
+    let end_str = end.to_rfc3339();
+
+    conn.execute(
+        "UPDATE time_sessions SET end_time = ?1, notes = COALESCE(?2, notes), paused_seconds = ?3 WHERE id = ?4",
+        rusqlite::params![end_str, notes, paused, timer.session_id],
+    )
+    .map_err(|e| format!("Failed to update session: {}", e))?;
+    conn.execute("DELETE FROM active_timers WHERE id = ?1", [timer.id])
+        .map_err(|e| format!("Failed to delete timer: {}", e))?;
+    pomodoro::abandon_work(conn, timer.session_id, &end_str)?;
+    Ok(timer.session_id)
+}
+
+/// Pick a paused timer back up, adding the pause to its paused total.
+pub fn resume_sync(conn: &Connection, now: DateTime<Utc>) -> Result<i64, String> {
+    let timer = load_timer(conn)?.ok_or_else(|| "No active timer found".to_string())?;
+    if !timer.is_paused {
+        return Err("The timer is not paused".to_string());
+    }
+    let paused = timer.paused_at.map_or(0, |paused_at| (now - paused_at).num_seconds().max(0));
+    conn.execute(
+        "UPDATE active_timers SET is_paused = 0, paused_at = NULL, auto_paused = 0,
+                total_paused_seconds = COALESCE(total_paused_seconds, 0) + ?1, last_heartbeat_at = ?2
+         WHERE id = ?3",
+        rusqlite::params![paused, now.to_rfc3339(), timer.id],
+    )
+    .map_err(|e| format!("Failed to resume timer: {}", e))?;
+    Ok(timer.id)
+}
+
+pub fn heartbeat_sync(conn: &Connection, now: DateTime<Utc>) -> Result<(), String> {
+    conn.execute(
+        "UPDATE active_timers SET last_heartbeat_at = ?1 WHERE user_id = 1 AND needs_review = 0",
+        [now.to_rfc3339()],
+    )
+    .map_err(|e| format!("Failed to record heartbeat: {}", e))?;
+    Ok(())
+}
+
+/// Hold a running timer whose heartbeat went stale while the app was down.
+/// Returns whether one was flagged.
+pub fn flag_stale_sync(conn: &Connection, now: DateTime<Utc>) -> Result<bool, String> {
+    let settings = load_settings(conn)?;
+    let Some(timer) = load_timer(conn)? else { return Ok(false) };
+    if timer.is_paused || timer.needs_review || !silent_for(timer.last_heartbeat, now, settings.stale_after_minutes) {
+        return Ok(false);
+    }
+    conn.execute("UPDATE active_timers SET needs_review = 1 WHERE id = ?1", [timer.id])
+        .map_err(|e| format!("Failed to flag stale timer: {}", e))?;
+    Ok(true)
+}
+
+/// Pause a running timer that has gone without heartbeats for too long, as of
+/// its last heartbeat. Returns the paused timer's id.
+pub fn idle_pause_sync(conn: &Connection, now: DateTime<Utc>) -> Result<Option<i64>, String> {
+    let Some(idle_minutes) = load_settings(conn)?.idle_pause_minutes else { return Ok(None) };
+    let Some(timer) = load_timer(conn)? else { return Ok(None) };
+    if timer.is_paused || timer.needs_review || !silent_for(timer.last_heartbeat, now, idle_minutes) {
+        return Ok(None);
+    }
+    conn.execute(
+        "UPDATE active_timers SET is_paused = 1, paused_at = ?1, auto_paused = 1 WHERE id = ?2",
+        rusqlite::params![timer.last_heartbeat.to_rfc3339(), timer.id],
+    )
+    .map_err(|e| format!("Failed to pause idle timer: {}", e))?;
+    Ok(Some(timer.id))
+}
+
+/// One tick of the background idle check: pause the timer if the gap since the
+/// last heartbeat is too long, otherwise record a heartbeat for this tick.
+pub fn idle_check_sync(conn: &Connection, now: DateTime<Utc>) -> Result<Option<i64>, String> {
+    let paused = idle_pause_sync(conn, now)?;
+    if paused.is_none() {
+        heartbeat_sync(conn, now)?;
+    }
+    Ok(paused)
+}
+
+pub fn stale_timer(conn: &Connection, now: DateTime<Utc>) -> Result<Option<StaleTimer>, String> {
+    let Some(timer) = load_timer(conn)?.filter(|timer| timer.needs_review) else { return Ok(None) };
+    let (task_id, task_title): (i64, String) = conn
+        .query_row(
+            "SELECT t.id, t.title FROM active_timers a JOIN tasks t ON t.id = a.task_id WHERE a.id = ?1",
+            [timer.id],
+            |row| Ok((row.get(0)?, row.get(1)?)),
+        )
+        .map_err(|e| format!("Failed to get timer task: {}", e))?;
+    Ok(Some(StaleTimer {
+        timer_id: timer.id,
+        task_id,
+        task_title,
+        session_id: timer.session_id,
+        start_time: timer.start_time.to_rfc3339(),
+        last_heartbeat_at: timer.last_heartbeat.to_rfc3339(),
+        unaccounted_seconds: (now - timer.last_heartbeat).num_seconds().max(0),
+    }))
+}
+
+pub fn resolve_stale_sync(conn: &Connection, now: DateTime<Utc>, resolution: StaleResolution) -> Result<(), String> {
+    let timer = load_timer(conn)?
+        .filter(|timer| timer.needs_review)
+        .ok_or_else(|| "No timer is waiting for review".to_string())?;
+
+    let discard = |conn: &Connection| -> Result<(), String> {
+        conn.execute("DELETE FROM active_timers WHERE id = ?1", [timer.id])
+            .map_err(|e| format!("Failed to delete timer: {}", e))?;
+        conn.execute("DELETE FROM time_sessions WHERE id = ?1", [timer.session_id])
+            .map_err(|e| format!("Failed to delete session: {}", e))?;
+        pomodoro::abandon_work(conn, timer.session_id, &now.to_rfc3339())
+    };
+
+    match resolution {
+        StaleResolution::StopAtHeartbeat if timer.last_heartbeat <= timer.start_time => discard(conn),
+        StaleResolution::StopAtHeartbeat => stop_active_timer(conn, timer.last_heartbeat, None).map(|_| ()),
+        StaleResolution::KeepFullDuration => {
+            conn.execute(
+                "UPDATE active_timers SET needs_review = 0, last_heartbeat_at = ?1 WHERE id = ?2",
+                rusqlite::params![now.to_rfc3339(), timer.id],
+            )
+            .map_err(|e| format!("Failed to keep timer: {}", e))?;
+            Ok(())
+        }
+        StaleResolution::Discard => discard(conn),
+    }
+}
+
+/// Check for idle timers in the background, sending the heartbeat while the
+/// process is alive.
+pub fn schedule_idle_checks(app: AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        let mut interval = tokio::time::interval(tokio::time::Duration::from_secs(IDLE_CHECK_SECS));
+        interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+        loop {
+            interval.tick().await;
+            let db = app.state::<DbConnection>();
+            let paused = {
+                let conn = db.lock().await;
+                idle_check_sync(&conn, Utc::now())
+            };
+            match paused {
+                Ok(Some(timer_id)) => {
+                    if let Err(e) = app.emit(IDLE_PAUSE_EVENT, timer_id) {
+                        eprintln!("Failed to emit idle pause event: {}", e);
+                    }
+                }
+                Ok(None) => {}
+                Err(e) => eprintln!("Idle timer check failed: {}", e),
+            }
+        }
+    });
+}
+
+// ---------- Commands ----------
+
+#[tauri::command]
+pub async fn timer_heartbeat(db: State<'_, DbConnection>) -> Result<(), String> {
+    let conn = db.lock().await;
+    heartbeat_sync(&conn, Utc::now())
+}
+
+/// The timer left running over a restart, if it is waiting for review.
+#[tauri::command]
+pub async fn get_stale_timer(db: State<'_, DbConnection>) -> Result<Option<StaleTimer>, String> {
+    let conn = db.lock().await;
+    stale_timer(&conn, Utc::now())
+}
+
+#[tauri::command]
+pub async fn resolve_stale_timer(db: State<'_, DbConnection>, resolution: StaleResolution) -> Result<(), String> {
+    let conn = db.lock().await;
+    let tx = conn.unchecked_transaction()
+        .map_err(|e| format!("Failed to begin transaction: {}", e))?;
+    resolve_stale_sync(&tx, Utc::now(), resolution)?;
+    tx.commit()
+        .map_err(|e| format!("Failed to commit: {}", e))
+}
+
+#[tauri::command]
+pub async fn get_timer_settings(db: State<'_, DbConnection>) -> Result<TimerSettings, String> {
+    let conn = db.lock().await;
+    load_settings(&conn)
+}
+
+#[tauri::command]
+pub async fn update_timer_settings(db: State<'_, DbConnection>, settings: TimerSettings) -> Result<TimerSettings, String> {
+    if settings.idle_pause_minutes.is_some_and(|minutes| minutes < 1) || settings.stale_after_minutes < 1 {
+        return Err("Timer thresholds must be at least a minute".to_string());
+    }
+    let conn = db.lock().await;
+    conn.execute(
+        "UPDATE timer_settings SET idle_pause_minutes = ?1, stale_after_minutes = ?2, updated_at = CURRENT_TIMESTAMP
+         WHERE id = 1",
+        rusqlite::params![settings.idle_pause_minutes, settings.stale_after_minutes],
+    )
+    .map_err(|e| format!("Failed to update timer settings: {}", e))?;
+    load_settings(&conn)
+}
+
+// ---------- Tests ----------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(minutes: i64) -> DateTime<Utc> {
+        parse_time("2026-05-04T09:00:00+00:00").unwrap() + Duration::minutes(minutes)
+    }
+
+    fn test_conn() -> Connection {
+        let conn = crate::database::test_conn();
+        let start = at(0).to_rfc3339();
+        conn.execute_batch(&format!(
+            "INSERT INTO tasks (id, user_id, title, status) VALUES (1, 1, 'Write', 'active');
+             INSERT INTO time_sessions (id, task_id, user_id, start_time, session_type) VALUES (7, 1, 1, '{start}', 'focus');
+             INSERT INTO active_timers (task_id, user_id, session_id, start_time) VALUES (1, 1, 7, '{start}');"
+        ))
+        .unwrap();
+        conn
+    }
+
+    fn session(conn: &Connection) -> Option<(i64, i64)> {
+        conn.query_row("SELECT duration_seconds, paused_seconds FROM time_sessions WHERE id = 7", [], |row| {
+            Ok((row.get(0)?, row.get(1)?))
+        })
+        .optional()
+        .unwrap()
+    }
+
+    #[test]
+    fn paused_time_is_capped_by_the_run() {
+        assert_eq!(paused_seconds(at(0), at(30), 300, None), 300);
+        assert_eq!(paused_seconds(at(0), at(30), 300, Some(at(20))), 900);
+        assert_eq!(paused_seconds(at(0), at(30), 3000, None), 1800);
+        assert!(silent_for(at(0), at(10), 10));
+        assert!(!silent_for(at(0), at(9), 10));
+    }
+
+    #[test]
+    fn idle_timers_pause_at_their_last_heartbeat() {
+        let conn = test_conn();
+        heartbeat_sync(&conn, at(20)).unwrap();
+        assert_eq!(idle_pause_sync(&conn, at(29)).unwrap(), None);
+        assert!(idle_pause_sync(&conn, at(30)).unwrap().is_some());
+
+        // Back after the gap: the idle ten minutes are left out of the session
+        resume_sync(&conn, at(30)).unwrap();
+        stop_active_timer(&conn, at(40), None).unwrap();
+        let (duration, paused) = session(&conn).unwrap();
+        assert_eq!(paused, 600);
+        assert!((duration - 1800).abs() <= 1, "duration {}", duration);
+    }
+
+    #[test]
+    fn the_idle_check_keeps_a_running_timer_alive() {
+        let conn = test_conn();
+        // Ticking every half minute, the timer never goes idle
+        for tick in 1..=60 {
+            assert_eq!(idle_check_sync(&conn, at(0) + Duration::seconds(30 * tick)).unwrap(), None);
+        }
+        assert!(!flag_stale_sync(&conn, at(32)).unwrap());
+
+        // A suspend between two ticks pauses it as of the tick before
+        assert!(idle_check_sync(&conn, at(75)).unwrap().is_some());
+        resume_sync(&conn, at(75)).unwrap();
+        stop_active_timer(&conn, at(80), None).unwrap();
+        let (duration, paused) = session(&conn).unwrap();
+        assert_eq!(paused, 45 * 60);
+        assert!((duration - 35 * 60).abs() <= 1, "duration {}", duration);
+    }
+
+    #[test]
+    fn stale_timers_wait_for_a_resolution() {
+        let conn = test_conn();
+        heartbeat_sync(&conn, at(15)).unwrap();
+        assert!(!flag_stale_sync(&conn, at(19)).unwrap());
+        assert!(flag_stale_sync(&conn, at(240)).unwrap());
+
+        // Held: heartbeats and the idle check leave it alone
+        heartbeat_sync(&conn, at(241)).unwrap();
+        assert_eq!(idle_pause_sync(&conn, at(300)).unwrap(), None);
+        let stale = stale_timer(&conn, at(241)).unwrap().unwrap();
+        assert_eq!((stale.task_title.as_str(), stale.unaccounted_seconds), ("Write", 226 * 60));
+
+        resolve_stale_sync(&conn, at(241), StaleResolution::StopAtHeartbeat).unwrap();
+        let (duration, _) = session(&conn).unwrap();
+        assert!((duration - 900).abs() <= 1, "duration {}", duration);
+        assert!(resolve_stale_sync(&conn, at(241), StaleResolution::Discard).is_err());
+    }
+
+    #[test]
+    fn stale_timers_can_be_kept_or_discarded() {
+        let conn = test_conn();
+        assert!(flag_stale_sync(&conn, at(60)).unwrap());
+        resolve_stale_sync(&conn, at(60), StaleResolution::KeepFullDuration).unwrap();
+        assert!(stale_timer(&conn, at(60)).unwrap().is_none());
+        assert_eq!(idle_pause_sync(&conn, at(65)).unwrap(), None);
+
+        assert!(flag_stale_sync(&conn, at(120)).unwrap());
+        resolve_stale_sync(&conn, at(120), StaleResolution::Discard).unwrap();
+        assert!(session(&conn).is_none());
+        assert!(load_timer(&conn).unwrap().is_none());
+    }
+}
@@ -0,0 +1,472 @@
+// Timesheets.
+//
+// Groups finished time sessions (breaks excluded) into day x project x task
+// entries for a date range, optionally rounding each entry to an increment
+// (e.g. up to the next 15 minutes) the way client invoices usually are. A
+// session counts towards the local day it started on. The same range can be
+// exported as CSV (the grouped entries) or as an iCalendar file with one event
+// per session; exports are returned as text for the frontend to save, like the
+// finance CSV import takes its file content as text.
+
+use std::collections::BTreeMap;
+
+use chrono::{DateTime, Duration, Local, NaiveDate, TimeZone, Utc};
+use rusqlite::Connection;
+use serde::{Deserialize, Serialize};
+use tauri::State;
+
+use crate::commands::recurrence::parse_date;
+use crate::commands::sessions::parse_time;
+use crate::database::DbConnection;
+
+// ---------- Types ----------
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RoundingMode {
+    Nearest,
+    Up,
+    Down,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Rounding {
+    pub increment_minutes: i64,
+    pub mode: RoundingMode,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct TimesheetRequest {
+    /// Inclusive local dates.
+    pub start_date: String,
+    pub end_date: String,
+    pub project_id: Option<i64>,
+    /// Applied to each entry; None keeps exact minutes.
+    pub rounding: Option<Rounding>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct TimesheetEntry {
+    pub date: String,
+    pub project_id: Option<i64>,
+    pub project_name: Option<String>,
+    pub task_id: i64,
+    pub task_title: String,
+    pub sessions: i64,
+    pub actual_seconds: i64,
+    pub rounded_minutes: i64,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ProjectTotal {
+    pub project_id: Option<i64>,
+    pub project_name: Option<String>,
+    pub actual_seconds: i64,
+    pub rounded_minutes: i64,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Timesheet {
+    pub start_date: String,
+    pub end_date: String,
+    pub entries: Vec<TimesheetEntry>,
+    pub projects: Vec<ProjectTotal>,
+    pub total_actual_seconds: i64,
+    pub total_rounded_minutes: i64,
+}
+
+/// One finished session with what a timesheet needs to know about it.
+#[derive(Debug, Clone)]
+pub struct SessionLine {
+    pub id: i64,
+    pub task_id: i64,
+    pub task_title: String,
+    pub project_id: Option<i64>,
+    pub project_name: Option<String>,
+    pub start: DateTime<Utc>,
+    pub end: DateTime<Utc>,
+    pub seconds: i64,
+    pub notes: Option<String>,
+}
+
+// ---------- Grouping and formatting (pure functions, unit-tested) ----------
+
+/// Minutes billed for `seconds` worked under `rounding`.
+pub fn round_minutes(seconds: i64, rounding: Option<Rounding>) -> i64 {
+    let Some(rounding) = rounding.filter(|r| r.increment_minutes > 0) else {
+        return (seconds as f64 / 60.0).round() as i64;
+    };
+    let increment = rounding.increment_minutes * 60;
+    let steps = match rounding.mode {
+        RoundingMode::Nearest => (seconds as f64 / increment as f64).round() as i64,
+        RoundingMode::Up => (seconds + increment - 1).div_euclid(increment),
+        RoundingMode::Down => seconds.div_euclid(increment),
+    };
+    steps * rounding.increment_minutes
+}
+
+/// Group sessions into entries for the local days `start..=end` in `tz`.
+pub fn build_timesheet<Tz: TimeZone>(
+    sessions: &[SessionLine],
+    start: NaiveDate,
+    end: NaiveDate,
+    rounding: Option<Rounding>,
+    tz: &Tz,
+) -> Timesheet {
+    let mut grouped: BTreeMap<(NaiveDate, Option<String>, String, i64), TimesheetEntry> = BTreeMap::new();
+    for session in sessions {
+        let day = session.start.with_timezone(tz).date_naive();
+        if day < start || day > end {
+            continue;
+        }
+        let key = (day, session.project_name.clone(), session.task_title.clone(), session.task_id);
+        let entry = grouped.entry(key).or_insert_with(|| TimesheetEntry {
+            date: day.format("%Y-%m-%d").to_string(),
+            project_id: session.project_id,
+            project_name: session.project_name.clone(),
+            task_id: session.task_id,
+            task_title: session.task_title.clone(),
+            sessions: 0,
+            actual_seconds: 0,
+            rounded_minutes: 0,
+        });
+        entry.sessions += 1;
+        entry.actual_seconds += session.seconds;
+    }
+
+    let mut entries: Vec<TimesheetEntry> = grouped.into_values().collect();
+    let mut projects: Vec<ProjectTotal> = Vec::new();
+    for entry in &mut entries {
+        entry.rounded_minutes = round_minutes(entry.actual_seconds, rounding);
+        match projects.iter_mut().find(|p| p.project_id == entry.project_id) {
+            Some(total) => {
+                total.actual_seconds += entry.actual_seconds;
+                total.rounded_minutes += entry.rounded_minutes;
+            }
+            None => projects.push(ProjectTotal {
+                project_id: entry.project_id,
+                project_name: entry.project_name.clone(),
+                actual_seconds: entry.actual_seconds,
+                rounded_minutes: entry.rounded_minutes,
+            }),
+        }
+    }
+    projects.sort_by(|a, b| b.rounded_minutes.cmp(&a.rounded_minutes).then(a.project_name.cmp(&b.project_name)));
+
+    Timesheet {
+        start_date: start.format("%Y-%m-%d").to_string(),
+        end_date: end.format("%Y-%m-%d").to_string(),
+        total_actual_seconds: entries.iter().map(|e| e.actual_seconds).sum(),
+        total_rounded_minutes: entries.iter().map(|e| e.rounded_minutes).sum(),
+        entries,
+        projects,
+    }
+}
+
+pub fn timesheet_csv(timesheet: &Timesheet) -> Result<String, String> {
+    let mut writer = csv::Writer::from_writer(Vec::new());
+    writer
+        .write_record(["Date", "Project", "Task", "Sessions", "Actual minutes", "Billed minutes", "Billed hours"])
+        .map_err(|e| format!("Failed to write CSV: {}", e))?;
+    for entry in &timesheet.entries {
+        writer
+            .write_record([
+                entry.date.clone(),
+                entry.project_name.clone().unwrap_or_default(),
+                entry.task_title.clone(),
+                entry.sessions.to_string(),
+                format!("{:.1}", entry.actual_seconds as f64 / 60.0),
+                entry.rounded_minutes.to_string(),
+                format!("{:.2}", entry.rounded_minutes as f64 / 60.0),
+            ])
+            .map_err(|e| format!("Failed to write CSV: {}", e))?;
+    }
+    let bytes = writer.into_inner().map_err(|e| format!("Failed to write CSV: {}", e))?;
+    String::from_utf8(bytes).map_err(|e| format!("Failed to write CSV: {}", e))
+}
+
+/// Escape a TEXT value (RFC 5545 section 3.3.11).
+fn ical_text(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace(';', "\\;")
+        .replace(',', "\\,")
+        .replace("\r\n", "\\n")
+        .replace('\n', "\\n")
+}
+
+/// Fold a content line at 75 octets (RFC 5545 section 3.1).
+fn ical_fold(line: &str) -> String {
+    let mut folded = String::new();
+    let mut width = 0;
+    for c in line.chars() {
+        if width + c.len_utf8() > 75 {
+            folded.push_str("\r\n ");
+            width = 1;
+        }
+        folded.push(c);
+        width += c.len_utf8();
+    }
+    folded
+}
+
+fn ical_time(time: DateTime<Utc>) -> String {
+    time.format("%Y%m%dT%H%M%SZ").to_string()
+}
+
+pub fn sessions_ical(sessions: &[SessionLine], stamp: DateTime<Utc>) -> String {
+    let mut lines = vec![
+        "BEGIN:VCALENDAR".to_string(),
+        "VERSION:2.0".to_string(),
+        "PRODID:-//Life Gamification//Timesheet//EN".to_string(),
+        "CALSCALE:GREGORIAN".to_string(),
+    ];
+    for session in sessions {
+        let summary = match &session.project_name {
+            Some(project) => format!("{} ({})", session.task_title, project),
+            None => session.task_title.clone(),
+        };
+        lines.push("BEGIN:VEVENT".to_string());
+        lines.push(format!("UID:time-session-{}@life-gamification", session.id));
+        lines.push(format!("DTSTAMP:{}", ical_time(stamp)));
+        lines.push(format!("DTSTART:{}", ical_time(session.start)));
+        lines.push(format!("DTEND:{}", ical_time(session.end)));
+        lines.push(format!("SUMMARY:{}", ical_text(&summary)));
+        if let Some(project) = &session.project_name {
+            lines.push(format!("CATEGORIES:{}", ical_text(project)));
+        }
+        let worked = format!("Worked {} min", (session.seconds as f64 / 60.0).round() as i64);
+        let description = match session.notes.as_deref().filter(|n| !n.is_empty()) {
+            Some(notes) => format!("{}\n{}", worked, notes),
+            None => worked,
+        };
+        lines.push(format!("DESCRIPTION:{}", ical_text(&description)));
+        lines.push("END:VEVENT".to_string());
+    }
+    lines.push("END:VCALENDAR".to_string());
+    lines.iter().map(|line| ical_fold(line) + "\r\n").collect()
+}
+
+// ---------- Persistence ----------
+
+fn parse_range(request: &TimesheetRequest) -> Result<(NaiveDate, NaiveDate), String> {
+    let start = parse_date(&request.start_date).ok_or_else(|| format!("Invalid start date: {}", request.start_date))?;
+    let end = parse_date(&request.end_date).ok_or_else(|| format!("Invalid end date: {}", request.end_date))?;
+    if end < start {
+        return Err("The end date is before the start date".to_string());
+    }
+    if let Some(rounding) = request.rounding {
+        if rounding.increment_minutes < 1 {
+            return Err("Round to at least one minute".to_string());
+        }
+    }
+    Ok((start, end))
+}
+
+/// Finished, non-break sessions starting around `start..=end` (a day either
+/// side, so every time zone's local days are covered), oldest first.
+pub fn load_sessions(conn: &Connection, start: NaiveDate, end: NaiveDate, project_id: Option<i64>) -> Result<Vec<SessionLine>, String> {
+    let mut stmt = conn
+        .prepare(
+            "SELECT s.id, s.task_id, COALESCE(t.title, 'Deleted task'), t.project_id, p.name,
+                    s.start_time, s.end_time, COALESCE(s.duration_seconds, 0), s.notes
+             FROM time_sessions s
+             LEFT JOIN tasks t ON t.id = s.task_id
+             LEFT JOIN projects p ON p.id = t.project_id
+             WHERE s.user_id = 1 AND s.end_time IS NOT NULL AND s.session_type != 'break' AND t.deleted_at IS NULL
+               AND julianday(s.start_time) >= julianday(?1) AND julianday(s.start_time) < julianday(?2)
+               AND (?3 IS NULL OR t.project_id = ?3)
+             ORDER BY julianday(s.start_time)",
+        )
+        .map_err(|e| format!("Failed to prepare sessions query: {}", e))?;
+    let rows = stmt
+        .query_map(
+            rusqlite::params![
+                (start - Duration::days(1)).format("%Y-%m-%d").to_string(),
+                (end + Duration::days(2)).format("%Y-%m-%d").to_string(),
+                project_id,
+            ],
+            |row| {
+                Ok((
+                    row.get::<_, i64>(0)?,
+                    row.get::<_, i64>(1)?,
+                    row.get::<_, String>(2)?,
+                    row.get::<_, Option<i64>>(3)?,
+                    row.get::<_, Option<String>>(4)?,
+                    row.get::<_, String>(5)?,
+                    row.get::<_, String>(6)?,
+                    row.get::<_, i64>(7)?,
+                    row.get::<_, Option<String>>(8)?,
+                ))
+            },
+        )
+        .map_err(|e| format!("Failed to get sessions: {}", e))?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| format!("Failed to read sessions: {}", e))?;
+
+    Ok(rows
+        .into_iter()
+        .filter_map(|(id, task_id, task_title, project_id, project_name, start, end, seconds, notes)| {
+            Some(SessionLine {
+                id,
+                task_id,
+                task_title,
+                project_id,
+                project_name,
+                start: parse_time(&start)?,
+                end: parse_time(&end)?,
+                seconds,
+                notes,
+            })
+        })
+        .collect())
+}
+
+/// The sessions of the request's local days.
+fn sessions_in_range<Tz: TimeZone>(conn: &Connection, request: &TimesheetRequest, tz: &Tz) -> Result<Vec<SessionLine>, String> {
+    let (start, end) = parse_range(request)?;
+    let mut sessions = load_sessions(conn, start, end, request.project_id)?;
+    sessions.retain(|s| {
+        let day = s.start.with_timezone(tz).date_naive();
+        day >= start && day <= end
+    });
+    Ok(sessions)
+}
+
+// ---------- Commands ----------
+
+#[tauri::command]
+pub async fn get_timesheet(db: State<'_, DbConnection>, request: TimesheetRequest) -> Result<Timesheet, String> {
+    let (start, end) = parse_range(&request)?;
+    let conn = db.lock().await;
+    let sessions = load_sessions(&conn, start, end, request.project_id)?;
+    Ok(build_timesheet(&sessions, start, end, request.rounding, &Local))
+}
+
+/// The timesheet as CSV text.
+#[tauri::command]
+pub async fn export_timesheet_csv(db: State<'_, DbConnection>, request: TimesheetRequest) -> Result<String, String> {
+    let (start, end) = parse_range(&request)?;
+    let conn = db.lock().await;
+    let sessions = load_sessions(&conn, start, end, request.project_id)?;
+    timesheet_csv(&build_timesheet(&sessions, start, end, request.rounding, &Local))
+}
+
+/// The range's sessions as iCalendar (.ics) text, one event per session.
+#[tauri::command]
+pub async fn export_sessions_ical(db: State<'_, DbConnection>, request: TimesheetRequest) -> Result<String, String> {
+    let conn = db.lock().await;
+    let sessions = sessions_in_range(&conn, &request, &Local)?;
+    Ok(sessions_ical(&sessions, Utc::now()))
+}
+
+// ---------- Tests ----------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn d(s: &str) -> NaiveDate {
+        NaiveDate::parse_from_str(s, "%Y-%m-%d").unwrap()
+    }
+
+    fn line(id: i64, task_id: i64, project: Option<&str>, start: &str, minutes: i64) -> SessionLine {
+        let start = parse_time(start).unwrap();
+        SessionLine {
+            id,
+            task_id,
+            task_title: format!("Task {}", task_id),
+            project_id: project.map(|_| 100),
+            project_name: project.map(str::to_string),
+            start,
+            end: start + Duration::minutes(minutes),
+            seconds: minutes * 60,
+            notes: None,
+        }
+    }
+
+    fn quarter(mode: RoundingMode) -> Option<Rounding> {
+        Some(Rounding { increment_minutes: 15, mode })
+    }
+
+    #[test]
+    fn rounds_to_increments() {
+        assert_eq!(round_minutes(20 * 60, None), 20);
+        assert_eq!(round_minutes(20 * 60, quarter(RoundingMode::Nearest)), 15);
+        assert_eq!(round_minutes(23 * 60, quarter(RoundingMode::Nearest)), 30);
+        assert_eq!(round_minutes(16 * 60, quarter(RoundingMode::Up)), 30);
+        assert_eq!(round_minutes(15 * 60, quarter(RoundingMode::Up)), 15);
+        assert_eq!(round_minutes(29 * 60, quarter(RoundingMode::Down)), 15);
+    }
+
+    #[test]
+    fn groups_by_day_project_and_task() {
+        let sessions = vec![
+            line(1, 1, Some("Client"), "2026-05-04T09:00:00Z", 20),
+            line(2, 1, Some("Client"), "2026-05-04T14:00:00Z", 20),
+            line(3, 2, None, "2026-05-04T15:00:00Z", 50),
+            line(4, 1, Some("Client"), "2026-05-05T23:50:00Z", 30),
+            line(5, 1, Some("Client"), "2026-05-07T09:00:00Z", 30),
+        ];
+        let sheet = build_timesheet(&sessions, d("2026-05-04"), d("2026-05-05"), quarter(RoundingMode::Up), &Utc);
+        let entries: Vec<(&str, i64, i64, i64)> = sheet
+            .entries
+            .iter()
+            .map(|e| (e.date.as_str(), e.task_id, e.sessions, e.rounded_minutes))
+            .collect();
+        // Sessions are rounded per entry, not one by one; a session counts on the day it started
+        assert_eq!(entries, vec![("2026-05-04", 2, 1, 60), ("2026-05-04", 1, 2, 45), ("2026-05-05", 1, 1, 30)]);
+        assert_eq!((sheet.total_actual_seconds, sheet.total_rounded_minutes), (120 * 60, 135));
+        assert_eq!(sheet.projects[0].project_name.as_deref(), Some("Client"));
+        assert_eq!(sheet.projects[0].rounded_minutes, 75);
+
+        let csv = timesheet_csv(&sheet).unwrap();
+        assert!(csv.starts_with("Date,Project,Task,Sessions,Actual minutes,Billed minutes,Billed hours\n"));
+        assert!(csv.contains("2026-05-04,Client,Task 1,2,40.0,45,0.75\n"));
+    }
+
+    #[test]
+    fn exports_sessions_as_icalendar() {
+        let mut session = line(7, 1, Some("Acme, Inc"), "2026-05-04T09:00:00Z", 45);
+        session.notes = Some("Fixed bug; wrote tests".to_string());
+        let ics = sessions_ical(&[session], parse_time("2026-05-08T12:00:00Z").unwrap());
+        assert!(ics.starts_with("BEGIN:VCALENDAR\r\nVERSION:2.0\r\n"));
+        assert!(ics.contains("UID:time-session-7@life-gamification\r\n"));
+        assert!(ics.contains("DTSTART:20260504T090000Z\r\nDTEND:20260504T094500Z\r\n"));
+        assert!(ics.contains("SUMMARY:Task 1 (Acme\\, Inc)\r\n"));
+        assert!(ics.contains("DESCRIPTION:Worked 45 min\\nFixed bug\\; wrote tests\r\n"));
+        assert!(ics.ends_with("END:VCALENDAR\r\n"));
+        assert!(ical_fold(&"x".repeat(100)).contains("\r\n "));
+    }
+
+    #[test]
+    fn loads_finished_work_sessions_for_a_project() {
+        let conn = crate::database::test_conn();
+        conn.execute_batch(
+            "INSERT INTO projects (id, name) VALUES (100, 'Client');
+             INSERT INTO tasks (id, user_id, title, status, project_id) VALUES (1, 1, 'Build', 'active', 100);
+             INSERT INTO tasks (id, user_id, title, status) VALUES (2, 1, 'Chores', 'active');
+             INSERT INTO time_sessions (task_id, user_id, start_time, end_time, duration_seconds, session_type) VALUES
+                 (1, 1, '2026-05-04T09:00:00+00:00', '2026-05-04T10:00:00+00:00', 3600, 'focus'),
+                 (1, 1, '2026-05-04T10:00:00+00:00', '2026-05-04T10:05:00+00:00', 300, 'break'),
+                 (1, 1, '2026-05-04 11:00:00', '2026-05-04 11:30:00', 1800, 'manual'),
+                 (2, 1, '2026-05-04T12:00:00+00:00', '2026-05-04T13:00:00+00:00', 3600, 'focus'),
+                 (1, 1, '2026-05-04T14:00:00+00:00', NULL, NULL, 'focus');",
+        )
+        .unwrap();
+
+        let request = TimesheetRequest {
+            start_date: "2026-05-04".to_string(),
+            end_date: "2026-05-04".to_string(),
+            project_id: Some(100),
+            rounding: None,
+        };
+        let sessions = sessions_in_range(&conn, &request, &Utc).unwrap();
+        let seconds: Vec<i64> = sessions.iter().map(|s| s.seconds).collect();
+        assert_eq!(seconds, vec![3600, 1800]);
+        assert_eq!(sessions[0].project_name.as_deref(), Some("Client"));
+
+        let all = load_sessions(&conn, d("2026-05-04"), d("2026-05-04"), None).unwrap();
+        assert_eq!(all.len(), 3);
+    }
+}
@@ -0,0 +1,601 @@
+// Trash and undo.
+//
+// delete_project, finance_delete_account and bulk task deletes move rows to the
+// trash by setting deleted_at; every listing skips trashed rows (NOT_TRASHED for
+// tasks). A task goes to the trash with its subtasks and recurring instances.
+// list_trash / restore_from_trash bring them back, and anything trashed longer
+// than trash_settings.retention_days is purged for good by an hourly job (tasks
+// of a purged project are left without a project, a purged account takes its
+// transactions with it, a purged task takes its sessions, tags and history).
+//
+// Destructive commands also return an undo token. It stays redeemable through
+// undo_action for UNDO_WINDOW_SECS, long enough for the UI's "Undo" toast, and
+// reverses exactly that one operation: a restore from the trash, or removing
+// what an import added (a CSV import's transactions, a capture or GitHub
+// import's tasks, which go to the trash).
+
+use chrono::{Duration, NaiveDateTime, Utc};
+use rusqlite::{Connection, OptionalExtension};
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Manager, State};
+
+use crate::commands::bulk;
+use crate::database::DbConnection;
+
+/// SQL condition for tasks (as `t`) that are not in the trash.
+pub const NOT_TRASHED: &str = "t.deleted_at IS NULL";
+
+/// SQL condition for scheduled notifications of tasks in the trash. False for
+/// notifications without a task.
+pub const TRASHED_TASK_NOTIFICATION: &str = "EXISTS (SELECT 1 FROM tasks tt
+         WHERE tt.id = scheduled_notifications.task_id AND tt.deleted_at IS NOT NULL)";
+
+/// A task with its subtasks and recurring instances, at any depth, as `tree`.
+const TASK_TREE: &str = "WITH RECURSIVE tree(id) AS (
+    SELECT ?1
+    UNION SELECT t.id FROM tasks t JOIN tree ON t.parent_task_id = tree.id OR t.parent_recurring_task_id = tree.id
+)";
+
+/// How long an undo token can be redeemed.
+pub const UNDO_WINDOW_SECS: i64 = 10;
+
+const TIMESTAMP_FORMAT: &str = "%Y-%m-%d %H:%M:%S";
+
+// ---------- Types ----------
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TrashKind {
+    Project,
+    FinanceAccount,
+    Task,
+}
+
+impl TrashKind {
+    const ALL: [TrashKind; 3] = [TrashKind::Project, TrashKind::FinanceAccount, TrashKind::Task];
+
+    fn table(self) -> &'static str {
+        match self {
+            TrashKind::Project => "projects",
+            TrashKind::FinanceAccount => "accounts",
+            TrashKind::Task => "tasks",
+        }
+    }
+
+    fn label(self) -> &'static str {
+        match self {
+            TrashKind::Project => "Project",
+            TrashKind::FinanceAccount => "Account",
+            TrashKind::Task => "Task",
+        }
+    }
+
+    fn name_column(self) -> &'static str {
+        match self {
+            TrashKind::Task => "title",
+            _ => "name",
+        }
+    }
+
+    /// Condition on `x` for the rows the trash lists: subtasks and instances
+    /// trashed with their parent are part of the parent's entry.
+    fn listed(self) -> &'static str {
+        match self {
+            TrashKind::Task => {
+                "NOT EXISTS(SELECT 1 FROM tasks p WHERE p.id IN (x.parent_task_id, x.parent_recurring_task_id)
+                            AND p.deleted_at IS NOT NULL)"
+            }
+            _ => "1",
+        }
+    }
+}
+
+/// What redeeming an undo token does.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum UndoAction {
+    Restore { kind: TrashKind, id: i64 },
+    DeleteTransactions { ids: Vec<i64> },
+    /// Bring back tasks a bulk delete moved to the trash.
+    RestoreTasks { ids: Vec<i64> },
+    /// Move the tasks an import created to the trash.
+    TrashTasks { ids: Vec<i64> },
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct UndoToken {
+    pub token: String,
+    pub description: String,
+    pub expires_at: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct TrashItem {
+    pub kind: TrashKind,
+    pub id: i64,
+    pub name: String,
+    pub deleted_at: String,
+    /// When the item will be purged for good.
+    pub purge_after: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TrashSettings {
+    /// Days a trashed item is kept before it is purged.
+    pub retention_days: i64,
+}
+
+// ---------- Trash ----------
+
+/// Move a row to the trash inside the caller's transaction. Returns its name.
+pub fn trash_item(conn: &Connection, kind: TrashKind, id: i64) -> Result<String, String> {
+    let name = item_name(conn, kind, id, false)?;
+    let sql = match kind {
+        TrashKind::Task => format!(
+            "{} UPDATE tasks SET deleted_at = datetime('now') WHERE id IN tree AND deleted_at IS NULL",
+            TASK_TREE
+        ),
+        _ => format!("UPDATE {} SET deleted_at = datetime('now') WHERE id = ?1", kind.table()),
+    };
+    conn.execute(&sql, [id])
+        .map_err(|e| format!("Failed to move {} to trash: {}", kind.label().to_lowercase(), e))?;
+    Ok(name)
+}
+
+/// Take a row back out of the trash. Returns its name.
+pub fn restore_item(conn: &Connection, kind: TrashKind, id: i64) -> Result<String, String> {
+    let name = item_name(conn, kind, id, true)?;
+    let sql = match kind {
+        // Only what was trashed along with the task, not what was deleted before
+        TrashKind::Task => format!(
+            "{} UPDATE tasks SET deleted_at = NULL
+             WHERE id IN tree AND deleted_at = (SELECT deleted_at FROM tasks WHERE id = ?1)",
+            TASK_TREE
+        ),
+        _ => format!("UPDATE {} SET deleted_at = NULL WHERE id = ?1", kind.table()),
+    };
+    conn.execute(&sql, [id])
+        .map_err(|e| format!("Failed to restore {}: {}", kind.label().to_lowercase(), e))?;
+    Ok(name)
+}
+
+/// Delete a trashed row for good, along with what only it owned.
+pub fn purge_item(conn: &Connection, kind: TrashKind, id: i64) -> Result<(), String> {
+    item_name(conn, kind, id, true)?;
+    let cleanup = match kind {
+        TrashKind::Project => "UPDATE tasks SET project_id = NULL WHERE project_id = ?1",
+        TrashKind::FinanceAccount => "DELETE FROM transactions WHERE account_id = ?1",
+        TrashKind::Task => return purge_task_tree(conn, id),
+    };
+    conn.execute(cleanup, [id])
+        .map_err(|e| format!("Failed to purge {}: {}", kind.label().to_lowercase(), e))?;
+    conn.execute(&format!("DELETE FROM {} WHERE id = ?1", kind.table()), [id])
+        .map_err(|e| format!("Failed to purge {}: {}", kind.label().to_lowercase(), e))?;
+    Ok(())
+}
+
+/// Delete a trashed task and everything trashed under it.
+fn purge_task_tree(conn: &Connection, id: i64) -> Result<(), String> {
+    let mut stmt = conn
+        .prepare(&format!(
+            "{} SELECT t.id FROM tasks t JOIN tree ON tree.id = t.id WHERE t.deleted_at IS NOT NULL",
+            TASK_TREE
+        ))
+        .map_err(|e| format!("Failed to prepare query: {}", e))?;
+    let ids = stmt
+        .query_map([id], |row| row.get::<_, i64>(0))
+        .map_err(|e| format!("Failed to get trashed tasks: {}", e))?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| format!("Failed to read trashed tasks: {}", e))?;
+    for id in ids {
+        // Deleting a recurring parent already took its instances
+        let exists = conn
+            .query_row("SELECT 1 FROM tasks WHERE id = ?1", [id], |_| Ok(()))
+            .optional()
+            .map_err(|e| format!("Failed to get task: {}", e))?
+            .is_some();
+        if exists {
+            bulk::delete_task_sync(conn, id)?;
+        }
+    }
+    Ok(())
+}
+
+/// Purge everything trashed more than retention_days before `now`, and drop
+/// undo tokens that can no longer be used. Returns how many items were purged.
+pub fn purge_expired(conn: &Connection, now: NaiveDateTime) -> Result<usize, String> {
+    let retention_days = load_settings(conn)?.retention_days;
+    let cutoff = (now - Duration::days(retention_days)).format(TIMESTAMP_FORMAT).to_string();
+    let mut purged = 0;
+    for kind in TrashKind::ALL {
+        let mut stmt = conn
+            .prepare(&format!(
+                "SELECT id FROM {} x WHERE deleted_at IS NOT NULL AND datetime(deleted_at) <= datetime(?1) AND {}",
+                kind.table(),
+                kind.listed()
+            ))
+            .map_err(|e| format!("Failed to prepare query: {}", e))?;
+        let ids = stmt
+            .query_map([&cutoff], |row| row.get::<_, i64>(0))
+            .map_err(|e| format!("Failed to get expired items: {}", e))?
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|e| format!("Failed to read expired items: {}", e))?;
+        for id in ids {
+            purge_item(conn, kind, id)?;
+            purged += 1;
+        }
+    }
+    conn.execute(
+        "DELETE FROM undo_tokens WHERE datetime(expires_at) < datetime(?1)",
+        [now.format(TIMESTAMP_FORMAT).to_string()],
+    )
+    .map_err(|e| format!("Failed to drop expired undo tokens: {}", e))?;
+    Ok(purged)
+}
+
+fn item_name(conn: &Connection, kind: TrashKind, id: i64, trashed: bool) -> Result<String, String> {
+    let state = if trashed { "IS NOT NULL" } else { "IS NULL" };
+    conn.query_row(
+        &format!("SELECT {} FROM {} WHERE id = ?1 AND deleted_at {}", kind.name_column(), kind.table(), state),
+        [id],
+        |row| row.get(0),
+    )
+    .optional()
+    .map_err(|e| format!("Failed to get {}: {}", kind.label().to_lowercase(), e))?
+    .ok_or_else(|| {
+        let place = if trashed { " in the trash" } else { "" };
+        format!("{} {} not found{}", kind.label(), id, place)
+    })
+}
+
+fn list_items(conn: &Connection) -> Result<Vec<TrashItem>, String> {
+    let retention_days = load_settings(conn)?.retention_days;
+    let mut items = Vec::new();
+    for kind in TrashKind::ALL {
+        let mut stmt = conn
+            .prepare(&format!(
+                "SELECT id, {}, deleted_at, datetime(deleted_at, '+' || ?1 || ' days')
+                 FROM {} x WHERE deleted_at IS NOT NULL AND {}",
+                kind.name_column(),
+                kind.table(),
+                kind.listed()
+            ))
+            .map_err(|e| format!("Failed to prepare trash query: {}", e))?;
+        let rows = stmt
+            .query_map([retention_days], |row| {
+                Ok(TrashItem {
+                    kind,
+                    id: row.get(0)?,
+                    name: row.get(1)?,
+                    deleted_at: row.get(2)?,
+                    purge_after: row.get(3)?,
+                })
+            })
+            .map_err(|e| format!("Failed to get trash: {}", e))?
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|e| format!("Failed to read trash: {}", e))?;
+        items.extend(rows);
+    }
+    items.sort_by(|a, b| b.deleted_at.cmp(&a.deleted_at));
+    Ok(items)
+}
+
+pub fn load_settings(conn: &Connection) -> Result<TrashSettings, String> {
+    conn.query_row("SELECT retention_days FROM trash_settings WHERE id = 1", [], |row| {
+        Ok(TrashSettings { retention_days: row.get(0)? })
+    })
+    .map_err(|e| format!("Failed to load trash settings: {}", e))
+}
+
+/// Purge expired trash once an hour.
+pub fn schedule_purge(app: AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        let mut interval = tokio::time::interval(tokio::time::Duration::from_secs(3600));
+        loop {
+            interval.tick().await;
+            let db = app.state::<DbConnection>();
+            let conn = db.lock().await;
+            match purge_expired(&conn, Utc::now().naive_utc()) {
+                Ok(0) => {}
+                Ok(purged) => println!("Purged {} item(s) from the trash", purged),
+                Err(e) => eprintln!("Trash purge failed: {}", e),
+            }
+        }
+    });
+}
+
+// ---------- Undo ----------
+
+/// Record how to reverse an operation and hand out a token for it.
+pub fn issue_undo(conn: &Connection, action: &UndoAction, description: &str, now: NaiveDateTime) -> Result<UndoToken, String> {
+    let token = uuid::Uuid::new_v4().to_string();
+    let expires_at = (now + Duration::seconds(UNDO_WINDOW_SECS)).format(TIMESTAMP_FORMAT).to_string();
+    let action = serde_json::to_string(action).map_err(|e| format!("Failed to encode undo action: {}", e))?;
+    conn.execute(
+        "INSERT INTO undo_tokens (token, action, description, created_at, expires_at) VALUES (?1, ?2, ?3, ?4, ?5)",
+        rusqlite::params![token, action, description, now.format(TIMESTAMP_FORMAT).to_string(), expires_at],
+    )
+    .map_err(|e| format!("Failed to issue undo token: {}", e))?;
+    Ok(UndoToken { token, description: description.to_string(), expires_at })
+}
+
+/// Reverse the operation behind `token`, if it is still within its window.
+/// Each token works once. Returns the token's description.
+pub fn redeem_undo(conn: &Connection, token: &str, now: NaiveDateTime) -> Result<String, String> {
+    let (action, description, expires_at, used_at): (String, String, String, Option<String>) = conn
+        .query_row(
+            "SELECT action, description, expires_at, used_at FROM undo_tokens WHERE token = ?1",
+            [token],
+            |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?)),
+        )
+        .optional()
+        .map_err(|e| format!("Failed to get undo token: {}", e))?
+        .ok_or("Nothing to undo")?;
+    if used_at.is_some() {
+        return Err("This has already been undone".to_string());
+    }
+    let expires_at = NaiveDateTime::parse_from_str(&expires_at, TIMESTAMP_FORMAT)
+        .map_err(|e| format!("Invalid undo token expiry: {}", e))?;
+    if now > expires_at {
+        return Err("It's too late to undo this".to_string());
+    }
+
+    match serde_json::from_str(&action).map_err(|e| format!("Invalid undo action: {}", e))? {
+        UndoAction::Restore { kind, id } => {
+            restore_item(conn, kind, id)?;
+        }
+        UndoAction::DeleteTransactions { ids } => {
+            let ids = serde_json::to_string(&ids).map_err(|e| format!("Failed to encode ids: {}", e))?;
+            conn.execute("DELETE FROM transactions WHERE id IN (SELECT value FROM json_each(?1))", [ids])
+                .map_err(|e| format!("Failed to remove imported transactions: {}", e))?;
+        }
+        UndoAction::RestoreTasks { ids } => {
+            // Subtasks and instances come back with their parent
+            for id in ids {
+                if item_name(conn, TrashKind::Task, id, true).is_ok() {
+                    restore_item(conn, TrashKind::Task, id)?;
+                }
+            }
+        }
+        UndoAction::TrashTasks { ids } => {
+            // Tasks the user deleted in the meantime are already gone
+            for id in ids {
+                if item_name(conn, TrashKind::Task, id, false).is_ok() {
+                    trash_item(conn, TrashKind::Task, id)?;
+                }
+            }
+        }
+    }
+    conn.execute(
+        "UPDATE undo_tokens SET used_at = ?1 WHERE token = ?2",
+        rusqlite::params![now.format(TIMESTAMP_FORMAT).to_string(), token],
+    )
+    .map_err(|e| format!("Failed to use undo token: {}", e))?;
+    Ok(description)
+}
+
+// ---------- Commands ----------
+
+/// Everything in the trash, most recently deleted first.
+#[tauri::command]
+pub async fn list_trash(db: State<'_, DbConnection>) -> Result<Vec<TrashItem>, String> {
+    let conn = db.lock().await;
+    purge_expired(&conn, Utc::now().naive_utc())?;
+    list_items(&conn)
+}
+
+#[tauri::command]
+pub async fn restore_from_trash(db: State<'_, DbConnection>, kind: TrashKind, id: i64) -> Result<(), String> {
+    let conn = db.lock().await;
+    restore_item(&conn, kind, id)?;
+    Ok(())
+}
+
+/// Delete one trashed item for good.
+#[tauri::command]
+pub async fn delete_from_trash(db: State<'_, DbConnection>, kind: TrashKind, id: i64) -> Result<(), String> {
+    let conn = db.lock().await;
+    let tx = conn.unchecked_transaction()
+        .map_err(|e| format!("Failed to begin transaction: {}", e))?;
+    purge_item(&tx, kind, id)?;
+    tx.commit()
+        .map_err(|e| format!("Failed to commit: {}", e))
+}
+
+/// Delete everything in the trash for good. Returns how many items went.
+#[tauri::command]
+pub async fn empty_trash(db: State<'_, DbConnection>) -> Result<usize, String> {
+    let conn = db.lock().await;
+    let tx = conn.unchecked_transaction()
+        .map_err(|e| format!("Failed to begin transaction: {}", e))?;
+    let items = list_items(&tx)?;
+    for item in &items {
+        purge_item(&tx, item.kind, item.id)?;
+    }
+    tx.commit()
+        .map_err(|e| format!("Failed to commit: {}", e))?;
+    Ok(items.len())
+}
+
+/// Reverse a destructive command using the token it returned.
+#[tauri::command]
+pub async fn undo_action(db: State<'_, DbConnection>, token: String) -> Result<String, String> {
+    let conn = db.lock().await;
+    let tx = conn.unchecked_transaction()
+        .map_err(|e| format!("Failed to begin transaction: {}", e))?;
+    let description = redeem_undo(&tx, &token, Utc::now().naive_utc())?;
+    tx.commit()
+        .map_err(|e| format!("Failed to commit: {}", e))?;
+    Ok(description)
+}
+
+#[tauri::command]
+pub async fn get_trash_settings(db: State<'_, DbConnection>) -> Result<TrashSettings, String> {
+    let conn = db.lock().await;
+    load_settings(&conn)
+}
+
+#[tauri::command]
+pub async fn update_trash_settings(db: State<'_, DbConnection>, settings: TrashSettings) -> Result<TrashSettings, String> {
+    if settings.retention_days < 1 {
+        return Err("Trash must keep items for at least a day".to_string());
+    }
+    let conn = db.lock().await;
+    conn.execute(
+        "UPDATE trash_settings SET retention_days = ?1, updated_at = CURRENT_TIMESTAMP WHERE id = 1",
+        [settings.retention_days],
+    )
+    .map_err(|e| format!("Failed to update trash settings: {}", e))?;
+    load_settings(&conn)
+}
+
+// ---------- Tests ----------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn test_conn() -> Connection {
+        let conn = crate::database::test_conn();
+        conn.execute_batch(
+            "INSERT INTO projects (id, user_id, name) VALUES (100, 1, 'Garden');
+             INSERT INTO tasks (id, user_id, title, status, project_id) VALUES (1, 1, 'Plant roses', 'active', 100);",
+        )
+        .unwrap();
+        conn
+    }
+
+    fn at(value: &str) -> NaiveDateTime {
+        NaiveDateTime::parse_from_str(value, TIMESTAMP_FORMAT).unwrap()
+    }
+
+    #[test]
+    fn trashed_items_can_be_restored_until_purged() {
+        let conn = test_conn();
+        assert_eq!(trash_item(&conn, TrashKind::Project, 100).unwrap(), "Garden");
+        assert!(trash_item(&conn, TrashKind::Project, 100).is_err());
+        let items = list_items(&conn).unwrap();
+        assert_eq!((items.len(), items[0].kind, items[0].name.as_str()), (1, TrashKind::Project, "Garden"));
+
+        restore_item(&conn, TrashKind::Project, 100).unwrap();
+        assert!(list_items(&conn).unwrap().is_empty());
+        assert!(restore_item(&conn, TrashKind::Project, 100).is_err());
+
+        // Nothing is purged within the retention period
+        conn.execute("UPDATE projects SET deleted_at = '2026-06-01 12:00:00' WHERE id = 100", []).unwrap();
+        assert_eq!(purge_expired(&conn, at("2026-06-30 12:00:00")).unwrap(), 0);
+        assert_eq!(purge_expired(&conn, at("2026-07-01 12:00:00")).unwrap(), 1);
+        let (projects, project_id): (i64, Option<i64>) = conn
+            .query_row(
+                "SELECT (SELECT COUNT(*) FROM projects WHERE id = 100), project_id FROM tasks WHERE id = 1",
+                [],
+                |row| Ok((row.get(0)?, row.get(1)?)),
+            )
+            .unwrap();
+        assert_eq!((projects, project_id), (0, None));
+    }
+
+    #[test]
+    fn undo_tokens_work_once_within_their_window() {
+        let conn = test_conn();
+        let now = at("2026-06-01 12:00:00");
+        trash_item(&conn, TrashKind::Project, 100).unwrap();
+        let undo = issue_undo(&conn, &UndoAction::Restore { kind: TrashKind::Project, id: 100 }, "Project \"Garden\" deleted", now)
+            .unwrap();
+        assert_eq!(undo.expires_at, "2026-06-01 12:00:10");
+
+        assert!(redeem_undo(&conn, &undo.token, now + Duration::seconds(11)).is_err());
+        assert_eq!(redeem_undo(&conn, &undo.token, now + Duration::seconds(5)).unwrap(), "Project \"Garden\" deleted");
+        assert!(list_items(&conn).unwrap().is_empty());
+        assert!(redeem_undo(&conn, &undo.token, now + Duration::seconds(6)).is_err());
+        assert!(redeem_undo(&conn, "no-such-token", now).is_err());
+    }
+
+    #[test]
+    fn tasks_go_to_the_trash_with_their_instances() {
+        let conn = test_conn();
+        conn.execute_batch(
+            "INSERT INTO tasks (id, user_id, title, status, recurrence_pattern) VALUES (2, 1, 'Run', 'active', 'daily');
+             INSERT INTO tasks (id, user_id, title, status, parent_recurring_task_id, instance_date)
+             VALUES (3, 1, 'Run', 'active', 2, '2026-06-01');
+             INSERT INTO tasks (id, user_id, title, status) VALUES (4, 1, 'Imported', 'active');",
+        )
+        .unwrap();
+        let visible = |conn: &Connection| -> Vec<i64> {
+            conn.prepare(&format!("SELECT id FROM tasks t WHERE {} ORDER BY id", NOT_TRASHED))
+                .unwrap()
+                .query_map([], |row| row.get(0))
+                .unwrap()
+                .collect::<Result<_, _>>()
+                .unwrap()
+        };
+
+        assert_eq!(trash_item(&conn, TrashKind::Task, 2).unwrap(), "Run");
+        assert_eq!(visible(&conn), vec![1, 4]);
+        // The instance is part of its series' entry
+        let items = list_items(&conn).unwrap();
+        assert_eq!((items.len(), items[0].kind, items[0].id), (1, TrashKind::Task, 2));
+        restore_item(&conn, TrashKind::Task, 2).unwrap();
+        assert_eq!(visible(&conn), vec![1, 2, 3, 4]);
+
+        // Undoing an import trashes the tasks it created
+        let now = at("2026-06-01 12:00:00");
+        let undo = issue_undo(&conn, &UndoAction::TrashTasks { ids: vec![4] }, "Captured 1 task(s)", now).unwrap();
+        redeem_undo(&conn, &undo.token, now).unwrap();
+        assert_eq!(visible(&conn), vec![1, 2, 3]);
+
+        trash_item(&conn, TrashKind::Task, 2).unwrap();
+        purge_item(&conn, TrashKind::Task, 2).unwrap();
+        let left: i64 = conn.query_row("SELECT COUNT(*) FROM tasks WHERE id IN (2, 3)", [], |row| row.get(0)).unwrap();
+        assert_eq!(left, 0);
+    }
+
+    #[test]
+    fn notifications_of_trashed_tasks_are_hidden() {
+        let conn = test_conn();
+        conn.execute_batch(
+            "INSERT INTO scheduled_notifications (id, task_id, notification_type, title, message, scheduled_for) VALUES
+                 (1, 1, 'due_soon', 'Due soon', 'Plant roses', datetime('now', '+1 hour')),
+                 (2, NULL, 'daily_agenda', 'Today', 'Your agenda', datetime('now', '+1 hour'));",
+        )
+        .unwrap();
+        let listed = |conn: &Connection| -> Vec<i64> {
+            conn.prepare(&format!("SELECT id FROM scheduled_notifications WHERE NOT {} ORDER BY id", TRASHED_TASK_NOTIFICATION))
+                .unwrap()
+                .query_map([], |row| row.get(0))
+                .unwrap()
+                .collect::<Result<_, _>>()
+                .unwrap()
+        };
+        assert_eq!(listed(&conn), vec![1, 2]);
+
+        // Notifications without a task stay listed while the trash has tasks in it
+        trash_item(&conn, TrashKind::Task, 1).unwrap();
+        assert_eq!(listed(&conn), vec![2]);
+    }
+
+    #[test]
+    fn undoing_an_import_removes_only_its_transactions() {
+        let conn = test_conn();
+        let account: i64 = conn.query_row("SELECT id FROM accounts ORDER BY id LIMIT 1", [], |row| row.get(0)).unwrap();
+        for (id, hash) in [(1, "a"), (2, "b"), (3, "c")] {
+            conn.execute(
+                "INSERT INTO transactions (id, account_id, date, amount_cents, merchant, source, source_hash)
+                 VALUES (?1, ?2, '2026-06-01', -500, 'Cafe', 'csv', ?3)",
+                rusqlite::params![id, account, hash],
+            )
+            .unwrap();
+        }
+        let now = at("2026-06-01 12:00:00");
+        let undo = issue_undo(&conn, &UndoAction::DeleteTransactions { ids: vec![2, 3] }, "Imported 2 transactions", now).unwrap();
+        redeem_undo(&conn, &undo.token, now).unwrap();
+        let left: Vec<i64> = conn
+            .prepare("SELECT id FROM transactions ORDER BY id")
+            .unwrap()
+            .query_map([], |row| row.get(0))
+            .unwrap()
+            .collect::<Result<_, _>>()
+            .unwrap();
+        assert_eq!(left, vec![1]);
+    }
+}
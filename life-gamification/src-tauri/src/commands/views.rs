@@ -0,0 +1,747 @@
+// Saved smart views.
+//
+// A view is a named query over tasks, written in a small language:
+//
+//   due<=+3d AND priority>=4 AND NOT category:someday
+//   project:"Home" AND estimate<30m
+//   (tag:errands OR tag:shopping) status:active
+//
+// Conditions are `field op value` with op one of : = != < <= > >=. Adjacent
+// conditions are ANDed; AND, OR, NOT and parentheses work as usual. A bare word
+// matches task titles. Queries compile to parameterized SQL over TASK_SELECT;
+// archived tasks are left out unless the query asks about status, and deferred
+// tasks unless it asks about start.
+//
+// Fields:
+//   due start created completed   dates: 2026-07-01, today, tomorrow, yesterday, +3d, -2w, none
+//   priority difficulty     numbers
+//   estimate                durations: 30m, 2h, 1h30m, 45 (minutes), none
+//   category status type project tag   names (case-insensitive); project/tag take none
+//   title                   `:` contains, `=` exact
+//   source                  where the task came from: github, reminders, calendar, recurring, user
+
+use chrono::{Duration, NaiveDate, Utc};
+use rusqlite::types::Value;
+use rusqlite::{Connection, OptionalExtension};
+use serde::Serialize;
+use tauri::State;
+
+use crate::commands::{defer, trash};
+use crate::database::DbConnection;
+use crate::Task;
+
+// ---------- Types ----------
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum CmpOp {
+    /// `field:value` - equality, or "contains" for title.
+    Has,
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Expr {
+    And(Box<Expr>, Box<Expr>),
+    Or(Box<Expr>, Box<Expr>),
+    Not(Box<Expr>),
+    Cond { field: String, op: CmpOp, value: String },
+    /// A bare word or phrase, matched against the title.
+    Text(String),
+}
+
+/// A query compiled to a SQL condition over `tasks t`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CompiledQuery {
+    pub sql: String,
+    pub params: Vec<Value>,
+    /// The query filters on status itself, so archived tasks aren't excluded.
+    pub mentions_status: bool,
+    /// The query filters on start date itself, so deferred tasks aren't excluded.
+    pub mentions_start: bool,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SavedView {
+    pub id: i64,
+    pub name: String,
+    pub query: String,
+    pub position: i64,
+    /// Matching tasks right now; None if the query no longer compiles.
+    pub task_count: Option<i64>,
+}
+
+// ---------- Lexing and parsing (pure, unit-tested) ----------
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Word(String),
+    Quoted(String),
+    Op(CmpOp),
+    LParen,
+    RParen,
+}
+
+fn tokenize(query: &str) -> Result<Vec<Token>, String> {
+    let mut tokens = Vec::new();
+    let mut chars = query.chars().peekable();
+    while let Some(&c) = chars.peek() {
+        match c {
+            c if c.is_whitespace() => {
+                chars.next();
+            }
+            '(' | ')' => {
+                chars.next();
+                tokens.push(if c == '(' { Token::LParen } else { Token::RParen });
+            }
+            '"' => {
+                chars.next();
+                let mut text = String::new();
+                loop {
+                    match chars.next() {
+                        Some('"') => break,
+                        Some(c) => text.push(c),
+                        None => return Err("Unclosed quote".to_string()),
+                    }
+                }
+                tokens.push(Token::Quoted(text));
+            }
+            ':' | '=' | '!' | '<' | '>' => {
+                chars.next();
+                let followed_by_eq = chars.peek() == Some(&'=');
+                let op = match (c, followed_by_eq) {
+                    (':', _) => CmpOp::Has,
+                    ('=', _) => CmpOp::Eq,
+                    ('!', true) => CmpOp::Ne,
+                    ('<', true) => CmpOp::Le,
+                    ('<', false) => CmpOp::Lt,
+                    ('>', true) => CmpOp::Ge,
+                    ('>', false) => CmpOp::Gt,
+                    _ => return Err("Expected != after !".to_string()),
+                };
+                if followed_by_eq && c != ':' && c != '=' {
+                    chars.next();
+                }
+                tokens.push(Token::Op(op));
+            }
+            _ => {
+                let mut word = String::new();
+                while let Some(&c) = chars.peek() {
+                    if c.is_whitespace() || "()\":=!<>".contains(c) {
+                        break;
+                    }
+                    word.push(c);
+                    chars.next();
+                }
+                tokens.push(Token::Word(word));
+            }
+        }
+    }
+    Ok(tokens)
+}
+
+fn is_keyword(token: Option<&Token>, keyword: &str) -> bool {
+    matches!(token, Some(Token::Word(word)) if word.eq_ignore_ascii_case(keyword))
+}
+
+struct Parser {
+    tokens: Vec<Token>,
+    pos: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.pos)
+    }
+
+    fn next(&mut self) -> Option<Token> {
+        let token = self.tokens.get(self.pos).cloned();
+        self.pos += 1;
+        token
+    }
+
+    fn or(&mut self) -> Result<Expr, String> {
+        let mut expr = self.and()?;
+        while is_keyword(self.peek(), "OR") {
+            self.next();
+            expr = Expr::Or(Box::new(expr), Box::new(self.and()?));
+        }
+        Ok(expr)
+    }
+
+    fn and(&mut self) -> Result<Expr, String> {
+        let mut expr = self.unary()?;
+        loop {
+            if is_keyword(self.peek(), "AND") {
+                self.next();
+            } else if self.peek().is_none() || self.peek() == Some(&Token::RParen) || is_keyword(self.peek(), "OR") {
+                return Ok(expr);
+            }
+            // Anything else starts another condition: implicit AND
+            expr = Expr::And(Box::new(expr), Box::new(self.unary()?));
+        }
+    }
+
+    fn unary(&mut self) -> Result<Expr, String> {
+        if is_keyword(self.peek(), "NOT") {
+            self.next();
+            return Ok(Expr::Not(Box::new(self.unary()?)));
+        }
+        match self.next() {
+            Some(Token::LParen) => {
+                let expr = self.or()?;
+                match self.next() {
+                    Some(Token::RParen) => Ok(expr),
+                    _ => Err("Missing closing parenthesis".to_string()),
+                }
+            }
+            Some(Token::Word(field)) => match self.peek() {
+                Some(Token::Op(op)) => {
+                    let op = *op;
+                    self.next();
+                    match self.next() {
+                        Some(Token::Word(value)) | Some(Token::Quoted(value)) => Ok(Expr::Cond {
+                            field: field.to_lowercase(),
+                            op,
+                            value,
+                        }),
+                        _ => Err(format!("Missing value after \"{}\"", field)),
+                    }
+                }
+                _ => Ok(Expr::Text(field)),
+            },
+            Some(Token::Quoted(text)) => Ok(Expr::Text(text)),
+            Some(Token::RParen) => Err("Unexpected \")\"".to_string()),
+            Some(Token::Op(_)) => Err("Comparison is missing a field name".to_string()),
+            None => Err("Query ended unexpectedly".to_string()),
+        }
+    }
+}
+
+pub fn parse_query(query: &str) -> Result<Expr, String> {
+    let mut parser = Parser { tokens: tokenize(query)?, pos: 0 };
+    if parser.peek().is_none() {
+        return Err("Query is empty".to_string());
+    }
+    let expr = parser.or()?;
+    match parser.peek() {
+        None => Ok(expr),
+        Some(_) => Err("Unexpected \")\"".to_string()),
+    }
+}
+
+// ---------- Values ----------
+
+/// A date: ISO, today/tomorrow/yesterday, or an offset from today like +3d / -2w.
+pub fn parse_date_value(value: &str, today: NaiveDate) -> Result<NaiveDate, String> {
+    match value.to_lowercase().as_str() {
+        "today" => return Ok(today),
+        "tomorrow" => return Ok(today + Duration::days(1)),
+        "yesterday" => return Ok(today - Duration::days(1)),
+        _ => {}
+    }
+    if let Ok(date) = NaiveDate::parse_from_str(value, "%Y-%m-%d") {
+        return Ok(date);
+    }
+    let invalid = || format!("Invalid date \"{}\"; use YYYY-MM-DD, today or +3d", value);
+    let (sign, rest) = match value.chars().next() {
+        Some('+') => (1, &value[1..]),
+        Some('-') => (-1, &value[1..]),
+        _ => return Err(invalid()),
+    };
+    let unit_days = match rest.chars().last() {
+        Some('d') | Some('D') => 1,
+        Some('w') | Some('W') => 7,
+        _ => return Err(invalid()),
+    };
+    let amount: i64 = rest[..rest.len() - 1].parse().map_err(|_| invalid())?;
+    Ok(today + Duration::days(sign * amount * unit_days))
+}
+
+/// A duration in minutes: 30m, 2h, 1h30m, or a bare number of minutes.
+pub fn parse_minutes(value: &str) -> Result<i64, String> {
+    let invalid = || format!("Invalid duration \"{}\"; use 30m, 2h or 1h30m", value);
+    if let Ok(minutes) = value.parse::<i64>() {
+        return Ok(minutes);
+    }
+    let mut total = 0;
+    let mut number = String::new();
+    for c in value.to_lowercase().chars() {
+        match c {
+            '0'..='9' => number.push(c),
+            'h' | 'm' => {
+                let n: i64 = number.parse().map_err(|_| invalid())?;
+                total += if c == 'h' { n * 60 } else { n };
+                number.clear();
+            }
+            _ => return Err(invalid()),
+        }
+    }
+    if !number.is_empty() {
+        return Err(invalid());
+    }
+    Ok(total)
+}
+
+// ---------- Compilation ----------
+
+fn sql_op(op: CmpOp) -> &'static str {
+    match op {
+        CmpOp::Has | CmpOp::Eq => "=",
+        CmpOp::Ne => "!=",
+        CmpOp::Lt => "<",
+        CmpOp::Le => "<=",
+        CmpOp::Gt => ">",
+        CmpOp::Ge => ">=",
+    }
+}
+
+/// Add a parameter and return its placeholder.
+fn bind(params: &mut Vec<Value>, value: Value) -> String {
+    params.push(value);
+    format!("?{}", params.len())
+}
+
+/// `field:none` / `field!=none` on a nullable column.
+fn null_check(column: &str, field: &str, op: CmpOp) -> Result<String, String> {
+    match op {
+        CmpOp::Has | CmpOp::Eq => Ok(format!("{} IS NULL", column)),
+        CmpOp::Ne => Ok(format!("{} IS NOT NULL", column)),
+        _ => Err(format!("{} can only be compared to none with : or !=", field)),
+    }
+}
+
+fn equality_only(field: &str, op: CmpOp) -> Result<(), String> {
+    match op {
+        CmpOp::Has | CmpOp::Eq | CmpOp::Ne => Ok(()),
+        _ => Err(format!("{} can only be compared with :, = or !=", field)),
+    }
+}
+
+fn compile_cond(field: &str, op: CmpOp, value: &str, today: NaiveDate, out: &mut CompiledQuery) -> Result<String, String> {
+    let params = &mut out.params;
+    let none = value.eq_ignore_ascii_case("none");
+    let sql = match field {
+        "due" | "start" | "created" | "completed" => {
+            out.mentions_start |= field == "start";
+            let column = match field {
+                "due" => "t.due_date",
+                "start" => "t.start_date",
+                "created" => "t.created_at",
+                _ => "t.completed_at",
+            };
+            if none {
+                return null_check(column, field, op);
+            }
+            let date = parse_date_value(value, today)?;
+            let p = bind(params, Value::Text(date.format("%Y-%m-%d").to_string()));
+            format!("({col} IS NOT NULL AND date({col}) {} {})", sql_op(op), p, col = column)
+        }
+        "priority" | "difficulty" => {
+            let number: i64 = value
+                .parse()
+                .map_err(|_| format!("{} must be a number, got \"{}\"", field, value))?;
+            let p = bind(params, Value::Integer(number));
+            format!("t.{} {} {}", field, sql_op(op), p)
+        }
+        "estimate" => {
+            let column = "t.estimated_time_minutes";
+            if none {
+                return null_check(column, field, op);
+            }
+            let p = bind(params, Value::Integer(parse_minutes(value)?));
+            format!("({col} IS NOT NULL AND {col} {} {})", sql_op(op), p, col = column)
+        }
+        "category" | "status" | "type" => {
+            equality_only(field, op)?;
+            out.mentions_status |= field == "status";
+            let column = match field {
+                "category" => "COALESCE(t.category, '')",
+                "status" => "t.status",
+                _ => "COALESCE(t.task_type, 'standard')",
+            };
+            let p = bind(params, Value::Text(value.to_string()));
+            format!("LOWER({}) {} LOWER({})", column, sql_op(op), p)
+        }
+        "project" | "tag" => {
+            equality_only(field, op)?;
+            let exists = if none {
+                match field {
+                    "project" => "t.project_id IS NOT NULL".to_string(),
+                    _ => "EXISTS(SELECT 1 FROM task_tags tt WHERE tt.task_id = t.id)".to_string(),
+                }
+            } else {
+                let p = bind(params, Value::Text(value.to_string()));
+                match field {
+                    "project" => format!(
+                        "EXISTS(SELECT 1 FROM projects p WHERE p.id = t.project_id AND p.deleted_at IS NULL
+                         AND LOWER(p.name) = LOWER({}))",
+                        p
+                    ),
+                    _ => format!(
+                        "EXISTS(SELECT 1 FROM task_tags tt JOIN tags g ON g.id = tt.tag_id
+                                WHERE tt.task_id = t.id AND g.name = {})",
+                        p
+                    ),
+                }
+            };
+            // `project:none` means "has no project", the opposite of the EXISTS
+            if none == (op == CmpOp::Ne) {
+                exists
+            } else {
+                format!("NOT {}", exists)
+            }
+        }
+        "source" => {
+            equality_only(field, op)?;
+            // Read off the links imports leave on the task; anything else is
+            // the user's own
+            let p = bind(params, Value::Text(value.to_string()));
+            format!(
+                "(CASE WHEN t.github_repo IS NOT NULL THEN 'github'
+                       WHEN t.reminder_id IS NOT NULL THEN 'reminders'
+                       WHEN t.source_event_uid IS NOT NULL THEN 'calendar'
+                       WHEN t.parent_recurring_task_id IS NOT NULL THEN 'recurring'
+                       ELSE 'user' END) {} LOWER({})",
+                sql_op(op),
+                p
+            )
+        }
+        "title" => {
+            equality_only(field, op)?;
+            let p = bind(params, Value::Text(value.to_string()));
+            match op {
+                CmpOp::Has => format!("instr(LOWER(t.title), LOWER({})) > 0", p),
+                _ => format!("LOWER(t.title) {} LOWER({})", sql_op(op), p),
+            }
+        }
+        _ => return Err(format!("Unknown field \"{}\"", field)),
+    };
+    Ok(sql)
+}
+
+fn compile_expr(expr: &Expr, today: NaiveDate, out: &mut CompiledQuery) -> Result<String, String> {
+    Ok(match expr {
+        Expr::And(a, b) => format!("({} AND {})", compile_expr(a, today, out)?, compile_expr(b, today, out)?),
+        Expr::Or(a, b) => format!("({} OR {})", compile_expr(a, today, out)?, compile_expr(b, today, out)?),
+        Expr::Not(a) => format!("NOT {}", compile_expr(a, today, out)?),
+        Expr::Cond { field, op, value } => compile_cond(field, *op, value, today, out)?,
+        Expr::Text(text) => {
+            let p = bind(&mut out.params, Value::Text(text.clone()));
+            format!("instr(LOWER(t.title), LOWER({})) > 0", p)
+        }
+    })
+}
+
+/// Compile a view query to a SQL condition, resolving relative dates against `today`.
+pub fn compile_query(query: &str, today: NaiveDate) -> Result<CompiledQuery, String> {
+    let expr = parse_query(query)?;
+    let mut compiled = CompiledQuery {
+        sql: String::new(),
+        params: Vec::new(),
+        mentions_status: false,
+        mentions_start: false,
+    };
+    compiled.sql = compile_expr(&expr, today, &mut compiled)?;
+    Ok(compiled)
+}
+
+fn where_clause(compiled: &CompiledQuery) -> String {
+    let mut conditions = vec!["t.user_id = 1", trash::NOT_TRASHED];
+    if !compiled.mentions_status {
+        conditions.push("t.status != 'archived'");
+    }
+    if !compiled.mentions_start {
+        conditions.push(defer::NOT_DEFERRED);
+    }
+    conditions.push(&compiled.sql);
+    conditions.join(" AND ")
+}
+
+// ---------- Persistence ----------
+
+pub fn run_query_sync(conn: &Connection, query: &str, today: NaiveDate) -> Result<Vec<Task>, String> {
+    let compiled = compile_query(query, today)?;
+    let sql = format!(
+        "{} WHERE {} ORDER BY t.priority DESC, t.due_date ASC",
+        crate::TASK_SELECT,
+        where_clause(&compiled)
+    );
+    let mut stmt = conn.prepare(&sql)
+        .map_err(|e| format!("Failed to prepare view query: {}", e))?;
+    let tasks = stmt
+        .query_map(rusqlite::params_from_iter(compiled.params), crate::task_from_row)
+        .map_err(|e| format!("Failed to run view: {}", e))?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| format!("Failed to read tasks: {}", e))?;
+    Ok(tasks)
+}
+
+/// Ids of the tasks matching a query, for commands that act on a view's tasks.
+pub fn matching_task_ids(conn: &Connection, query: &str, today: NaiveDate) -> Result<Vec<i64>, String> {
+    let compiled = compile_query(query, today)?;
+    let sql = format!("SELECT t.id FROM tasks t WHERE {} ORDER BY t.id", where_clause(&compiled));
+    let mut stmt = conn.prepare(&sql)
+        .map_err(|e| format!("Failed to prepare view query: {}", e))?;
+    let ids = stmt
+        .query_map(rusqlite::params_from_iter(compiled.params), |row| row.get(0))
+        .map_err(|e| format!("Failed to run view: {}", e))?
+        .collect::<Result<Vec<i64>, _>>()
+        .map_err(|e| format!("Failed to read tasks: {}", e))?;
+    Ok(ids)
+}
+
+fn count_query(conn: &Connection, query: &str, today: NaiveDate) -> Result<i64, String> {
+    let compiled = compile_query(query, today)?;
+    let sql = format!("SELECT COUNT(*) FROM tasks t WHERE {}", where_clause(&compiled));
+    conn.query_row(&sql, rusqlite::params_from_iter(compiled.params), |row| row.get(0))
+        .map_err(|e| format!("Failed to count view: {}", e))
+}
+
+fn load_views(conn: &Connection, today: NaiveDate) -> Result<Vec<SavedView>, String> {
+    let mut stmt = conn
+        .prepare("SELECT id, name, query, position FROM saved_views WHERE user_id = 1 ORDER BY position, id")
+        .map_err(|e| format!("Failed to prepare query: {}", e))?;
+    let views = stmt
+        .query_map([], |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?)))
+        .map_err(|e| format!("Failed to get views: {}", e))?
+        .collect::<Result<Vec<(i64, String, String, i64)>, _>>()
+        .map_err(|e| format!("Failed to read views: {}", e))?;
+    Ok(views
+        .into_iter()
+        .map(|(id, name, query, position)| SavedView {
+            task_count: count_query(conn, &query, today).ok(),
+            id,
+            name,
+            query,
+            position,
+        })
+        .collect())
+}
+
+fn view_query(conn: &Connection, view_id: i64) -> Result<String, String> {
+    conn.query_row("SELECT query FROM saved_views WHERE id = ?1 AND user_id = 1", [view_id], |row| row.get(0))
+        .optional()
+        .map_err(|e| format!("Failed to get view: {}", e))?
+        .ok_or_else(|| format!("View {} not found", view_id))
+}
+
+fn validate(name: &str, query: &str) -> Result<(), String> {
+    if name.trim().is_empty() {
+        return Err("View name cannot be empty".to_string());
+    }
+    compile_query(query, Utc::now().naive_utc().date()).map(|_| ())
+}
+
+fn map_save_error(e: rusqlite::Error, name: &str) -> String {
+    if e.to_string().contains("UNIQUE") {
+        format!("A view named \"{}\" already exists", name.trim())
+    } else {
+        format!("Failed to save view: {}", e)
+    }
+}
+
+// ---------- Commands ----------
+
+/// Saved views, in sidebar order, with their current task counts.
+#[tauri::command]
+pub async fn get_saved_views(db: State<'_, DbConnection>) -> Result<Vec<SavedView>, String> {
+    let conn = db.lock().await;
+    load_views(&conn, Utc::now().naive_utc().date())
+}
+
+#[tauri::command]
+pub async fn create_saved_view(db: State<'_, DbConnection>, name: String, query: String) -> Result<SavedView, String> {
+    validate(&name, &query)?;
+    let conn = db.lock().await;
+    conn.execute(
+        "INSERT INTO saved_views (user_id, name, query, position)
+         VALUES (1, ?1, ?2, (SELECT COALESCE(MAX(position), -1) + 1 FROM saved_views WHERE user_id = 1))",
+        rusqlite::params![name.trim(), query.trim()],
+    )
+    .map_err(|e| map_save_error(e, &name))?;
+    let view_id = conn.last_insert_rowid();
+    load_views(&conn, Utc::now().naive_utc().date())?
+        .into_iter()
+        .find(|view| view.id == view_id)
+        .ok_or_else(|| "View not found".to_string())
+}
+
+#[tauri::command]
+pub async fn update_saved_view(
+    db: State<'_, DbConnection>,
+    view_id: i64,
+    name: Option<String>,
+    query: Option<String>,
+    position: Option<i64>,
+) -> Result<SavedView, String> {
+    let conn = db.lock().await;
+    let (current_name, current_query): (String, String) = conn
+        .query_row(
+            "SELECT name, query FROM saved_views WHERE id = ?1 AND user_id = 1",
+            [view_id],
+            |row| Ok((row.get(0)?, row.get(1)?)),
+        )
+        .optional()
+        .map_err(|e| format!("Failed to get view: {}", e))?
+        .ok_or_else(|| format!("View {} not found", view_id))?;
+
+    let name = name.unwrap_or(current_name);
+    let query = query.unwrap_or(current_query);
+    validate(&name, &query)?;
+    conn.execute(
+        "UPDATE saved_views SET name = ?1, query = ?2, position = COALESCE(?3, position),
+         updated_at = CURRENT_TIMESTAMP WHERE id = ?4",
+        rusqlite::params![name.trim(), query.trim(), position, view_id],
+    )
+    .map_err(|e| map_save_error(e, &name))?;
+    load_views(&conn, Utc::now().naive_utc().date())?
+        .into_iter()
+        .find(|view| view.id == view_id)
+        .ok_or_else(|| "View not found".to_string())
+}
+
+#[tauri::command]
+pub async fn delete_saved_view(db: State<'_, DbConnection>, view_id: i64) -> Result<(), String> {
+    let conn = db.lock().await;
+    conn.execute("DELETE FROM saved_views WHERE id = ?1 AND user_id = 1", [view_id])
+        .map_err(|e| format!("Failed to delete view: {}", e))?;
+    Ok(())
+}
+
+/// Tasks matching a saved view.
+#[tauri::command]
+pub async fn run_view(db: State<'_, DbConnection>, view_id: i64) -> Result<Vec<Task>, String> {
+    let conn = db.lock().await;
+    let query = view_query(&conn, view_id)?;
+    run_query_sync(&conn, &query, Utc::now().naive_utc().date())
+}
+
+/// Tasks matching an unsaved query, for previewing a view while editing it.
+#[tauri::command]
+pub async fn run_view_query(db: State<'_, DbConnection>, query: String) -> Result<Vec<Task>, String> {
+    let conn = db.lock().await;
+    run_query_sync(&conn, &query, Utc::now().naive_utc().date())
+}
+
+// ---------- Tests ----------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn day(s: &str) -> NaiveDate {
+        NaiveDate::parse_from_str(s, "%Y-%m-%d").unwrap()
+    }
+
+    fn cond(field: &str, op: CmpOp, value: &str) -> Expr {
+        Expr::Cond { field: field.to_string(), op, value: value.to_string() }
+    }
+
+    fn ids(conn: &Connection, query: &str) -> Vec<i64> {
+        let mut ids: Vec<i64> = run_query_sync(conn, query, day("2026-07-01")).unwrap().iter().map(|t| t.id).collect();
+        ids.sort();
+        ids
+    }
+
+    #[test]
+    fn parses_precedence_and_implicit_and() {
+        let expr = parse_query("due<=+3d AND priority>=4 OR NOT category:\"some day\"").unwrap();
+        assert_eq!(
+            expr,
+            Expr::Or(
+                Box::new(Expr::And(
+                    Box::new(cond("due", CmpOp::Le, "+3d")),
+                    Box::new(cond("priority", CmpOp::Ge, "4")),
+                )),
+                Box::new(Expr::Not(Box::new(cond("category", CmpOp::Has, "some day")))),
+            )
+        );
+        assert_eq!(
+            parse_query("(tag:a OR tag:b) report").unwrap(),
+            Expr::And(
+                Box::new(Expr::Or(Box::new(cond("tag", CmpOp::Has, "a")), Box::new(cond("tag", CmpOp::Has, "b")))),
+                Box::new(Expr::Text("report".to_string())),
+            )
+        );
+        assert!(parse_query("").is_err());
+        assert!(parse_query("(priority>3").is_err());
+        assert!(parse_query("priority>=").is_err());
+        assert!(parse_query("title:\"open").is_err());
+    }
+
+    #[test]
+    fn parses_dates_and_durations() {
+        let today = day("2026-07-01");
+        assert_eq!(parse_date_value("+3d", today).unwrap(), day("2026-07-04"));
+        assert_eq!(parse_date_value("-2w", today).unwrap(), day("2026-06-17"));
+        assert_eq!(parse_date_value("Tomorrow", today).unwrap(), day("2026-07-02"));
+        assert_eq!(parse_date_value("2026-12-31", today).unwrap(), day("2026-12-31"));
+        assert!(parse_date_value("soon", today).is_err());
+
+        assert_eq!(parse_minutes("30m").unwrap(), 30);
+        assert_eq!(parse_minutes("1h30m").unwrap(), 90);
+        assert_eq!(parse_minutes("45").unwrap(), 45);
+        assert!(parse_minutes("2x").is_err());
+    }
+
+    #[test]
+    fn compiles_to_parameterized_sql() {
+        let compiled = compile_query("due<=+3d NOT category:someday", day("2026-07-01")).unwrap();
+        assert_eq!(
+            compiled.sql,
+            "((t.due_date IS NOT NULL AND date(t.due_date) <= ?1) AND NOT LOWER(COALESCE(t.category, '')) = LOWER(?2))"
+        );
+        assert_eq!(compiled.params, vec![Value::Text("2026-07-04".into()), Value::Text("someday".into())]);
+        assert!(!compiled.mentions_status);
+
+        assert!(compile_query("color:red", day("2026-07-01")).is_err());
+        assert!(compile_query("category<work", day("2026-07-01")).is_err());
+        assert!(compile_query("priority:high", day("2026-07-01")).is_err());
+    }
+
+    #[test]
+    fn runs_queries_against_tasks() {
+        let conn = crate::database::test_conn();
+        conn.execute_batch(
+            "INSERT INTO projects (id, user_id, name) VALUES (100, 1, 'Home');
+             INSERT INTO tasks (id, user_id, title, status, priority, category, due_date, estimated_time_minutes, project_id)
+             VALUES (1, 1, 'Fix sink', 'active', 5, 'chores', '2026-07-02', 20, 100);
+             INSERT INTO tasks (id, user_id, title, status, priority, category, due_date, estimated_time_minutes)
+             VALUES (2, 1, 'Write report', 'active', 4, 'work', '2026-07-10', 120);
+             INSERT INTO tasks (id, user_id, title, status, priority, category)
+             VALUES (3, 1, 'Learn piano', 'active', 2, 'someday');
+             INSERT INTO tasks (id, user_id, title, status, priority, category, due_date)
+             VALUES (4, 1, 'Old plan', 'archived', 5, 'work', '2026-07-01');
+             INSERT INTO tasks (id, user_id, title, status, priority, start_date)
+             VALUES (5, 1, 'Snoozed', 'active', 5, '2999-01-01 00:00:00');",
+        )
+        .unwrap();
+        crate::commands::tags::add_tags(&conn, 2, &["Focus".to_string()]).unwrap();
+
+        assert_eq!(ids(&conn, "due<=+3d AND priority>=4 AND NOT category:someday"), vec![1]);
+        assert_eq!(ids(&conn, "project:\"home\" AND estimate<30m"), vec![1]);
+        assert_eq!(ids(&conn, "NOT category:someday"), vec![1, 2]);
+        assert_eq!(ids(&conn, "due:none OR tag:focus"), vec![2, 3]);
+        assert_eq!(ids(&conn, "project:none report"), vec![2]);
+        // Archived tasks only show up when the query asks about status
+        assert_eq!(ids(&conn, "status:archived OR due<=today"), vec![4]);
+        // Deferred tasks likewise only show up when it asks about start
+        assert_eq!(ids(&conn, "start>today"), vec![5]);
+
+        conn.execute(
+            "INSERT INTO tasks (id, user_id, title, status, github_repo, github_issue_number)
+             VALUES (6, 1, 'Synced issue', 'active', 'acme/app', 12)",
+            [],
+        )
+        .unwrap();
+        assert_eq!(ids(&conn, "source:GitHub"), vec![6]);
+        assert_eq!(ids(&conn, "source!=github priority>=4"), vec![1, 2]);
+
+        assert_eq!(count_query(&conn, "priority>=4", day("2026-07-01")).unwrap(), 2);
+    }
+}
@@ -92,7 +92,7 @@ pub async fn init_database() -> Result<DbConnection, String> {
     Ok(Arc::new(Mutex::new(conn)))
 }
 
-fn run_migrations(conn: &rusqlite::Connection) -> Result<(), String> {
+pub(crate) fn run_migrations(conn: &rusqlite::Connection) -> Result<(), String> {
     // Create migrations table
     conn.execute(
         "CREATE TABLE IF NOT EXISTS migrations (
@@ -123,6 +123,25 @@ fn run_migrations(conn: &rusqlite::Connection) -> Result<(), String> {
         ("013_reminders.sql", include_str!("../migrations/013_reminders.sql")),
         ("015_unlocked_content.sql", include_str!("../migrations/015_unlocked_content.sql")),
         ("016_connections.sql", include_str!("../migrations/016_connections.sql")),
+        ("017_progression.sql", include_str!("../migrations/017_progression.sql")),
+        ("018_reward_ledger.sql", include_str!("../migrations/018_reward_ledger.sql")),
+        ("019_task_reopen.sql", include_str!("../migrations/019_task_reopen.sql")),
+        ("020_subtasks.sql", include_str!("../migrations/020_subtasks.sql")),
+        ("021_economy.sql", include_str!("../migrations/021_economy.sql")),
+        ("022_damage.sql", include_str!("../migrations/022_damage.sql")),
+        ("023_task_search.sql", include_str!("../migrations/023_task_search.sql")),
+        ("024_tags.sql", include_str!("../migrations/024_tags.sql")),
+        ("025_saved_views.sql", include_str!("../migrations/025_saved_views.sql")),
+        ("026_start_dates.sql", include_str!("../migrations/026_start_dates.sql")),
+        ("027_task_templates.sql", include_str!("../migrations/027_task_templates.sql")),
+        ("028_goal_progress.sql", include_str!("../migrations/028_goal_progress.sql")),
+        ("029_trash.sql", include_str!("../migrations/029_trash.sql")),
+        ("030_task_events.sql", include_str!("../migrations/030_task_events.sql")),
+        ("031_overdue_rollover.sql", include_str!("../migrations/031_overdue_rollover.sql")),
+        ("032_pauses.sql", include_str!("../migrations/032_pauses.sql")),
+        ("033_pomodoro.sql", include_str!("../migrations/033_pomodoro.sql")),
+        ("034_timer_heartbeat.sql", include_str!("../migrations/034_timer_heartbeat.sql")),
+        ("035_task_trash.sql", include_str!("../migrations/035_task_trash.sql")),
     ];
 
     for (filename, sql) in migrations {
@@ -154,6 +173,14 @@ fn run_migrations(conn: &rusqlite::Connection) -> Result<(), String> {
     Ok(())
 }
 
+/// An in-memory database with every migration applied, for tests.
+#[cfg(test)]
+pub(crate) fn test_conn() -> rusqlite::Connection {
+    let conn = rusqlite::Connection::open_in_memory().expect("open in-memory db");
+    run_migrations(&conn).expect("apply migrations");
+    conn
+}
+
 fn add_column_if_not_exists(
     conn: &rusqlite::Connection,
     table: &str,
@@ -214,38 +241,6 @@ pub async fn get_user(conn: &DbConnection, user_id: i64) -> Result<User, String>
     Ok(user)
 }
 
-pub async fn update_user(conn: &DbConnection, user: &User) -> Result<(), String> {
-    let conn = conn.lock().await;
-
-    conn.execute(
-        "UPDATE users SET
-         username = ?2, level = ?3, experience_points = ?4, experience_to_next_level = ?5,
-         strength = ?6, intelligence = ?7, endurance = ?8, charisma = ?9, luck = ?10,
-         current_health = ?11, max_health = ?12, gold = ?13, theme_preference = ?14, equipped_title = ?15
-         WHERE id = ?1",
-        rusqlite::params![
-            user.id,
-            user.username,
-            user.level,
-            user.experience_points,
-            user.experience_to_next_level,
-            user.strength,
-            user.intelligence,
-            user.endurance,
-            user.charisma,
-            user.luck,
-            user.current_health,
-            user.max_health,
-            user.gold,
-            user.theme_preference,
-            user.equipped_title,
-        ],
-    )
-    .map_err(|e| format!("Failed to update user: {}", e))?;
-
-    Ok(())
-}
-
 pub async fn get_tasks(conn: &DbConnection, user_id: i64) -> Result<Vec<Task>, String> {
     let conn = conn.lock().await;
 
@@ -333,40 +328,6 @@ pub async fn create_task(conn: &DbConnection, task: &Task) -> Result<i64, String
     Ok(task_id)
 }
 
-pub async fn complete_task(conn: &DbConnection, task_id: i64, user_id: i64) -> Result<(i32, i32), String> {
-    let conn = conn.lock().await;
-
-    let tx = conn.unchecked_transaction()
-        .map_err(|e| format!("Failed to begin transaction: {}", e))?;
-
-    // Get task details
-    let (xp_reward, gold_reward): (i32, i32) = tx.query_row(
-        "SELECT base_experience_reward, gold_reward FROM tasks WHERE id = ?1 AND user_id = ?2",
-        [task_id, user_id],
-        |row| Ok((row.get(0)?, row.get(1)?)),
-    )
-    .map_err(|e| format!("Failed to get task rewards: {}", e))?;
-
-    // Update task status
-    tx.execute(
-        "UPDATE tasks SET status = 'completed', completed_at = CURRENT_TIMESTAMP WHERE id = ?1",
-        [task_id],
-    )
-    .map_err(|e| format!("Failed to update task status: {}", e))?;
-
-    // Update user stats
-    tx.execute(
-        "UPDATE users SET experience_points = experience_points + ?1, gold = gold + ?2 WHERE id = ?3",
-        rusqlite::params![xp_reward, gold_reward, user_id],
-    )
-    .map_err(|e| format!("Failed to update user stats: {}", e))?;
-
-    tx.commit()
-        .map_err(|e| format!("Failed to commit transaction: {}", e))?;
-
-    Ok((xp_reward, gold_reward))
-}
-
 pub async fn get_all_achievements(conn: &DbConnection) -> Result<Vec<Achievement>, String> {
     let conn = conn.lock().await;
 
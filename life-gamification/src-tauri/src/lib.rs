@@ -9,12 +9,37 @@ use std::path::Path;
 
 // Import avatar and calendar commands
 mod commands;
+use commands::activity;
 use commands::avatar;
+use commands::bulk;
 use commands::calendar;
 use commands::capture;
+use commands::completions;
 use commands::finance;
 use commands::github;
+use commands::goals;
 use commands::health;
+use commands::ledger::{self, Reward, RewardSource};
+use commands::pauses;
+use commands::pomodoro;
+use commands::progression;
+use commands::damage;
+use commands::defer;
+use commands::difficulty;
+use commands::economy;
+use commands::estimates;
+use commands::recurrence;
+use commands::rollover;
+use commands::scoring;
+use commands::search;
+use commands::sessions;
+use commands::subtasks;
+use commands::tags;
+use commands::templates;
+use commands::timers;
+use commands::timesheet;
+use commands::trash;
+use commands::views;
 use commands::reminders;
 use commands::connections;
 use commands::simplefin;
@@ -120,6 +145,13 @@ pub struct Task {
     pub last_completed_date: Option<String>,
     pub streak_bonus_multiplier: Option<f64>,
     pub project_id: Option<i64>,  // Project this task belongs to
+    // Subtasks and dependencies
+    pub parent_task_id: Option<i64>,
+    pub complete_with_subtasks: bool,  // Auto-complete once every subtask is done
+    pub blocked: bool,  // Waiting on an unfinished "blocked by" task
+    pub effective_difficulty: f64,  // Difficulty adjusted for the user's stats in this category
+    pub tags: Vec<String>,
+    pub start_date: Option<String>,  // Hidden from the active list until then (UTC)
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -163,6 +195,9 @@ pub struct CreateTaskRequest {
     pub goal_unit: Option<String>,
     pub recurrence_pattern: Option<String>,  // JSON string for recurring tasks
     pub project_id: Option<i64>,  // Project to assign this task to
+    pub parent_task_id: Option<i64>,  // Create as a subtask of this task
+    pub tags: Option<Vec<String>>,
+    pub start_date: Option<String>,  // Defer the new task until this date
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -344,28 +379,6 @@ fn greet(name: &str) -> String {
     format!("Hello, {}! You've been greeted from Rust!", name)
 }
 
-// Helper function to calculate level from XP
-// Helper function to calculate effective difficulty based on user stats
-fn calculate_effective_difficulty(base_difficulty: i64, user: &User, task_category: &str) -> f64 {
-    let stat_modifier = match task_category {
-        "Exercise" | "Health" => user.endurance as f64 * 0.03, // 3% easier per END point
-        "Study" | "Work" => user.intelligence as f64 * 0.03,   // 3% easier per INT point  
-        "Social" => user.charisma as f64 * 0.03,               // 3% easier per CHA point
-        _ => ((user.strength + user.endurance) as f64 / 2.0) * 0.02, // 2% easier per avg STR/END
-    };
-    
-    // Lower effective difficulty means task is easier for this user
-    let effective_diff = base_difficulty as f64 * (1.0 - stat_modifier.min(0.5)); // Cap at 50% reduction
-    effective_diff.max(1.0) // Minimum difficulty of 1
-}
-
-fn calculate_level_and_progress(xp: i64) -> (i64, i64) {
-    let level = 1 + (xp / 100);
-    let xp_for_next_level = (level + 1) * 100;
-    let xp_to_next = xp_for_next_level - xp;
-    (level, xp_to_next)
-}
-
 // Initialize default user and tasks
 // REMOVED: initialize_default_data() - Data now comes from database
 // fn initialize_default_data() {
@@ -411,68 +424,40 @@ async fn get_user(db: tauri::State<'_, DbConnection>) -> Result<User, String> {
 }
 
 #[tauri::command]
-async fn get_tasks(db: tauri::State<'_, DbConnection>, status: Option<String>) -> Result<Vec<Task>, String> {
+async fn get_tasks(
+    db: tauri::State<'_, DbConnection>,
+    status: Option<String>,
+    tags: Option<Vec<String>>,
+    include_deferred: Option<bool>,
+) -> Result<Vec<Task>, String> {
     let conn = db.lock().await;
 
-    let query = match status {
-        Some(ref s) => format!(
-            "SELECT t.id, t.user_id, t.title, t.description, t.category, t.difficulty,
-             t.base_experience_reward, t.gold_reward, t.due_date, t.status, t.priority,
-             COALESCE(t.task_type, 'standard') as task_type,
-             tp.target_progress as goal_target, tp.current_progress as goal_current,
-             t.recurrence_pattern, t.parent_recurring_task_id, t.instance_date,
-             t.current_streak, t.longest_streak, t.last_completed_date, t.streak_bonus_multiplier,
-             t.project_id
-             FROM tasks t
-             LEFT JOIN task_progress tp ON t.id = tp.task_id
-             WHERE t.user_id = 1 AND t.status = '{}' AND t.status != 'archived'
-             ORDER BY t.priority DESC, t.due_date ASC", s
-        ),
-        None => "SELECT t.id, t.user_id, t.title, t.description, t.category, t.difficulty,
-             t.base_experience_reward, t.gold_reward, t.due_date, t.status, t.priority,
-             COALESCE(t.task_type, 'standard') as task_type,
-             tp.target_progress as goal_target, tp.current_progress as goal_current,
-             t.recurrence_pattern, t.parent_recurring_task_id, t.instance_date,
-             t.current_streak, t.longest_streak, t.last_completed_date, t.streak_bonus_multiplier,
-             t.project_id
-             FROM tasks t
-             LEFT JOIN task_progress tp ON t.id = tp.task_id
-             WHERE t.user_id = 1 AND t.status != 'archived'
-             ORDER BY t.priority DESC, t.due_date ASC".to_string(),
-    };
+    // Only tasks carrying every requested tag
+    let tags = tags
+        .map(|tags| tags.iter().filter_map(|t| tags::normalize_tag(t)).collect::<Vec<_>>())
+        .filter(|tags| !tags.is_empty())
+        .map(|tags| serde_json::to_string(&tags))
+        .transpose()
+        .map_err(|e| format!("Failed to encode tags: {}", e))?;
+
+    // Deferred tasks stay hidden until their start date unless asked for
+    let deferred_filter = if include_deferred.unwrap_or(false) { "1" } else { defer::NOT_DEFERRED };
+
+    let query = format!(
+        "{} WHERE t.user_id = 1 AND (?1 IS NULL OR t.status = ?1) AND t.status != 'archived' AND {} AND {}
+         AND (?2 IS NULL OR (SELECT COUNT(*) FROM task_tags tt JOIN tags g ON g.id = tt.tag_id
+                             WHERE tt.task_id = t.id AND g.name IN (SELECT value FROM json_each(?2)))
+                            = (SELECT COUNT(DISTINCT value COLLATE NOCASE) FROM json_each(?2)))
+         ORDER BY t.priority DESC, t.due_date ASC",
+        TASK_SELECT,
+        deferred_filter,
+        trash::NOT_TRASHED
+    );
 
     let mut stmt = conn.prepare(&query)
         .map_err(|e| format!("Failed to prepare tasks query: {}", e))?;
 
-    let task_iter = stmt.query_map([], |row| {
-        Ok(Task {
-            id: row.get::<_, i64>(0)?,
-            user_id: row.get::<_, i64>(1)?,
-            title: row.get(2)?,
-            description: row.get(3)?,
-            category: row.get(4)?,
-            difficulty: row.get::<_, i32>(5).map(|v| v as i64)?,
-            base_experience_reward: row.get::<_, i32>(6).map(|v| v as i64)?,
-            gold_reward: row.get::<_, i32>(7).map(|v| v as i64)?,
-            due_date: row.get(8)?,
-            status: row.get(9)?,
-            priority: row.get::<_, i32>(10).map(|v| v as i64)?,
-            created_at: "".to_string(), // Not in database
-            completed_at: None, // TODO: add to database schema
-            task_type: row.get(11)?,
-            goal_target: row.get::<_, Option<i32>>(12).ok().flatten().map(|v| v as i64),
-            goal_current: row.get::<_, Option<i32>>(13).ok().flatten().map(|v| v as i64),
-            goal_unit: None, // Not in database yet
-            recurrence_pattern: row.get(14)?,
-            parent_recurring_task_id: row.get::<_, Option<i32>>(15).ok().flatten().map(|v| v as i64),
-            instance_date: row.get(16)?,
-            current_streak: row.get::<_, Option<i32>>(17).ok().flatten().map(|v| v as i64),
-            longest_streak: row.get::<_, Option<i32>>(18).ok().flatten().map(|v| v as i64),
-            last_completed_date: row.get(19)?,
-            streak_bonus_multiplier: row.get(20)?,
-            project_id: row.get::<_, Option<i32>>(21).ok().flatten().map(|v| v as i64),
-        })
-    })
+    let task_iter = stmt.query_map(rusqlite::params![status, tags], task_from_row)
     .map_err(|e| format!("Failed to query tasks: {}", e))?;
 
     let tasks: Result<Vec<Task>, _> = task_iter.collect();
@@ -486,20 +471,27 @@ async fn create_task(db: tauri::State<'_, DbConnection>, task_data: CreateTaskRe
     let priority = task_data.priority.unwrap_or(3);
     let task_type = task_data.task_type.unwrap_or_else(|| "standard".to_string());
 
-    // Calculate rewards based on difficulty
-    let base_xp = 10 + (difficulty - 1) * 5;
-    let gold_reward = 1 + (difficulty - 1);
+    // Reject schedules the recurrence engine could not follow
+    if let Some(ref pattern) = task_data.recurrence_pattern {
+        recurrence::RecurrencePattern::parse(pattern)?;
+    }
+    let start_date = task_data.start_date.as_deref().map(defer::parse_start).transpose()?
+        .map(|start| start.format(defer::START_FORMAT).to_string());
 
     let conn = db.lock().await;
 
+    // Calculate rewards based on difficulty
+    let (base_xp, gold_reward) = economy::task_rewards(&conn, difficulty)?;
+
     // Insert task into database
     let tx = conn.unchecked_transaction()
         .map_err(|e| format!("Failed to begin transaction: {}", e))?;
 
     tx.execute(
         "INSERT INTO tasks (user_id, title, description, category, difficulty,
-         base_experience_reward, gold_reward, due_date, status, priority, task_type, recurrence_pattern, project_id)
-         VALUES (1, ?1, ?2, ?3, ?4, ?5, ?6, ?7, 'active', ?8, ?9, ?10, ?11)",
+         base_experience_reward, gold_reward, due_date, status, priority, task_type, recurrence_pattern, project_id,
+         start_date)
+         VALUES (1, ?1, ?2, ?3, ?4, ?5, ?6, ?7, 'active', ?8, ?9, ?10, ?11, ?12)",
         rusqlite::params![
             task_data.title,
             task_data.description,
@@ -512,12 +504,21 @@ async fn create_task(db: tauri::State<'_, DbConnection>, task_data: CreateTaskRe
             task_type,
             task_data.recurrence_pattern,
             task_data.project_id.map(|v| v as i32),
+            start_date,
         ],
     )
     .map_err(|e| format!("Failed to insert task: {}", e))?;
 
     let task_id = tx.last_insert_rowid();
 
+    if let Some(parent_id) = task_data.parent_task_id {
+        subtasks::set_parent(&tx, task_id, Some(parent_id))?;
+    }
+
+    if let Some(tag_names) = &task_data.tags {
+        tags::add_tags(&tx, task_id, tag_names)?;
+    }
+
     // If it's a goal-based task, create progress tracking
     if task_type == "goal" && task_data.goal_target.is_some() {
         tx.execute(
@@ -532,253 +533,151 @@ async fn create_task(db: tauri::State<'_, DbConnection>, task_data: CreateTaskRe
         .map_err(|e| format!("Failed to commit transaction: {}", e))?;
 
     // Return the created task
-    Ok(Task {
-        id: task_id,
-        user_id: 1,
-        title: task_data.title,
-        description: task_data.description,
-        category,
-        difficulty,
-        base_experience_reward: base_xp,
-        gold_reward,
-        due_date: task_data.due_date,
-        status: "active".to_string(),
-        priority,
-        created_at: "".to_string(),
-        completed_at: None,
-        task_type,
-        goal_target: task_data.goal_target,
-        goal_current: if task_data.goal_target.is_some() { Some(0) } else { None },
-        goal_unit: task_data.goal_unit,
-        recurrence_pattern: task_data.recurrence_pattern,
-        parent_recurring_task_id: None,
-        instance_date: None,
-        current_streak: Some(0),
-        longest_streak: Some(0),
-        last_completed_date: None,
-        streak_bonus_multiplier: Some(1.0),
-        project_id: task_data.project_id,
-    })
+    let mut task = fetch_task_sync(&conn, task_id)?;
+    task.goal_unit = task_data.goal_unit;
+    Ok(task)
 }
 
 #[tauri::command]
-async fn complete_task(db: tauri::State<'_, DbConnection>, task_id: i64) -> Result<Task, String> {
+async fn complete_task(app: tauri::AppHandle, db: tauri::State<'_, DbConnection>, task_id: i64) -> Result<Task, String> {
     // Perform all database operations in a single scope
-    {
+    let level_up = {
         let conn = db.lock().await;
 
         // Start transaction for atomic updates
         let tx = conn.unchecked_transaction()
             .map_err(|e| format!("Failed to begin transaction: {}", e))?;
-
-        // Get task details and check if already completed
-        let (task_status, xp_reward, gold_reward, parent_recurring_id, current_streak, last_completed):
-            (String, i32, i32, Option<i32>, Option<i32>, Option<String>) = tx.query_row(
-            "SELECT status, base_experience_reward, gold_reward, parent_recurring_task_id,
-             current_streak, last_completed_date FROM tasks WHERE id = ?1 AND user_id = 1",
-            [task_id],
-            |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?, row.get(4)?, row.get(5)?)),
-        )
-        .map_err(|e| format!("Task not found: {}", e))?;
-
-        if task_status == "completed" {
-            drop(tx);
-            // Already completed, will fetch and return below
-        } else {
-            // Get user stats for bonus calculations (including skill tree and active stat buffs)
-            let user = fetch_user_sync(&tx)?;
-            let (_, buffed_int, _, _, buffed_luck) = apply_stat_buffs_to_user_stats(&user);
-
-            // Calculate stat bonuses
-            let int_bonus = buffed_int as f64 * 0.02; // 2% XP bonus per INT point
-            let luck_bonus = buffed_luck as f64 * 0.015; // 1.5% gold bonus per LUCK point
-
-            // Calculate streak bonus for recurring tasks
-            let (new_streak, streak_multiplier) = if parent_recurring_id.is_some() {
-                use chrono::{NaiveDate, Utc};
-                let today = Utc::now().naive_utc().date();
-
-                let new_streak = if let Some(last_completed_str) = last_completed {
-                    // Parse last completed date
-                    if let Ok(last_date) = NaiveDate::parse_from_str(&last_completed_str, "%Y-%m-%d") {
-                        let days_since = (today - last_date).num_days();
-
-                        if days_since == 1 {
-                            // Consecutive day - increment streak
-                            current_streak.unwrap_or(0) + 1
-                        } else if days_since == 0 {
-                            // Same day - keep current streak
-                            current_streak.unwrap_or(0)
-                        } else {
-                            // Streak broken - start new streak at 1
-                            1
-                        }
-                    } else {
-                        1 // Can't parse date, start fresh
-                    }
-                } else {
-                    1 // First completion
-                };
-
-                // Calculate streak bonus multiplier
-                let multiplier = if new_streak >= 30 {
-                    3.0 // 3x XP at 30+ day streak
-                } else if new_streak >= 7 {
-                    2.0 // 2x XP at 7+ day streak
-                } else {
-                    1.0 // No bonus
-                };
-
-                (new_streak, multiplier)
-            } else {
-                (0, 1.0) // Not a recurring task
-            };
-
-            let stat_xp = (xp_reward as f64 * (1.0 + int_bonus) * streak_multiplier) as i64;
-            let stat_gold = (gold_reward as f64 * (1.0 + luck_bonus)) as i64;
-
-            // Apply any active XP/gold buff multipliers (persisted in active_buffs)
-            let (final_xp, final_gold) = apply_buff_effects_to_rewards(stat_xp, stat_gold);
-
-            // Mark task as completed and update streak fields for recurring tasks
-            if parent_recurring_id.is_some() {
-                use chrono::Utc;
-                let today = Utc::now().format("%Y-%m-%d").to_string();
-                let longest = current_streak.unwrap_or(0).max(new_streak);
-
-                tx.execute(
-                    "UPDATE tasks SET status = 'completed', completed_at = CURRENT_TIMESTAMP,
-                     current_streak = ?2, longest_streak = ?3, last_completed_date = ?4,
-                     streak_bonus_multiplier = ?5 WHERE id = ?1",
-                    rusqlite::params![task_id, new_streak, longest, today, streak_multiplier],
-                )
-                .map_err(|e| format!("Failed to update task status: {}", e))?;
-            } else {
-                tx.execute(
-                    "UPDATE tasks SET status = 'completed', completed_at = CURRENT_TIMESTAMP WHERE id = ?1",
-                    [task_id],
-                )
-                .map_err(|e| format!("Failed to update task status: {}", e))?;
-            }
-
-            // Update user stats with rewards
-            tx.execute(
-                "UPDATE users SET experience_points = experience_points + ?1, gold = gold + ?2 WHERE id = 1",
-                rusqlite::params![final_xp, final_gold],
-            )
-            .map_err(|e| format!("Failed to update user stats: {}", e))?;
-
-            println!("Task completed! Base XP: {} -> {} (with stat bonuses), Base Gold: {} -> {} (with stat bonuses)",
-                xp_reward, final_xp, gold_reward, final_gold);
-
-            tx.commit()
-                .map_err(|e| format!("Failed to commit transaction: {}", e))?;
-        }
-    } // Connection is dropped here
+        let level_up = complete_task_sync(&tx, task_id)?;
+        tx.commit()
+            .map_err(|e| format!("Failed to commit transaction: {}", e))?;
+        level_up
+    }; // Connection is dropped here
+    progression::emit_level_up(&app, level_up);
 
     // Now fetch and return the completed task
-    get_tasks(db, Some("completed".to_string()))
-        .await?
-        .into_iter()
-        .find(|t| t.id == task_id)
-        .ok_or("Task not found after completion".to_string())
-}
-
-#[tauri::command]
-async fn update_task_progress(db: tauri::State<'_, DbConnection>, task_id: i64, progress_amount: i64) -> Result<Task, String> {
-    let conn = db.lock().await;
-
-    // Check if task is a goal task
-    let (task_type, status, current_progress, target_progress): (String, String, Option<i32>, Option<i32>) = conn.query_row(
-        "SELECT t.task_type, t.status, tp.current_progress, tp.target_progress
-         FROM tasks t
-         LEFT JOIN task_progress tp ON t.id = tp.task_id
-         WHERE t.id = ?1",
+    get_task_by_id(db, task_id).await
+}
+
+// Complete a task inside the caller's transaction: rewards through the ledger,
+// recurring streaks, and auto-completion of a parent whose subtasks are now all
+// done. Completing an already-completed task is a no-op.
+fn complete_task_sync(conn: &Connection, task_id: i64) -> Result<Option<progression::LevelUp>, String> {
+    // Get task details and check it can still be completed
+    let (task_status, trashed, xp_reward, gold_reward, parent_recurring_id, instance_date, difficulty, category):
+        (String, bool, i32, i32, Option<i32>, Option<String>, i64, String) = conn.query_row(
+        "SELECT status, deleted_at IS NOT NULL, base_experience_reward, gold_reward, parent_recurring_task_id,
+         instance_date, COALESCE(difficulty, 5), category FROM tasks WHERE id = ?1 AND user_id = 1",
         [task_id],
-        |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?)),
+        |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?, row.get(4)?, row.get(5)?, row.get(6)?, row.get(7)?)),
     )
     .map_err(|e| format!("Task not found: {}", e))?;
 
-    if task_type != "goal" {
-        return Err("Task is not a goal-based task".to_string());
+    // Only open tasks pay out: not completed, failed, archived or in the trash
+    if trashed {
+        return Err(format!("Task {} is in the trash", task_id));
     }
-
-    if status == "completed" {
-        drop(conn);
-        return get_tasks(db, Some("completed".to_string()))
-            .await?
-            .into_iter()
-            .find(|t| t.id == task_id)
-            .ok_or("Task not found".to_string());
+    if task_status != "active" {
+        return Err(format!("Task {} is {}, only active tasks can be completed", task_id, task_status));
     }
 
-    // Update progress
-    let current = current_progress.unwrap_or(0);
-    let new_current = current + progress_amount as i32;
-    let target = target_progress.ok_or("Goal task has no target")?;
+    // Calculate streak bonus for recurring tasks. Streak state lives on the
+    // parent so it carries across instances; it counts consecutive scheduled
+    // occurrences, not calendar days.
+    let streak = match parent_recurring_id {
+        Some(parent_id) => Some(recurrence::streak_after_completion(conn, parent_id as i64, instance_date.as_deref())?),
+        None => None,
+    };
+    let streak_multiplier = streak.as_ref().map(|s| s.multiplier).unwrap_or(1.0);
 
-    conn.execute(
-        "UPDATE task_progress SET current_progress = ?1 WHERE task_id = ?2",
-        rusqlite::params![new_current, task_id],
-    )
-    .map_err(|e| format!("Failed to update progress: {}", e))?;
-
-    // Check if goal is reached
-    if new_current >= target {
-        // Mark as completed and award rewards
-        let (xp_reward, gold_reward): (i32, i32) = conn.query_row(
-            "SELECT base_experience_reward, gold_reward FROM tasks WHERE id = ?1",
-            [task_id],
-            |row| Ok((row.get(0)?, row.get(1)?)),
-        )
-        .map_err(|e| format!("Failed to get rewards: {}", e))?;
+    // Effective difficulty, INT / LUCK (with skill tree and stat buffs), streak
+    // and XP/gold buffs, scaled and capped by the economy config
+    let difficulty_multiplier = difficulty::task_reward_multiplier(conn, difficulty, &category)?;
+    let bonuses = economy::user_bonuses(conn, difficulty_multiplier, streak_multiplier)?;
+    let (xp, gold) = economy::load_config(conn)?.completion_rewards(xp_reward as i64, gold_reward as i64, &bonuses);
 
-        let tx = conn.unchecked_transaction()
-            .map_err(|e| format!("Failed to begin transaction: {}", e))?;
+    // Snapshot the state this completion overwrites so reopen_task can undo it
+    completions::record_completion(conn, task_id, parent_recurring_id.map(|id| id as i64), None)?;
 
-        tx.execute(
-            "UPDATE tasks SET status = 'completed', completed_at = CURRENT_TIMESTAMP WHERE id = ?1",
-            [task_id],
-        )
-        .map_err(|e| format!("Failed to complete task: {}", e))?;
+    // Mark task as completed and update streak fields for recurring tasks
+    if let (Some(parent_id), Some(streak)) = (parent_recurring_id, streak.as_ref()) {
+        for id in [task_id, parent_id as i64] {
+            conn.execute(
+                "UPDATE tasks SET current_streak = ?2, longest_streak = ?3, last_completed_date = ?4,
+                 streak_bonus_multiplier = ?5 WHERE id = ?1",
+                rusqlite::params![id, streak.current, streak.longest, streak.last_completed, streak.multiplier],
+            )
+            .map_err(|e| format!("Failed to update streak: {}", e))?;
+        }
+    }
+    conn.execute(
+        "UPDATE tasks SET status = 'completed', completed_at = CURRENT_TIMESTAMP WHERE id = ?1",
+        [task_id],
+    )
+    .map_err(|e| format!("Failed to update task status: {}", e))?;
 
-        tx.execute(
-            "UPDATE users SET experience_points = experience_points + ?1, gold = gold + ?2 WHERE id = 1",
-            rusqlite::params![xp_reward, gold_reward],
-        )
-        .map_err(|e| format!("Failed to update user: {}", e))?;
+    // Update user stats with rewards
+    let source = RewardSource::new(ledger::TASK_COMPLETION, task_id);
+    ledger::grant_gold(conn, &source, &gold, None)?;
+    let level_up = ledger::grant_xp(conn, &source, &xp, None)?;
 
-        tx.commit()
-            .map_err(|e| format!("Failed to commit: {}", e))?;
+    println!("Task completed! Base XP: {} -> {} (with stat bonuses), Base Gold: {} -> {} (with stat bonuses)",
+        xp_reward, xp.final_amount, gold_reward, gold.final_amount);
 
-        println!("Goal task completed! Awarded {} XP and {} gold", xp_reward, gold_reward);
-    }
+    // Completing the last open subtask may complete the parent as well
+    let parent_level_up = match subtasks::parent_ready_to_complete(conn, task_id)? {
+        Some(parent_id) => complete_task_sync(conn, parent_id)?,
+        None => None,
+    };
 
-    drop(conn);
+    Ok(progression::combine(level_up, parent_level_up))
+}
 
-    // Return updated task
-    get_tasks(db, None)
-        .await?
-        .into_iter()
-        .find(|t| t.id == task_id)
-        .ok_or("Task not found".to_string())
+// Goal progress is logged and paid out (milestones, completion) in goals.rs
+#[tauri::command]
+async fn update_task_progress(
+    app: tauri::AppHandle,
+    db: tauri::State<'_, DbConnection>,
+    task_id: i64,
+    progress_amount: i64,
+    note: Option<String>,
+) -> Result<Task, String> {
+    let conn = db.lock().await;
+    let tx = conn.unchecked_transaction()
+        .map_err(|e| format!("Failed to begin transaction: {}", e))?;
+    let level_up = goals::record_progress(&tx, task_id, goals::ProgressChange::By(progress_amount), note.as_deref())?;
+    tx.commit()
+        .map_err(|e| format!("Failed to commit: {}", e))?;
+    progression::emit_level_up(&app, level_up);
+    fetch_task_sync(&conn, task_id)
 }
 
 #[tauri::command]
 async fn generate_recurring_instances(db: tauri::State<'_, DbConnection>) -> Result<Vec<Task>, String> {
-    use chrono::Utc;
-    let today = Utc::now().format("%Y-%m-%d").to_string();
-
     let conn = db.lock().await;
+    generate_recurring_instances_sync(&conn, Utc::now().naive_utc().date())
+}
+
+/// Create every instance due by `today`, catching up on occurrences missed
+/// while the app was closed (recorded as failed). Opens one transaction per
+/// series, so it must not run inside another transaction.
+fn generate_recurring_instances_sync(conn: &Connection, today: chrono::NaiveDate) -> Result<Vec<Task>, String> {
+    use recurrence::{parse_date, RecurrencePattern, CATCH_UP_DAYS};
+
+    let _source = activity::with_source(conn, activity::RECURRING)?;
+    let pauses = pauses::PauseWindows::load(conn)?;
 
     // Get all recurring parent tasks (tasks with recurrence_pattern that are not instances themselves)
+    // along with the date of the most recent instance already generated for each.
     let mut stmt = conn.prepare(
-        "SELECT id, title, description, category, difficulty, base_experience_reward,
-         gold_reward, priority, recurrence_pattern, current_streak, longest_streak
-         FROM tasks
-         WHERE recurrence_pattern IS NOT NULL
-         AND parent_recurring_task_id IS NULL
-         AND status = 'active'"
+        "SELECT t.id, t.title, t.description, t.category, t.difficulty, t.base_experience_reward,
+         t.gold_reward, t.priority, t.recurrence_pattern, t.current_streak, t.longest_streak,
+         t.created_at, t.project_id,
+         (SELECT MAX(rti.instance_date) FROM recurring_task_instances rti WHERE rti.recurring_task_id = t.id)
+         FROM tasks t
+         WHERE t.recurrence_pattern IS NOT NULL
+         AND t.parent_recurring_task_id IS NULL
+         AND t.status = 'active'
+         AND t.deleted_at IS NULL"
     )
     .map_err(|e| format!("Failed to prepare query: {}", e))?;
 
@@ -795,40 +694,65 @@ async fn generate_recurring_instances(db: tauri::State<'_, DbConnection>) -> Res
             row.get::<_, String>(8)?,  // recurrence_pattern
             row.get::<_, Option<i32>>(9)?,  // current_streak
             row.get::<_, Option<i32>>(10)?,  // longest_streak
+            row.get::<_, Option<String>>(11)?,  // created_at
+            row.get::<_, Option<i64>>(12)?,  // project_id
+            row.get::<_, Option<String>>(13)?,  // last generated instance_date
         ))
     })
     .map_err(|e| format!("Failed to query recurring tasks: {}", e))?
     .collect::<Result<Vec<_>, _>>()
     .map_err(|e| format!("Failed to collect recurring tasks: {}", e))?;
+    drop(stmt);
 
     let mut created_instances = Vec::new();
 
     for (parent_id, title, description, category, difficulty, base_xp, gold, priority,
-         recurrence_pattern, current_streak, longest_streak) in recurring_tasks {
-
-        // Check if instance already exists for today
-        let exists: bool = conn.query_row(
-            "SELECT EXISTS(
-                SELECT 1 FROM recurring_task_instances
-                WHERE recurring_task_id = ?1 AND instance_date = ?2
-            )",
-            rusqlite::params![parent_id, &today],
-            |row| row.get(0),
-        )
-        .unwrap_or(false);
+         recurrence_pattern, current_streak, longest_streak, created_at, project_id, last_instance) in recurring_tasks {
+
+        let pattern = match RecurrencePattern::parse(&recurrence_pattern) {
+            Ok(pattern) => pattern,
+            Err(e) => {
+                println!("Skipping recurring task {}: {}", parent_id, e);
+                continue;
+            }
+        };
+        let anchor = pattern.anchor(created_at.as_deref().and_then(parse_date).unwrap_or(today));
+
+        // Catch up on every scheduled date since the last generated instance,
+        // but never further back than CATCH_UP_DAYS.
+        let from = last_instance
+            .as_deref()
+            .and_then(parse_date)
+            .map(|last| last + Duration::days(1))
+            .unwrap_or(anchor)
+            .max(today - Duration::days(CATCH_UP_DAYS));
+        // Paused dates (vacation or this series) get no instance at all
+        let mut dates = pattern.occurrences_between(anchor, from, today);
+        dates.retain(|date| !pauses.is_paused(Some(parent_id), *date));
+        let finished = pattern.is_finished(anchor, today);
+        if dates.is_empty() && !finished {
+            continue;
+        }
+
+        let tx = conn.unchecked_transaction()
+            .map_err(|e| format!("Failed to begin transaction: {}", e))?;
 
-        if !exists {
-            // Create today's instance
-            let tx = conn.unchecked_transaction()
-                .map_err(|e| format!("Failed to begin transaction: {}", e))?;
+        let mut missed_any = false;
+        for date in dates {
+            let instance_date = date.format("%Y-%m-%d").to_string();
+
+            // Occurrences that passed while the app was closed are recorded as missed
+            // so they break the streak; only today's occurrence is actionable.
+            let status = if date < today { "failed" } else { "active" };
+            missed_any |= date < today;
 
             tx.execute(
                 "INSERT INTO tasks (user_id, title, description, category, difficulty,
-                 base_experience_reward, gold_reward, status, priority, task_type,
+                 base_experience_reward, gold_reward, due_date, status, priority, task_type,
                  parent_recurring_task_id, instance_date, current_streak, longest_streak,
-                 recurrence_pattern, streak_bonus_multiplier)
-                 VALUES (1, ?1, ?2, ?3, ?4, ?5, ?6, 'active', ?7, 'recurring',
-                 ?8, ?9, ?10, ?11, ?12, 1.0)",
+                 recurrence_pattern, streak_bonus_multiplier, project_id)
+                 VALUES (1, ?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, 'recurring',
+                 ?10, ?7, ?11, ?12, ?13, 1.0, ?14)",
                 rusqlite::params![
                     title,
                     description,
@@ -836,12 +760,14 @@ async fn generate_recurring_instances(db: tauri::State<'_, DbConnection>) -> Res
                     difficulty,
                     base_xp,
                     gold,
+                    &instance_date,
+                    status,
                     priority,
                     parent_id,
-                    &today,
                     current_streak,
                     longest_streak,
                     recurrence_pattern,
+                    project_id,
                 ],
             )
             .map_err(|e| format!("Failed to create instance: {}", e))?;
@@ -852,44 +778,37 @@ async fn generate_recurring_instances(db: tauri::State<'_, DbConnection>) -> Res
             tx.execute(
                 "INSERT INTO recurring_task_instances (recurring_task_id, instance_task_id, instance_date)
                  VALUES (?1, ?2, ?3)",
-                rusqlite::params![parent_id, instance_id, &today],
+                rusqlite::params![parent_id, instance_id, &instance_date],
             )
             .map_err(|e| format!("Failed to track instance: {}", e))?;
 
-            tx.commit()
-                .map_err(|e| format!("Failed to commit: {}", e))?;
-
-            let title_for_log = title.clone();
-        created_instances.push(Task {
-                id: instance_id,
-                user_id: 1,
-                title,
-                description,
-                category,
-                difficulty: difficulty as i64,
-                base_experience_reward: base_xp as i64,
-                gold_reward: gold as i64,
-                due_date: None,
-                status: "active".to_string(),
-                priority: priority as i64,
-                created_at: today.clone(),
-                completed_at: None,
-                task_type: "recurring".to_string(),
-                goal_target: None,
-                goal_current: None,
-                goal_unit: None,
-                recurrence_pattern: Some(recurrence_pattern),
-                parent_recurring_task_id: Some(parent_id),
-                instance_date: Some(today.clone()),
-                current_streak: current_streak.map(|v| v as i64),
-                longest_streak: longest_streak.map(|v| v as i64),
-                last_completed_date: None,
-                streak_bonus_multiplier: Some(1.0),
-                project_id: None,
-            });
+            tags::copy_tags(&tx, parent_id, instance_id)?;
 
-            println!("Created recurring instance for task '{}' (ID: {})", title_for_log, instance_id);
+            println!("Created recurring instance for task '{}' on {} (ID: {}, {})", title, instance_date, instance_id, status);
+
+            created_instances.push(fetch_task_sync(&tx, instance_id)?);
+        }
+
+        // Once the series has run past its end date / occurrence limit the parent is done
+        if finished {
+            tx.execute(
+                "UPDATE tasks SET status = 'completed', completed_at = CURRENT_TIMESTAMP WHERE id = ?1",
+                [parent_id],
+            )
+            .map_err(|e| format!("Failed to close recurring series: {}", e))?;
         }
+
+        // A missed occurrence ends the current streak
+        if missed_any {
+            tx.execute(
+                "UPDATE tasks SET current_streak = 0, streak_bonus_multiplier = 1.0 WHERE id = ?1",
+                [parent_id],
+            )
+            .map_err(|e| format!("Failed to reset streak: {}", e))?;
+        }
+
+        tx.commit()
+            .map_err(|e| format!("Failed to commit: {}", e))?;
     }
 
     Ok(created_instances)
@@ -906,13 +825,13 @@ async fn get_projects(db: tauri::State<'_, DbConnection>, status: Option<String>
             "SELECT id, user_id, name, description, color, icon, status, due_date, priority,
              total_tasks, completed_tasks, total_xp_earned, created_at, completed_at
              FROM projects
-             WHERE user_id = 1 AND status = '{}'
+             WHERE user_id = 1 AND deleted_at IS NULL AND status = '{}'
              ORDER BY priority DESC, created_at DESC", s
         ),
         None => "SELECT id, user_id, name, description, color, icon, status, due_date, priority,
              total_tasks, completed_tasks, total_xp_earned, created_at, completed_at
              FROM projects
-             WHERE user_id = 1
+             WHERE user_id = 1 AND deleted_at IS NULL
              ORDER BY priority DESC, created_at DESC".to_string(),
     };
 
@@ -1016,18 +935,25 @@ async fn update_project(db: tauri::State<'_, DbConnection>, project_id: i64, pro
         .ok_or_else(|| "Project not found after update".to_string())
 }
 
+// Deleted projects go to the trash; their tasks keep project_id until the
+// project is purged (see trash.rs)
 #[tauri::command]
-async fn delete_project(db: tauri::State<'_, DbConnection>, project_id: i64) -> Result<String, String> {
+async fn delete_project(db: tauri::State<'_, DbConnection>, project_id: i64) -> Result<trash::UndoToken, String> {
     let conn = db.lock().await;
+    let tx = conn.unchecked_transaction()
+        .map_err(|e| format!("Failed to begin transaction: {}", e))?;
 
-    // This will set project_id to NULL for all tasks due to ON DELETE SET NULL
-    conn.execute(
-        "DELETE FROM projects WHERE id = ?1",
-        rusqlite::params![project_id],
-    )
-    .map_err(|e| format!("Failed to delete project: {}", e))?;
+    let name = trash::trash_item(&tx, trash::TrashKind::Project, project_id)?;
+    let undo = trash::issue_undo(
+        &tx,
+        &trash::UndoAction::Restore { kind: trash::TrashKind::Project, id: project_id },
+        &format!("Project \"{}\" moved to trash", name),
+        Utc::now().naive_utc(),
+    )?;
 
-    Ok(format!("Project {} deleted successfully", project_id))
+    tx.commit()
+        .map_err(|e| format!("Failed to commit: {}", e))?;
+    Ok(undo)
 }
 
 #[tauri::command]
@@ -1078,6 +1004,11 @@ async fn start_timer(db: tauri::State<'_, DbConnection>, task_id: i64, session_t
 
     let timer_id = conn.last_insert_rowid();
 
+    // Pomodoro timers run through the server-side cycle
+    if session_type_str == "pomodoro" {
+        pomodoro::begin_work(&conn, task_id, session_id, &now)?;
+    }
+
     // Return the created timer
     let timer = conn.query_row(
         "SELECT id, task_id, user_id, session_id, start_time, is_paused, paused_at, total_paused_seconds, created_at
@@ -1139,32 +1070,37 @@ async fn pause_timer(db: tauri::State<'_, DbConnection>) -> Result<ActiveTimer,
 }
 
 #[tauri::command]
-async fn stop_timer(db: tauri::State<'_, DbConnection>, notes: Option<String>) -> Result<TimeSession, String> {
+async fn resume_timer(db: tauri::State<'_, DbConnection>) -> Result<ActiveTimer, String> {
     let conn = db.lock().await;
-    let now = chrono::Utc::now().to_rfc3339();
+    let timer_id = timers::resume_sync(&conn, chrono::Utc::now())?;
 
-    // Get active timer
-    let (timer_id, session_id, task_id): (i64, i64, i64) = conn.query_row(
-        "SELECT id, session_id, task_id FROM active_timers WHERE user_id = 1",
-        [],
-        |row| Ok((
-            row.get::<_, i32>(0)? as i64,
-            row.get::<_, i32>(1)? as i64,
-            row.get::<_, i32>(2)? as i64,
-        ))
-    ).map_err(|_| "No active timer found".to_string())?;
+    // Return updated timer
+    let timer = conn.query_row(
+        "SELECT id, task_id, user_id, session_id, start_time, is_paused, paused_at, total_paused_seconds, created_at
+         FROM active_timers WHERE id = ?1",
+        [timer_id],
+        |row| Ok(ActiveTimer {
+            id: row.get::<_, i32>(0)? as i64,
+            task_id: row.get::<_, i32>(1)? as i64,
+            user_id: row.get::<_, i32>(2)? as i64,
+            session_id: row.get::<_, i32>(3)? as i64,
+            start_time: row.get(4)?,
+            is_paused: row.get(5)?,
+            paused_at: row.get(6)?,
+            total_paused_seconds: row.get::<_, i32>(7)? as i64,
+            created_at: row.get(8)?,
+        })
+    ).map_err(|e| format!("Failed to retrieve timer: {}", e))?;
 
-    // Update time session with end time and notes
-    conn.execute(
-        "UPDATE time_sessions SET end_time = ?1, notes = ?2 WHERE id = ?3",
-        rusqlite::params![now, notes, session_id],
-    ).map_err(|e| format!("Failed to update session: {}", e))?;
+    Ok(timer)
+}
 
-    // Delete active timer
-    conn.execute(
-        "DELETE FROM active_timers WHERE id = ?1",
-        [timer_id],
-    ).map_err(|e| format!("Failed to delete timer: {}", e))?;
+#[tauri::command]
+async fn stop_timer(db: tauri::State<'_, DbConnection>, notes: Option<String>) -> Result<TimeSession, String> {
+    let conn = db.lock().await;
+
+    // Close the session (minus paused time) and delete the active timer
+    let session_id = timers::stop_active_timer(&conn, chrono::Utc::now(), notes.as_deref())?;
 
     // Return the completed session
     let session = conn.query_row(
@@ -1381,17 +1317,17 @@ async fn get_scheduled_notifications(
 
     let (query, params): (String, Vec<Box<dyn rusqlite::ToSql>>) = match status {
         Some(s) => (
-            "SELECT id, user_id, task_id, notification_type, title, message, scheduled_for, status,
+            format!("SELECT id, user_id, task_id, notification_type, title, message, scheduled_for, status,
                     snoozed_until, snooze_count, priority, action_url, created_at, sent_at
-             FROM scheduled_notifications WHERE user_id = 1 AND status = ?1
-             ORDER BY scheduled_for ASC".to_string(),
+             FROM scheduled_notifications WHERE user_id = 1 AND status = ?1 AND NOT {} AND NOT {}
+             ORDER BY scheduled_for ASC", pauses::SILENCED_NOTIFICATION, trash::TRASHED_TASK_NOTIFICATION),
             vec![Box::new(s)]
         ),
         None => (
-            "SELECT id, user_id, task_id, notification_type, title, message, scheduled_for, status,
+            format!("SELECT id, user_id, task_id, notification_type, title, message, scheduled_for, status,
                     snoozed_until, snooze_count, priority, action_url, created_at, sent_at
-             FROM scheduled_notifications WHERE user_id = 1
-             ORDER BY scheduled_for ASC".to_string(),
+             FROM scheduled_notifications WHERE user_id = 1 AND NOT {} AND NOT {}
+             ORDER BY scheduled_for ASC", pauses::SILENCED_NOTIFICATION, trash::TRASHED_TASK_NOTIFICATION),
             vec![]
         ),
     };
@@ -1645,19 +1581,25 @@ async fn mark_notification_sent(db: tauri::State<'_, DbConnection>, notification
 #[tauri::command]
 async fn get_pending_notifications(db: tauri::State<'_, DbConnection>) -> Result<Vec<ScheduledNotification>, String> {
     let conn = db.lock().await;
+    get_pending_notifications_sync(&conn)
+}
 
+/// Notifications due for delivery, leaving out those held back by a pause and
+/// those for tasks in the trash.
+fn get_pending_notifications_sync(conn: &Connection) -> Result<Vec<ScheduledNotification>, String> {
     // NOTE: v_pending_notifications does not expose all columns needed here (status,
     // snooze fields, timestamps), so query the table directly with the same
     // "due within 5 minutes" window, also including snoozed notifications whose
     // snooze period has elapsed.
-    let mut stmt = conn.prepare(
+    let mut stmt = conn.prepare(&format!(
         "SELECT id, user_id, task_id, notification_type, title, message, scheduled_for, status,
                 snoozed_until, snooze_count, priority, action_url, created_at, sent_at
          FROM scheduled_notifications
          WHERE user_id = 1
            AND ((status = 'pending' AND datetime(scheduled_for) <= datetime('now', '+5 minutes'))
              OR (status = 'snoozed' AND snoozed_until IS NOT NULL AND datetime(snoozed_until) <= datetime('now', '+5 minutes')))
-         ORDER BY scheduled_for ASC"
+           AND NOT {} AND NOT {}
+         ORDER BY scheduled_for ASC", pauses::SILENCED_NOTIFICATION, trash::TRASHED_TASK_NOTIFICATION)
     ).map_err(|e| format!("Failed to prepare query: {}", e))?;
 
     let notifications_iter = stmt.query_map([], |row| {
@@ -1724,7 +1666,7 @@ async fn get_user_achievements(db: tauri::State<'_, DbConnection>) -> Result<Vec
 }
 
 #[tauri::command]
-async fn check_achievements(db: tauri::State<'_, DbConnection>) -> Result<Vec<Achievement>, String> {
+async fn check_achievements(app: tauri::AppHandle, db: tauri::State<'_, DbConnection>) -> Result<Vec<Achievement>, String> {
     let conn = db.lock().await;
 
     // Get user stats
@@ -1795,11 +1737,10 @@ async fn check_achievements(db: tauri::State<'_, DbConnection>) -> Result<Vec<Ac
                 .map_err(|e| format!("Failed to unlock achievement: {}", e))?;
 
                 // Award rewards
-                conn.execute(
-                    "UPDATE users SET experience_points = experience_points + ?1, gold = gold + ?2 WHERE id = 1",
-                    rusqlite::params![achievement.experience_reward as i32, achievement.gold_reward as i32],
-                )
-                .map_err(|e| format!("Failed to award rewards: {}", e))?;
+                let source = RewardSource::new(ledger::ACHIEVEMENT, achievement.id);
+                ledger::grant_gold(&conn, &source, &Reward::flat(achievement.gold_reward), Some(&achievement.name))?;
+                let level_up = ledger::grant_xp(&conn, &source, &Reward::flat(achievement.experience_reward), Some(&achievement.name))?;
+                progression::emit_level_up(&app, level_up);
 
                 println!("Achievement unlocked: {} - Rewarded {} XP and {} gold",
                     achievement.name, achievement.experience_reward, achievement.gold_reward);
@@ -2250,22 +2191,15 @@ async fn unequip_title(db: tauri::State<'_, DbConnection>) -> Result<User, Strin
 
 // Apply active XP/gold buff multipliers (from active_buffs) to task rewards.
 // Uses the strongest active multiplier of each type to prevent unbounded stacking.
-fn apply_buff_effects_to_rewards(base_xp: i64, base_gold: i64) -> (i64, i64) {
-    let (xp_mult, gold_mult) = match get_db_connection() {
-        Ok(conn) => conn.query_row(
-            "SELECT COALESCE(MAX(CASE WHEN buff_type = 'xp_boost' THEN effect_value END), 1.0),
-                    COALESCE(MAX(CASE WHEN buff_type = 'gold_boost' THEN effect_value END), 1.0)
-             FROM active_buffs WHERE user_id = 1 AND expires_at > datetime('now')",
-            [],
-            |row| Ok((row.get::<_, f64>(0)?, row.get::<_, f64>(1)?)),
-        ).unwrap_or((1.0, 1.0)),
-        Err(_) => (1.0, 1.0),
-    };
-
-    (
-        ((base_xp as f64) * xp_mult).round() as i64,
-        ((base_gold as f64) * gold_mult).round() as i64,
-    )
+// Strongest active XP and gold buff multipliers (1.0 when none are active)
+fn active_reward_buffs(conn: &Connection) -> (f64, f64) {
+    conn.query_row(
+        "SELECT COALESCE(MAX(CASE WHEN buff_type = 'xp_boost' THEN effect_value END), 1.0),
+                COALESCE(MAX(CASE WHEN buff_type = 'gold_boost' THEN effect_value END), 1.0)
+         FROM active_buffs WHERE user_id = 1 AND expires_at > datetime('now')",
+        [],
+        |row| Ok((row.get::<_, f64>(0)?, row.get::<_, f64>(1)?)),
+    ).unwrap_or((1.0, 1.0))
 }
 
 fn get_user_skill_stats_sync() -> Result<UserSkillStats, String> {
@@ -2756,12 +2690,6 @@ async fn update_node_position(node_key: String, x_position: f64, y_position: f64
     Ok(())
 }
 
-// Get recommended task difficulty based on user stats
-#[tauri::command]
-async fn get_recommended_difficulty(task_category: String) -> Result<i64, String> {
-    Ok(5)
-}
-
 #[tauri::command]
 async fn purchase_item(db: tauri::State<'_, DbConnection>, item_id: String, price: i64) -> Result<User, String> {
     let conn = db.lock().await;
@@ -2795,7 +2723,7 @@ async fn purchase_item(db: tauri::State<'_, DbConnection>, item_id: String, pric
     }
 
     // Deduct gold
-    tx.execute("UPDATE users SET gold = gold - ?1 WHERE id = 1", [price])
+    ledger::grant_gold(&tx, &RewardSource::new(ledger::PURCHASE, &item_id), &Reward::flat(-price), Some(&item.name))
         .map_err(|e| format!("Failed to deduct gold: {}", e))?;
 
     // Upsert the inventory row (the `name` column stores the catalog item id)
@@ -3170,18 +3098,34 @@ const TASK_SELECT: &str = "SELECT t.id, t.user_id, t.title, t.description, t.cat
      tp.target_progress as goal_target, tp.current_progress as goal_current,
      t.recurrence_pattern, t.parent_recurring_task_id, t.instance_date,
      t.current_streak, t.longest_streak, t.last_completed_date, t.streak_bonus_multiplier,
-     t.project_id
+     t.project_id, t.parent_task_id, COALESCE(t.complete_with_subtasks, 0),
+     EXISTS(SELECT 1 FROM task_dependencies d JOIN tasks b ON b.id = d.blocked_by_task_id
+            WHERE d.task_id = t.id AND b.status NOT IN ('completed', 'archived')) as blocked,
+     u.strength, u.intelligence, u.endurance, u.charisma,
+     (SELECT group_concat(g.name, char(31)) FROM task_tags tt JOIN tags g ON g.id = tt.tag_id
+      WHERE tt.task_id = t.id) as tags,
+     t.start_date
      FROM tasks t
-     LEFT JOIN task_progress tp ON t.id = tp.task_id";
+     LEFT JOIN task_progress tp ON t.id = tp.task_id
+     LEFT JOIN users u ON u.id = t.user_id";
 
 fn task_from_row(row: &rusqlite::Row) -> rusqlite::Result<Task> {
+    let difficulty = row.get::<_, i64>(5)?;
+    let category: String = row.get(4)?;
+    let stats = difficulty::Stats {
+        strength: row.get::<_, Option<i64>>(25)?.unwrap_or(0),
+        intelligence: row.get::<_, Option<i64>>(26)?.unwrap_or(0),
+        endurance: row.get::<_, Option<i64>>(27)?.unwrap_or(0),
+        charisma: row.get::<_, Option<i64>>(28)?.unwrap_or(0),
+    };
     Ok(Task {
         id: row.get::<_, i64>(0)?,
         user_id: row.get::<_, i64>(1)?,
         title: row.get(2)?,
         description: row.get(3)?,
-        category: row.get(4)?,
-        difficulty: row.get::<_, i64>(5)?,
+        effective_difficulty: difficulty::effective_difficulty(difficulty, &stats, &category),
+        category,
+        difficulty,
         base_experience_reward: row.get::<_, i64>(6)?,
         gold_reward: row.get::<_, i64>(7)?,
         due_date: row.get(8)?,
@@ -3201,15 +3145,27 @@ fn task_from_row(row: &rusqlite::Row) -> rusqlite::Result<Task> {
         last_completed_date: row.get(19)?,
         streak_bonus_multiplier: row.get(20)?,
         project_id: row.get::<_, Option<i64>>(21)?,
+        parent_task_id: row.get::<_, Option<i64>>(22)?,
+        complete_with_subtasks: row.get(23)?,
+        blocked: row.get(24)?,
+        tags: row
+            .get::<_, Option<String>>(29)?
+            .map(|tags| tags.split('\u{1f}').map(str::to_string).collect())
+            .unwrap_or_default(),
+        start_date: row.get(30)?,
     })
 }
 
+fn fetch_task_sync(conn: &Connection, task_id: i64) -> Result<Task, String> {
+    let query = format!("{} WHERE t.id = ?1", TASK_SELECT);
+    conn.query_row(&query, rusqlite::params![task_id], task_from_row)
+        .map_err(|e| format!("Failed to get task {}: {}", task_id, e))
+}
+
 #[tauri::command]
 async fn get_task_by_id(db: tauri::State<'_, DbConnection>, task_id: i64) -> Result<Task, String> {
     let conn = db.lock().await;
-    let query = format!("{} WHERE t.id = ?1", TASK_SELECT);
-    conn.query_row(&query, rusqlite::params![task_id], |row| task_from_row(row))
-        .map_err(|e| format!("Failed to get task {}: {}", task_id, e))
+    fetch_task_sync(&conn, task_id)
 }
 
 #[tauri::command]
@@ -3221,16 +3177,16 @@ async fn get_task_statistics(db: tauri::State<'_, DbConnection>) -> Result<serde
             .map_err(|e| format!("Failed to compute task statistics: {}", e))
     };
 
-    let total = count("SELECT COUNT(*) FROM tasks WHERE user_id = 1")?;
-    let active = count("SELECT COUNT(*) FROM tasks WHERE user_id = 1 AND status = 'active'")?;
-    let completed = count("SELECT COUNT(*) FROM tasks WHERE user_id = 1 AND status = 'completed'")?;
-    let failed = count("SELECT COUNT(*) FROM tasks WHERE user_id = 1 AND status = 'failed'")?;
+    let total = count("SELECT COUNT(*) FROM tasks WHERE user_id = 1 AND deleted_at IS NULL")?;
+    let active = count("SELECT COUNT(*) FROM tasks WHERE user_id = 1 AND deleted_at IS NULL AND status = 'active'")?;
+    let completed = count("SELECT COUNT(*) FROM tasks WHERE user_id = 1 AND deleted_at IS NULL AND status = 'completed'")?;
+    let failed = count("SELECT COUNT(*) FROM tasks WHERE user_id = 1 AND deleted_at IS NULL AND status = 'failed'")?;
     let completed_today = count(
-        "SELECT COUNT(*) FROM tasks WHERE user_id = 1 AND status = 'completed'
+        "SELECT COUNT(*) FROM tasks WHERE user_id = 1 AND deleted_at IS NULL AND status = 'completed'
          AND date(completed_at) = date('now', 'localtime')",
     )?;
     let completed_this_week = count(
-        "SELECT COUNT(*) FROM tasks WHERE user_id = 1 AND status = 'completed'
+        "SELECT COUNT(*) FROM tasks WHERE user_id = 1 AND deleted_at IS NULL AND status = 'completed'
          AND completed_at >= datetime('now', '-7 days')",
     )?;
 
@@ -3240,7 +3196,7 @@ async fn get_task_statistics(db: tauri::State<'_, DbConnection>) -> Result<serde
         let mut stmt = conn
             .prepare(
                 "SELECT category, COUNT(*) FROM tasks
-                 WHERE user_id = 1 AND status = 'completed' GROUP BY category",
+                 WHERE user_id = 1 AND deleted_at IS NULL AND status = 'completed' GROUP BY category",
             )
             .map_err(|e| format!("Failed to prepare category statistics: {}", e))?;
         let rows = stmt
@@ -3284,14 +3240,14 @@ async fn get_user_detailed_stats(db: tauri::State<'_, DbConnection>) -> Result<s
     };
 
     let total_tasks_completed =
-        count("SELECT COUNT(*) FROM tasks WHERE user_id = 1 AND status = 'completed'")?;
+        count("SELECT COUNT(*) FROM tasks WHERE user_id = 1 AND deleted_at IS NULL AND status = 'completed'")?;
     let tasks_completed_today = count(
-        "SELECT COUNT(*) FROM tasks WHERE user_id = 1 AND status = 'completed'
+        "SELECT COUNT(*) FROM tasks WHERE user_id = 1 AND deleted_at IS NULL AND status = 'completed'
          AND date(completed_at) = date('now', 'localtime')",
     )?;
     let total_xp_from_tasks = count(
         "SELECT COALESCE(SUM(base_experience_reward), 0) FROM tasks
-         WHERE user_id = 1 AND status = 'completed'",
+         WHERE user_id = 1 AND deleted_at IS NULL AND status = 'completed'",
     )?;
     let achievements_unlocked =
         count("SELECT COUNT(*) FROM user_achievements WHERE user_id = 1")?;
@@ -3351,16 +3307,18 @@ async fn get_user_task_history(
 
 #[tauri::command]
 async fn add_experience(
+    app: tauri::AppHandle,
     db: tauri::State<'_, DbConnection>,
     user_id: i64,
     amount: i64,
 ) -> Result<User, String> {
+    if user_id != 1 {
+        return Err(format!("Unknown user {}", user_id));
+    }
     let conn = db.lock().await;
-    conn.execute(
-        "UPDATE users SET experience_points = experience_points + ?1 WHERE id = ?2",
-        rusqlite::params![amount, user_id],
-    )
-    .map_err(|e| format!("Failed to add experience: {}", e))?;
+    let level_up = ledger::grant_xp(&conn, &RewardSource::untracked(ledger::MANUAL), &Reward::flat(amount), None)
+        .map_err(|e| format!("Failed to add experience: {}", e))?;
+    progression::emit_level_up(&app, level_up);
     fetch_user_sync(&conn)
 }
 
@@ -3370,12 +3328,12 @@ async fn add_gold(
     user_id: i64,
     amount: i64,
 ) -> Result<User, String> {
+    if user_id != 1 {
+        return Err(format!("Unknown user {}", user_id));
+    }
     let conn = db.lock().await;
-    conn.execute(
-        "UPDATE users SET gold = gold + ?1 WHERE id = ?2",
-        rusqlite::params![amount, user_id],
-    )
-    .map_err(|e| format!("Failed to add gold: {}", e))?;
+    ledger::grant_gold(&conn, &RewardSource::untracked(ledger::MANUAL), &Reward::flat(amount), None)
+        .map_err(|e| format!("Failed to add gold: {}", e))?;
     fetch_user_sync(&conn)
 }
 
@@ -3469,6 +3427,16 @@ pub fn run() {
                 }
             });
 
+            // Hold any timer left running over a crash before anything moves it on
+            tauri::async_runtime::block_on(async {
+                let conn = db_conn.lock().await;
+                match timers::flag_stale_sync(&conn, chrono::Utc::now()) {
+                    Ok(true) => println!("A timer was left running over a restart; waiting for review"),
+                    Ok(false) => {}
+                    Err(e) => eprintln!("Failed to check for stale timers: {}", e),
+                }
+            });
+
             // Store database connection in app state
             app.manage(db_conn);
 
@@ -3481,6 +3449,18 @@ pub fn run() {
                 }
             });
 
+            // Deal overdue damage once a day
+            damage::schedule_sweep(app.handle().clone());
+
+            // Purge expired trash every hour
+            trash::schedule_purge(app.handle().clone());
+
+            // Run pomodoro cycles server-side
+            pomodoro::schedule_ticks(app.handle().clone());
+
+            // Send timer heartbeats and pause timers across a sleep
+            timers::schedule_idle_checks(app.handle().clone());
+
             Ok(())
         })
         .plugin(tauri_plugin_opener::init())
@@ -3523,7 +3503,7 @@ pub fn run() {
             get_user_titles,
             equip_title,
             unequip_title,
-            get_recommended_difficulty,
+            difficulty::get_recommended_difficulty,
             get_active_buffs,
             apply_buff,
             get_skill_nodes,
@@ -3610,7 +3590,92 @@ pub fn run() {
             connections::get_connections,
             connections::disconnect_provider,
             simplefin::simplefin_connect,
-            simplefin::simplefin_sync
+            simplefin::simplefin_sync,
+            progression::get_progression_settings,
+            progression::update_progression_settings,
+            ledger::get_reward_ledger,
+            ledger::reconcile_reward_ledger,
+            completions::reopen_task,
+            subtasks::set_task_parent,
+            subtasks::set_complete_with_subtasks,
+            subtasks::get_subtasks,
+            subtasks::add_task_dependency,
+            subtasks::remove_task_dependency,
+            subtasks::get_task_dependencies,
+            economy::get_economy_config,
+            economy::update_economy_config,
+            economy::reset_economy_config,
+            economy::preview_task_reward,
+            difficulty::get_difficulty_recommendation,
+            damage::run_overdue_sweep,
+            damage::get_damage_events,
+            damage::get_damage_settings,
+            damage::update_damage_settings,
+            search::search_tasks,
+            tags::get_tags,
+            tags::create_tag,
+            tags::rename_tag,
+            tags::set_tag_color,
+            tags::delete_tag,
+            tags::add_task_tag,
+            tags::remove_task_tag,
+            tags::get_task_tags,
+            views::get_saved_views,
+            views::create_saved_view,
+            views::update_saved_view,
+            views::delete_saved_view,
+            views::run_view,
+            views::run_view_query,
+            defer::defer_task,
+            defer::undefer_task,
+            defer::get_deferred_tasks,
+            templates::get_templates,
+            templates::create_template,
+            templates::update_template,
+            templates::delete_template,
+            templates::save_task_as_template,
+            templates::instantiate_template,
+            bulk::bulk_update_tasks,
+            goals::set_task_progress,
+            goals::get_goal_progress_history,
+            goals::get_goal_progress_series,
+            trash::list_trash,
+            trash::restore_from_trash,
+            trash::delete_from_trash,
+            trash::empty_trash,
+            trash::undo_action,
+            trash::get_trash_settings,
+            trash::update_trash_settings,
+            activity::add_task_comment,
+            activity::update_task_comment,
+            activity::delete_task_comment,
+            activity::get_task_timeline,
+            scoring::get_next_actions,
+            rollover::get_rollover_policies,
+            rollover::set_rollover_policy,
+            rollover::delete_rollover_policy,
+            pauses::create_pause,
+            pauses::end_pause,
+            pauses::get_pauses,
+            pomodoro::get_pomodoro_state,
+            pomodoro::stop_pomodoro,
+            pomodoro::get_pomodoro_settings,
+            pomodoro::update_pomodoro_settings,
+            resume_timer,
+            timers::timer_heartbeat,
+            timers::get_stale_timer,
+            timers::resolve_stale_timer,
+            timers::get_timer_settings,
+            timers::update_timer_settings,
+            sessions::add_time_session,
+            sessions::update_time_session,
+            sessions::split_time_session,
+            sessions::merge_time_sessions,
+            timesheet::get_timesheet,
+            timesheet::export_timesheet_csv,
+            timesheet::export_sessions_ical,
+            estimates::get_estimate_report,
+            estimates::suggest_estimate
         ])
         .run(tauri::generate_context!())
         .expect("error while running tauri application");